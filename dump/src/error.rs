@@ -14,12 +14,15 @@ pub enum Error {
     Serde(#[from] serde_json::Error),
     #[error(transparent)]
     Uuid(#[from] uuid::Error),
+    #[error(transparent)]
+    Encryption(#[from] file_store::encryption::Error),
 }
 
 impl ErrorCode for Error {
     fn error_code(&self) -> Code {
         match self {
             Error::Io(e) => e.error_code(),
+            Error::Encryption(e) => e.error_code(),
 
             // These errors either happen when creating a dump and don't need any error code,
             // or come from an internal bad deserialization.
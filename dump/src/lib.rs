@@ -1,11 +1,16 @@
 #![allow(clippy::type_complexity)]
 #![allow(clippy::wrong_self_convention)]
 
+use std::collections::BTreeMap;
+
 use meilisearch_types::error::ResponseError;
 use meilisearch_types::keys::Key;
 use meilisearch_types::milli::update::IndexDocumentsMethod;
 use meilisearch_types::settings::Unchecked;
-use meilisearch_types::tasks::{Details, IndexSwap, KindWithContent, Status, Task, TaskId};
+use meilisearch_types::tasks::{
+    ConflictPolicy, Details, DumpContent, DumpSource, IndexSwap, KindWithContent, Status, Task,
+    TaskId,
+};
 use meilisearch_types::InstanceUid;
 use roaring::RoaringBitmap;
 use serde::{Deserialize, Serialize};
@@ -16,7 +21,7 @@ mod reader;
 mod writer;
 
 pub use error::Error;
-pub use reader::{DumpReader, UpdateFile};
+pub use reader::{DumpIndexReader, DumpReader, UpdateFile};
 pub use writer::DumpWriter;
 
 const CURRENT_DUMP_VERSION: Version = Version::V6;
@@ -30,6 +35,8 @@ pub struct Metadata {
     pub db_version: String,
     #[serde(with = "time::serde::rfc3339")]
     pub dump_date: OffsetDateTime,
+    #[serde(default)]
+    pub content: DumpContent,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -127,8 +134,16 @@ pub enum KindDump {
     DumpCreation {
         keys: Vec<Key>,
         instance_uid: Option<InstanceUid>,
+        #[serde(default)]
+        content: DumpContent,
+    },
+    DumpImport {
+        source: DumpSource,
+        renames: BTreeMap<String, String>,
+        on_conflict: ConflictPolicy,
     },
     SnapshotCreation,
+    SuggestionsRefresh,
 }
 
 impl From<Task> for TaskDump {
@@ -187,10 +202,14 @@ impl From<KindWithContent> for KindDump {
             KindWithContent::TaskDeletion { query, tasks } => {
                 KindDump::TasksDeletion { query, tasks }
             }
-            KindWithContent::DumpCreation { keys, instance_uid } => {
-                KindDump::DumpCreation { keys, instance_uid }
+            KindWithContent::DumpCreation { keys, instance_uid, content } => {
+                KindDump::DumpCreation { keys, instance_uid, content }
+            }
+            KindWithContent::DumpImport { source, renames, on_conflict } => {
+                KindDump::DumpImport { source, renames, on_conflict }
             }
             KindWithContent::SnapshotCreation => KindDump::SnapshotCreation,
+            KindWithContent::SuggestionsRefresh => KindDump::SuggestionsRefresh,
         }
     }
 }
@@ -202,14 +221,16 @@ pub(crate) mod test {
     use std::str::FromStr;
 
     use big_s::S;
+    use file_store::encryption::EncryptionKey;
     use maplit::btreeset;
+    use meili_snap::snapshot;
     use meilisearch_types::index_uid::IndexUid;
     use meilisearch_types::keys::{Action, Key};
     use meilisearch_types::milli::update::Setting;
     use meilisearch_types::milli::{self};
     use meilisearch_types::settings::{Checked, Settings};
     use meilisearch_types::star_or::StarOr;
-    use meilisearch_types::tasks::{Details, Status};
+    use meilisearch_types::tasks::{Details, DumpContent, Status};
     use serde_json::{json, Map, Value};
     use time::macros::datetime;
     use uuid::Uuid;
@@ -255,11 +276,14 @@ pub(crate) mod test {
             sortable_attributes: Setting::Set(btreeset! { S("age") }),
             ranking_rules: Setting::NotSet,
             stop_words: Setting::NotSet,
+            stop_words_presets: Setting::NotSet,
             synonyms: Setting::NotSet,
             distinct_attribute: Setting::NotSet,
             typo_tolerance: Setting::NotSet,
             faceting: Setting::NotSet,
             pagination: Setting::NotSet,
+            rules: Setting::NotSet,
+            search_cutoff_ms: Setting::NotSet,
             _kind: std::marker::PhantomData,
         };
         settings.check()
@@ -370,8 +394,12 @@ pub(crate) mod test {
     }
 
     pub fn create_test_dump() -> File {
+        create_test_dump_with_encryption_key(None)
+    }
+
+    pub fn create_test_dump_with_encryption_key(encryption_key: Option<&EncryptionKey>) -> File {
         let instance_uid = create_test_instance_uid();
-        let dump = DumpWriter::new(Some(instance_uid)).unwrap();
+        let dump = DumpWriter::new(Some(instance_uid), DumpContent::default()).unwrap();
 
         // ========== Adding an index
         let documents = create_test_documents();
@@ -409,7 +437,7 @@ pub(crate) mod test {
 
         // create the dump
         let mut file = tempfile::tempfile().unwrap();
-        dump.persist_to(&mut file).unwrap();
+        dump.persist_to(&mut file, encryption_key).unwrap();
         file.seek(SeekFrom::Start(0)).unwrap();
 
         file
@@ -418,12 +446,13 @@ pub(crate) mod test {
     #[test]
     fn test_creating_and_read_dump() {
         let mut file = create_test_dump();
-        let mut dump = DumpReader::open(&mut file).unwrap();
+        let mut dump = DumpReader::open(&mut file, None).unwrap();
 
         // ==== checking the top level infos
         assert_eq!(dump.version(), Version::V6);
         assert!(dump.date().is_some());
         assert_eq!(dump.instance_uid().unwrap().unwrap(), create_test_instance_uid());
+        assert_eq!(dump.content(), DumpContent::default());
 
         // ==== checking the index
         let mut indexes = dump.indexes().unwrap();
@@ -460,4 +489,57 @@ pub(crate) mod test {
             assert_eq!(key.unwrap(), expected);
         }
     }
+
+    #[test]
+    fn test_creating_and_read_encrypted_dump() {
+        let key = EncryptionKey::new("the doggos are sleeping");
+        let mut file = create_test_dump_with_encryption_key(Some(&key));
+
+        // ==== the dump can't be read without the key, or with another key
+        let error = DumpReader::open(&mut file, None).err().unwrap();
+        snapshot!(error, @"The data is encrypted but no encryption key was provided.");
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        let wrong_key = EncryptionKey::new("the catto is awake");
+        let error = DumpReader::open(&mut file, Some(&wrong_key)).err().unwrap();
+        snapshot!(error, @"The provided encryption key does not match the key used to encrypt the data.");
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        // ==== with the right key everything is there
+        let mut dump = DumpReader::open(&mut file, Some(&key)).unwrap();
+        assert_eq!(dump.version(), Version::V6);
+        assert_eq!(dump.instance_uid().unwrap().unwrap(), create_test_instance_uid());
+
+        let mut indexes = dump.indexes().unwrap();
+        let mut index = indexes.next().unwrap().unwrap();
+        for (document, expected) in index.documents().unwrap().zip(create_test_documents()) {
+            assert_eq!(document.unwrap(), expected);
+        }
+        assert_eq!(index.settings().unwrap(), create_test_settings());
+        drop(index);
+        drop(indexes);
+
+        for (key, expected) in dump.keys().unwrap().zip(create_test_api_keys()) {
+            assert_eq!(key.unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_creating_and_read_partial_dump() {
+        let content =
+            DumpContent { indexes: Some(vec!["doggos".to_string()]), tasks: false, keys: false };
+        let dump = DumpWriter::new(None, content.clone()).unwrap();
+        dump.create_tasks_queue().unwrap().flush().unwrap();
+        dump.create_keys().unwrap().flush().unwrap();
+
+        let mut file = tempfile::tempfile().unwrap();
+        dump.persist_to(&mut file, None).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        let mut dump = DumpReader::open(&mut file, None).unwrap();
+        assert_eq!(dump.content(), content);
+        assert!(dump.indexes().unwrap().next().is_none());
+        assert!(dump.tasks().unwrap().next().is_none());
+        assert!(dump.keys().unwrap().next().is_none());
+    }
 }
@@ -121,7 +121,11 @@ impl CompatV5ToV6 {
                         },
                         v5::tasks::TaskContent::Dump { uid: _ } => {
                             // in v6 we compute the dump_uid from the started_at processing time
-                            v6::Kind::DumpCreation { keys: keys.clone(), instance_uid }
+                            v6::Kind::DumpCreation {
+                                keys: keys.clone(),
+                                instance_uid,
+                                content: Default::default(),
+                            }
                         }
                     },
                     canceled_by: None,
@@ -318,6 +322,7 @@ impl<T> From<v5::Settings<T>> for v6::Settings<v6::Unchecked> {
             sortable_attributes: settings.sortable_attributes.into(),
             ranking_rules: settings.ranking_rules.into(),
             stop_words: settings.stop_words.into(),
+            stop_words_presets: v6::Setting::NotSet,
             synonyms: settings.synonyms.into(),
             distinct_attribute: settings.distinct_attribute.into(),
             typo_tolerance: match settings.typo_tolerance {
@@ -340,6 +345,7 @@ impl<T> From<v5::Settings<T>> for v6::Settings<v6::Unchecked> {
             faceting: match settings.faceting {
                 v5::Setting::Set(faceting) => v6::Setting::Set(v6::FacetingSettings {
                     max_values_per_facet: faceting.max_values_per_facet.into(),
+                    sort_facet_values_by: v6::Setting::NotSet,
                 }),
                 v5::Setting::Reset => v6::Setting::Reset,
                 v5::Setting::NotSet => v6::Setting::NotSet,
@@ -351,6 +357,8 @@ impl<T> From<v5::Settings<T>> for v6::Settings<v6::Unchecked> {
                 v5::Setting::Reset => v6::Setting::Reset,
                 v5::Setting::NotSet => v6::Setting::NotSet,
             },
+            rules: v6::Setting::NotSet,
+            search_cutoff_ms: v6::Setting::NotSet,
             _kind: std::marker::PhantomData,
         }
     }
@@ -1,7 +1,9 @@
 use std::fs::File;
 use std::io::{BufReader, Read};
 
-use flate2::bufread::GzDecoder;
+use file_store::encryption::{self, EncryptionKey};
+use flate2::read::GzDecoder;
+use meilisearch_types::tasks::DumpContent;
 use serde::Deserialize;
 use tempfile::TempDir;
 
@@ -29,10 +31,11 @@ pub enum DumpReader {
 }
 
 impl DumpReader {
-    pub fn open(dump: impl Read) -> Result<DumpReader> {
+    /// Opens a dump, decrypting it with the given key if it is encrypted.
+    pub fn open(dump: impl Read, encryption_key: Option<&EncryptionKey>) -> Result<DumpReader> {
         let path = TempDir::new()?;
-        let mut dump = BufReader::new(dump);
-        let gz = GzDecoder::new(&mut dump);
+        let dump = encryption::decrypt_if_needed(BufReader::new(dump), encryption_key)?;
+        let gz = GzDecoder::new(dump);
         let mut archive = tar::Archive::new(gz);
         archive.unpack(path.path())?;
 
@@ -77,6 +80,14 @@ impl DumpReader {
         }
     }
 
+    /// Returns what the dump contains, the dumps prior to the v6 always contain everything.
+    pub fn content(&self) -> DumpContent {
+        match self {
+            DumpReader::Current(current) => current.content(),
+            DumpReader::Compat(_) => DumpContent::default(),
+        }
+    }
+
     pub fn indexes(&self) -> Result<Box<dyn Iterator<Item = Result<DumpIndexReader>> + '_>> {
         match self {
             DumpReader::Current(current) => {
@@ -192,7 +203,7 @@ pub(crate) mod test {
     #[test]
     fn import_dump_v5() {
         let dump = File::open("tests/assets/v5.dump").unwrap();
-        let mut dump = DumpReader::open(dump).unwrap();
+        let mut dump = DumpReader::open(dump, None).unwrap();
 
         // top level infos
         insta::assert_display_snapshot!(dump.date().unwrap(), @"2022-10-04 15:55:10.344982459 +00:00:00");
@@ -270,7 +281,7 @@ pub(crate) mod test {
     #[test]
     fn import_dump_v4() {
         let dump = File::open("tests/assets/v4.dump").unwrap();
-        let mut dump = DumpReader::open(dump).unwrap();
+        let mut dump = DumpReader::open(dump, None).unwrap();
 
         // top level infos
         insta::assert_display_snapshot!(dump.date().unwrap(), @"2022-10-06 12:53:49.131989609 +00:00:00");
@@ -347,7 +358,7 @@ pub(crate) mod test {
     #[test]
     fn import_dump_v3() {
         let dump = File::open("tests/assets/v3.dump").unwrap();
-        let mut dump = DumpReader::open(dump).unwrap();
+        let mut dump = DumpReader::open(dump, None).unwrap();
 
         // top level infos
         insta::assert_display_snapshot!(dump.date().unwrap(), @"2022-10-07 11:39:03.709153554 +00:00:00");
@@ -440,7 +451,7 @@ pub(crate) mod test {
     #[test]
     fn import_dump_v2() {
         let dump = File::open("tests/assets/v2.dump").unwrap();
-        let mut dump = DumpReader::open(dump).unwrap();
+        let mut dump = DumpReader::open(dump, None).unwrap();
 
         // top level infos
         insta::assert_display_snapshot!(dump.date().unwrap(), @"2022-10-09 20:27:59.904096267 +00:00:00");
@@ -533,7 +544,7 @@ pub(crate) mod test {
     #[test]
     fn import_dump_v1() {
         let dump = File::open("tests/assets/v1.dump").unwrap();
-        let mut dump = DumpReader::open(dump).unwrap();
+        let mut dump = DumpReader::open(dump, None).unwrap();
 
         // top level infos
         assert_eq!(dump.date(), None);
@@ -79,6 +79,10 @@ impl V6Reader {
         Ok(self.instance_uid)
     }
 
+    pub fn content(&self) -> meilisearch_types::tasks::DumpContent {
+        self.metadata.content.clone()
+    }
+
     pub fn indexes(&self) -> Result<Box<dyn Iterator<Item = Result<V6IndexReader>> + '_>> {
         let entries = fs::read_dir(self.dump.path().join("indexes"))?;
         Ok(Box::new(
@@ -2,10 +2,12 @@ use std::fs::{self, File};
 use std::io::{BufWriter, Write};
 use std::path::PathBuf;
 
+use file_store::encryption::{EncryptedWriter, EncryptionKey};
 use flate2::write::GzEncoder;
 use flate2::Compression;
 use meilisearch_types::keys::Key;
 use meilisearch_types::settings::{Checked, Settings};
+use meilisearch_types::tasks::DumpContent;
 use serde_json::{Map, Value};
 use tempfile::TempDir;
 use time::OffsetDateTime;
@@ -19,7 +21,7 @@ pub struct DumpWriter {
 }
 
 impl DumpWriter {
-    pub fn new(instance_uuid: Option<Uuid>) -> Result<DumpWriter> {
+    pub fn new(instance_uuid: Option<Uuid>, content: DumpContent) -> Result<DumpWriter> {
         let dir = TempDir::new()?;
 
         if let Some(instance_uuid) = instance_uuid {
@@ -33,6 +35,7 @@ impl DumpWriter {
             dump_version: CURRENT_DUMP_VERSION,
             db_version: env!("CARGO_PKG_VERSION").to_string(),
             dump_date: OffsetDateTime::now_utc(),
+            content,
         };
         fs::write(dir.path().join("metadata.json"), serde_json::to_string(&metadata)?)?;
 
@@ -53,16 +56,33 @@ impl DumpWriter {
         TaskWriter::new(self.dir.path().join("tasks"))
     }
 
-    pub fn persist_to(self, mut writer: impl Write) -> Result<()> {
-        let gz_encoder = GzEncoder::new(&mut writer, Compression::default());
-        let mut tar_encoder = tar::Builder::new(gz_encoder);
-        tar_encoder.append_dir_all(".", self.dir.path())?;
-        let gz_encoder = tar_encoder.into_inner()?;
-        gz_encoder.finish()?;
+    /// Writes the dump in the writer, it is encrypted if a key is given.
+    pub fn persist_to(
+        self,
+        mut writer: impl Write,
+        encryption_key: Option<&EncryptionKey>,
+    ) -> Result<()> {
+        match encryption_key {
+            Some(key) => {
+                let encrypted = EncryptedWriter::new(&mut writer, key)?;
+                self.write_tar_gz(encrypted)?.finish()?;
+            }
+            None => {
+                self.write_tar_gz(&mut writer)?;
+            }
+        }
         writer.flush()?;
 
         Ok(())
     }
+
+    fn write_tar_gz<W: Write>(&self, writer: W) -> Result<W> {
+        let gz_encoder = GzEncoder::new(writer, Compression::default());
+        let mut tar_encoder = tar::Builder::new(gz_encoder);
+        tar_encoder.append_dir_all(".", self.dir.path())?;
+        let gz_encoder = tar_encoder.into_inner()?;
+        Ok(gz_encoder.finish()?)
+    }
 }
 
 pub struct KeyWriter {
@@ -296,7 +316,12 @@ pub(crate) mod test {
         {
           "dumpVersion": "V6",
           "dbVersion": "[version]",
-          "dumpDate": "[date]"
+          "dumpDate": "[date]",
+          "content": {
+            "indexes": null,
+            "tasks": true,
+            "keys": true
+          }
         }
         "###);
 
@@ -0,0 +1,494 @@
+/*!
+Transparent encryption of the data Meilisearch stores at rest.
+
+An encrypted stream starts with a fixed-size header followed by a list of
+authenticated chunks:
+
+```text
+header: magic (8 bytes) | version (1 byte) | key fingerprint (8 bytes) | nonce prefix (7 bytes)
+chunk:  frame (u32 LE, high bit set on the last chunk) | ciphertext + tag (frame & !LAST_CHUNK bytes)
+```
+
+Every chunk is sealed with AES-256-GCM. Its nonce is made of the random nonce prefix
+of the stream, the big-endian index of the chunk and a byte telling if it is the last
+chunk. Reordering, removing or truncating chunks is thus detected when decrypting.
+
+The key fingerprint lets us tell apart a wrong key from corrupted data.
+*/
+
+use std::fmt;
+use std::io::{self, Chain, Cursor, Read, Seek, SeekFrom, Write};
+use std::sync::Arc;
+
+use ring::aead::{self, Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM};
+use ring::hkdf::{Salt, HKDF_SHA256};
+use ring::rand::{SecureRandom, SystemRandom};
+
+const MAGIC: &[u8; 8] = b"MEILIENC";
+const VERSION: u8 = 1;
+const FINGERPRINT_LEN: usize = 8;
+const NONCE_PREFIX_LEN: usize = 7;
+/// The length of the header of an encrypted stream.
+pub const HEADER_LEN: usize = MAGIC.len() + 1 + FINGERPRINT_LEN + NONCE_PREFIX_LEN;
+
+/// The size of the plaintext sealed in each chunk.
+const CHUNK_SIZE: usize = 64 * 1024;
+/// The bit of a chunk frame telling that it is the last chunk of the stream.
+const LAST_CHUNK: u32 = 1 << 31;
+/// The size of a chunk frame and of the tag appended to its ciphertext.
+const CHUNK_OVERHEAD: usize = 4 + 16;
+
+const HKDF_SALT: &[u8] = b"meilisearch-encryption-at-rest";
+const KEY_INFO: &[u8] = b"aes-256-gcm";
+const FINGERPRINT_INFO: &[u8] = b"fingerprint";
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("The data is encrypted but no encryption key was provided.")]
+    MissingKey,
+    #[error("The provided encryption key does not match the key used to encrypt the data.")]
+    WrongKey,
+    #[error("Unsupported encryption format version `{0}`.")]
+    UnsupportedVersion(u8),
+    #[error("The encrypted data is corrupted or has been truncated.")]
+    Corrupted,
+    #[error(transparent)]
+    IoError(#[from] io::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl From<Error> for io::Error {
+    fn from(error: Error) -> io::Error {
+        match error {
+            Error::IoError(e) => e,
+            e => io::Error::new(io::ErrorKind::InvalidData, e),
+        }
+    }
+}
+
+/// A key used to encrypt and decrypt the data at rest.
+///
+/// It is derived from the secret given by the user, which must have a high entropy.
+#[derive(Clone)]
+pub struct EncryptionKey {
+    key: Arc<LessSafeKey>,
+    fingerprint: [u8; FINGERPRINT_LEN],
+}
+
+impl EncryptionKey {
+    pub fn new(secret: impl AsRef<[u8]>) -> EncryptionKey {
+        let prk = Salt::new(HKDF_SHA256, HKDF_SALT).extract(secret.as_ref());
+
+        let mut key = [0; 32];
+        prk.expand(&[KEY_INFO], HKDF_SHA256)
+            .and_then(|okm| okm.fill(&mut key))
+            .expect("32 bytes is a valid HKDF-SHA256 output length");
+        let key = UnboundKey::new(&AES_256_GCM, &key).expect("AES-256 keys are 32 bytes long");
+
+        let mut hash = [0; 32];
+        prk.expand(&[FINGERPRINT_INFO], HKDF_SHA256)
+            .and_then(|okm| okm.fill(&mut hash))
+            .expect("32 bytes is a valid HKDF-SHA256 output length");
+        let mut fingerprint = [0; FINGERPRINT_LEN];
+        fingerprint.copy_from_slice(&hash[..FINGERPRINT_LEN]);
+
+        EncryptionKey { key: Arc::new(LessSafeKey::new(key)), fingerprint }
+    }
+}
+
+impl fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EncryptionKey").finish_non_exhaustive()
+    }
+}
+
+fn nonce(prefix: &[u8; NONCE_PREFIX_LEN], counter: u32, last: bool) -> Nonce {
+    let mut nonce = [0; aead::NONCE_LEN];
+    nonce[..NONCE_PREFIX_LEN].copy_from_slice(prefix);
+    nonce[NONCE_PREFIX_LEN..NONCE_PREFIX_LEN + 4].copy_from_slice(&counter.to_be_bytes());
+    nonce[aead::NONCE_LEN - 1] = last as u8;
+    Nonce::assume_unique_for_key(nonce)
+}
+
+/// Fills the buffer, a stream that ends too early is considered as truncated.
+fn read_exact(reader: &mut impl Read, buf: &mut [u8]) -> Result<()> {
+    reader.read_exact(buf).map_err(|e| match e.kind() {
+        io::ErrorKind::UnexpectedEof => Error::Corrupted,
+        _ => Error::IoError(e),
+    })
+}
+
+/// Returns `true` if the given bytes start with the header of an encrypted stream.
+pub fn is_encrypted(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+/// Returns the length of the plaintext of an encrypted stream of `len` bytes.
+///
+/// All the chunks but the last one hold exactly `CHUNK_SIZE` bytes of plaintext and the
+/// last one always holds less, the length is thus known without decrypting anything.
+pub fn plaintext_len(len: u64) -> Result<u64> {
+    let body = len.checked_sub(HEADER_LEN as u64).ok_or(Error::Corrupted)?;
+    let full_chunk = (CHUNK_SIZE + CHUNK_OVERHEAD) as u64;
+    if body % full_chunk < CHUNK_OVERHEAD as u64 {
+        return Err(Error::Corrupted);
+    }
+    let chunks = body / full_chunk + 1;
+    Ok(body - chunks * CHUNK_OVERHEAD as u64)
+}
+
+/// A writer that encrypts everything written into it.
+///
+/// [`finish`](EncryptedWriter::finish) *must* be called once everything has been written,
+/// otherwise the stream will be considered as truncated when decrypting it.
+pub struct EncryptedWriter<W: Write> {
+    inner: W,
+    key: EncryptionKey,
+    nonce_prefix: [u8; NONCE_PREFIX_LEN],
+    counter: u32,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> EncryptedWriter<W> {
+    /// Writes the header of the encrypted stream in the writer.
+    pub fn new(mut inner: W, key: &EncryptionKey) -> io::Result<EncryptedWriter<W>> {
+        let mut nonce_prefix = [0; NONCE_PREFIX_LEN];
+        SystemRandom::new().fill(&mut nonce_prefix).map_err(|_| {
+            io::Error::new(io::ErrorKind::Other, "could not generate a random nonce")
+        })?;
+
+        inner.write_all(MAGIC)?;
+        inner.write_all(&[VERSION])?;
+        inner.write_all(&key.fingerprint)?;
+        inner.write_all(&nonce_prefix)?;
+
+        Ok(EncryptedWriter {
+            inner,
+            key: key.clone(),
+            nonce_prefix,
+            counter: 0,
+            buffer: Vec::with_capacity(CHUNK_SIZE + AES_256_GCM.tag_len()),
+        })
+    }
+
+    fn write_chunk(&mut self, last: bool) -> io::Result<()> {
+        let nonce = nonce(&self.nonce_prefix, self.counter, last);
+        self.key
+            .key
+            .seal_in_place_append_tag(nonce, Aad::empty(), &mut self.buffer)
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "could not encrypt a chunk"))?;
+
+        let mut frame = self.buffer.len() as u32;
+        if last {
+            frame |= LAST_CHUNK;
+        }
+        self.inner.write_all(&frame.to_le_bytes())?;
+        self.inner.write_all(&self.buffer)?;
+        self.buffer.clear();
+
+        self.counter = self.counter.checked_add(1).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::Other, "too much data to encrypt in a single stream")
+        })?;
+        Ok(())
+    }
+
+    /// Seals the last chunk and returns the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.write_chunk(true)?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for EncryptedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = buf.len().min(CHUNK_SIZE - self.buffer.len());
+        self.buffer.extend_from_slice(&buf[..len]);
+        if self.buffer.len() == CHUNK_SIZE {
+            self.write_chunk(false)?;
+        }
+        Ok(len)
+    }
+
+    /// Flushes the inner writer, the data that doesn't fill a whole chunk is kept in memory.
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// A reader that decrypts a stream written by an [`EncryptedWriter`].
+pub struct DecryptedReader<R: Read> {
+    inner: R,
+    key: EncryptionKey,
+    nonce_prefix: [u8; NONCE_PREFIX_LEN],
+    counter: u32,
+    plaintext: Vec<u8>,
+    position: usize,
+    finished: bool,
+}
+
+impl<R: Read> DecryptedReader<R> {
+    /// Reads the header of the encrypted stream and checks that it was encrypted with the given key.
+    pub fn new(mut inner: R, key: &EncryptionKey) -> Result<DecryptedReader<R>> {
+        let mut header = [0; HEADER_LEN];
+        read_exact(&mut inner, &mut header)?;
+        DecryptedReader::from_header(inner, &header, key)
+    }
+
+    fn from_header(inner: R, header: &[u8; HEADER_LEN], key: &EncryptionKey) -> Result<Self> {
+        let (magic, header) = header.split_at(MAGIC.len());
+        if magic != MAGIC {
+            return Err(Error::Corrupted);
+        }
+        let (version, header) = header.split_at(1);
+        if version[0] != VERSION {
+            return Err(Error::UnsupportedVersion(version[0]));
+        }
+        let (fingerprint, header) = header.split_at(FINGERPRINT_LEN);
+        if fingerprint != key.fingerprint {
+            return Err(Error::WrongKey);
+        }
+        let mut nonce_prefix = [0; NONCE_PREFIX_LEN];
+        nonce_prefix.copy_from_slice(header);
+
+        Ok(DecryptedReader {
+            inner,
+            key: key.clone(),
+            nonce_prefix,
+            counter: 0,
+            plaintext: Vec::with_capacity(CHUNK_SIZE + AES_256_GCM.tag_len()),
+            position: 0,
+            finished: false,
+        })
+    }
+
+    fn read_chunk(&mut self) -> Result<()> {
+        let mut frame = [0; 4];
+        read_exact(&mut self.inner, &mut frame)?;
+        let frame = u32::from_le_bytes(frame);
+        let last = frame & LAST_CHUNK != 0;
+        let len = (frame & !LAST_CHUNK) as usize;
+        if len > CHUNK_SIZE + AES_256_GCM.tag_len() {
+            return Err(Error::Corrupted);
+        }
+
+        self.plaintext.resize(len, 0);
+        read_exact(&mut self.inner, &mut self.plaintext)?;
+
+        let nonce = nonce(&self.nonce_prefix, self.counter, last);
+        let plaintext_len = self
+            .key
+            .key
+            .open_in_place(nonce, Aad::empty(), &mut self.plaintext)
+            .map_err(|_| Error::Corrupted)?
+            .len();
+        self.plaintext.truncate(plaintext_len);
+
+        self.position = 0;
+        self.finished = last;
+        self.counter = self.counter.checked_add(1).ok_or(Error::Corrupted)?;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for DecryptedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.plaintext.len() {
+            if self.finished {
+                return Ok(0);
+            }
+            self.read_chunk()?;
+        }
+
+        let len = buf.len().min(self.plaintext.len() - self.position);
+        buf[..len].copy_from_slice(&self.plaintext[self.position..self.position + len]);
+        self.position += len;
+        Ok(len)
+    }
+}
+
+/// Only the chunk containing the new position is decrypted, the encrypted stream must start
+/// at the beginning of the inner reader.
+impl<R: Read + Seek> Seek for DecryptedReader<R> {
+    fn seek(&mut self, position: SeekFrom) -> io::Result<u64> {
+        // the loaded chunk is the one before the next chunk to read.
+        let loaded_chunk = self.counter.checked_sub(1).map(u64::from);
+        let current = loaded_chunk.unwrap_or(0) * CHUNK_SIZE as u64 + self.position as u64;
+        let mut len = || -> io::Result<u64> {
+            let inner_position = self.inner.stream_position()?;
+            let len = self.inner.seek(SeekFrom::End(0))?;
+            self.inner.seek(SeekFrom::Start(inner_position))?;
+            Ok(plaintext_len(len)?)
+        };
+        let target = match position {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(delta) => u64::try_from(current as i128 + delta as i128).ok(),
+            SeekFrom::End(delta) => u64::try_from(len()? as i128 + delta as i128).ok(),
+        };
+        let target = match target {
+            Some(target) if target <= len()? => target,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "cannot seek outside of an encrypted stream",
+                ))
+            }
+        };
+
+        let chunk = target / CHUNK_SIZE as u64;
+        if loaded_chunk != Some(chunk) {
+            let chunk_start = HEADER_LEN as u64 + chunk * (CHUNK_SIZE + CHUNK_OVERHEAD) as u64;
+            self.inner.seek(SeekFrom::Start(chunk_start))?;
+            self.counter = u32::try_from(chunk).map_err(|_| Error::Corrupted)?;
+            self.read_chunk()?;
+        }
+        self.position = (target % CHUNK_SIZE as u64) as usize;
+        if self.position > self.plaintext.len() {
+            return Err(Error::Corrupted.into());
+        }
+        Ok(target)
+    }
+}
+
+/// A reader returned by [`decrypt_if_needed`].
+pub enum MaybeDecrypted<R: Read> {
+    Plain(Chain<Cursor<Vec<u8>>, R>),
+    Decrypted(DecryptedReader<R>),
+}
+
+impl<R: Read> Read for MaybeDecrypted<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            MaybeDecrypted::Plain(reader) => reader.read(buf),
+            MaybeDecrypted::Decrypted(reader) => reader.read(buf),
+        }
+    }
+}
+
+/// Returns a reader over the plaintext of the given reader.
+///
+/// The data is decrypted if it starts with the header of an encrypted stream and is
+/// returned as is otherwise, so that data written before the encryption was enabled
+/// can still be read.
+pub fn decrypt_if_needed<R: Read>(
+    mut reader: R,
+    key: Option<&EncryptionKey>,
+) -> Result<MaybeDecrypted<R>> {
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    (&mut reader).take(HEADER_LEN as u64).read_to_end(&mut header)?;
+
+    if !is_encrypted(&header) {
+        return Ok(MaybeDecrypted::Plain(Cursor::new(header).chain(reader)));
+    }
+
+    let key = key.ok_or(Error::MissingKey)?;
+    let header: [u8; HEADER_LEN] = header.try_into().map_err(|_| Error::Corrupted)?;
+    DecryptedReader::from_header(reader, &header, key).map(MaybeDecrypted::Decrypted)
+}
+
+#[cfg(test)]
+mod test {
+    use meili_snap::snapshot;
+
+    use super::*;
+
+    fn encrypt(content: &[u8], key: &EncryptionKey) -> Vec<u8> {
+        let mut writer = EncryptedWriter::new(Vec::new(), key).unwrap();
+        writer.write_all(content).unwrap();
+        writer.finish().unwrap()
+    }
+
+    fn decrypt(content: &[u8], key: Option<&EncryptionKey>) -> io::Result<Vec<u8>> {
+        let mut reader = decrypt_if_needed(content, key)?;
+        let mut plaintext = Vec::new();
+        reader.read_to_end(&mut plaintext)?;
+        Ok(plaintext)
+    }
+
+    #[test]
+    fn encrypt_and_decrypt() {
+        let key = EncryptionKey::new("a very secret key");
+
+        for len in [0, 1, 42, CHUNK_SIZE - 1, CHUNK_SIZE, CHUNK_SIZE + 1, 3 * CHUNK_SIZE + 12] {
+            let content: Vec<u8> = (0..len).map(|i| i as u8).collect();
+            let encrypted = encrypt(&content, &key);
+            assert!(is_encrypted(&encrypted));
+            assert_ne!(&encrypted[HEADER_LEN..], &content[..]);
+            assert_eq!(decrypt(&encrypted, Some(&key)).unwrap(), content);
+        }
+    }
+
+    #[test]
+    fn seek_in_encrypted_stream() {
+        let key = EncryptionKey::new("a very secret key");
+
+        for len in [0, 42, CHUNK_SIZE, 3 * CHUNK_SIZE + 12] {
+            let content: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+            let encrypted = encrypt(&content, &key);
+            assert_eq!(plaintext_len(encrypted.len() as u64).unwrap(), len as u64);
+
+            let mut reader = DecryptedReader::new(Cursor::new(&encrypted), &key).unwrap();
+            for offset in [len, len / 2, CHUNK_SIZE.min(len), 0, len.saturating_sub(1)] {
+                assert_eq!(reader.seek(SeekFrom::Start(offset as u64)).unwrap(), offset as u64);
+                let mut rest = Vec::new();
+                reader.read_to_end(&mut rest).unwrap();
+                assert_eq!(rest, &content[offset..]);
+            }
+
+            assert_eq!(reader.seek(SeekFrom::End(0)).unwrap(), len as u64);
+            assert!(reader.seek(SeekFrom::End(1)).is_err());
+            let offset = reader.seek(SeekFrom::Current(-(len as i64 / 3))).unwrap() as usize;
+            assert_eq!(offset, len - len / 3);
+            let mut rest = Vec::new();
+            reader.read_to_end(&mut rest).unwrap();
+            assert_eq!(rest, &content[offset..]);
+        }
+    }
+
+    #[test]
+    fn plaintext_is_returned_as_is() {
+        let key = EncryptionKey::new("a very secret key");
+        for content in [&b""[..], b"MEILI", br#"{ "id": 1, "name": "kefir" }"#] {
+            assert_eq!(decrypt(content, Some(&key)).unwrap(), content);
+            assert_eq!(decrypt(content, None).unwrap(), content);
+        }
+    }
+
+    #[test]
+    fn missing_or_wrong_key() {
+        let key = EncryptionKey::new("a very secret key");
+        let encrypted = encrypt(b"kefir is a good doggo", &key);
+
+        let error = decrypt(&encrypted, None).unwrap_err();
+        snapshot!(error, @"The data is encrypted but no encryption key was provided.");
+
+        let wrong_key = EncryptionKey::new("another key");
+        let error = decrypt(&encrypted, Some(&wrong_key)).unwrap_err();
+        snapshot!(error, @"The provided encryption key does not match the key used to encrypt the data.");
+    }
+
+    #[test]
+    fn truncated_or_tampered_data() {
+        let key = EncryptionKey::new("a very secret key");
+        let content = vec![12; 2 * CHUNK_SIZE + 12];
+        let encrypted = encrypt(&content, &key);
+
+        // the last chunk is missing
+        let truncated = &encrypted[..HEADER_LEN + 2 * (4 + CHUNK_SIZE + AES_256_GCM.tag_len())];
+        let error = decrypt(truncated, Some(&key)).unwrap_err();
+        snapshot!(error, @"The encrypted data is corrupted or has been truncated.");
+
+        // a byte of the ciphertext has been modified
+        let mut tampered = encrypted.clone();
+        tampered[HEADER_LEN + 12] ^= 1;
+        let error = decrypt(&tampered, Some(&key)).unwrap_err();
+        snapshot!(error, @"The encrypted data is corrupted or has been truncated.");
+
+        // the first chunk has been marked as the last one
+        let mut tampered = encrypted;
+        tampered[HEADER_LEN + 3] |= 0x80;
+        let error = decrypt(&tampered, Some(&key)).unwrap_err();
+        snapshot!(error, @"The encrypted data is corrupted or has been truncated.");
+    }
+}
@@ -1,12 +1,16 @@
 use std::collections::BTreeSet;
 use std::fs::File as StdFile;
-use std::ops::{Deref, DerefMut};
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 use tempfile::NamedTempFile;
 use uuid::Uuid;
 
+use crate::encryption::{DecryptedReader, EncryptedWriter, EncryptionKey};
+
+pub mod encryption;
+
 const UPDATE_FILES_PATH: &str = "updates/updates_files";
 
 #[derive(Debug, thiserror::Error)]
@@ -15,36 +19,26 @@ pub enum Error {
     IoError(#[from] std::io::Error),
     #[error(transparent)]
     PersistError(#[from] tempfile::PersistError),
+    #[error(transparent)]
+    EncryptionError(#[from] encryption::Error),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
 
-impl Deref for File {
-    type Target = NamedTempFile;
-
-    fn deref(&self) -> &Self::Target {
-        &self.file
-    }
-}
-
-impl DerefMut for File {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.file
-    }
-}
-
 #[cfg_attr(test, faux::create)]
 #[derive(Clone, Debug)]
 pub struct FileStore {
     path: PathBuf,
+    /// The key used to encrypt the update files while they are written, if any.
+    encryption_key: Option<EncryptionKey>,
 }
 
 #[cfg(not(test))]
 impl FileStore {
-    pub fn new(path: impl AsRef<Path>) -> Result<FileStore> {
+    pub fn new(path: impl AsRef<Path>, encryption_key: Option<EncryptionKey>) -> Result<FileStore> {
         let path = path.as_ref().to_path_buf();
         std::fs::create_dir_all(&path)?;
-        Ok(FileStore { path })
+        Ok(FileStore { path, encryption_key })
     }
 }
 
@@ -56,7 +50,7 @@ impl FileStore {
         let file = NamedTempFile::new_in(&self.path)?;
         let uuid = Uuid::new_v4();
         let path = self.path.join(uuid.to_string());
-        let update_file = File { file, path };
+        let update_file = File::new(file, path, self.encryption_key.as_ref())?;
 
         Ok((uuid, update_file))
     }
@@ -67,24 +61,35 @@ impl FileStore {
         let file = NamedTempFile::new_in(&self.path)?;
         let uuid = Uuid::from_u128(uuid);
         let path = self.path.join(uuid.to_string());
-        let update_file = File { file, path };
+        let update_file = File::new(file, path, self.encryption_key.as_ref())?;
 
         Ok((uuid, update_file))
     }
 
-    /// Returns the file corresponding to the requested uuid.
-    pub fn get_update(&self, uuid: Uuid) -> Result<StdFile> {
+    /// Returns the content of the file corresponding to the requested uuid.
+    ///
+    /// If the file is encrypted, its content is decrypted on the fly while it is read.
+    pub fn get_update(&self, uuid: Uuid) -> Result<UpdateReader> {
         let path = self.get_update_path(uuid);
-        let file = StdFile::open(path)?;
-        Ok(file)
+        let mut file = StdFile::open(path)?;
+        if !is_encrypted_file(&mut file)? {
+            return Ok(UpdateReader::Plain(file));
+        }
+        let key = self.encryption_key.as_ref().ok_or(encryption::Error::MissingKey)?;
+        let reader = DecryptedReader::new(BufReader::new(file), key)?;
+        Ok(UpdateReader::Decrypted(reader))
     }
 
     /// Returns the path that correspond to this uuid, the path could not exists.
+    ///
+    /// The content of this file may be encrypted, use [`get_update`](FileStore::get_update) to read it.
     pub fn get_update_path(&self, uuid: Uuid) -> PathBuf {
         self.path.join(uuid.to_string())
     }
 
     /// Copies the content of the update file pointed to by `uuid` to the `dst` directory.
+    ///
+    /// The file is copied as is, an encrypted update file stays encrypted.
     pub fn snapshot(&self, uuid: Uuid, dst: impl AsRef<Path>) -> Result<()> {
         let src = self.path.join(uuid.to_string());
         let mut dst = dst.as_ref().join(UPDATE_FILES_PATH);
@@ -94,8 +99,15 @@ impl FileStore {
         Ok(())
     }
 
+    /// Returns the size of the content of the file, without decrypting it.
     pub fn get_size(&self, uuid: Uuid) -> Result<u64> {
-        Ok(self.get_update(uuid)?.metadata()?.len())
+        let mut file = StdFile::open(self.get_update_path(uuid))?;
+        let len = file.metadata()?.len();
+        if is_encrypted_file(&mut file)? {
+            Ok(encryption::plaintext_len(len)?)
+        } else {
+            Ok(len)
+        }
     }
 
     pub fn delete(&self, uuid: Uuid) -> Result<()> {
@@ -119,14 +131,122 @@ impl FileStore {
     }
 }
 
+/// Returns `true` if the file starts with the header of an encrypted stream, the file is
+/// then rewound.
+fn is_encrypted_file(file: &mut StdFile) -> Result<bool> {
+    let mut header = Vec::new();
+    (&mut *file).take(encryption::HEADER_LEN as u64).read_to_end(&mut header)?;
+    file.seek(SeekFrom::Start(0))?;
+    Ok(encryption::is_encrypted(&header))
+}
+
+/// The content of an update file, decrypted on the fly if it is encrypted.
+pub enum UpdateReader {
+    Plain(StdFile),
+    Decrypted(DecryptedReader<BufReader<StdFile>>),
+}
+
+impl Read for UpdateReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            UpdateReader::Plain(file) => file.read(buf),
+            UpdateReader::Decrypted(reader) => reader.read(buf),
+        }
+    }
+}
+
+impl Seek for UpdateReader {
+    fn seek(&mut self, position: SeekFrom) -> io::Result<u64> {
+        match self {
+            UpdateReader::Plain(file) => file.seek(position),
+            UpdateReader::Decrypted(reader) => reader.seek(position),
+        }
+    }
+}
+
+/// A new update file, everything written into it is encrypted on the way if an encryption
+/// key was given to the file store, the plaintext never reaches the disk.
 pub struct File {
     path: PathBuf,
     file: NamedTempFile,
+    writer: UpdateWriter,
+}
+
+enum UpdateWriter {
+    Plain(BufWriter<StdFile>),
+    Encrypted(EncryptedWriter<BufWriter<StdFile>>),
 }
 
 impl File {
+    fn new(file: NamedTempFile, path: PathBuf, key: Option<&EncryptionKey>) -> Result<File> {
+        let writer = BufWriter::new(file.reopen()?);
+        let writer = match key {
+            Some(key) => UpdateWriter::Encrypted(EncryptedWriter::new(writer, key)?),
+            None => UpdateWriter::Plain(writer),
+        };
+        Ok(File { path, file, writer })
+    }
+
+    /// Moves the file to its final location in the file store.
     pub fn persist(self) -> Result<()> {
+        let writer = match self.writer {
+            UpdateWriter::Plain(writer) => writer,
+            UpdateWriter::Encrypted(writer) => writer.finish()?,
+        };
+        writer.into_inner().map_err(|e| e.into_error())?;
         self.file.persist(&self.path)?;
         Ok(())
     }
 }
+
+impl Write for File {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match &mut self.writer {
+            UpdateWriter::Plain(writer) => writer.write(buf),
+            UpdateWriter::Encrypted(writer) => writer.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.writer {
+            UpdateWriter::Plain(writer) => writer.flush(),
+            UpdateWriter::Encrypted(writer) => writer.flush(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn update_files_are_encrypted_while_written() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = EncryptionKey::new("a very secret key");
+        let content = b"{ \"id\": 1, \"name\": \"kefir\" }";
+
+        let path = dir.path().join("update");
+        let file = NamedTempFile::new_in(dir.path()).unwrap();
+        let mut file = File::new(file, path.clone(), Some(&key)).unwrap();
+        file.write_all(content).unwrap();
+        file.flush().unwrap();
+        file.persist().unwrap();
+
+        let encrypted = std::fs::read(&path).unwrap();
+        assert!(encryption::is_encrypted(&encrypted));
+        assert!(!encrypted.windows(5).any(|window| window == b"kefir"));
+        assert_eq!(
+            encryption::plaintext_len(encrypted.len() as u64).unwrap(),
+            content.len() as u64
+        );
+
+        let mut file = StdFile::open(&path).unwrap();
+        assert!(is_encrypted_file(&mut file).unwrap());
+        let mut reader =
+            UpdateReader::Decrypted(DecryptedReader::new(BufReader::new(file), &key).unwrap());
+        reader.seek(SeekFrom::Start(19)).unwrap();
+        let mut rest = String::new();
+        reader.read_to_string(&mut rest).unwrap();
+        assert_eq!(rest, "\"kefir\" }");
+    }
+}
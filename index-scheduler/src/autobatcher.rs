@@ -60,7 +60,9 @@ impl From<KindWithContent> for AutobatchKind {
             KindWithContent::TaskCancelation { .. }
             | KindWithContent::TaskDeletion { .. }
             | KindWithContent::DumpCreation { .. }
-            | KindWithContent::SnapshotCreation => {
+            | KindWithContent::DumpImport { .. }
+            | KindWithContent::SnapshotCreation
+            | KindWithContent::SuggestionsRefresh => {
                 panic!("The autobatcher should never be called with tasks that don't apply to an index.")
             }
         }
@@ -19,8 +19,8 @@ one indexing operation.
 
 use std::collections::{BTreeSet, HashSet};
 use std::ffi::OsStr;
-use std::fs::{self, File};
-use std::io::BufWriter;
+use std::fs;
+use std::io::{BufWriter, Read};
 
 use dump::IndexMetadata;
 use log::{debug, error, info};
@@ -29,12 +29,18 @@ use meilisearch_types::milli::documents::{obkv_to_object, DocumentsBatchReader};
 use meilisearch_types::milli::heed::CompactionOption;
 use meilisearch_types::milli::update::{
     DocumentAdditionResult, DocumentDeletionResult, IndexDocumentsConfig, IndexDocumentsMethod,
-    Settings as MilliSettings,
+    Setting, Settings as MilliSettings,
 };
 use meilisearch_types::milli::{self, BEU32};
-use meilisearch_types::settings::{apply_settings_to_builder, Settings, Unchecked};
-use meilisearch_types::tasks::{Details, IndexSwap, Kind, KindWithContent, Status, Task};
-use meilisearch_types::{compression, Index, VERSION_FILE_NAME};
+use meilisearch_types::settings::{
+    apply_settings_to_builder, apply_settings_to_index, resolve_stop_words, Settings, Unchecked,
+};
+use meilisearch_types::tasks::{
+    ConflictPolicy, Details, DumpSource, IndexSwap, Kind, KindWithContent, Status, Task,
+};
+use meilisearch_types::{
+    compression, settings_history, suggestions, vectors, Index, VERSION_FILE_NAME,
+};
 use roaring::RoaringBitmap;
 use time::macros::format_description;
 use time::OffsetDateTime;
@@ -44,6 +50,9 @@ use crate::autobatcher::{self, BatchKind};
 use crate::utils::{self, swap_index_uid_in_task};
 use crate::{Error, IndexScheduler, ProcessingTasks, Result, TaskId};
 
+/// The maximum number of popular queries of an index added to its suggestions.
+const MAX_SUGGESTED_QUERIES: usize = 1000;
+
 /// Represents a combination of tasks that can all be processed at the same time.
 ///
 /// A batch contains the set of tasks that it represents (accessible through
@@ -61,7 +70,9 @@ pub(crate) enum Batch {
     },
     TaskDeletion(Task),
     SnapshotCreation(Vec<Task>),
+    SuggestionsRefresh(Vec<Task>),
     Dump(Task),
+    DumpImport(Task),
     IndexOperation {
         op: IndexOperation,
         must_create_index: bool,
@@ -143,11 +154,12 @@ impl Batch {
             Batch::TaskCancelation { task, .. }
             | Batch::TaskDeletion(task)
             | Batch::Dump(task)
+            | Batch::DumpImport(task)
             | Batch::IndexCreation { task, .. }
             | Batch::IndexUpdate { task, .. } => vec![task.uid],
-            Batch::SnapshotCreation(tasks) | Batch::IndexDeletion { tasks, .. } => {
-                tasks.iter().map(|task| task.uid).collect()
-            }
+            Batch::SnapshotCreation(tasks)
+            | Batch::SuggestionsRefresh(tasks)
+            | Batch::IndexDeletion { tasks, .. } => tasks.iter().map(|task| task.uid).collect(),
             Batch::IndexOperation { op, .. } => match op {
                 IndexOperation::DocumentImport { tasks, .. }
                 | IndexOperation::DocumentDeletion { tasks, .. }
@@ -409,7 +421,8 @@ impl IndexScheduler {
     /// 2. We get the *next* task to delete.
     /// 3. We get the *next* snapshot to process.
     /// 4. We get the *next* dump to process.
-    /// 5. We get the *next* tasks to process for a specific index.
+    /// 5. We get the suggestions refreshes to process.
+    /// 6. We get the *next* tasks to process for a specific index.
     pub(crate) fn create_next_batch(&self, rtxn: &RoTxn) -> Result<Option<Batch>> {
         #[cfg(test)]
         self.maybe_fail(crate::tests::FailureLocation::InsideCreateBatch)?;
@@ -451,16 +464,24 @@ impl IndexScheduler {
             )));
         }
 
-        // 5. We make a batch from the unprioritised tasks. Start by taking the next enqueued task.
+        // 5. we batch the suggestions refreshes, refreshing once is enough for all of them.
+        let to_refresh = self.get_kind(rtxn, Kind::SuggestionsRefresh)? & enqueued;
+        if !to_refresh.is_empty() {
+            return Ok(Some(Batch::SuggestionsRefresh(self.get_existing_tasks(rtxn, to_refresh)?)));
+        }
+
+        // 6. We make a batch from the unprioritised tasks. Start by taking the next enqueued task.
         let task_id = if let Some(task_id) = enqueued.min() { task_id } else { return Ok(None) };
         let task = self.get_task(rtxn, task_id)?.ok_or(Error::CorruptedTaskQueue)?;
 
-        // If the task is not associated with any index, verify that it is an index swap and
-        // create the batch directly. Otherwise, get the index name associated with the task
-        // and use the autobatcher to batch the enqueued tasks associated with it
+        // If the task is not associated with any index, verify that it is a dump import or an
+        // index swap and create the batch directly. Otherwise, get the index name associated
+        // with the task and use the autobatcher to batch the enqueued tasks associated with it
 
         let index_name = if let Some(&index_name) = task.indexes().first() {
             index_name
+        } else if matches!(task.kind, KindWithContent::DumpImport { .. }) {
+            return Ok(Some(Batch::DumpImport(task)));
         } else {
             assert!(matches!(&task.kind, KindWithContent::IndexSwap { swaps } if swaps.is_empty()));
             return Ok(Some(Batch::IndexSwap { task }));
@@ -590,7 +611,22 @@ impl IndexScheduler {
                 Ok(vec![task])
             }
             Batch::SnapshotCreation(mut tasks) => {
-                fs::create_dir_all(&self.snapshots_path)?;
+                // Find the original name of the database
+                // TODO find a better way to get this path
+                let mut base_path = self.env.path().to_owned();
+                base_path.pop();
+                let db_name = base_path.file_name().and_then(OsStr::to_str).unwrap_or("data.ms");
+
+                if self.incremental_snapshots {
+                    self.create_incremental_snapshot(db_name)?;
+
+                    for task in &mut tasks {
+                        task.status = Status::Succeeded;
+                    }
+
+                    return Ok(tasks);
+                }
+
                 let temp_snapshot_dir = tempfile::tempdir()?;
 
                 // 1. Snapshot the version file.
@@ -656,23 +692,41 @@ impl IndexScheduler {
                 auth.copy_to_path(dst.join("data.mdb"), CompactionOption::Enabled)?;
 
                 // 5. Copy and tarball the flat snapshot
-                // 5.1 Find the original name of the database
-                // TODO find a better way to get this path
-                let mut base_path = self.env.path().to_owned();
-                base_path.pop();
-                let db_name = base_path.file_name().and_then(OsStr::to_str).unwrap_or("data.ms");
-
-                // 5.2 Tarball the content of the snapshot in a tempfile with a .snapshot extension
-                let snapshot_path = self.snapshots_path.join(format!("{}.snapshot", db_name));
+                // 5.1 Tarball the content of the snapshot in a tempfile
                 let temp_snapshot_file = tempfile::NamedTempFile::new_in(&self.snapshots_path)?;
-                compression::to_tar_gz(temp_snapshot_dir.path(), temp_snapshot_file.path())?;
-                let file = temp_snapshot_file.persist(snapshot_path)?;
+                compression::to_tar_gz(
+                    temp_snapshot_dir.path(),
+                    temp_snapshot_file.path(),
+                    self.encryption_key.as_ref(),
+                )?;
 
-                // 5.3 Change the permission to make the snapshot readonly
+                // 5.2 Change the permission to make the snapshot readonly
+                let file = temp_snapshot_file.as_file();
                 let mut permissions = file.metadata()?.permissions();
                 permissions.set_readonly(true);
                 file.set_permissions(permissions)?;
 
+                // 5.3 Store the snapshot with a .snapshot extension
+                self.snapshots_storage
+                    .put(&format!("{}.snapshot", db_name), temp_snapshot_file.path())?;
+
+                for task in &mut tasks {
+                    task.status = Status::Succeeded;
+                }
+
+                Ok(tasks)
+            }
+            Batch::SuggestionsRefresh(mut tasks) => {
+                let rtxn = self.env.read_txn()?;
+                for (index_uid, index) in self.index_mapper.indexes(&rtxn)? {
+                    let popular_queries =
+                        self.search_analytics.popular_queries(&index_uid, MAX_SUGGESTED_QUERIES);
+
+                    let mut index_wtxn = index.write_txn()?;
+                    suggestions::update_suggestions(&index, &mut index_wtxn, popular_queries)?;
+                    index_wtxn.commit()?;
+                }
+
                 for task in &mut tasks {
                     task.status = Status::Succeeded;
                 }
@@ -681,64 +735,79 @@ impl IndexScheduler {
             }
             Batch::Dump(mut task) => {
                 let started_at = OffsetDateTime::now_utc();
-                let (keys, instance_uid) =
-                    if let KindWithContent::DumpCreation { keys, instance_uid } = &task.kind {
-                        (keys, instance_uid)
+                let (keys, instance_uid, content) =
+                    if let KindWithContent::DumpCreation { keys, instance_uid, content } =
+                        &task.kind
+                    {
+                        (keys, instance_uid, content)
                     } else {
                         unreachable!();
                     };
-                let dump = dump::DumpWriter::new(*instance_uid)?;
+                let dump = dump::DumpWriter::new(*instance_uid, content.clone())?;
 
                 // 1. dump the keys
                 let mut dump_keys = dump.create_keys()?;
-                for key in keys {
-                    dump_keys.push_key(key)?;
+                if content.keys {
+                    for key in keys {
+                        dump_keys.push_key(key)?;
+                    }
                 }
                 dump_keys.flush()?;
 
                 let rtxn = self.env.read_txn()?;
 
-                // 2. dump the tasks
+                // 2. dump the tasks, only keeping the ones that only concern the dumped indexes
                 let mut dump_tasks = dump.create_tasks_queue()?;
-                for ret in self.all_tasks.iter(&rtxn)? {
-                    let (_, mut t) = ret?;
-                    let status = t.status;
-                    let content_file = t.content_uuid();
-
-                    // In the case we're dumping ourselves we want to be marked as finished
-                    // to not loop over ourselves indefinitely.
-                    if t.uid == task.uid {
-                        let finished_at = OffsetDateTime::now_utc();
-
-                        // We're going to fake the date because we don't know if everything is going to go well.
-                        // But we need to dump the task as finished and successful.
-                        // If something fail everything will be set appropriately in the end.
-                        t.status = Status::Succeeded;
-                        t.started_at = Some(started_at);
-                        t.finished_at = Some(finished_at);
-                    }
-                    let mut dump_content_file = dump_tasks.push_task(&t.into())?;
-
-                    // 2.1. Dump the `content_file` associated with the task if there is one and the task is not finished yet.
-                    if let Some(content_file) = content_file {
-                        if status == Status::Enqueued {
-                            let content_file = self.file_store.get_update(content_file)?;
-
-                            let reader = DocumentsBatchReader::from_reader(content_file)
-                                .map_err(milli::Error::from)?;
-
-                            let (mut cursor, documents_batch_index) =
-                                reader.into_cursor_and_fields_index();
-
-                            while let Some(doc) =
-                                cursor.next_document().map_err(milli::Error::from)?
-                            {
-                                dump_content_file.push_document(&obkv_to_object(
-                                    &doc,
-                                    &documents_batch_index,
-                                )?)?;
+                if content.tasks {
+                    for ret in self.all_tasks.iter(&rtxn)? {
+                        let (_, mut t) = ret?;
+                        if !t.indexes().iter().all(|index| content.contains_index(index)) {
+                            continue;
+                        }
+                        let status = t.status;
+                        // Only the document additions have a content file made of documents.
+                        let content_file = match t.kind {
+                            KindWithContent::DocumentAdditionOrUpdate { content_file, .. } => {
+                                Some(content_file)
+                            }
+                            _ => None,
+                        };
+
+                        // In the case we're dumping ourselves we want to be marked as finished
+                        // to not loop over ourselves indefinitely.
+                        if t.uid == task.uid {
+                            let finished_at = OffsetDateTime::now_utc();
+
+                            // We're going to fake the date because we don't know if everything is going to go well.
+                            // But we need to dump the task as finished and successful.
+                            // If something fail everything will be set appropriately in the end.
+                            t.status = Status::Succeeded;
+                            t.started_at = Some(started_at);
+                            t.finished_at = Some(finished_at);
+                        }
+                        let mut dump_content_file = dump_tasks.push_task(&t.into())?;
+
+                        // 2.1. Dump the `content_file` associated with the task if there is one and the task is not finished yet.
+                        if let Some(content_file) = content_file {
+                            if status == Status::Enqueued {
+                                let content_file = self.file_store.get_update(content_file)?;
+
+                                let reader = DocumentsBatchReader::from_reader(content_file)
+                                    .map_err(milli::Error::from)?;
+
+                                let (mut cursor, documents_batch_index) =
+                                    reader.into_cursor_and_fields_index();
+
+                                while let Some(doc) =
+                                    cursor.next_document().map_err(milli::Error::from)?
+                                {
+                                    dump_content_file.push_document(&obkv_to_object(
+                                        &doc,
+                                        &documents_batch_index,
+                                    )?)?;
+                                }
+                                dump_content_file.flush()?;
                             }
-                            dump_content_file.flush()?;
                         }
                     }
                 }
@@ -746,6 +815,9 @@ impl IndexScheduler {
 
                 // 3. Dump the indexes
                 for (uid, index) in self.index_mapper.indexes(&rtxn)? {
+                    if !content.contains_index(&uid) {
+                        continue;
+                    }
                     let rtxn = index.read_txn()?;
                     let metadata = IndexMetadata {
                         uid: uid.clone(),
@@ -774,15 +846,134 @@ impl IndexScheduler {
                     "[year repr:full][month repr:numerical][day padding:zero]-[hour padding:zero][minute padding:zero][second padding:zero][subsecond digits:3]"
                 )).unwrap();
 
-                let path = self.dumps_path.join(format!("{}.dump", dump_uid));
-                let file = File::create(path)?;
-                dump.persist_to(BufWriter::new(file))?;
+                let mut file = tempfile::NamedTempFile::new_in(&self.dumps_path)?;
+                dump.persist_to(BufWriter::new(file.as_file_mut()), self.encryption_key.as_ref())?;
+                self.dumps_storage.put(&format!("{}.dump", dump_uid), file.path())?;
 
                 // if we reached this step we can tell the scheduler we succeeded to dump ourselves.
                 task.status = Status::Succeeded;
                 task.details = Some(Details::Dump { dump_uid: Some(dump_uid) });
                 Ok(vec![task])
             }
+            Batch::DumpImport(mut task) => {
+                let (source, renames, on_conflict) = if let KindWithContent::DumpImport {
+                    source,
+                    renames,
+                    on_conflict,
+                } = &task.kind
+                {
+                    (source, renames, *on_conflict)
+                } else {
+                    unreachable!();
+                };
+
+                // 1. Open the dump, either from the update files or from the dumps storage.
+                let reader: Box<dyn Read + Send> = match source {
+                    DumpSource::Upload { content_file } => {
+                        Box::new(self.file_store.get_update(*content_file)?)
+                    }
+                    DumpSource::Path { path } => match self.dumps_storage.get(path) {
+                        Ok(reader) => reader,
+                        Err(crate::storage::Error::NotFound(_)) => {
+                            return Err(Error::DumpNotFound(path.clone()))
+                        }
+                        Err(e) => return Err(e.into()),
+                    },
+                };
+                let dump_reader = dump::DumpReader::open(reader, self.encryption_key.as_ref())?;
+                let index_readers =
+                    dump_reader.indexes()?.collect::<std::result::Result<Vec<_>, _>>()?;
+
+                // 2. Compute the uid of each imported index and make sure they're all unique.
+                for uid in renames.keys() {
+                    if !index_readers.iter().any(|reader| &reader.metadata().uid == uid) {
+                        return Err(Error::IndexNotFoundInDump(uid.clone()));
+                    }
+                }
+                let mut targets = HashSet::new();
+                for reader in &index_readers {
+                    let uid = &reader.metadata().uid;
+                    let target = renames.get(uid).unwrap_or(uid);
+                    if !targets.insert(target.clone()) {
+                        return Err(Error::DumpImportDuplicateIndexFound(target.clone()));
+                    }
+                }
+
+                // 3. Apply the conflict policy before modifying anything.
+                let rtxn = self.env.read_txn()?;
+                let mut to_import = Vec::new();
+                let mut skipped_indexes = Vec::new();
+                for reader in index_readers {
+                    let uid = &reader.metadata().uid;
+                    let target = renames.get(uid).unwrap_or(uid).clone();
+                    if self.index_mapper.exists(&rtxn, &target)? {
+                        match on_conflict {
+                            ConflictPolicy::Fail => return Err(Error::IndexAlreadyExists(target)),
+                            ConflictPolicy::Skip => {
+                                skipped_indexes.push(target);
+                                continue;
+                            }
+                            ConflictPolicy::Overwrite => (),
+                        }
+                    }
+                    to_import.push((target, reader));
+                }
+                drop(rtxn);
+
+                // 4. Retrieve or create the indexes, the new ones keep their dumped dates.
+                let mut created_indexes = Vec::new();
+                let mut indexes = Vec::new();
+                for (target, reader) in to_import {
+                    let rtxn = self.env.read_txn()?;
+                    let index = if self.index_mapper.exists(&rtxn, &target)? {
+                        self.index_mapper.index(&rtxn, &target)?
+                    } else {
+                        drop(rtxn);
+                        let metadata = reader.metadata();
+                        let date = Some((metadata.created_at, metadata.updated_at));
+                        let wtxn = self.env.write_txn()?;
+                        let index = self.index_mapper.create_index(wtxn, &target, date)?;
+                        created_indexes.push(target.clone());
+                        index
+                    };
+                    indexes.push((target, index, reader));
+                }
+
+                // 5. Import every index and only commit once all of them succeeded.
+                let result = (|| -> Result<Vec<_>> {
+                    let mut index_wtxns = Vec::new();
+                    for (target, index, reader) in indexes.iter_mut() {
+                        info!("Importing index `{}` from the dump.", target);
+                        let mut index_wtxn = index.write_txn()?;
+                        self.import_dumped_index(&mut index_wtxn, index, reader)?;
+                        index_wtxns.push(index_wtxn);
+                    }
+                    Ok(index_wtxns)
+                })();
+
+                match result {
+                    Ok(index_wtxns) => {
+                        for index_wtxn in index_wtxns {
+                            index_wtxn.commit()?;
+                        }
+                    }
+                    Err(e) => {
+                        // Remove the indexes we created as nothing has been imported in them.
+                        for uid in created_indexes {
+                            let wtxn = self.env.write_txn()?;
+                            self.index_mapper.delete_index(wtxn, &uid)?;
+                        }
+                        return Err(e);
+                    }
+                }
+
+                task.status = Status::Succeeded;
+                task.details = Some(Details::DumpImport {
+                    imported_indexes: Some(indexes.into_iter().map(|(uid, _, _)| uid).collect()),
+                    skipped_indexes: Some(skipped_indexes),
+                });
+                Ok(vec![task])
+            }
             Batch::IndexOperation { op, must_create_index } => {
                 let index_uid = op.index_uid();
                 let index = if must_create_index {
@@ -956,6 +1147,7 @@ impl IndexScheduler {
         match operation {
             IndexOperation::DocumentClear { mut tasks, .. } => {
                 let count = milli::update::ClearDocuments::new(index_wtxn, index).execute()?;
+                vectors::update_vectors(index, index_wtxn, &RoaringBitmap::new())?;
 
                 let mut first_clear_found = false;
                 for task in &mut tasks {
@@ -1011,12 +1203,16 @@ impl IndexScheduler {
                 )?;
 
                 let mut results = Vec::new();
+                let mut imported_files = Vec::new();
                 for content_uuid in content_files.into_iter() {
                     let content_file = self.file_store.get_update(content_uuid)?;
                     let reader = DocumentsBatchReader::from_reader(content_file)
                         .map_err(milli::Error::from)?;
                     let (new_builder, user_result) = builder.add_documents(reader)?;
                     builder = new_builder;
+                    if user_result.is_ok() {
+                        imported_files.push(content_uuid);
+                    }
 
                     let user_result = match user_result {
                         Ok(count) => Ok(DocumentAdditionResult {
@@ -1032,6 +1228,10 @@ impl IndexScheduler {
                 if results.iter().any(|res| res.is_ok()) {
                     let addition = builder.execute()?;
                     info!("document addition done: {:?}", addition);
+
+                    // The vectors of the `_vectors` field must follow the documents.
+                    let imported = self.imported_docids(index_wtxn, index, &imported_files)?;
+                    vectors::update_vectors(index, index_wtxn, &imported)?;
                 } else if primary_key_has_been_set {
                     // Everything failed but we've set a primary key.
                     // We need to remove it.
@@ -1075,6 +1275,7 @@ impl IndexScheduler {
                 });
 
                 let DocumentDeletionResult { deleted_documents, .. } = builder.execute()?;
+                vectors::update_vectors(index, index_wtxn, &RoaringBitmap::new())?;
 
                 for (task, documents) in tasks.iter_mut().zip(documents) {
                     task.status = Status::Succeeded;
@@ -1087,13 +1288,23 @@ impl IndexScheduler {
                 Ok(tasks)
             }
             IndexOperation::Settings { index_uid: _, settings, mut tasks } => {
+                let all_checked_settings: Vec<_> =
+                    settings.iter().map(|(_, settings)| settings.clone().check()).collect();
+                let stop_words = resolve_stop_words(index, index_wtxn, &all_checked_settings)?;
+
                 let indexer_config = self.index_mapper.indexer_config();
                 let mut builder = milli::update::Settings::new(index_wtxn, index, indexer_config);
+                match stop_words {
+                    Setting::Set(stop_words) => builder.set_stop_words(stop_words),
+                    Setting::Reset => builder.reset_stop_words(),
+                    Setting::NotSet => (),
+                }
 
-                for (task, (_, settings)) in tasks.iter_mut().zip(settings) {
-                    let checked_settings = settings.clone().check();
+                for ((task, (_, settings)), checked_settings) in
+                    tasks.iter_mut().zip(settings).zip(&all_checked_settings)
+                {
                     task.details = Some(Details::SettingsUpdate { settings: Box::new(settings) });
-                    apply_settings_to_builder(&checked_settings, &mut builder);
+                    apply_settings_to_builder(checked_settings, &mut builder);
 
                     // We can apply the status right now and if an update fail later
                     // the whole batch will be marked as failed.
@@ -1106,6 +1317,15 @@ impl IndexScheduler {
                     || must_stop_processing.get(),
                 )?;
 
+                // The settings unknown to milli are applied in order, once milli is done.
+                for checked_settings in &all_checked_settings {
+                    apply_settings_to_index(checked_settings, index_wtxn, index)?;
+                }
+
+                // The resulting settings are kept in the history to be restored later on.
+                let task_uids = tasks.iter().map(|task| task.uid).collect();
+                settings_history::record_settings_version(index, index_wtxn, task_uids)?;
+
                 Ok(tasks)
             }
             IndexOperation::SettingsAndDocumentImport {
@@ -1173,6 +1393,45 @@ impl IndexScheduler {
         }
     }
 
+    /// Returns the ids of the documents of the given update files once they're imported
+    /// in the index, or all the documents of the index when their primary key is nested.
+    fn imported_docids(
+        &self,
+        index_wtxn: &RwTxn,
+        index: &Index,
+        content_files: &[Uuid],
+    ) -> Result<RoaringBitmap> {
+        let primary_key = match index.primary_key(index_wtxn)? {
+            Some(primary_key) => primary_key.to_string(),
+            None => return Ok(RoaringBitmap::new()),
+        };
+        let external_documents_ids = index.external_documents_ids(index_wtxn)?;
+
+        let mut docids = RoaringBitmap::new();
+        for content_uuid in content_files {
+            let content_file = self.file_store.get_update(*content_uuid)?;
+            let reader =
+                DocumentsBatchReader::from_reader(content_file).map_err(milli::Error::from)?;
+            let (mut cursor, documents_batch_index) = reader.into_cursor_and_fields_index();
+            let field_id = match documents_batch_index.id(&primary_key) {
+                Some(field_id) => field_id,
+                None => return Ok(index.documents_ids(index_wtxn)?),
+            };
+            while let Some(document) = cursor.next_document().map_err(milli::Error::from)? {
+                let external_id =
+                    match document.get(field_id).map(serde_json::from_slice::<serde_json::Value>) {
+                        Some(Ok(serde_json::Value::String(id))) => id.trim().to_string(),
+                        Some(Ok(serde_json::Value::Number(id))) => id.to_string(),
+                        _ => continue,
+                    };
+                if let Some(docid) = external_documents_ids.get(&external_id) {
+                    docids.insert(docid);
+                }
+            }
+        }
+        Ok(docids)
+    }
+
     /// Delete each given task from all the databases (if it is deleteable).
     ///
     /// Return the number of tasks that were actually deleted.
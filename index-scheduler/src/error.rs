@@ -28,6 +28,14 @@ pub enum Error {
     SwapDuplicateIndexesFound(Vec<String>),
     #[error("Corrupted dump.")]
     CorruptedDump,
+    #[error("Dump `{0}` not found.")]
+    DumpNotFound(String),
+    #[error("Index `{0}` not found in the dump.")]
+    IndexNotFoundInDump(String),
+    #[error(
+        "Indexes must be imported only once during a dump import. `{0}` was specified several times."
+    )]
+    DumpImportDuplicateIndexFound(String),
     #[error(
         "Task `{field}` `{date}` is invalid. It should follow the YYYY-MM-DD or RFC 3339 date-time format."
     )]
@@ -79,6 +87,12 @@ pub enum Error {
     IoError(#[from] std::io::Error),
     #[error(transparent)]
     Persist(#[from] tempfile::PersistError),
+    #[error(transparent)]
+    Storage(#[from] crate::storage::Error),
+    #[error(transparent)]
+    Vectors(#[from] meilisearch_types::vectors::Error),
+    #[error(transparent)]
+    Suggestions(#[from] meilisearch_types::suggestions::Error),
 
     #[error(transparent)]
     Anyhow(#[from] anyhow::Error),
@@ -102,6 +116,9 @@ impl ErrorCode for Error {
             Error::IndexAlreadyExists(_) => Code::IndexAlreadyExists,
             Error::SwapDuplicateIndexesFound(_) => Code::DuplicateIndexFound,
             Error::SwapDuplicateIndexFound(_) => Code::DuplicateIndexFound,
+            Error::DumpNotFound(_) => Code::DumpNotFound,
+            Error::IndexNotFoundInDump(_) => Code::IndexNotFound,
+            Error::DumpImportDuplicateIndexFound(_) => Code::DuplicateIndexFound,
             Error::InvalidTaskDate { .. } => Code::InvalidTaskDateFilter,
             Error::InvalidTaskUids { .. } => Code::InvalidTaskUidsFilter,
             Error::InvalidTaskStatuses { .. } => Code::InvalidTaskStatusesFilter,
@@ -118,6 +135,9 @@ impl ErrorCode for Error {
             Error::HeedTransaction(e) => e.error_code(),
             Error::FileStore(e) => e.error_code(),
             Error::IoError(e) => e.error_code(),
+            Error::Storage(e) => e.error_code(),
+            Error::Vectors(e) => e.error_code(),
+            Error::Suggestions(e) => e.error_code(),
             Error::Persist(e) => e.error_code(),
             // Irrecoverable errors
             Error::Anyhow(_) => Code::Internal,
@@ -30,8 +30,15 @@ pub fn snapshot_index_scheduler(scheduler: &IndexScheduler) -> String {
         wake_up: _,
         dumps_path: _,
         snapshots_path: _,
+        dumps_storage: _,
+        snapshots_storage: _,
+        incremental_snapshots: _,
+        snapshot_retention: _,
+        search_cache: _,
+        search_analytics: _,
         auth_path: _,
         version_file_path: _,
+        encryption_key: _,
         test_breakpoint_sdr: _,
         planned_failures: _,
         run_loop_iteration: _,
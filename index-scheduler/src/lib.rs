@@ -24,12 +24,17 @@ pub mod error;
 mod index_mapper;
 #[cfg(test)]
 mod insta_snapshot;
+pub mod search_analytics;
+pub mod search_cache;
+pub mod snapshot;
+pub mod storage;
 mod utils;
 mod uuid_codec;
 
 pub type Result<T> = std::result::Result<T, Error>;
 pub type TaskId = u32;
 
+use std::io::{BufReader, BufWriter};
 use std::ops::{Bound, RangeBounds};
 use std::path::PathBuf;
 use std::sync::atomic::AtomicBool;
@@ -37,17 +42,23 @@ use std::sync::atomic::Ordering::Relaxed;
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
-use dump::{KindDump, TaskDump, UpdateFile};
+use dump::{DumpIndexReader, KindDump, TaskDump, UpdateFile};
 pub use error::Error;
+use file_store::encryption::EncryptionKey;
 use file_store::FileStore;
 use meilisearch_types::error::ResponseError;
 use meilisearch_types::heed::types::{OwnedType, SerdeBincode, SerdeJson, Str};
-use meilisearch_types::heed::{self, Database, Env, RoTxn};
-use meilisearch_types::milli;
-use meilisearch_types::milli::documents::DocumentsBatchBuilder;
-use meilisearch_types::milli::update::IndexerConfig;
+use meilisearch_types::heed::{self, Database, Env, RoTxn, RwTxn};
+use meilisearch_types::milli::documents::{DocumentsBatchBuilder, DocumentsBatchReader};
+use meilisearch_types::milli::update::{
+    IndexDocumentsConfig, IndexDocumentsMethod, IndexerConfig, Setting,
+};
 use meilisearch_types::milli::{CboRoaringBitmapCodec, Index, RoaringBitmapCodec, BEU32};
-use meilisearch_types::tasks::{Kind, KindWithContent, Status, Task};
+use meilisearch_types::settings::{
+    apply_settings_to_builder, apply_settings_to_index, resolve_stop_words,
+};
+use meilisearch_types::tasks::{Details, Kind, KindWithContent, Status, Task};
+use meilisearch_types::{milli, vectors};
 use roaring::RoaringBitmap;
 use synchronoise::SignalEvent;
 use time::OffsetDateTime;
@@ -55,6 +66,9 @@ use utils::{filter_out_references_to_newer_tasks, keep_tasks_within_datetimes, m
 use uuid::Uuid;
 
 use crate::index_mapper::IndexMapper;
+use crate::search_analytics::SearchAnalytics;
+use crate::search_cache::SearchCache;
+use crate::storage::Storage;
 use crate::utils::{check_index_swap_validity, clamp_to_page_size};
 
 pub(crate) type BEI128 =
@@ -223,10 +237,14 @@ pub struct IndexSchedulerOptions {
     pub update_file_path: PathBuf,
     /// The path to the folder containing meilisearch's indexes.
     pub indexes_path: PathBuf,
-    /// The path to the folder containing the snapshots.
+    /// The path to the folder in which the snapshots are prepared before being stored.
     pub snapshots_path: PathBuf,
-    /// The path to the folder containing the dumps.
+    /// The path to the folder in which the dumps are prepared before being stored.
     pub dumps_path: PathBuf,
+    /// The storage on which the snapshots are written.
+    pub snapshots_storage: Arc<dyn Storage>,
+    /// The storage on which the dumps are written.
+    pub dumps_storage: Arc<dyn Storage>,
     /// The maximum size, in bytes, of each meilisearch index.
     pub task_db_size: usize,
     /// The maximum size, in bytes, of the tasks index.
@@ -236,6 +254,17 @@ pub struct IndexSchedulerOptions {
     /// Set to `true` iff the index scheduler is allowed to automatically
     /// batch tasks together, to process multiple tasks at once.
     pub autobatching_enabled: bool,
+    /// The key used to encrypt the update files, the dumps and the snapshots, if any.
+    pub encryption_key: Option<EncryptionKey>,
+    /// Set to `true` to create incremental snapshots, made of generations that reuse
+    /// the unchanged indexes of the previous one, instead of a single tarball.
+    pub incremental_snapshots: bool,
+    /// The number of generations of the incremental snapshots to keep.
+    pub snapshot_retention: usize,
+    /// The maximum size, in bytes, of the cached search responses, 0 disables the cache.
+    pub search_cache_size: usize,
+    /// Set to `true` to record the searches and their events in the search analytics.
+    pub search_analytics: bool,
 }
 
 /// Structure which holds meilisearch's indexes and schedules the tasks
@@ -285,18 +314,39 @@ pub struct IndexScheduler {
     /// Whether auto-batching is enabled or not.
     pub(crate) autobatching_enabled: bool,
 
-    /// The path used to create the dumps.
+    /// The path used to prepare the dumps.
     pub(crate) dumps_path: PathBuf,
 
-    /// The path used to create the snapshots.
+    /// The path used to prepare the snapshots.
     pub(crate) snapshots_path: PathBuf,
 
+    /// The storage used to store the dumps.
+    pub(crate) dumps_storage: Arc<dyn Storage>,
+
+    /// The storage used to store the snapshots.
+    pub(crate) snapshots_storage: Arc<dyn Storage>,
+
+    /// Whether the snapshots are incremental or not.
+    pub(crate) incremental_snapshots: bool,
+
+    /// The number of generations of the incremental snapshots to keep.
+    pub(crate) snapshot_retention: usize,
+
+    /// The cached search responses, invalidated when a batch touching their index is processed.
+    pub(crate) search_cache: Arc<SearchCache>,
+
+    /// The searches made on each index and the events following them.
+    pub(crate) search_analytics: Arc<SearchAnalytics>,
+
     /// The path to the folder containing the auth LMDB env.
     pub(crate) auth_path: PathBuf,
 
     /// The path to the version file of Meilisearch.
     pub(crate) version_file_path: PathBuf,
 
+    /// The key used to encrypt the dumps and the snapshots, if any.
+    pub(crate) encryption_key: Option<EncryptionKey>,
+
     // ================= test
     // The next entry is dedicated to the tests.
     /// Provide a way to set a breakpoint in multiple part of the scheduler.
@@ -336,8 +386,15 @@ impl IndexScheduler {
             autobatching_enabled: self.autobatching_enabled,
             snapshots_path: self.snapshots_path.clone(),
             dumps_path: self.dumps_path.clone(),
+            snapshots_storage: self.snapshots_storage.clone(),
+            incremental_snapshots: self.incremental_snapshots,
+            snapshot_retention: self.snapshot_retention,
+            search_cache: self.search_cache.clone(),
+            search_analytics: self.search_analytics.clone(),
+            dumps_storage: self.dumps_storage.clone(),
             auth_path: self.auth_path.clone(),
             version_file_path: self.version_file_path.clone(),
+            encryption_key: self.encryption_key.clone(),
             #[cfg(test)]
             test_breakpoint_sdr: self.test_breakpoint_sdr.clone(),
             #[cfg(test)]
@@ -358,13 +415,14 @@ impl IndexScheduler {
         std::fs::create_dir_all(&options.tasks_path)?;
         std::fs::create_dir_all(&options.update_file_path)?;
         std::fs::create_dir_all(&options.indexes_path)?;
+        std::fs::create_dir_all(&options.snapshots_path)?;
         std::fs::create_dir_all(&options.dumps_path)?;
 
         let env = heed::EnvOpenOptions::new()
             .max_dbs(10)
             .map_size(clamp_to_page_size(options.task_db_size))
             .open(options.tasks_path)?;
-        let file_store = FileStore::new(&options.update_file_path)?;
+        let file_store = FileStore::new(&options.update_file_path, options.encryption_key.clone())?;
 
         // allow unreachable_code to get rids of the warning in the case of a test build.
         let this = Self {
@@ -391,8 +449,15 @@ impl IndexScheduler {
             autobatching_enabled: options.autobatching_enabled,
             dumps_path: options.dumps_path,
             snapshots_path: options.snapshots_path,
+            dumps_storage: options.dumps_storage,
+            snapshots_storage: options.snapshots_storage,
+            incremental_snapshots: options.incremental_snapshots,
+            snapshot_retention: options.snapshot_retention,
+            search_cache: Arc::new(SearchCache::new(options.search_cache_size)),
+            search_analytics: Arc::new(SearchAnalytics::new(options.search_analytics)),
             auth_path: options.auth_path,
             version_file_path: options.version_file_path,
+            encryption_key: options.encryption_key,
 
             #[cfg(test)]
             test_breakpoint_sdr,
@@ -406,6 +471,16 @@ impl IndexScheduler {
         Ok(this)
     }
 
+    /// Returns the cache of the search responses.
+    pub fn search_cache(&self) -> &SearchCache {
+        &self.search_cache
+    }
+
+    /// Returns the analytics of the searches.
+    pub fn search_analytics(&self) -> &SearchAnalytics {
+        &self.search_analytics
+    }
+
     pub fn read_txn(&self) -> Result<RoTxn> {
         self.env.read_txn().map_err(|e| e.into())
     }
@@ -764,7 +839,7 @@ impl IndexScheduler {
         let content_uuid = match content_file {
             Some(content_file) if task.status == Status::Enqueued => {
                 let (uuid, mut file) = self.create_update_file()?;
-                let mut builder = DocumentsBatchBuilder::new(file.as_file_mut());
+                let mut builder = DocumentsBatchBuilder::new(&mut file);
                 for doc in content_file {
                     builder.append_json_object(&doc?)?;
                 }
@@ -835,10 +910,14 @@ impl IndexScheduler {
                 KindDump::TasksDeletion { query, tasks } => {
                     KindWithContent::TaskDeletion { query, tasks }
                 }
-                KindDump::DumpCreation { keys, instance_uid } => {
-                    KindWithContent::DumpCreation { keys, instance_uid }
+                KindDump::DumpCreation { keys, instance_uid, content } => {
+                    KindWithContent::DumpCreation { keys, instance_uid, content }
+                }
+                KindDump::DumpImport { source, renames, on_conflict } => {
+                    KindWithContent::DumpImport { source, renames, on_conflict }
                 }
                 KindDump::SnapshotCreation => KindWithContent::SnapshotCreation,
+                KindDump::SuggestionsRefresh => KindWithContent::SuggestionsRefresh,
             },
         };
 
@@ -875,6 +954,90 @@ impl IndexScheduler {
         Ok(index)
     }
 
+    /// Import the primary key, settings and documents of a dumped index into the given index.
+    ///
+    /// The documents already present in the index are removed first. Nothing is committed,
+    /// it's up to the caller to commit the write transaction.
+    ///
+    /// Returns the number of imported documents.
+    pub fn import_dumped_index(
+        &self,
+        wtxn: &mut RwTxn,
+        index: &Index,
+        index_reader: &mut DumpIndexReader,
+    ) -> Result<u64> {
+        let indexer_config = self.index_mapper.indexer_config();
+        let must_stop_processing = self.must_stop_processing.clone();
+
+        // 1. Clear the documents of the index, if any.
+        if !index.documents_ids(wtxn)?.is_empty() {
+            milli::update::ClearDocuments::new(wtxn, index).execute()?;
+        }
+
+        // The stop words are resolved along with their presets before the builder borrows
+        // the transaction.
+        let settings = index_reader.settings()?;
+        let stop_words = resolve_stop_words(index, wtxn, [&settings])?;
+
+        let mut builder = milli::update::Settings::new(wtxn, index, indexer_config);
+        // 2. Import the primary key if there is one.
+        if let Some(ref primary_key) = index_reader.metadata().primary_key {
+            builder.set_primary_key(primary_key.to_string());
+        }
+
+        // 3. Import the settings.
+        log::info!("Importing the settings.");
+        match stop_words {
+            Setting::Set(stop_words) => builder.set_stop_words(stop_words),
+            Setting::Reset => builder.reset_stop_words(),
+            Setting::NotSet => (),
+        }
+        apply_settings_to_builder(&settings, &mut builder);
+        builder.execute(
+            |indexing_step| log::debug!("update: {:?}", indexing_step),
+            || must_stop_processing.get(),
+        )?;
+        apply_settings_to_index(&settings, wtxn, index)?;
+
+        // 4. Import the documents.
+        // 4.1 We need to recreate the grenad+obkv format accepted by the index.
+        log::info!("Importing the documents.");
+        let file = tempfile::tempfile()?;
+        let mut builder = DocumentsBatchBuilder::new(BufWriter::new(file));
+        for document in index_reader.documents()? {
+            builder.append_json_object(&document?)?;
+        }
+
+        // This flush the content of the batch builder.
+        let file = builder.into_inner()?.into_inner().map_err(|e| e.into_error())?;
+
+        // 4.2 We feed it to the milli index.
+        let reader = BufReader::new(file);
+        let reader = DocumentsBatchReader::from_reader(reader).map_err(milli::Error::from)?;
+
+        let builder = milli::update::IndexDocuments::new(
+            wtxn,
+            index,
+            indexer_config,
+            IndexDocumentsConfig {
+                update_method: IndexDocumentsMethod::ReplaceDocuments,
+                ..Default::default()
+            },
+            |indexing_step| log::debug!("update: {:?}", indexing_step),
+            || must_stop_processing.get(),
+        )?;
+
+        let (builder, user_result) = builder.add_documents(reader)?;
+        let documents_count = user_result.map_err(milli::Error::from)?;
+        log::info!("{} documents found.", documents_count);
+        builder.execute()?;
+        // The vectors of the `_vectors` field must follow the documents.
+        let documents_ids = index.documents_ids(wtxn)?;
+        vectors::update_vectors(index, wtxn, &documents_ids)?;
+
+        Ok(documents_count)
+    }
+
     /// Create a file and register it in the index scheduler.
     ///
     /// The returned file and uuid can be used to associate
@@ -958,6 +1121,26 @@ impl IndexScheduler {
                 #[cfg(test)]
                 self.breakpoint(Breakpoint::ProcessBatchSucceeded);
 
+                // The indexes are already committed, their cached searches are outdated.
+                for task in &tasks {
+                    for index_uid in task.indexes() {
+                        self.search_cache.invalidate(index_uid);
+                    }
+                    // The indexes of a dump import are only known once it's processed.
+                    if let Some(Details::DumpImport { imported_indexes: Some(indexes), .. }) =
+                        &task.details
+                    {
+                        indexes
+                            .iter()
+                            .for_each(|index_uid| self.search_cache.invalidate(index_uid));
+                    }
+                }
+                for task in tasks.iter().filter(|task| task.status == Status::Succeeded) {
+                    if let KindWithContent::IndexDeletion { index_uid } = &task.kind {
+                        self.search_analytics.delete_index(index_uid);
+                    }
+                }
+
                 #[allow(unused_variables)]
                 for (i, mut task) in tasks.into_iter().enumerate() {
                     task.started_at = Some(started_at);
@@ -1068,7 +1251,7 @@ impl IndexScheduler {
 
 #[cfg(test)]
 mod tests {
-    use std::io::{BufWriter, Seek, Write};
+    use std::io::{BufWriter, Write};
     use std::time::Instant;
 
     use big_s::S;
@@ -1089,6 +1272,7 @@ mod tests {
 
     use super::*;
     use crate::insta_snapshot::{snapshot_bitmap, snapshot_index_scheduler};
+    use crate::storage::LocalStorage;
 
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub enum FailureLocation {
@@ -1105,11 +1289,19 @@ mod tests {
         pub fn test(
             autobatching_enabled: bool,
             planned_failures: Vec<(usize, FailureLocation)>,
+        ) -> (Self, IndexSchedulerHandle) {
+            Self::test_with_custom_options(autobatching_enabled, planned_failures, |_| ())
+        }
+
+        pub fn test_with_custom_options(
+            autobatching_enabled: bool,
+            planned_failures: Vec<(usize, FailureLocation)>,
+            customize_options: impl FnOnce(&mut IndexSchedulerOptions),
         ) -> (Self, IndexSchedulerHandle) {
             let tempdir = TempDir::new().unwrap();
             let (sender, receiver) = crossbeam::channel::bounded(0);
 
-            let options = IndexSchedulerOptions {
+            let mut options = IndexSchedulerOptions {
                 version_file_path: tempdir.path().join(VERSION_FILE_NAME),
                 auth_path: tempdir.path().join("auth"),
                 tasks_path: tempdir.path().join("db_path"),
@@ -1117,11 +1309,19 @@ mod tests {
                 indexes_path: tempdir.path().join("indexes"),
                 snapshots_path: tempdir.path().join("snapshots"),
                 dumps_path: tempdir.path().join("dumps"),
+                snapshots_storage: Arc::new(LocalStorage::new(tempdir.path().join("snapshots"))),
+                dumps_storage: Arc::new(LocalStorage::new(tempdir.path().join("dumps"))),
                 task_db_size: 1000 * 1000, // 1 MB, we don't use MiB on purpose.
                 index_size: 1000 * 1000,   // 1 MB, we don't use MiB on purpose.
                 indexer_config: IndexerConfig::default(),
                 autobatching_enabled,
+                encryption_key: None,
+                incremental_snapshots: false,
+                snapshot_retention: 1,
+                search_cache_size: 0,
+                search_analytics: false,
             };
+            customize_options(&mut options);
 
             let index_scheduler = Self::new(options, sender, planned_failures).unwrap();
 
@@ -1192,7 +1392,7 @@ mod tests {
     /// Adapting to the new json reading interface
     pub fn read_json(
         bytes: &[u8],
-        write: impl Write + Seek,
+        write: impl Write,
     ) -> std::result::Result<u64, DocumentFormatError> {
         let temp_file = NamedTempFile::new().unwrap();
         let mut buffer = BufWriter::new(temp_file.reopen().unwrap());
@@ -1219,7 +1419,7 @@ mod tests {
         );
 
         let (_uuid, mut file) = index_scheduler.create_update_file_with_uuid(file_uuid).unwrap();
-        let documents_count = read_json(content.as_bytes(), file.as_file_mut()).unwrap();
+        let documents_count = read_json(content.as_bytes(), &mut file).unwrap();
         (file, documents_count)
     }
 
@@ -1599,7 +1799,7 @@ mod tests {
         }"#;
 
         let (uuid, mut file) = index_scheduler.create_update_file_with_uuid(0).unwrap();
-        let documents_count = read_json(content.as_bytes(), file.as_file_mut()).unwrap();
+        let documents_count = read_json(content.as_bytes(), &mut file).unwrap();
         file.persist().unwrap();
         index_scheduler
             .register(KindWithContent::DocumentAdditionOrUpdate {
@@ -1636,7 +1836,7 @@ mod tests {
         snapshot!(snapshot_index_scheduler(&index_scheduler), name: "registered_the_first_task");
 
         let (uuid, mut file) = index_scheduler.create_update_file_with_uuid(0).unwrap();
-        let documents_count = read_json(content.as_bytes(), file.as_file_mut()).unwrap();
+        let documents_count = read_json(content.as_bytes(), &mut file).unwrap();
         file.persist().unwrap();
         index_scheduler
             .register(KindWithContent::DocumentAdditionOrUpdate {
@@ -1803,7 +2003,7 @@ mod tests {
         }"#;
 
         let (uuid, mut file) = index_scheduler.create_update_file_with_uuid(0).unwrap();
-        let documents_count = read_json(content.as_bytes(), file.as_file_mut()).unwrap();
+        let documents_count = read_json(content.as_bytes(), &mut file).unwrap();
         file.persist().unwrap();
         index_scheduler
             .register(KindWithContent::DocumentAdditionOrUpdate {
@@ -1961,7 +2161,7 @@ mod tests {
             );
 
             let (uuid, mut file) = index_scheduler.create_update_file_with_uuid(i).unwrap();
-            let documents_count = read_json(content.as_bytes(), file.as_file_mut()).unwrap();
+            let documents_count = read_json(content.as_bytes(), &mut file).unwrap();
             file.persist().unwrap();
             index_scheduler
                 .register(KindWithContent::DocumentAdditionOrUpdate {
@@ -2008,7 +2208,7 @@ mod tests {
             );
 
             let (uuid, mut file) = index_scheduler.create_update_file_with_uuid(i).unwrap();
-            let documents_count = read_json(content.as_bytes(), file.as_file_mut()).unwrap();
+            let documents_count = read_json(content.as_bytes(), &mut file).unwrap();
             file.persist().unwrap();
             index_scheduler
                 .register(KindWithContent::DocumentAdditionOrUpdate {
@@ -2057,7 +2257,7 @@ mod tests {
             );
 
             let (uuid, mut file) = index_scheduler.create_update_file_with_uuid(i).unwrap();
-            let documents_count = read_json(content.as_bytes(), file.as_file_mut()).unwrap();
+            let documents_count = read_json(content.as_bytes(), &mut file).unwrap();
             file.persist().unwrap();
             index_scheduler
                 .register(KindWithContent::DocumentAdditionOrUpdate {
@@ -2107,7 +2307,7 @@ mod tests {
             );
 
             let (uuid, mut file) = index_scheduler.create_update_file_with_uuid(i).unwrap();
-            let documents_count = read_json(content.as_bytes(), file.as_file_mut()).unwrap();
+            let documents_count = read_json(content.as_bytes(), &mut file).unwrap();
             file.persist().unwrap();
             index_scheduler
                 .register(KindWithContent::DocumentAdditionOrUpdate {
@@ -2158,7 +2358,7 @@ mod tests {
             );
 
             let (uuid, mut file) = index_scheduler.create_update_file_with_uuid(i).unwrap();
-            let documents_count = read_json(content.as_bytes(), file.as_file_mut()).unwrap();
+            let documents_count = read_json(content.as_bytes(), &mut file).unwrap();
             file.persist().unwrap();
             index_scheduler
                 .register(KindWithContent::DocumentAdditionOrUpdate {
@@ -2605,7 +2805,7 @@ mod tests {
         }"#;
 
         let (uuid, mut file) = index_scheduler.create_update_file_with_uuid(0).unwrap();
-        let documents_count = read_json(content.as_bytes(), file.as_file_mut()).unwrap();
+        let documents_count = read_json(content.as_bytes(), &mut file).unwrap();
         file.persist().unwrap();
         index_scheduler
             .register(KindWithContent::DocumentAdditionOrUpdate {
@@ -2643,7 +2843,7 @@ mod tests {
         }"#;
 
         let (uuid, mut file) = index_scheduler.create_update_file_with_uuid(0).unwrap();
-        let documents_count = read_json(content.as_bytes(), file.as_file_mut()).unwrap();
+        let documents_count = read_json(content.as_bytes(), &mut file).unwrap();
         file.persist().unwrap();
         index_scheduler
             .register(KindWithContent::DocumentAdditionOrUpdate {
@@ -2699,7 +2899,7 @@ mod tests {
             );
 
             let (uuid, mut file) = index_scheduler.create_update_file_with_uuid(i).unwrap();
-            let documents_count = read_json(content.as_bytes(), file.as_file_mut()).unwrap();
+            let documents_count = read_json(content.as_bytes(), &mut file).unwrap();
             file.persist().unwrap();
             index_scheduler
                 .register(KindWithContent::DocumentAdditionOrUpdate {
@@ -2747,7 +2947,7 @@ mod tests {
             );
 
             let (uuid, mut file) = index_scheduler.create_update_file_with_uuid(i).unwrap();
-            let documents_count = read_json(content.as_bytes(), file.as_file_mut()).unwrap();
+            let documents_count = read_json(content.as_bytes(), &mut file).unwrap();
             file.persist().unwrap();
             index_scheduler
                 .register(KindWithContent::DocumentAdditionOrUpdate {
@@ -2801,7 +3001,7 @@ mod tests {
             );
 
             let (uuid, mut file) = index_scheduler.create_update_file_with_uuid(i).unwrap();
-            let documents_count = read_json(content.as_bytes(), file.as_file_mut()).unwrap();
+            let documents_count = read_json(content.as_bytes(), &mut file).unwrap();
             file.persist().unwrap();
             index_scheduler
                 .register(KindWithContent::DocumentAdditionOrUpdate {
@@ -2860,7 +3060,7 @@ mod tests {
             );
 
             let (uuid, mut file) = index_scheduler.create_update_file_with_uuid(i).unwrap();
-            let documents_count = read_json(content.as_bytes(), file.as_file_mut()).unwrap();
+            let documents_count = read_json(content.as_bytes(), &mut file).unwrap();
             file.persist().unwrap();
             index_scheduler
                 .register(KindWithContent::DocumentAdditionOrUpdate {
@@ -2924,7 +3124,7 @@ mod tests {
             let allow_index_creation = i % 2 != 0;
 
             let (uuid, mut file) = index_scheduler.create_update_file_with_uuid(i).unwrap();
-            let documents_count = read_json(content.as_bytes(), file.as_file_mut()).unwrap();
+            let documents_count = read_json(content.as_bytes(), &mut file).unwrap();
             file.persist().unwrap();
             index_scheduler
                 .register(KindWithContent::DocumentAdditionOrUpdate {
@@ -2977,7 +3177,7 @@ mod tests {
             let allow_index_creation = i % 2 != 0;
 
             let (uuid, mut file) = index_scheduler.create_update_file_with_uuid(i).unwrap();
-            let documents_count = read_json(content.as_bytes(), file.as_file_mut()).unwrap();
+            let documents_count = read_json(content.as_bytes(), &mut file).unwrap();
             file.persist().unwrap();
             index_scheduler
                 .register(KindWithContent::DocumentAdditionOrUpdate {
@@ -3031,4 +3231,79 @@ mod tests {
         // No matter what happens in process_batch, the index_scheduler should be internally consistent
         snapshot!(snapshot_index_scheduler(&index_scheduler), name: "index_creation_failed");
     }
+
+    #[test]
+    fn incremental_snapshots_reuse_unchanged_indexes() {
+        let key = EncryptionKey::new("the doggos are sleeping");
+        let (index_scheduler, mut handle) =
+            IndexScheduler::test_with_custom_options(true, vec![], |options| {
+                options.incremental_snapshots = true;
+                options.snapshot_retention = 2;
+                options.encryption_key = Some(key.clone());
+            });
+        std::fs::create_dir_all(&index_scheduler.auth_path).unwrap();
+        let storage = &*index_scheduler.snapshots_storage;
+        let manifest = |generation| {
+            let dir = snapshot::generation_dir("data.ms.snapshots", generation);
+            snapshot::read_manifest(storage, &dir, Some(&key)).unwrap()
+        };
+        let reused_from = |manifest: &snapshot::Manifest, uid: &str| {
+            manifest.indexes.values().find(|index| index.uid == uid).unwrap().reused_from
+        };
+
+        index_scheduler.register(index_creation_task("catto", "id")).unwrap();
+        index_scheduler.register(index_creation_task("doggo", "id")).unwrap();
+        handle.advance_n_successful_batches(2);
+
+        index_scheduler.create_incremental_snapshot("data.ms").unwrap();
+        let first = manifest(1);
+        assert_eq!(reused_from(&first, "catto"), None);
+        assert_eq!(reused_from(&first, "doggo"), None);
+
+        // only the doggo index is updated before the second generation
+        let (file, documents_count) = sample_documents(&index_scheduler, 0, 0);
+        file.persist().unwrap();
+        index_scheduler
+            .register(replace_document_import_task("doggo", Some("id"), 0, documents_count))
+            .unwrap();
+        handle.advance_one_successful_batch();
+
+        index_scheduler.create_incremental_snapshot("data.ms").unwrap();
+        let second = manifest(2);
+        assert_eq!(reused_from(&second, "catto"), Some(1));
+        assert_eq!(reused_from(&second, "doggo"), None);
+        let doggo = index_scheduler.index("doggo").unwrap();
+        let updated_at = doggo.updated_at(&doggo.read_txn().unwrap()).unwrap();
+        let doggo_manifest = second.indexes.values().find(|index| index.uid == "doggo").unwrap();
+        assert_eq!(doggo_manifest.updated_at, updated_at);
+
+        // the reused indexes keep pointing to the generation they were first snapshotted in
+        index_scheduler.create_incremental_snapshot("data.ms").unwrap();
+        let third = manifest(3);
+        assert_eq!(reused_from(&third, "catto"), Some(1));
+        assert_eq!(reused_from(&third, "doggo"), Some(2));
+
+        // the first generation exceeds the retention of two generations
+        let generations = snapshot::list_generations(storage, "data.ms.snapshots").unwrap();
+        assert_eq!(generations.keys().copied().collect::<Vec<_>>(), [2, 3]);
+
+        // the copied indexes are still complete once their original generation is removed
+        let dest = TempDir::new().unwrap();
+        snapshot::restore_incremental_snapshot(
+            storage,
+            "data.ms.snapshots",
+            dest.path(),
+            Some(&key),
+        )
+        .unwrap();
+        for (uuid, index) in &third.indexes {
+            let path = dest.path().join("indexes").join(uuid.to_string());
+            let mut options = heed::EnvOpenOptions::new();
+            options.map_size(1000 * 1000);
+            let restored = Index::new(options, path).unwrap();
+            let rtxn = restored.read_txn().unwrap();
+            let expected = if index.uid == "doggo" { 1 } else { 0 };
+            assert_eq!(restored.number_of_documents(&rtxn).unwrap(), expected);
+        }
+    }
 }
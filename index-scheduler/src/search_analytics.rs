@@ -0,0 +1,286 @@
+//! An in-memory store of the searches made on each index and of the events following them,
+//! such as the clicks on the hits and the conversions.
+//!
+//! Every recorded search gets a query uid that the events refer to. Only the last searches
+//! are kept to tie the events to their query, and the analytics start over on every launch.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// The maximum number of distinct queries tracked per index, the following ones
+/// are only counted in the totals of the index.
+const MAX_TRACKED_QUERIES: usize = 10_000;
+/// The number of the last searches to which the events can be tied.
+const MAX_TRACKED_SEARCHES: usize = 100_000;
+
+pub struct SearchAnalytics {
+    enabled: bool,
+    inner: Mutex<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    indexes: HashMap<String, IndexAnalytics>,
+    /// The index and the query of the last searches, by query uid.
+    searches: HashMap<Uuid, (String, String)>,
+    /// The uids of the last searches, from the oldest one.
+    searches_order: VecDeque<Uuid>,
+}
+
+#[derive(Default)]
+struct IndexAnalytics {
+    total: Counters,
+    queries: HashMap<String, Counters>,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Counters {
+    searches: u64,
+    zero_hit_searches: u64,
+    processing_time: Duration,
+    clicks: u64,
+    conversions: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SearchEventKind {
+    Click,
+    Conversion,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexSearchAnalytics {
+    pub total_searches: u64,
+    pub total_zero_hit_searches: u64,
+    pub average_processing_time_ms: f64,
+    pub clicks: u64,
+    pub conversions: u64,
+    /// The most frequent queries, the most frequent first.
+    pub top_queries: Vec<QueryAnalytics>,
+    /// The most frequent queries without any hit, the most frequent first.
+    pub top_zero_hit_queries: Vec<QueryAnalytics>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryAnalytics {
+    pub q: String,
+    pub searches: u64,
+    pub zero_hit_searches: u64,
+    pub average_processing_time_ms: f64,
+    pub clicks: u64,
+    pub conversions: u64,
+}
+
+impl SearchAnalytics {
+    pub fn new(enabled: bool) -> Self {
+        SearchAnalytics { enabled, inner: Mutex::default() }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Records a search and returns the query uid to which its events must refer.
+    /// The queries are tracked case-insensitively and without their surrounding spaces.
+    pub fn record_search(
+        &self,
+        index_uid: &str,
+        q: &str,
+        total_hits: u64,
+        processing_time: Duration,
+    ) -> Uuid {
+        let q = q.trim().to_lowercase();
+        let query_uid = Uuid::new_v4();
+
+        let mut inner = self.inner.lock().unwrap();
+        let index = inner.indexes.entry(index_uid.to_string()).or_default();
+        index.total.record_search(total_hits, processing_time);
+        if let Some(counters) = index.counters(&q) {
+            counters.record_search(total_hits, processing_time);
+        }
+
+        if inner.searches_order.len() == MAX_TRACKED_SEARCHES {
+            if let Some(oldest) = inner.searches_order.pop_front() {
+                inner.searches.remove(&oldest);
+            }
+        }
+        inner.searches_order.push_back(query_uid);
+        inner.searches.insert(query_uid, (index_uid.to_string(), q));
+
+        query_uid
+    }
+
+    /// Returns the uid of the index of the search, or `None` if the query uid is unknown or too old.
+    pub fn index_of(&self, query_uid: Uuid) -> Option<String> {
+        let inner = self.inner.lock().unwrap();
+        inner.searches.get(&query_uid).map(|(index_uid, _)| index_uid.clone())
+    }
+
+    /// Records an event following the search of the given query uid and returns the uid of
+    /// its index, or `None` if the query uid is unknown or too old.
+    pub fn record_event(&self, query_uid: Uuid, kind: SearchEventKind) -> Option<String> {
+        let mut inner = self.inner.lock().unwrap();
+        let (index_uid, q) = inner.searches.get(&query_uid).cloned()?;
+        let index = inner.indexes.entry(index_uid.clone()).or_default();
+        index.total.record_event(kind);
+        if let Some(counters) = index.queries.get_mut(&q) {
+            counters.record_event(kind);
+        }
+        Some(index_uid)
+    }
+
+    /// Returns the analytics of the index along with its `limit` most frequent queries.
+    pub fn index_analytics(&self, index_uid: &str, limit: usize) -> IndexSearchAnalytics {
+        let inner = self.inner.lock().unwrap();
+        let index = match inner.indexes.get(index_uid) {
+            Some(index) => index,
+            None => return IndexAnalytics::default().analytics(limit),
+        };
+        index.analytics(limit)
+    }
+
+    /// Returns the `limit` most frequent queries of the index that found some hits,
+    /// along with their number of searches.
+    pub fn popular_queries(&self, index_uid: &str, limit: usize) -> Vec<(String, u64)> {
+        let inner = self.inner.lock().unwrap();
+        let index = match inner.indexes.get(index_uid) {
+            Some(index) => index,
+            None => return Vec::new(),
+        };
+        let mut queries: Vec<_> = index
+            .queries
+            .iter()
+            .filter(|(_, counters)| counters.zero_hit_searches < counters.searches)
+            .map(|(q, counters)| (q.clone(), counters.searches))
+            .collect();
+        queries.sort_by(|(lq, lc), (rq, rc)| rc.cmp(lc).then(lq.cmp(rq)));
+        queries.truncate(limit);
+        queries
+    }
+
+    /// Forgets everything about the index, it must be called when the index is deleted.
+    pub fn delete_index(&self, index_uid: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.indexes.remove(index_uid);
+    }
+}
+
+impl IndexAnalytics {
+    /// Returns the counters of the query, if it's tracked or there is room to track it.
+    fn counters(&mut self, q: &str) -> Option<&mut Counters> {
+        if self.queries.len() >= MAX_TRACKED_QUERIES && !self.queries.contains_key(q) {
+            return None;
+        }
+        Some(self.queries.entry(q.to_string()).or_default())
+    }
+
+    fn analytics(&self, limit: usize) -> IndexSearchAnalytics {
+        let top_queries = |only_zero_hits: bool| {
+            let mut queries: Vec<_> = self
+                .queries
+                .iter()
+                .filter(|(_, counters)| !only_zero_hits || counters.zero_hit_searches > 0)
+                .collect();
+            let count = |counters: &Counters| {
+                if only_zero_hits {
+                    counters.zero_hit_searches
+                } else {
+                    counters.searches
+                }
+            };
+            queries.sort_by(|(lq, lc), (rq, rc)| count(rc).cmp(&count(lc)).then(lq.cmp(rq)));
+            queries.into_iter().take(limit).map(|(q, counters)| counters.analytics(q)).collect()
+        };
+
+        IndexSearchAnalytics {
+            total_searches: self.total.searches,
+            total_zero_hit_searches: self.total.zero_hit_searches,
+            average_processing_time_ms: self.total.average_processing_time_ms(),
+            clicks: self.total.clicks,
+            conversions: self.total.conversions,
+            top_queries: top_queries(false),
+            top_zero_hit_queries: top_queries(true),
+        }
+    }
+}
+
+impl Counters {
+    fn record_search(&mut self, total_hits: u64, processing_time: Duration) {
+        self.searches += 1;
+        if total_hits == 0 {
+            self.zero_hit_searches += 1;
+        }
+        self.processing_time += processing_time;
+    }
+
+    fn record_event(&mut self, kind: SearchEventKind) {
+        match kind {
+            SearchEventKind::Click => self.clicks += 1,
+            SearchEventKind::Conversion => self.conversions += 1,
+        }
+    }
+
+    fn average_processing_time_ms(&self) -> f64 {
+        if self.searches == 0 {
+            0.0
+        } else {
+            self.processing_time.as_micros() as f64 / 1000.0 / self.searches as f64
+        }
+    }
+
+    fn analytics(&self, q: &str) -> QueryAnalytics {
+        QueryAnalytics {
+            q: q.to_string(),
+            searches: self.searches,
+            zero_hit_searches: self.zero_hit_searches,
+            average_processing_time_ms: self.average_processing_time_ms(),
+            clicks: self.clicks,
+            conversions: self.conversions,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn top_queries_and_events() {
+        let analytics = SearchAnalytics::new(true);
+        let ms = Duration::from_millis;
+        analytics.record_search("movies", "Carol", 3, ms(2));
+        let query_uid = analytics.record_search("movies", " carol ", 3, ms(4));
+        analytics.record_search("movies", "batman", 0, ms(6));
+        analytics.record_search("books", "carol", 1, ms(2));
+
+        assert_eq!(
+            analytics.record_event(query_uid, SearchEventKind::Click).as_deref(),
+            Some("movies")
+        );
+        analytics.record_event(query_uid, SearchEventKind::Conversion);
+        assert_eq!(analytics.record_event(Uuid::new_v4(), SearchEventKind::Click), None);
+        assert_eq!(analytics.index_of(query_uid).as_deref(), Some("movies"));
+
+        let movies = analytics.index_analytics("movies", 10);
+        assert_eq!((movies.total_searches, movies.total_zero_hit_searches), (3, 1));
+        assert_eq!((movies.clicks, movies.conversions), (1, 1));
+        assert_eq!(movies.average_processing_time_ms, 4.0);
+        let top: Vec<_> = movies.top_queries.iter().map(|q| (q.q.as_str(), q.searches)).collect();
+        assert_eq!(top, [("carol", 2), ("batman", 1)]);
+        assert_eq!(movies.top_queries[0].clicks, 1);
+        let zero_hits: Vec<_> = movies.top_zero_hit_queries.iter().map(|q| q.q.as_str()).collect();
+        assert_eq!(zero_hits, ["batman"]);
+        assert_eq!(analytics.popular_queries("movies", 10), [("carol".to_string(), 2)]);
+
+        analytics.delete_index("movies");
+        assert_eq!(analytics.index_analytics("movies", 10).total_searches, 0);
+        assert_eq!(analytics.index_analytics("books", 1).top_queries.len(), 1);
+    }
+}
@@ -0,0 +1,194 @@
+//! An in-memory LRU cache of the responses of the searches.
+//!
+//! Every index has a write generation, bumped each time the scheduler commits a batch touching
+//! the index, which drops the cached responses of the index. A response computed while a batch
+//! was committed is never cached since it was computed on an older generation.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+
+pub struct SearchCache {
+    /// The maximum size, in bytes, of the cached responses and their keys.
+    capacity: usize,
+    inner: Mutex<Inner>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+#[derive(Default)]
+struct Inner {
+    generations: HashMap<String, u64>,
+    entries: HashMap<(String, String), Entry>,
+    /// The keys of the entries, from the least recently used one.
+    recency: BTreeMap<u64, (String, String)>,
+    clock: u64,
+    size: usize,
+}
+
+struct Entry {
+    response: Arc<Vec<u8>>,
+    last_used: u64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+    pub size: usize,
+}
+
+impl SearchCache {
+    /// Creates a cache holding at most `capacity` bytes, it's disabled when `capacity` is 0.
+    pub fn new(capacity: usize) -> Self {
+        SearchCache {
+            capacity,
+            inner: Mutex::default(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    /// Returns the current write generation of the index, it must be read before the search
+    /// and given back when its response is inserted.
+    pub fn generation(&self, index_uid: &str) -> u64 {
+        self.inner.lock().unwrap().generations.get(index_uid).copied().unwrap_or_default()
+    }
+
+    /// Returns the cached response of the search, if any, and marks it as the most recently used.
+    pub fn get(&self, index_uid: &str, key: &str) -> Option<Arc<Vec<u8>>> {
+        let mut inner = self.inner.lock().unwrap();
+        let inner = &mut *inner;
+        inner.clock += 1;
+        let entry_key = (index_uid.to_string(), key.to_string());
+        match inner.entries.get_mut(&entry_key) {
+            Some(entry) => {
+                inner.recency.remove(&entry.last_used);
+                entry.last_used = inner.clock;
+                inner.recency.insert(inner.clock, entry_key);
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Some(entry.response.clone())
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    /// Caches the response of a search computed on the given `generation` of the index,
+    /// the least recently used responses are evicted to make room for it.
+    pub fn insert(&self, index_uid: &str, key: String, generation: u64, response: Vec<u8>) {
+        let size = entry_size(index_uid, &key, &response);
+        if size > self.capacity {
+            return;
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        if inner.generations.get(index_uid).copied().unwrap_or_default() != generation {
+            return;
+        }
+
+        let entry_key = (index_uid.to_string(), key);
+        inner.remove(&entry_key);
+        while inner.size + size > self.capacity {
+            let least_recently_used = match inner.recency.values().next() {
+                Some(entry_key) => entry_key.clone(),
+                None => break,
+            };
+            inner.remove(&least_recently_used);
+        }
+
+        inner.clock += 1;
+        let last_used = inner.clock;
+        inner.size += size;
+        inner.recency.insert(last_used, entry_key.clone());
+        inner.entries.insert(entry_key, Entry { response: Arc::new(response), last_used });
+    }
+
+    /// Bumps the write generation of the index and drops its cached responses.
+    pub fn invalidate(&self, index_uid: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        *inner.generations.entry(index_uid.to_string()).or_default() += 1;
+        let keys: Vec<_> =
+            inner.entries.keys().filter(|(uid, _)| uid == index_uid).cloned().collect();
+        for key in keys {
+            inner.remove(&key);
+        }
+    }
+
+    pub fn stats(&self) -> SearchCacheStats {
+        let inner = self.inner.lock().unwrap();
+        SearchCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: inner.entries.len(),
+            size: inner.size,
+        }
+    }
+}
+
+impl Inner {
+    fn remove(&mut self, entry_key: &(String, String)) {
+        if let Some(entry) = self.entries.remove(entry_key) {
+            self.recency.remove(&entry.last_used);
+            self.size -= entry_size(&entry_key.0, &entry_key.1, &entry.response);
+        }
+    }
+}
+
+fn entry_size(index_uid: &str, key: &str, response: &[u8]) -> usize {
+    index_uid.len() + key.len() + response.len()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn evict_least_recently_used() {
+        let cache = SearchCache::new(30);
+        cache.insert("movies", "a".to_string(), 0, vec![0; 8]);
+        cache.insert("movies", "b".to_string(), 0, vec![0; 8]);
+        assert!(cache.get("movies", "a").is_some());
+
+        // there is only room for two entries, `b` is the least recently used.
+        cache.insert("movies", "c".to_string(), 0, vec![0; 8]);
+        assert!(cache.get("movies", "a").is_some());
+        assert!(cache.get("movies", "b").is_none());
+        assert!(cache.get("movies", "c").is_some());
+
+        // an entry bigger than the cache is never inserted.
+        cache.insert("movies", "d".to_string(), 0, vec![0; 100]);
+        assert!(cache.get("movies", "d").is_none());
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries, stats.size), (3, 2, 2, 30));
+    }
+
+    #[test]
+    fn invalidate_an_index() {
+        let cache = SearchCache::new(1000);
+        cache.insert("movies", "a".to_string(), 0, vec![0; 8]);
+        cache.insert("books", "a".to_string(), 0, vec![0; 8]);
+
+        let generation = cache.generation("movies");
+        cache.invalidate("movies");
+        assert!(cache.get("movies", "a").is_none());
+        assert!(cache.get("books", "a").is_some());
+
+        // a response computed before the invalidation is outdated.
+        cache.insert("movies", "a".to_string(), generation, vec![0; 8]);
+        assert!(cache.get("movies", "a").is_none());
+        cache.insert("movies", "a".to_string(), cache.generation("movies"), vec![0; 8]);
+        assert!(cache.get("movies", "a").is_some());
+    }
+}
@@ -0,0 +1,463 @@
+/*!
+This module handles the incremental snapshots.
+
+Instead of a single tarball overwritten on every snapshot, an incremental snapshot
+is a directory of the snapshots [`Storage`] containing a list of generations:
+
+```text
+data.ms.snapshots/
+├── generation-00000000000000000001/
+│   ├── manifest.json
+│   ├── VERSION
+│   ├── auth/data.mdb
+│   ├── tasks/data.mdb
+│   ├── update_files/…
+│   └── indexes/<uuid>/data.mdb
+└── generation-00000000000000000002/
+    └── …
+```
+
+The manifest of a generation stores the `updated_at` date of every index it contains.
+When creating a new generation, the indexes that were not updated since the previous
+generation are copied from it by the storage instead of being uploaded again, which
+only creates a hard link on the local file system.
+
+The manifest is always the last file written in a generation, a generation without
+a manifest is an incomplete generation that is ignored.
+
+When an encryption key is provided, every file of a generation is encrypted, except
+the update files which are already encrypted by the file store.
+*/
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use anyhow::{bail, Context};
+use file_store::encryption::{self, EncryptedWriter, EncryptionKey, MaybeDecrypted};
+use log::{info, warn};
+use meilisearch_types::heed::{CompactionOption, Env};
+use meilisearch_types::tasks::Status;
+use meilisearch_types::VERSION_FILE_NAME;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::storage::Storage;
+use crate::{Error, IndexScheduler, Result};
+
+const GENERATION_PREFIX: &str = "generation-";
+const MANIFEST_FILE_NAME: &str = "manifest.json";
+const UPDATE_FILES_DIR: &str = "update_files";
+
+/// Describes the content of a generation of an incremental snapshot.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Manifest {
+    pub generation: u64,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    pub indexes: BTreeMap<Uuid, IndexManifest>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexManifest {
+    pub uid: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+    /// The generation the index was copied from, if it was reused from a previous generation.
+    pub reused_from: Option<u64>,
+}
+
+impl IndexScheduler {
+    /// Creates a new generation of the incremental snapshot and removes the generations
+    /// that exceed the retention.
+    pub(crate) fn create_incremental_snapshot(&self, db_name: &str) -> Result<()> {
+        let storage = &*self.snapshots_storage;
+        let key = self.encryption_key.as_ref();
+        let snapshot_dir = format!("{}.snapshots", db_name);
+
+        let generations = list_generations(storage, &snapshot_dir)?;
+        let generation = generations.keys().last().map_or(1, |generation| generation + 1);
+        let previous = match complete_generations(&snapshot_dir, &generations).pop() {
+            Some(previous) => {
+                match read_manifest(storage, &generation_dir(&snapshot_dir, previous), key) {
+                    Ok(manifest) => Some(manifest),
+                    Err(e) => {
+                        warn!(
+                            "Could not read the previous snapshot generation, ignoring it: {}",
+                            e
+                        );
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+
+        // Every file is prepared in a temporary directory, uploaded, and removed right away.
+        let temp_dir = tempfile::tempdir_in(&self.snapshots_path)?;
+        let dst_dir = generation_dir(&snapshot_dir, generation);
+        let put = |name: &str, path: &Path| -> Result<()> {
+            storage.put(&join(&dst_dir, name), path)?;
+            fs::remove_file(path)?;
+            Ok(())
+        };
+
+        // 1. Snapshot the version file.
+        let version = temp_dir.path().join(VERSION_FILE_NAME);
+        copy_file(&self.version_file_path, &version, key)?;
+        put(VERSION_FILE_NAME, &version)?;
+
+        // 2. Snapshot the index-scheduler LMDB env, see the full snapshot for
+        //    why the update files are retrieved after copying the env.
+        let tasks = temp_dir.path().join("tasks.mdb");
+        copy_env(&self.env, &tasks, key)?;
+        put("tasks/data.mdb", &tasks)?;
+        let rtxn = self.env.read_txn()?;
+
+        for task_id in self.get_status(&rtxn, Status::Enqueued)? {
+            let task = self.get_task(&rtxn, task_id)?.ok_or(Error::CorruptedTaskQueue)?;
+            if let Some(content_uuid) = task.content_uuid() {
+                let src = self.file_store.get_update_path(content_uuid);
+                let name = format!("{}/{}", UPDATE_FILES_DIR, content_uuid);
+                storage.put(&join(&dst_dir, &name), &src)?;
+            }
+        }
+
+        // 3. Snapshot the indexes, reusing the ones that didn't change since the previous generation.
+        let mut indexes = BTreeMap::new();
+        for result in self.index_mapper.index_mapping.iter(&rtxn)? {
+            let (name, uuid) = result?;
+            let index = self.index_mapper.index(&rtxn, name)?;
+            let updated_at = index.updated_at(&index.read_txn()?)?;
+            let index_file = format!("indexes/{}/data.mdb", uuid);
+
+            let reusable = previous.as_ref().and_then(|manifest| {
+                let previous_index = manifest.indexes.get(&uuid)?;
+                let src = join(&generation_dir(&snapshot_dir, manifest.generation), &index_file);
+                let reusable = previous_index.updated_at == updated_at
+                    && is_readable_with(storage, &src, key).unwrap_or(false);
+                reusable.then(|| (src, previous_index.reused_from.unwrap_or(manifest.generation)))
+            });
+
+            let reused_from = match reusable {
+                Some((src, reused_from)) => {
+                    storage.copy(&src, &join(&dst_dir, &index_file))?;
+                    Some(reused_from)
+                }
+                None => {
+                    let path = temp_dir.path().join(uuid.to_string());
+                    copy_env(&index, &path, key)?;
+                    put(&index_file, &path)?;
+                    None
+                }
+            };
+
+            indexes.insert(uuid, IndexManifest { uid: name.to_string(), updated_at, reused_from });
+        }
+
+        drop(rtxn);
+
+        // 4. Snapshot the auth LMDB env
+        // TODO We can't use the open_auth_store_env function here but we should
+        let auth = meilisearch_types::heed::EnvOpenOptions::new()
+            .map_size(1024 * 1024 * 1024) // 1 GiB
+            .max_dbs(2)
+            .open(&self.auth_path)?;
+        let auth_path = temp_dir.path().join("auth.mdb");
+        copy_env(&auth, &auth_path, key)?;
+        put("auth/data.mdb", &auth_path)?;
+
+        // 5. Write the manifest, which marks the generation as complete
+        let manifest = Manifest { generation, created_at: OffsetDateTime::now_utc(), indexes };
+        let reused = manifest.indexes.values().filter(|index| index.reused_from.is_some()).count();
+        let manifest = serde_json::to_vec_pretty(&manifest).map_err(anyhow::Error::from)?;
+        let manifest_path = temp_dir.path().join(MANIFEST_FILE_NAME);
+        write_file(&manifest_path, &manifest, key)?;
+        put(MANIFEST_FILE_NAME, &manifest_path)?;
+        info!(
+            "Created the snapshot generation {} in {}, reused {} unchanged indexes.",
+            generation, dst_dir, reused,
+        );
+
+        // 6. Remove the generations that exceed the retention, along with
+        //    the incomplete generations older than the ones we keep.
+        let generations = list_generations(storage, &snapshot_dir)?;
+        let complete = complete_generations(&snapshot_dir, &generations);
+        let excess = complete.len().saturating_sub(self.snapshot_retention.max(1));
+        if let Some(oldest_kept) = complete.get(excess) {
+            for keys in generations.range(..oldest_kept).map(|(_, keys)| keys) {
+                // The manifest goes first so a partially deleted generation is never seen as complete.
+                let (manifests, others): (Vec<_>, Vec<_>) =
+                    keys.iter().partition(|key| key.ends_with(MANIFEST_FILE_NAME));
+                for key in manifests.into_iter().chain(others) {
+                    storage.delete(key)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Restores the latest generation of the incremental snapshot stored in the `dir`
+/// directory of the storage into `dest`.
+///
+/// `dir` can either be the directory containing the generations or a single generation.
+pub fn restore_incremental_snapshot(
+    storage: &dyn Storage,
+    dir: &str,
+    dest: impl AsRef<Path>,
+    encryption_key: Option<&EncryptionKey>,
+) -> anyhow::Result<()> {
+    let dest = dest.as_ref();
+    let dir = dir.trim_matches('/');
+    let generation_dir = if storage.list(dir)?.contains(&join(dir, MANIFEST_FILE_NAME)) {
+        dir.to_string()
+    } else {
+        let generations = list_generations(storage, dir)?;
+        match complete_generations(dir, &generations).pop() {
+            Some(generation) => generation_dir(dir, generation),
+            None => bail!("no snapshot generation found in `{}`", dir),
+        }
+    };
+
+    let manifest = read_manifest(storage, &generation_dir, encryption_key)
+        .with_context(|| format!("could not read the snapshot `{}`", generation_dir))?;
+    info!(
+        "Importing the snapshot generation {} of the {}",
+        manifest.generation, manifest.created_at
+    );
+
+    let prefix = join(&generation_dir, "");
+    for key in storage.list(&generation_dir)? {
+        let name = match key.strip_prefix(&prefix) {
+            Some(name) if name != MANIFEST_FILE_NAME => name,
+            _ => continue,
+        };
+        let dst = dest.join(name);
+        fs::create_dir_all(dst.parent().unwrap_or(dest))?;
+
+        let mut writer = BufWriter::new(File::create(&dst)?);
+        let mut reader = BufReader::new(storage.get(&key)?);
+        if name.starts_with(UPDATE_FILES_DIR) {
+            // the update files are kept as-is, the file store decrypts them when needed.
+            io::copy(&mut reader, &mut writer)?;
+        } else {
+            let mut reader = encryption::decrypt_if_needed(reader, encryption_key)?;
+            io::copy(&mut reader, &mut writer)?;
+        }
+        writer.flush()?;
+    }
+
+    Ok(())
+}
+
+fn join(dir: &str, name: &str) -> String {
+    if dir.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", dir, name)
+    }
+}
+
+pub(crate) fn generation_dir(snapshot_dir: &str, generation: u64) -> String {
+    join(snapshot_dir, &format!("{}{:020}", GENERATION_PREFIX, generation))
+}
+
+/// Returns the keys of every generation, complete or not, found in `snapshot_dir`.
+pub(crate) fn list_generations(
+    storage: &dyn Storage,
+    snapshot_dir: &str,
+) -> Result<BTreeMap<u64, Vec<String>>> {
+    let prefix = join(snapshot_dir, GENERATION_PREFIX);
+    let mut generations = BTreeMap::<u64, Vec<String>>::new();
+    for key in storage.list(snapshot_dir)? {
+        let generation = key
+            .strip_prefix(&prefix)
+            .and_then(|rest| rest.split_once('/'))
+            .and_then(|(generation, _)| generation.parse().ok());
+        if let Some(generation) = generation {
+            generations.entry(generation).or_default().push(key);
+        }
+    }
+    Ok(generations)
+}
+
+/// Returns the generations containing a manifest, sorted from the oldest to the most recent.
+fn complete_generations(snapshot_dir: &str, generations: &BTreeMap<u64, Vec<String>>) -> Vec<u64> {
+    generations
+        .iter()
+        .filter(|(generation, keys)| {
+            let manifest = join(&generation_dir(snapshot_dir, **generation), MANIFEST_FILE_NAME);
+            keys.contains(&manifest)
+        })
+        .map(|(generation, _)| *generation)
+        .collect()
+}
+
+pub(crate) fn read_manifest(
+    storage: &dyn Storage,
+    generation_dir: &str,
+    encryption_key: Option<&EncryptionKey>,
+) -> anyhow::Result<Manifest> {
+    let reader = storage.get(&join(generation_dir, MANIFEST_FILE_NAME))?;
+    let reader = encryption::decrypt_if_needed(BufReader::new(reader), encryption_key)?;
+    Ok(serde_json::from_reader(reader)?)
+}
+
+/// Returns `true` if the object can be read with the given key,
+/// i.e. it is encrypted with this key, or it is not encrypted and no key is given.
+fn is_readable_with(
+    storage: &dyn Storage,
+    object: &str,
+    encryption_key: Option<&EncryptionKey>,
+) -> Result<bool> {
+    let reader = storage.get(object)?;
+    match encryption::decrypt_if_needed(reader, encryption_key) {
+        Ok(MaybeDecrypted::Plain(_)) => Ok(encryption_key.is_none()),
+        Ok(MaybeDecrypted::Decrypted(_)) => Ok(true),
+        Err(_) => Ok(false),
+    }
+}
+
+/// Copies an LMDB env to the file at `dst`, encrypting it if a key is given.
+fn copy_env(env: &Env, dst: &Path, encryption_key: Option<&EncryptionKey>) -> Result<()> {
+    match encryption_key {
+        Some(key) => {
+            let mut writer = EncryptedWriter::new(BufWriter::new(File::create(dst)?), key)?;
+            stream_env(env, &mut writer)?;
+            writer.finish()?.flush()?;
+        }
+        None => {
+            env.copy_to_path(dst, CompactionOption::Enabled)?;
+        }
+    }
+    Ok(())
+}
+
+/// Streams a compacted copy of an LMDB env into the writer.
+///
+/// LMDB copies the env into one end of a pipe while we read the other end,
+/// so the plain content of the env is never written to the disk.
+fn stream_env(env: &Env, writer: &mut impl Write) -> Result<()> {
+    let (mut reader, sender) = io::pipe()?;
+    let env = env.clone();
+    let copy = std::thread::spawn(move || {
+        #[cfg(unix)]
+        let handle = std::os::unix::io::AsRawFd::as_raw_fd(&sender);
+        #[cfg(windows)]
+        let handle = std::os::windows::io::AsRawHandle::as_raw_handle(&sender);
+        // Safety: the pipe is only closed once LMDB is done writing in it.
+        let result = unsafe { env.copy_to_fd(handle, CompactionOption::Enabled) };
+        drop(sender);
+        result
+    });
+
+    let streamed = io::copy(&mut reader, writer);
+    // Closing our end makes LMDB fail instead of blocking forever if we stopped reading early.
+    drop(reader);
+    let copied = copy.join().map_err(|_| Error::ProcessBatchPanicked)?;
+    copied?;
+    streamed?;
+    Ok(())
+}
+
+fn copy_file(src: &Path, dst: &Path, encryption_key: Option<&EncryptionKey>) -> Result<()> {
+    match encryption_key {
+        Some(key) => {
+            let mut reader = BufReader::new(File::open(src)?);
+            let mut writer = EncryptedWriter::new(BufWriter::new(File::create(dst)?), key)?;
+            io::copy(&mut reader, &mut writer)?;
+            writer.finish()?.flush()?;
+        }
+        None => {
+            fs::copy(src, dst)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_file(dst: &Path, content: &[u8], encryption_key: Option<&EncryptionKey>) -> Result<()> {
+    let mut writer = BufWriter::new(File::create(dst)?);
+    match encryption_key {
+        Some(key) => {
+            let mut writer = EncryptedWriter::new(&mut writer, key)?;
+            writer.write_all(content)?;
+            writer.finish()?;
+        }
+        None => writer.write_all(content)?,
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::storage::LocalStorage;
+
+    fn put(storage: &dyn Storage, key: &str, content: &[u8]) {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        fs::write(file.path(), content).unwrap();
+        storage.put(key, file.path()).unwrap();
+    }
+
+    #[test]
+    fn generations_are_listed_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = LocalStorage::new(dir.path());
+        for generation in [10, 2, 1] {
+            let manifest = join(&generation_dir("db.snapshots", generation), MANIFEST_FILE_NAME);
+            put(&storage, &manifest, b"{}");
+        }
+        // an incomplete generation and any unrelated file are ignored
+        put(&storage, &join(&generation_dir("db.snapshots", 11), VERSION_FILE_NAME), b"1.0.0");
+        put(&storage, "db.snapshots/generation-notes", b"hello");
+
+        let generations = list_generations(&storage, "db.snapshots").unwrap();
+        assert_eq!(generations.keys().copied().collect::<Vec<_>>(), [1, 2, 10, 11]);
+        assert_eq!(complete_generations("db.snapshots", &generations), [1, 2, 10]);
+    }
+
+    #[test]
+    fn restore_encrypted_generation() {
+        let key = EncryptionKey::new("the doggos are sleeping");
+        let dir = tempfile::tempdir().unwrap();
+        let storage = LocalStorage::new(dir.path());
+        let generation_dir = generation_dir("db.snapshots", 3);
+
+        let manifest = Manifest {
+            generation: 3,
+            created_at: OffsetDateTime::now_utc(),
+            indexes: BTreeMap::new(),
+        };
+        let manifest = serde_json::to_vec(&manifest).unwrap();
+        let temp = tempfile::tempdir().unwrap();
+        for (name, content) in
+            [(MANIFEST_FILE_NAME, &manifest[..]), (VERSION_FILE_NAME, &b"1.0.0"[..])]
+        {
+            let path = temp.path().join(name);
+            write_file(&path, content, Some(&key)).unwrap();
+            storage.put(&join(&generation_dir, name), &path).unwrap();
+        }
+        let update_file = format!("{}/update", UPDATE_FILES_DIR);
+        put(&storage, &join(&generation_dir, &update_file), b"untouched");
+
+        let dest = tempfile::tempdir().unwrap();
+        let error = restore_incremental_snapshot(&storage, "db.snapshots", dest.path(), None);
+        assert!(error.is_err());
+
+        restore_incremental_snapshot(&storage, "db.snapshots/", dest.path(), Some(&key)).unwrap();
+        assert_eq!(fs::read(dest.path().join(VERSION_FILE_NAME)).unwrap(), b"1.0.0");
+        assert_eq!(fs::read(dest.path().join(&update_file)).unwrap(), b"untouched");
+        assert!(!dest.path().join(MANIFEST_FILE_NAME).exists());
+
+        let version = join(&generation_dir, VERSION_FILE_NAME);
+        assert!(is_readable_with(&storage, &version, Some(&key)).unwrap());
+        assert!(!is_readable_with(&storage, &version, None).unwrap());
+    }
+}
@@ -0,0 +1,139 @@
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use uuid::Uuid;
+
+use super::{Error, Result, Storage};
+
+/// Stores the objects as files in a directory of the local file system.
+#[derive(Debug, Clone)]
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: impl Into<PathBuf>) -> LocalStorage {
+        LocalStorage { root: root.into() }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.root.join(key)
+    }
+
+    /// Moves the file at `src`, or a copy of it if it can't be hard-linked, to `dst`
+    /// through a temporary file so that `dst` is never partially written.
+    fn link_or_copy(src: &Path, dst: &Path) -> Result<()> {
+        let parent = dst.parent().unwrap_or_else(|| Path::new("."));
+        fs::create_dir_all(parent)?;
+
+        let temp = parent.join(format!(".{}.tmp", Uuid::new_v4()));
+        if fs::hard_link(src, &temp).is_err() {
+            fs::copy(src, &temp)?;
+        }
+        if let Err(e) = fs::rename(&temp, dst) {
+            let _ = fs::remove_file(&temp);
+            return Err(e.into());
+        }
+        Ok(())
+    }
+}
+
+impl Storage for LocalStorage {
+    fn put(&self, key: &str, src: &Path) -> Result<()> {
+        LocalStorage::link_or_copy(src, &self.path(key))
+    }
+
+    fn get(&self, key: &str) -> Result<Box<dyn Read + Send>> {
+        match File::open(self.path(key)) {
+            Ok(file) => Ok(Box::new(file)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Err(Error::NotFound(key.to_string())),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn exists(&self, key: &str) -> Result<bool> {
+        Ok(self.path(key).is_file())
+    }
+
+    fn copy(&self, from: &str, to: &str) -> Result<()> {
+        let src = self.path(from);
+        if !src.is_file() {
+            return Err(Error::NotFound(from.to_string()));
+        }
+        LocalStorage::link_or_copy(&src, &self.path(to))
+    }
+
+    fn list(&self, dir: &str) -> Result<Vec<String>> {
+        fn visit(root: &Path, dir: &Path, keys: &mut Vec<String>) -> io::Result<()> {
+            for entry in fs::read_dir(dir)? {
+                let entry = entry?;
+                let path = entry.path();
+                if entry.file_type()?.is_dir() {
+                    visit(root, &path, keys)?;
+                } else if let Ok(relative) = path.strip_prefix(root) {
+                    let components: Vec<_> =
+                        relative.components().map(|c| c.as_os_str().to_string_lossy()).collect();
+                    keys.push(components.join("/"));
+                }
+            }
+            Ok(())
+        }
+
+        let mut keys = Vec::new();
+        let dir = self.path(dir);
+        if dir.is_dir() {
+            visit(&self.root, &dir, &mut keys)?;
+        }
+        keys.sort_unstable();
+        Ok(keys)
+    }
+
+    fn delete(&self, key: &str) -> Result<()> {
+        let path = self.path(key);
+        match fs::remove_file(&path) {
+            Ok(()) => (),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e.into()),
+        }
+
+        // remove the directories left empty, stopping at the root of the storage.
+        for dir in path.ancestors().skip(1).take_while(|dir| *dir != self.root) {
+            if fs::remove_dir(dir).is_err() {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn put_get_list_delete() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = LocalStorage::new(dir.path().join("storage"));
+        let src = dir.path().join("src");
+        fs::write(&src, "hello").unwrap();
+
+        storage.put("a/b/c.dump", &src).unwrap();
+        storage.put("a/d.dump", &src).unwrap();
+        storage.copy("a/d.dump", "e.dump").unwrap();
+        assert!(storage.exists("e.dump").unwrap());
+        assert!(!storage.exists("a/b").unwrap());
+        assert_eq!(storage.list("a").unwrap(), ["a/b/c.dump", "a/d.dump"]);
+        assert_eq!(storage.list("").unwrap(), ["a/b/c.dump", "a/d.dump", "e.dump"]);
+
+        let mut content = String::new();
+        storage.get("e.dump").unwrap().read_to_string(&mut content).unwrap();
+        assert_eq!(content, "hello");
+
+        storage.delete("a/b/c.dump").unwrap();
+        storage.delete("a/b/c.dump").unwrap();
+        assert!(!dir.path().join("storage/a/b").exists());
+        assert!(matches!(storage.get("a/b/c.dump"), Err(Error::NotFound(_))));
+        assert_eq!(storage.list("a").unwrap(), ["a/d.dump"]);
+    }
+}
@@ -0,0 +1,67 @@
+/*!
+The storage backends on which the snapshots and the dumps are stored.
+
+A [`Storage`] is a flat key-value store of files where the keys are `/`-separated
+paths, the same way an object store works. Two implementations are provided:
+- [`LocalStorage`] stores the files in a directory of the local file system.
+- [`S3Storage`] stores the files in a bucket of an S3-compatible object store.
+*/
+
+mod local;
+mod s3;
+
+use std::fmt;
+use std::io::{self, Read};
+use std::path::Path;
+
+pub use local::LocalStorage;
+use meilisearch_types::error::{Code, ErrorCode};
+pub use s3::{S3Credentials, S3Storage};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Object `{0}` not found in the storage.")]
+    NotFound(String),
+    #[error("Invalid storage url `{0}`. It should contain the bucket, e.g. `http://localhost:9000/my-bucket`.")]
+    InvalidUrl(String),
+    #[error("The storage responded with the status code {status}: {message}")]
+    Remote { status: u16, message: String },
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    #[error(transparent)]
+    IoError(#[from] io::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl ErrorCode for Error {
+    fn error_code(&self) -> Code {
+        match self {
+            Error::IoError(e) => e.error_code(),
+            Error::NotFound(_) | Error::InvalidUrl(_) | Error::Remote { .. } | Error::Http(_) => {
+                Code::Internal
+            }
+        }
+    }
+}
+
+/// A backend on which the snapshots and the dumps are stored.
+pub trait Storage: fmt::Debug + Send + Sync {
+    /// Stores the content of the local file at `src` under `key`, replacing the previous object if any.
+    fn put(&self, key: &str, src: &Path) -> Result<()>;
+
+    /// Returns a reader streaming the content of the object stored under `key`.
+    fn get(&self, key: &str) -> Result<Box<dyn Read + Send>>;
+
+    /// Returns `true` if an object is stored under `key`, without reading its content.
+    fn exists(&self, key: &str) -> Result<bool>;
+
+    /// Copies the object stored under `from` to `to` without going through Meilisearch.
+    fn copy(&self, from: &str, to: &str) -> Result<()>;
+
+    /// Recursively lists the keys of the objects stored in the `dir` directory.
+    fn list(&self, dir: &str) -> Result<Vec<String>>;
+
+    /// Deletes the object stored under `key`. Deleting a missing object is not an error.
+    fn delete(&self, key: &str) -> Result<()>;
+}
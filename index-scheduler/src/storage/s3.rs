@@ -0,0 +1,645 @@
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use hmac::{Hmac, Mac};
+use once_cell::sync::OnceCell;
+use reqwest::blocking::{Body, Client, RequestBuilder, Response};
+use reqwest::header::CONTENT_LENGTH;
+use reqwest::{Method, StatusCode, Url};
+use sha2::{Digest, Sha256};
+use time::macros::format_description;
+use time::OffsetDateTime;
+
+use super::{Error, Result, Storage};
+
+/// The size of the parts of the multipart uploads and copies.
+const DEFAULT_PART_SIZE: u64 = 256 * 1024 * 1024; // 256 MiB
+/// The maximum size of an object copied in a single request.
+const MAX_SINGLE_COPY_SIZE: u64 = 5 * 1024 * 1024 * 1024; // 5 GiB
+
+#[derive(Clone)]
+pub struct S3Credentials {
+    pub access_key: String,
+    pub secret_key: String,
+}
+
+impl std::fmt::Debug for S3Credentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("S3Credentials").field("access_key", &self.access_key).finish()
+    }
+}
+
+/// Stores the objects in a bucket of an S3-compatible object store.
+///
+/// The bucket is accessed with path-style URLs, e.g. `http://localhost:9000/my-bucket`,
+/// which are supported by AWS as well as by the self-hosted stores like MinIO.
+/// The requests are signed with the AWS Signature Version 4 when credentials are given.
+#[derive(Debug)]
+pub struct S3Storage {
+    /// The endpoint, without any path.
+    endpoint: Url,
+    bucket: String,
+    /// The prefix added to all the keys, empty or ending with a `/`.
+    prefix: String,
+    region: String,
+    credentials: Option<S3Credentials>,
+    part_size: u64,
+    max_single_copy_size: u64,
+    /// The blocking client can't be created from an async context, we thus create it lazily.
+    client: OnceCell<Client>,
+}
+
+impl S3Storage {
+    /// Creates a storage from the URL of a bucket, optionally followed by a prefix under which
+    /// all the objects are stored, e.g. `https://s3.eu-west-3.amazonaws.com/my-bucket/meilisearch`.
+    pub fn new(
+        url: &str,
+        region: impl Into<String>,
+        credentials: Option<S3Credentials>,
+    ) -> Result<S3Storage> {
+        let mut endpoint = Url::parse(url).map_err(|_| Error::InvalidUrl(url.to_string()))?;
+        let mut segments = endpoint.path().split('/').filter(|s| !s.is_empty());
+        let bucket = match segments.next() {
+            Some(bucket) => bucket.to_string(),
+            None => return Err(Error::InvalidUrl(url.to_string())),
+        };
+        let prefix: String = segments.map(|s| format!("{}/", s)).collect();
+        endpoint.set_path("");
+        endpoint.set_query(None);
+
+        Ok(S3Storage {
+            endpoint,
+            bucket,
+            prefix,
+            region: region.into(),
+            credentials,
+            part_size: DEFAULT_PART_SIZE,
+            max_single_copy_size: MAX_SINGLE_COPY_SIZE,
+            client: OnceCell::new(),
+        })
+    }
+
+    /// Returns a storage storing its objects under the `prefix` directory of this storage.
+    pub fn with_prefix(mut self, prefix: &str) -> S3Storage {
+        for segment in prefix.split('/').filter(|s| !s.is_empty() && *s != ".") {
+            self.prefix.push_str(segment);
+            self.prefix.push('/');
+        }
+        self
+    }
+
+    fn client(&self) -> Result<&Client> {
+        self.client.get_or_try_init(|| Client::builder().timeout(None).build()).map_err(Error::from)
+    }
+
+    fn object_path(&self, key: &str) -> String {
+        format!("/{}/{}{}", self.bucket, self.prefix, key)
+    }
+
+    /// Builds a signed request on the given path, `headers` must only contain `x-amz-*` headers.
+    fn request(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, &str)],
+        headers: &[(&str, &str)],
+    ) -> Result<RequestBuilder> {
+        let path = uri_encode(path, false);
+        let mut query: Vec<_> =
+            query.iter().map(|(k, v)| (uri_encode(k, true), uri_encode(v, true))).collect();
+        query.sort_unstable();
+        let query = query.iter().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<_>>().join("&");
+
+        let mut url = self.endpoint.clone();
+        url.set_path(&path);
+        url.set_query(Some(&query).filter(|q| !q.is_empty()).map(|q| q.as_str()));
+
+        let now = OffsetDateTime::now_utc();
+        let amz_date =
+            now.format(format_description!("[year][month][day]T[hour][minute][second]Z")).unwrap();
+        let mut amz_headers: Vec<(String, String)> = headers
+            .iter()
+            .map(|(name, value)| (name.to_lowercase(), value.trim().to_string()))
+            .chain([
+                ("x-amz-content-sha256".to_string(), "UNSIGNED-PAYLOAD".to_string()),
+                ("x-amz-date".to_string(), amz_date.clone()),
+            ])
+            .collect();
+        amz_headers.sort_unstable();
+
+        let mut request = self.client()?.request(method.clone(), url.clone());
+        for (name, value) in &amz_headers {
+            request = request.header(name, value);
+        }
+
+        if let Some(credentials) = &self.credentials {
+            let host = match url.port() {
+                Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+                None => url.host_str().unwrap_or_default().to_string(),
+            };
+            let mut signed_headers = vec![("host".to_string(), host)];
+            signed_headers.extend(amz_headers);
+            signed_headers.sort_unstable();
+
+            let authorization = sign(
+                credentials,
+                &self.region,
+                &amz_date,
+                method.as_str(),
+                &path,
+                &query,
+                &signed_headers,
+            );
+            request = request.header("authorization", authorization);
+        }
+
+        Ok(request)
+    }
+
+    fn send(&self, key: &str, request: RequestBuilder) -> Result<Response> {
+        let response = request.send()?;
+        match response.status() {
+            status if status.is_success() => Ok(response),
+            StatusCode::NOT_FOUND => Err(Error::NotFound(key.to_string())),
+            status => Err(Error::Remote {
+                status: status.as_u16(),
+                message: response.text().unwrap_or_default(),
+            }),
+        }
+    }
+
+    fn content_length(&self, key: &str) -> Result<u64> {
+        let request = self.request(Method::HEAD, &self.object_path(key), &[], &[])?;
+        let response = self.send(key, request)?;
+        // `Response::content_length` is always zero for the HEAD requests.
+        let content_length = response
+            .headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|length| length.to_str().ok()?.parse().ok());
+        Ok(content_length.unwrap_or_default())
+    }
+
+    /// Creates an object of `len` bytes in multiple parts, `upload_part` is called with the
+    /// part number, upload id, offset and size of every part and must return its ETag.
+    fn multipart(
+        &self,
+        key: &str,
+        len: u64,
+        mut upload_part: impl FnMut(u64, &str, u64, u64) -> Result<String>,
+    ) -> Result<()> {
+        let path = self.object_path(key);
+        let request = self.request(Method::POST, &path, &[("uploads", "")], &[])?;
+        let body = self.send(key, request)?.text()?;
+        let upload_id = match xml_values(&body, "UploadId").pop() {
+            Some(upload_id) => upload_id,
+            None => return Err(Error::Remote { status: 200, message: body }),
+        };
+
+        let result = (|| {
+            let mut completion = String::from("<CompleteMultipartUpload>");
+            for (index, offset) in (0..len).step_by(self.part_size as usize).enumerate() {
+                let part_number = index as u64 + 1;
+                let size = self.part_size.min(len - offset);
+                let etag = upload_part(part_number, &upload_id, offset, size)?;
+                let _ = write!(
+                    completion,
+                    "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+                    part_number, etag
+                );
+            }
+            completion.push_str("</CompleteMultipartUpload>");
+
+            let query = [("uploadId", upload_id.as_str())];
+            let request = self.request(Method::POST, &path, &query, &[])?.body(completion);
+            // the completion can fail after the status has been sent.
+            let body = self.send(key, request)?.text()?;
+            if body.contains("<Error>") {
+                return Err(Error::Remote { status: 200, message: body });
+            }
+            Ok(())
+        })();
+
+        if result.is_err() {
+            let query = [("uploadId", upload_id.as_str())];
+            if let Ok(request) = self.request(Method::DELETE, &path, &query, &[]) {
+                let _ = self.send(key, request);
+            }
+        }
+        result
+    }
+
+    fn etag(response: &Response) -> String {
+        response
+            .headers()
+            .get("etag")
+            .and_then(|etag| etag.to_str().ok())
+            .unwrap_or_default()
+            .to_string()
+    }
+}
+
+impl Storage for S3Storage {
+    fn put(&self, key: &str, src: &Path) -> Result<()> {
+        let path = self.object_path(key);
+        let file = File::open(src)?;
+        let len = file.metadata()?.len();
+
+        if len <= self.part_size {
+            let request = self.request(Method::PUT, &path, &[], &[])?.body(Body::sized(file, len));
+            self.send(key, request)?;
+            return Ok(());
+        }
+
+        self.multipart(key, len, |part_number, upload_id, offset, size| {
+            let mut part = file.try_clone()?;
+            part.seek(SeekFrom::Start(offset))?;
+            let part_number = part_number.to_string();
+            let query = [("partNumber", part_number.as_str()), ("uploadId", upload_id)];
+            let request = self
+                .request(Method::PUT, &path, &query, &[])?
+                .body(Body::sized(part.take(size), size));
+            Ok(S3Storage::etag(&self.send(key, request)?))
+        })
+    }
+
+    fn get(&self, key: &str) -> Result<Box<dyn Read + Send>> {
+        let request = self.request(Method::GET, &self.object_path(key), &[], &[])?;
+        Ok(Box::new(self.send(key, request)?))
+    }
+
+    fn exists(&self, key: &str) -> Result<bool> {
+        match self.content_length(key) {
+            Ok(_) => Ok(true),
+            Err(Error::NotFound(_)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn copy(&self, from: &str, to: &str) -> Result<()> {
+        let path = self.object_path(to);
+        let source = uri_encode(&self.object_path(from), false);
+        let len = self.content_length(from)?;
+
+        if len <= self.max_single_copy_size {
+            let headers = [("x-amz-copy-source", source.as_str())];
+            let request = self.request(Method::PUT, &path, &[], &headers)?;
+            let body = self.send(from, request)?.text()?;
+            if body.contains("<Error>") {
+                return Err(Error::Remote { status: 200, message: body });
+            }
+            return Ok(());
+        }
+
+        self.multipart(to, len, |part_number, upload_id, offset, size| {
+            let part_number = part_number.to_string();
+            let range = format!("bytes={}-{}", offset, offset + size - 1);
+            let query = [("partNumber", part_number.as_str()), ("uploadId", upload_id)];
+            let headers = [
+                ("x-amz-copy-source", source.as_str()),
+                ("x-amz-copy-source-range", range.as_str()),
+            ];
+            let request = self.request(Method::PUT, &path, &query, &headers)?;
+            let body = self.send(from, request)?.text()?;
+            Ok(xml_values(&body, "ETag").pop().unwrap_or_default())
+        })
+    }
+
+    fn list(&self, dir: &str) -> Result<Vec<String>> {
+        let mut prefix = format!("{}{}", self.prefix, dir.trim_matches('/'));
+        if !prefix.is_empty() && !prefix.ends_with('/') {
+            prefix.push('/');
+        }
+
+        let bucket_path = format!("/{}", self.bucket);
+        let mut keys = Vec::new();
+        let mut continuation_token: Option<String> = None;
+        loop {
+            let mut query = vec![("list-type", "2"), ("prefix", prefix.as_str())];
+            if let Some(token) = &continuation_token {
+                query.push(("continuation-token", token.as_str()));
+            }
+            let request = self.request(Method::GET, &bucket_path, &query, &[])?;
+            let body = self.send(dir, request)?.text()?;
+
+            keys.extend(
+                xml_values(&body, "Key")
+                    .into_iter()
+                    .filter_map(|key| key.strip_prefix(&self.prefix).map(String::from)),
+            );
+
+            continuation_token = xml_values(&body, "NextContinuationToken").pop();
+            if !body.contains("<IsTruncated>true</IsTruncated>") || continuation_token.is_none() {
+                break;
+            }
+        }
+
+        keys.sort_unstable();
+        Ok(keys)
+    }
+
+    fn delete(&self, key: &str) -> Result<()> {
+        let request = self.request(Method::DELETE, &self.object_path(key), &[], &[])?;
+        match self.send(key, request) {
+            Ok(_) | Err(Error::NotFound(_)) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Returns the `Authorization` header of a request signed with the AWS Signature Version 4.
+///
+/// `headers` must be sorted and contain the lowercased `host` and `x-amz-*` headers.
+fn sign(
+    credentials: &S3Credentials,
+    region: &str,
+    amz_date: &str,
+    method: &str,
+    path: &str,
+    query: &str,
+    headers: &[(String, String)],
+) -> String {
+    let date = &amz_date[..8];
+    let scope = format!("{}/{}/s3/aws4_request", date, region);
+    let signed_headers =
+        headers.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>().join(";");
+    let canonical_headers: String =
+        headers.iter().map(|(name, value)| format!("{}:{}\n", name, value)).collect();
+
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{}\n{}\nUNSIGNED-PAYLOAD",
+        method, path, query, canonical_headers, signed_headers
+    );
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let key = hmac(format!("AWS4{}", credentials.secret_key).as_bytes(), date.as_bytes());
+    let key = hmac(&key, region.as_bytes());
+    let key = hmac(&key, b"s3");
+    let key = hmac(&key, b"aws4_request");
+    let signature = hex::encode(hmac(&key, string_to_sign.as_bytes()));
+
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        credentials.access_key, scope, signed_headers, signature
+    )
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Percent-encodes everything but the unreserved characters, and the `/` if `encode_slash` is `false`.
+fn uri_encode(s: &str, encode_slash: bool) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            b'/' if !encode_slash => encoded.push('/'),
+            _ => {
+                let _ = write!(encoded, "%{:02X}", byte);
+            }
+        }
+    }
+    encoded
+}
+
+/// Returns the unescaped values of all the `tag` elements of an XML document.
+fn xml_values(xml: &str, tag: &str) -> Vec<String> {
+    let (open, close) = (format!("<{}>", tag), format!("</{}>", tag));
+    let mut values = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        rest = &rest[start + open.len()..];
+        let end = match rest.find(&close) {
+            Some(end) => end,
+            None => break,
+        };
+        let value = rest[..end]
+            .replace("&quot;", "\"")
+            .replace("&apos;", "'")
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&amp;", "&");
+        values.push(value);
+        rest = &rest[end + close.len()..];
+    }
+    values
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    use super::*;
+
+    type Objects = Arc<Mutex<BTreeMap<String, Vec<u8>>>>;
+    type Parts = Arc<Mutex<BTreeMap<(String, u64), Vec<u8>>>>;
+
+    /// A minimal stand-in for an S3-compatible store, just enough to exercise the storage.
+    fn fake_s3() -> (String, Objects) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/bucket", listener.local_addr().unwrap());
+        let objects = Objects::default();
+        let parts = Parts::default();
+
+        let store = objects.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let (objects, parts) = (store.clone(), parts.clone());
+                thread::spawn(move || handle(stream.unwrap(), objects, parts));
+            }
+        });
+
+        (url, objects)
+    }
+
+    fn handle(stream: TcpStream, objects: Objects, parts: Parts) {
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut writer = stream;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                return;
+            }
+            let mut request_line = line.split_whitespace();
+            let method = request_line.next().unwrap().to_string();
+            let target = request_line.next().unwrap().to_string();
+
+            let mut headers = BTreeMap::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                match line.trim_end().split_once(':') {
+                    Some((name, value)) => {
+                        headers.insert(name.to_lowercase(), value.trim().to_string())
+                    }
+                    None => break,
+                };
+            }
+            let len = headers.get("content-length").map_or(0, |l| l.parse().unwrap());
+            let mut body = vec![0; len];
+            reader.read_exact(&mut body).unwrap();
+
+            let (path, query) = target.split_once('?').unwrap_or((&target, ""));
+            let key = path.trim_start_matches("/bucket/").replace("%2F", "/");
+            let query: BTreeMap<_, _> =
+                query.split('&').filter_map(|p| p.split_once('=')).collect();
+            let copy_source = headers
+                .get("x-amz-copy-source")
+                .map(|source| source.trim_start_matches("/bucket/").to_string());
+
+            let mut objects = objects.lock().unwrap();
+            let mut parts = parts.lock().unwrap();
+            let (status, etag, response) = match (method.as_str(), copy_source) {
+                ("PUT", source) if query.contains_key("partNumber") => {
+                    let part_number: u64 = query["partNumber"].parse().unwrap();
+                    let content = match (source, headers.get("x-amz-copy-source-range")) {
+                        (Some(source), Some(range)) => {
+                            let (start, end) =
+                                range.trim_start_matches("bytes=").split_once('-').unwrap();
+                            let (start, end): (usize, usize) =
+                                (start.parse().unwrap(), end.parse().unwrap());
+                            objects[&source][start..=end].to_vec()
+                        }
+                        _ => body,
+                    };
+                    parts.insert((query["uploadId"].to_string(), part_number), content);
+                    let etag = format!("\"{}\"", part_number);
+                    (
+                        200,
+                        etag.clone(),
+                        format!("<CopyPartResult><ETag>{}</ETag></CopyPartResult>", etag),
+                    )
+                }
+                ("PUT", Some(source)) => match objects.get(&source).cloned() {
+                    Some(content) => {
+                        objects.insert(key, content);
+                        (200, String::new(), "<CopyObjectResult></CopyObjectResult>".to_string())
+                    }
+                    None => (404, String::new(), "<Error></Error>".to_string()),
+                },
+                ("PUT", None) => {
+                    objects.insert(key, body);
+                    (200, String::new(), String::new())
+                }
+                ("POST", _) if query.contains_key("uploads") => {
+                    (200, String::new(), format!("<UploadId>{}</UploadId>", key))
+                }
+                ("POST", _) => {
+                    let upload_id = query["uploadId"];
+                    let content = parts
+                        .iter()
+                        .filter(|((id, _), _)| id == upload_id)
+                        .flat_map(|(_, part)| part.clone())
+                        .collect();
+                    objects.insert(key, content);
+                    (200, String::new(), "<CompleteMultipartUploadResult/>".to_string())
+                }
+                ("DELETE", _) => {
+                    objects.remove(&key);
+                    (204, String::new(), String::new())
+                }
+                ("GET", _) if query.contains_key("list-type") => {
+                    let prefix = query["prefix"].replace("%2F", "/");
+                    let keys: String = objects
+                        .keys()
+                        .filter(|key| key.starts_with(&prefix))
+                        .map(|key| format!("<Contents><Key>{}</Key></Contents>", key))
+                        .collect();
+                    (200, String::new(), format!("<ListBucketResult>{}</ListBucketResult>", keys))
+                }
+                ("GET" | "HEAD", _) => match objects.get(&key) {
+                    Some(content) => (200, String::new(), String::from_utf8_lossy(content).into()),
+                    None => (404, String::new(), "<Error></Error>".to_string()),
+                },
+                _ => (400, String::new(), String::new()),
+            };
+
+            let body = if method == "HEAD" { "" } else { response.as_str() };
+            write!(
+                writer,
+                "HTTP/1.1 {} OK\r\nETag: {}\r\nContent-Length: {}\r\n\r\n{}",
+                status,
+                etag,
+                response.len(),
+                body
+            )
+            .unwrap();
+        }
+    }
+
+    #[test]
+    fn put_get_copy_list_delete() {
+        let (url, objects) = fake_s3();
+        let credentials =
+            S3Credentials { access_key: "minio".to_string(), secret_key: "minio123".to_string() };
+        let mut storage =
+            S3Storage::new(&url, "us-east-1", Some(credentials)).unwrap().with_prefix("./dumps/");
+        // forces the multipart uploads and copies of the big objects
+        storage.part_size = 4;
+        storage.max_single_copy_size = 4;
+
+        let dir = tempfile::tempdir().unwrap();
+        let (small, big) = (dir.path().join("small"), dir.path().join("big"));
+        std::fs::write(&small, "hey").unwrap();
+        std::fs::write(&big, "hello world").unwrap();
+
+        storage.put("a/small.dump", &small).unwrap();
+        storage.put("a/big.dump", &big).unwrap();
+        storage.copy("a/big.dump", "b/big.dump").unwrap();
+        assert!(storage.exists("b/big.dump").unwrap());
+        assert!(!storage.exists("b").unwrap());
+        assert_eq!(storage.list("a").unwrap(), ["a/big.dump", "a/small.dump"]);
+        assert_eq!(storage.list("").unwrap(), ["a/big.dump", "a/small.dump", "b/big.dump"]);
+        assert_eq!(objects.lock().unwrap()["dumps/b/big.dump"], b"hello world");
+
+        let mut content = String::new();
+        storage.get("a/big.dump").unwrap().read_to_string(&mut content).unwrap();
+        assert_eq!(content, "hello world");
+
+        storage.delete("a/big.dump").unwrap();
+        assert!(matches!(storage.get("a/big.dump"), Err(Error::NotFound(_))));
+        assert_eq!(storage.list("a").unwrap(), ["a/small.dump"]);
+    }
+
+    #[test]
+    fn parse_urls() {
+        let storage = S3Storage::new("https://s3.amazonaws.com/bucket/some/prefix/", "eu", None)
+            .unwrap()
+            .with_prefix("snapshots");
+        assert_eq!(storage.bucket, "bucket");
+        assert_eq!(
+            storage.object_path("data.ms.snapshot"),
+            "/bucket/some/prefix/snapshots/data.ms.snapshot"
+        );
+        assert!(S3Storage::new("https://s3.amazonaws.com", "eu", None).is_err());
+        assert!(S3Storage::new("not an url", "eu", None).is_err());
+    }
+
+    #[test]
+    fn encode_and_parse() {
+        assert_eq!(uri_encode("/bucket/a b/é~", false), "/bucket/a%20b/%C3%A9~");
+        assert_eq!(uri_encode("a/b", true), "a%2Fb");
+        assert_eq!(
+            xml_values(
+                "<R><Key>a&amp;b</Key><Key>c</Key><IsTruncated>false</IsTruncated></R>",
+                "Key"
+            ),
+            ["a&b", "c"]
+        );
+    }
+}
@@ -257,7 +257,9 @@ pub fn swap_index_uid_in_task(task: &mut Task, swap: (&str, &str)) {
         K::TaskCancelation { .. }
         | K::TaskDeletion { .. }
         | K::DumpCreation { .. }
-        | K::SnapshotCreation => (),
+        | K::DumpImport { .. }
+        | K::SnapshotCreation
+        | K::SuggestionsRefresh => (),
     };
     if let Some(Details::IndexSwap { swaps }) = &mut task.details {
         for IndexSwap { indexes: (lhs, rhs) } in swaps.iter_mut() {
@@ -495,6 +497,9 @@ impl IndexScheduler {
                     Details::Dump { dump_uid: _ } => {
                         assert_eq!(kind.as_kind(), Kind::DumpCreation);
                     }
+                    Details::DumpImport { imported_indexes: _, skipped_indexes: _ } => {
+                        assert_eq!(kind.as_kind(), Kind::DumpImport);
+                    }
                 }
             }
 
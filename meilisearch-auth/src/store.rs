@@ -108,7 +108,7 @@ impl HeedAuthStore {
                     actions.extend([Action::SettingsGet, Action::SettingsUpdate].iter());
                 }
                 Action::DumpsAll => {
-                    actions.insert(Action::DumpsCreate);
+                    actions.extend([Action::DumpsCreate, Action::DumpsImport]);
                 }
                 Action::TasksAll => {
                     actions.extend([Action::TasksGet, Action::TasksDelete, Action::TasksCancel]);
@@ -1,26 +1,60 @@
 use std::fs::{create_dir_all, File};
-use std::io::Write;
+use std::io::{BufReader, BufWriter, Read, Write};
 use std::path::Path;
 
+use file_store::encryption::{self, EncryptedWriter, EncryptionKey};
 use flate2::read::GzDecoder;
 use flate2::write::GzEncoder;
 use flate2::Compression;
 use tar::{Archive, Builder};
 
-pub fn to_tar_gz(src: impl AsRef<Path>, dest: impl AsRef<Path>) -> anyhow::Result<()> {
+/// Tarballs the content of `src` in `dest`, the archive is encrypted if a key is given.
+pub fn to_tar_gz(
+    src: impl AsRef<Path>,
+    dest: impl AsRef<Path>,
+    encryption_key: Option<&EncryptionKey>,
+) -> anyhow::Result<()> {
     let mut f = File::create(dest)?;
-    let gz_encoder = GzEncoder::new(&mut f, Compression::default());
+    match encryption_key {
+        Some(key) => {
+            let writer = EncryptedWriter::new(BufWriter::new(&mut f), key)?;
+            let writer = write_tar_gz(src, writer)?;
+            writer.finish()?;
+        }
+        None => {
+            write_tar_gz(src, &mut f)?;
+        }
+    }
+    f.flush()?;
+    Ok(())
+}
+
+fn write_tar_gz<W: Write>(src: impl AsRef<Path>, writer: W) -> anyhow::Result<W> {
+    let gz_encoder = GzEncoder::new(writer, Compression::default());
     let mut tar_encoder = Builder::new(gz_encoder);
     tar_encoder.append_dir_all(".", src)?;
     let gz_encoder = tar_encoder.into_inner()?;
-    gz_encoder.finish()?;
-    f.flush()?;
-    Ok(())
+    Ok(gz_encoder.finish()?)
 }
 
-pub fn from_tar_gz(src: impl AsRef<Path>, dest: impl AsRef<Path>) -> anyhow::Result<()> {
+/// Unpacks the tarball `src` in `dest`, decrypting it first if it is encrypted.
+pub fn from_tar_gz(
+    src: impl AsRef<Path>,
+    dest: impl AsRef<Path>,
+    encryption_key: Option<&EncryptionKey>,
+) -> anyhow::Result<()> {
     let f = File::open(&src)?;
-    let gz = GzDecoder::new(f);
+    from_tar_gz_reader(f, dest, encryption_key)
+}
+
+/// Same as [`from_tar_gz`] but reads the archive from `src` as it is streamed.
+pub fn from_tar_gz_reader(
+    src: impl Read,
+    dest: impl AsRef<Path>,
+    encryption_key: Option<&EncryptionKey>,
+) -> anyhow::Result<()> {
+    let reader = encryption::decrypt_if_needed(BufReader::new(src), encryption_key)?;
+    let gz = GzDecoder::new(reader);
     let mut ar = Archive::new(gz);
     create_dir_all(&dest)?;
     ar.unpack(&dest)?;
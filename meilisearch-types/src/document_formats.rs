@@ -1,7 +1,7 @@
 use std::borrow::Borrow;
 use std::fmt::{self, Debug, Display};
 use std::fs::File;
-use std::io::{self, Seek, Write};
+use std::io::{self, Write};
 use std::marker::PhantomData;
 
 use either::Either;
@@ -106,7 +106,7 @@ impl ErrorCode for DocumentFormatError {
 }
 
 /// Reads CSV from input and write an obkv batch to writer.
-pub fn read_csv(file: &File, writer: impl Write + Seek) -> Result<u64> {
+pub fn read_csv(file: &File, writer: impl Write) -> Result<u64> {
     let mut builder = DocumentsBatchBuilder::new(writer);
     let mmap = unsafe { MmapOptions::new().map(file)? };
     let csv = csv::Reader::from_reader(mmap.as_ref());
@@ -119,21 +119,17 @@ pub fn read_csv(file: &File, writer: impl Write + Seek) -> Result<u64> {
 }
 
 /// Reads JSON from temporary file  and write an obkv batch to writer.
-pub fn read_json(file: &File, writer: impl Write + Seek) -> Result<u64> {
+pub fn read_json(file: &File, writer: impl Write) -> Result<u64> {
     read_json_inner(file, writer, PayloadType::Json)
 }
 
 /// Reads JSON from temporary file  and write an obkv batch to writer.
-pub fn read_ndjson(file: &File, writer: impl Write + Seek) -> Result<u64> {
+pub fn read_ndjson(file: &File, writer: impl Write) -> Result<u64> {
     read_json_inner(file, writer, PayloadType::Ndjson)
 }
 
 /// Reads JSON from temporary file  and write an obkv batch to writer.
-fn read_json_inner(
-    file: &File,
-    writer: impl Write + Seek,
-    payload_type: PayloadType,
-) -> Result<u64> {
+fn read_json_inner(file: &File, writer: impl Write, payload_type: PayloadType) -> Result<u64> {
     let mut builder = DocumentsBatchBuilder::new(writer);
     let mmap = unsafe { MmapOptions::new().map(file)? };
     let mut deserializer = serde_json::Deserializer::from_slice(&mmap);
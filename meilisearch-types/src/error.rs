@@ -140,8 +140,10 @@ pub enum Code {
     BadRequest,
     DatabaseSizeLimitReached,
     DocumentNotFound,
+    SettingsVersionNotFound,
     Internal,
     InvalidGeoField,
+    InvalidVectorsField,
     InvalidRankingRule,
     InvalidStore,
     InvalidToken,
@@ -244,8 +246,14 @@ impl Code {
                 ErrCode::internal("database_size_limit_reached", StatusCode::INTERNAL_SERVER_ERROR)
             }
             DocumentNotFound => ErrCode::invalid("document_not_found", StatusCode::NOT_FOUND),
+            SettingsVersionNotFound => {
+                ErrCode::invalid("settings_version_not_found", StatusCode::NOT_FOUND)
+            }
             Internal => ErrCode::internal("internal", StatusCode::INTERNAL_SERVER_ERROR),
             InvalidGeoField => ErrCode::invalid("invalid_geo_field", StatusCode::BAD_REQUEST),
+            InvalidVectorsField => {
+                ErrCode::invalid("invalid_vectors_field", StatusCode::BAD_REQUEST)
+            }
             InvalidToken => ErrCode::authentication("invalid_api_key", StatusCode::FORBIDDEN),
             MissingAuthorizationHeader => {
                 ErrCode::authentication("missing_authorization_header", StatusCode::UNAUTHORIZED)
@@ -427,6 +435,21 @@ impl ErrorCode for file_store::Error {
         match self {
             Self::IoError(e) => e.error_code(),
             Self::PersistError(e) => e.error_code(),
+            Self::EncryptionError(e) => e.error_code(),
+        }
+    }
+}
+
+impl ErrorCode for file_store::encryption::Error {
+    fn error_code(&self) -> Code {
+        use file_store::encryption::Error;
+
+        match self {
+            Error::IoError(e) => e.error_code(),
+            Error::MissingKey
+            | Error::WrongKey
+            | Error::UnsupportedVersion(_)
+            | Error::Corrupted => Code::Internal,
         }
     }
 }
@@ -262,6 +262,8 @@ pub enum Action {
     KeysUpdate,
     #[serde(rename = "keys.delete")]
     KeysDelete,
+    #[serde(rename = "dumps.import")]
+    DumpsImport,
 }
 
 impl Action {
@@ -298,6 +300,7 @@ impl Action {
             KEYS_GET => Some(Self::KeysGet),
             KEYS_UPDATE => Some(Self::KeysUpdate),
             KEYS_DELETE => Some(Self::KeysDelete),
+            DUMPS_IMPORT => Some(Self::DumpsImport),
             _otherwise => None,
         }
     }
@@ -340,6 +343,7 @@ pub mod actions {
     pub const KEYS_GET: u8 = KeysGet.repr();
     pub const KEYS_UPDATE: u8 = KeysUpdate.repr();
     pub const KEYS_DELETE: u8 = KeysDelete.repr();
+    pub const DUMPS_IMPORT: u8 = DumpsImport.repr();
 }
 
 #[derive(Debug, thiserror::Error)]
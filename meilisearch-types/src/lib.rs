@@ -4,8 +4,13 @@ pub mod error;
 pub mod index_uid;
 pub mod keys;
 pub mod settings;
+pub mod settings_history;
 pub mod star_or;
+pub mod stop_words;
+pub mod suggestions;
+pub mod synonyms;
 pub mod tasks;
+pub mod vectors;
 pub mod versioning;
 
 pub use milli;
@@ -5,12 +5,26 @@ use std::num::NonZeroUsize;
 use fst::IntoStreamer;
 use milli::update::Setting;
 use milli::{Index, DEFAULT_VALUES_PER_FACET};
-use serde::{Deserialize, Serialize, Serializer};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::heed::types::{SerdeJson, Str};
+use crate::heed::{RoTxn, RwTxn};
+use crate::stop_words::StopWordsPreset;
 
 /// The maximimum number of results that the engine
 /// will be able to return in one search call.
 pub const DEFAULT_PAGINATION_MAX_TOTAL_HITS: usize = 1000;
 
+/// The keys of the settings unknown to milli, they're stored as json in the main database
+/// of the index.
+mod main_key {
+    pub const SORT_FACET_VALUES_BY: &str = "meilisearch-sort-facet-values-by";
+    pub const RULES: &str = "meilisearch-rules";
+    pub const SEARCH_CUTOFF_MS: &str = "meilisearch-search-cutoff-ms";
+    pub const STOP_WORDS: &str = "meilisearch-stop-words";
+    pub const STOP_WORDS_PRESETS: &str = "meilisearch-stop-words-presets";
+}
+
 fn serialize_with_wildcard<S>(
     field: &Setting<Vec<String>>,
     s: S,
@@ -73,6 +87,26 @@ pub struct FacetingSettings {
     #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
     #[serde(default, skip_serializing_if = "Setting::is_not_set")]
     pub max_values_per_facet: Setting<usize>,
+    /// The order of the values of each facet, `*` applies to all the other facets.
+    #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    pub sort_facet_values_by: Setting<BTreeMap<String, FacetValuesSort>>,
+}
+
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum FacetValuesSort {
+    /// Sort the values in alphanumerical order.
+    Alpha,
+    /// Sort the values by their number of documents, the most frequent first.
+    Count,
+}
+
+impl Default for FacetValuesSort {
+    fn default() -> Self {
+        FacetValuesSort::Alpha
+    }
 }
 
 #[cfg_attr(test, derive(proptest_derive::Arbitrary))]
@@ -85,6 +119,102 @@ pub struct PaginationSettings {
     pub max_total_hits: Setting<usize>,
 }
 
+/// A rule changing the hits of the searches matching all its conditions,
+/// a rule without condition applies to every search.
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryRule {
+    pub uid: String,
+    #[serde(default)]
+    pub conditions: RuleConditions,
+    #[serde(default)]
+    pub consequences: RuleConsequences,
+}
+
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleConditions {
+    /// The query must be this one, ignoring the case and the spaces.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query_is: Option<String>,
+    /// The query must contain these words in this order.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query_contains: Option<String>,
+    /// The filter of the search must contain this filter expression.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter_active: Option<String>,
+}
+
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleConsequences {
+    /// The documents to put at a given position of the hits.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pin: Vec<PinnedDocument>,
+    /// The documents to remove from the hits.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_document_ids",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub hide: Vec<String>,
+    /// A filter added to the filter of the search.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+    /// The hits matching this filter are moved before the other ones.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boost: Option<String>,
+}
+
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct PinnedDocument {
+    #[serde(deserialize_with = "deserialize_document_id")]
+    pub id: String,
+    /// The position of the document in the hits, starting at 1.
+    pub position: usize,
+}
+
+/// The ids of the documents can be given as strings or integers.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DocumentIdRepr {
+    String(String),
+    Integer(u64),
+}
+
+impl From<DocumentIdRepr> for String {
+    fn from(id: DocumentIdRepr) -> String {
+        match id {
+            DocumentIdRepr::String(id) => id,
+            DocumentIdRepr::Integer(id) => id.to_string(),
+        }
+    }
+}
+
+fn deserialize_document_id<'de, D>(deserializer: D) -> std::result::Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    DocumentIdRepr::deserialize(deserializer).map(String::from)
+}
+
+fn deserialize_document_ids<'de, D>(deserializer: D) -> std::result::Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let ids = Vec::<DocumentIdRepr>::deserialize(deserializer)?;
+    Ok(ids.into_iter().map(String::from).collect())
+}
+
 /// Holds all the settings for an index. `T` can either be `Checked` if they represents settings
 /// whose validity is guaranteed, or `Unchecked` if they need to be validated. In the later case, a
 /// call to `check` will return a `Settings<Checked>` from a `Settings<Unchecked>`.
@@ -109,7 +239,7 @@ pub struct Settings<T> {
     )]
     #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
     pub searchable_attributes: Setting<Vec<String>>,
-
+    /// The weight of the searchable attributes, the attributes without weight have a weight of 1.
     #[serde(default, skip_serializing_if = "Setting::is_not_set")]
     #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
     pub filterable_attributes: Setting<BTreeSet<String>>,
@@ -122,6 +252,10 @@ pub struct Settings<T> {
     #[serde(default, skip_serializing_if = "Setting::is_not_set")]
     #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
     pub stop_words: Setting<BTreeSet<String>>,
+    /// The built-in lists of stop words used along with the `stop_words`.
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
+    pub stop_words_presets: Setting<BTreeSet<StopWordsPreset>>,
     #[serde(default, skip_serializing_if = "Setting::is_not_set")]
     #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
     pub synonyms: Setting<BTreeMap<String, Vec<String>>>,
@@ -137,6 +271,12 @@ pub struct Settings<T> {
     #[serde(default, skip_serializing_if = "Setting::is_not_set")]
     #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
     pub pagination: Setting<PaginationSettings>,
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
+    pub rules: Setting<Vec<QueryRule>>,
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
+    pub search_cutoff_ms: Setting<u64>,
 
     #[serde(skip)]
     pub _kind: PhantomData<T>,
@@ -151,11 +291,14 @@ impl Settings<Checked> {
             sortable_attributes: Setting::Reset,
             ranking_rules: Setting::Reset,
             stop_words: Setting::Reset,
+            stop_words_presets: Setting::Reset,
             synonyms: Setting::Reset,
             distinct_attribute: Setting::Reset,
             typo_tolerance: Setting::Reset,
             faceting: Setting::Reset,
             pagination: Setting::Reset,
+            rules: Setting::Reset,
+            search_cutoff_ms: Setting::Reset,
             _kind: PhantomData,
         }
     }
@@ -168,11 +311,14 @@ impl Settings<Checked> {
             sortable_attributes,
             ranking_rules,
             stop_words,
+            stop_words_presets,
             synonyms,
             distinct_attribute,
             typo_tolerance,
             faceting,
             pagination,
+            rules,
+            search_cutoff_ms,
             ..
         } = self;
 
@@ -183,11 +329,14 @@ impl Settings<Checked> {
             sortable_attributes,
             ranking_rules,
             stop_words,
+            stop_words_presets,
             synonyms,
             distinct_attribute,
             typo_tolerance,
             faceting,
             pagination,
+            rules,
+            search_cutoff_ms,
             _kind: PhantomData,
         }
     }
@@ -224,11 +373,14 @@ impl Settings<Unchecked> {
             sortable_attributes: self.sortable_attributes,
             ranking_rules: self.ranking_rules,
             stop_words: self.stop_words,
+            stop_words_presets: self.stop_words_presets,
             synonyms: self.synonyms,
             distinct_attribute: self.distinct_attribute,
             typo_tolerance: self.typo_tolerance,
             faceting: self.faceting,
             pagination: self.pagination,
+            rules: self.rules,
+            search_cutoff_ms: self.search_cutoff_ms,
             _kind: PhantomData,
         }
     }
@@ -242,6 +394,8 @@ pub struct Facets {
     pub min_level_size: Option<NonZeroUsize>,
 }
 
+/// Applies the settings known by milli to the builder, except for the stop words which must
+/// first be resolved along with their presets by [`resolve_stop_words`].
 pub fn apply_settings_to_builder(
     settings: &Settings<Checked>,
     builder: &mut milli::update::Settings,
@@ -278,12 +432,6 @@ pub fn apply_settings_to_builder(
         Setting::NotSet => (),
     }
 
-    match settings.stop_words {
-        Setting::Set(ref stop_words) => builder.set_stop_words(stop_words.clone()),
-        Setting::Reset => builder.reset_stop_words(),
-        Setting::NotSet => (),
-    }
-
     match settings.synonyms {
         Setting::Set(ref synonyms) => builder.set_synonyms(synonyms.clone().into_iter().collect()),
         Setting::Reset => builder.reset_synonyms(),
@@ -372,6 +520,155 @@ pub fn apply_settings_to_builder(
     }
 }
 
+/// Applies the settings unknown to milli directly in the main database of the index.
+pub fn apply_settings_to_index(
+    settings: &Settings<Checked>,
+    wtxn: &mut RwTxn,
+    index: &Index,
+) -> crate::heed::Result<()> {
+    let sort_facet_values_by = match settings.faceting {
+        Setting::Set(ref value) => value.sort_facet_values_by.as_ref(),
+        Setting::Reset => Setting::Reset,
+        Setting::NotSet => Setting::NotSet,
+    };
+    match sort_facet_values_by {
+        Setting::Set(value) => {
+            index.main.put::<_, Str, SerdeJson<_>>(wtxn, main_key::SORT_FACET_VALUES_BY, value)?
+        }
+        Setting::Reset => {
+            index.main.delete::<_, Str>(wtxn, main_key::SORT_FACET_VALUES_BY)?;
+        }
+        Setting::NotSet => (),
+    }
+
+    match settings.rules {
+        Setting::Set(ref value) => {
+            index.main.put::<_, Str, SerdeJson<_>>(wtxn, main_key::RULES, value)?
+        }
+        Setting::Reset => {
+            index.main.delete::<_, Str>(wtxn, main_key::RULES)?;
+        }
+        Setting::NotSet => (),
+    }
+
+    match settings.search_cutoff_ms {
+        Setting::Set(ref value) => {
+            index.main.put::<_, Str, SerdeJson<_>>(wtxn, main_key::SEARCH_CUTOFF_MS, value)?
+        }
+        Setting::Reset => {
+            index.main.delete::<_, Str>(wtxn, main_key::SEARCH_CUTOFF_MS)?;
+        }
+        Setting::NotSet => (),
+    }
+
+    match settings.stop_words_presets {
+        Setting::Set(ref value) => {
+            index.main.put::<_, Str, SerdeJson<_>>(wtxn, main_key::STOP_WORDS_PRESETS, value)?
+        }
+        Setting::Reset => {
+            index.main.delete::<_, Str>(wtxn, main_key::STOP_WORDS_PRESETS)?;
+        }
+        Setting::NotSet => (),
+    }
+
+    Ok(())
+}
+
+/// Returns the stop words milli must use once the settings are applied in order: the stop
+/// words of the user along with the words of their presets. milli only knows this union, so
+/// the stop words of the user are also stored on their own to be returned as they were given.
+pub fn resolve_stop_words<'a>(
+    index: &Index,
+    wtxn: &mut RwTxn,
+    settings: impl IntoIterator<Item = &'a Settings<Checked>>,
+) -> Result<Setting<BTreeSet<String>>, milli::Error> {
+    let mut new_stop_words = None;
+    let mut new_presets = None;
+    for settings in settings {
+        match settings.stop_words {
+            Setting::Set(ref value) => new_stop_words = Some(value.clone()),
+            Setting::Reset => new_stop_words = Some(BTreeSet::new()),
+            Setting::NotSet => (),
+        }
+        match settings.stop_words_presets {
+            Setting::Set(ref value) => new_presets = Some(value.clone()),
+            Setting::Reset => new_presets = Some(BTreeSet::new()),
+            Setting::NotSet => (),
+        }
+    }
+
+    if new_stop_words.is_none() && new_presets.is_none() {
+        return Ok(Setting::NotSet);
+    }
+    let mut stop_words = match new_stop_words {
+        Some(stop_words) => stop_words,
+        None => self::stop_words(index, wtxn)?,
+    };
+    let presets = match new_presets {
+        Some(presets) => presets,
+        None => stop_words_presets(index, wtxn)?,
+    };
+    index.main.put::<_, Str, SerdeJson<_>>(wtxn, main_key::STOP_WORDS, &stop_words)?;
+
+    presets.iter().for_each(|preset| stop_words.extend(preset.words()));
+    if stop_words.is_empty() {
+        Ok(Setting::Reset)
+    } else {
+        Ok(Setting::Set(stop_words))
+    }
+}
+
+/// Returns the stop words given by the user, without the words of their presets.
+pub fn stop_words(index: &Index, rtxn: &RoTxn) -> Result<BTreeSet<String>, milli::Error> {
+    match index.main.get::<_, Str, SerdeJson<BTreeSet<String>>>(rtxn, main_key::STOP_WORDS)? {
+        Some(stop_words) => Ok(stop_words),
+        // the stop words of the indexes which never used the presets are only known by milli.
+        None => match index.stop_words(rtxn)? {
+            Some(stop_words) => Ok(stop_words.stream().into_strs()?.into_iter().collect()),
+            None => Ok(BTreeSet::new()),
+        },
+    }
+}
+
+/// Returns the built-in lists of stop words used by the index.
+pub fn stop_words_presets(
+    index: &Index,
+    rtxn: &RoTxn,
+) -> crate::heed::Result<BTreeSet<StopWordsPreset>> {
+    Ok(index
+        .main
+        .get::<_, Str, SerdeJson<BTreeSet<StopWordsPreset>>>(rtxn, main_key::STOP_WORDS_PRESETS)?
+        .unwrap_or_default())
+}
+
+/// Returns the query rules of the index, in the order they were given.
+pub fn rules(index: &Index, rtxn: &RoTxn) -> crate::heed::Result<Vec<QueryRule>> {
+    Ok(index
+        .main
+        .get::<_, Str, SerdeJson<Vec<QueryRule>>>(rtxn, main_key::RULES)?
+        .unwrap_or_default())
+}
+
+/// Returns the time budget, in milliseconds, of the searches on the index, if any.
+pub fn search_cutoff_ms(index: &Index, rtxn: &RoTxn) -> crate::heed::Result<Option<u64>> {
+    index.main.get::<_, Str, SerdeJson<u64>>(rtxn, main_key::SEARCH_CUTOFF_MS)
+}
+
+/// Returns the order of the values of each facet, `*` applies to all the other facets.
+pub fn sort_facet_values_by(
+    index: &Index,
+    rtxn: &RoTxn,
+) -> crate::heed::Result<BTreeMap<String, FacetValuesSort>> {
+    let sort_facet_values_by = index
+        .main
+        .get::<_, Str, SerdeJson<BTreeMap<String, FacetValuesSort>>>(
+            rtxn,
+            main_key::SORT_FACET_VALUES_BY,
+        )?
+        .unwrap_or_else(|| BTreeMap::from([("*".to_string(), FacetValuesSort::Alpha)]));
+    Ok(sort_facet_values_by)
+}
+
 pub fn settings(
     index: &Index,
     rtxn: &crate::heed::RoTxn,
@@ -389,13 +686,7 @@ pub fn settings(
 
     let criteria = index.criteria(rtxn)?.into_iter().map(|c| c.to_string()).collect();
 
-    let stop_words = index
-        .stop_words(rtxn)?
-        .map(|stop_words| -> Result<BTreeSet<_>, milli::Error> {
-            Ok(stop_words.stream().into_strs()?.into_iter().collect())
-        })
-        .transpose()?
-        .unwrap_or_default();
+    let stop_words = stop_words(index, rtxn)?;
     let distinct_field = index.distinct_field(rtxn)?.map(String::from);
 
     // in milli each word in the synonyms map were split on their separator. Since we lost
@@ -429,6 +720,7 @@ pub fn settings(
         max_values_per_facet: Setting::Set(
             index.max_values_per_facet(rtxn)?.unwrap_or(DEFAULT_VALUES_PER_FACET),
         ),
+        sort_facet_values_by: Setting::Set(sort_facet_values_by(index, rtxn)?),
     };
 
     let pagination = PaginationSettings {
@@ -450,6 +742,7 @@ pub fn settings(
         sortable_attributes: Setting::Set(sortable_attributes),
         ranking_rules: Setting::Set(criteria),
         stop_words: Setting::Set(stop_words),
+        stop_words_presets: Setting::Set(stop_words_presets(index, rtxn)?),
         distinct_attribute: match distinct_field {
             Some(field) => Setting::Set(field),
             None => Setting::Reset,
@@ -458,6 +751,11 @@ pub fn settings(
         typo_tolerance: Setting::Set(typo_tolerance),
         faceting: Setting::Set(faceting),
         pagination: Setting::Set(pagination),
+        rules: Setting::Set(rules(index, rtxn)?),
+        search_cutoff_ms: match search_cutoff_ms(index, rtxn)? {
+            Some(cutoff) => Setting::Set(cutoff),
+            None => Setting::Reset,
+        },
         _kind: PhantomData,
     })
 }
@@ -482,11 +780,14 @@ pub(crate) mod test {
             sortable_attributes: Setting::NotSet,
             ranking_rules: Setting::NotSet,
             stop_words: Setting::NotSet,
+            stop_words_presets: Setting::NotSet,
             synonyms: Setting::NotSet,
             distinct_attribute: Setting::NotSet,
             typo_tolerance: Setting::NotSet,
             faceting: Setting::NotSet,
             pagination: Setting::NotSet,
+            rules: Setting::NotSet,
+            search_cutoff_ms: Setting::NotSet,
             _kind: PhantomData::<Unchecked>,
         };
 
@@ -503,11 +804,14 @@ pub(crate) mod test {
             sortable_attributes: Setting::NotSet,
             ranking_rules: Setting::NotSet,
             stop_words: Setting::NotSet,
+            stop_words_presets: Setting::NotSet,
             synonyms: Setting::NotSet,
             distinct_attribute: Setting::NotSet,
             typo_tolerance: Setting::NotSet,
             faceting: Setting::NotSet,
             pagination: Setting::NotSet,
+            rules: Setting::NotSet,
+            search_cutoff_ms: Setting::NotSet,
             _kind: PhantomData::<Unchecked>,
         };
 
@@ -0,0 +1,156 @@
+//! The history of the settings of an index.
+//!
+//! Every processed settings update stores the whole settings of the index it results in,
+//! so that the previous versions can be compared and restored. The history is kept in the
+//! main database of the index, one key per version, and isn't part of the dumps.
+
+use std::collections::BTreeMap;
+
+use milli::heed::types::{DecodeIgnore, SerdeJson, Str};
+use milli::heed::{RoTxn, RwTxn};
+use milli::Index;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use time::OffsetDateTime;
+
+use crate::settings::{settings, Settings, Unchecked};
+use crate::tasks::TaskId;
+
+mod main_key {
+    pub const HISTORY_PREFIX: &str = "meilisearch-settings-history-";
+
+    /// The versions are zero-padded so that the keys are ordered like the versions.
+    pub fn history(version: u32) -> String {
+        format!("{}{:010}", HISTORY_PREFIX, version)
+    }
+}
+
+/// The number of versions kept in the history, the oldest ones are forgotten first.
+pub const MAX_SETTINGS_VERSIONS: usize = 100;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsVersion {
+    pub version: u32,
+    /// The settings tasks processed together to reach this version.
+    pub task_uids: Vec<TaskId>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+    pub settings: Settings<Unchecked>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SettingDiff {
+    pub before: Value,
+    pub after: Value,
+}
+
+/// Stores the current settings of the index as a new version, once the settings tasks are
+/// applied, and returns the number of this version.
+pub fn record_settings_version(
+    index: &Index,
+    wtxn: &mut RwTxn,
+    task_uids: Vec<TaskId>,
+) -> Result<u32, milli::Error> {
+    let version = last_settings_version(index, wtxn)?.map_or(1, |last| last + 1);
+    let settings = settings(index, wtxn)?.into_unchecked();
+    let settings_version =
+        SettingsVersion { version, task_uids, updated_at: OffsetDateTime::now_utc(), settings };
+    index.main.put::<_, Str, SerdeJson<_>>(wtxn, &main_key::history(version), &settings_version)?;
+
+    // The versions follow each other, the oldest one is forgotten when there are too many.
+    if let Some(oldest) = version.checked_sub(MAX_SETTINGS_VERSIONS as u32) {
+        index.main.delete::<_, Str>(wtxn, &main_key::history(oldest))?;
+    }
+
+    Ok(version)
+}
+
+/// Returns the number of the last version of the settings of the index, if any.
+fn last_settings_version(index: &Index, rtxn: &RoTxn) -> milli::heed::Result<Option<u32>> {
+    let mut iter =
+        index.main.rev_prefix_iter::<_, Str, DecodeIgnore>(rtxn, main_key::HISTORY_PREFIX)?;
+    match iter.next().transpose()? {
+        Some((key, ())) => {
+            let version = key[main_key::HISTORY_PREFIX.len()..].parse();
+            Ok(Some(version.map_err(|_| milli::heed::Error::Decoding)?))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Returns the versions of the settings of the index, the oldest first.
+pub fn settings_history(index: &Index, rtxn: &RoTxn) -> milli::heed::Result<Vec<SettingsVersion>> {
+    index
+        .main
+        .prefix_iter::<_, Str, SerdeJson<SettingsVersion>>(rtxn, main_key::HISTORY_PREFIX)?
+        .map(|result| result.map(|(_, settings_version)| settings_version))
+        .collect()
+}
+
+/// Returns a version of the settings of the index, if it is still in the history.
+pub fn settings_version(
+    index: &Index,
+    rtxn: &RoTxn,
+    version: u32,
+) -> milli::heed::Result<Option<SettingsVersion>> {
+    index.main.get::<_, Str, SerdeJson<SettingsVersion>>(rtxn, &main_key::history(version))
+}
+
+/// Returns the settings which changed between two versions, by their name.
+pub fn settings_diff(
+    before: &Settings<Unchecked>,
+    after: &Settings<Unchecked>,
+) -> BTreeMap<String, SettingDiff> {
+    let (before, after) = match (serde_json::to_value(before), serde_json::to_value(after)) {
+        (Ok(Value::Object(before)), Ok(Value::Object(after))) => (before, after),
+        _ => return BTreeMap::new(),
+    };
+
+    before
+        .keys()
+        .chain(after.keys())
+        .filter(|name| before.get(*name) != after.get(*name))
+        .map(|name| {
+            let diff = SettingDiff {
+                before: before.get(name).cloned().unwrap_or_default(),
+                after: after.get(name).cloned().unwrap_or_default(),
+            };
+            (name.clone(), diff)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use milli::update::Setting;
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn diff_settings() {
+        let before = Settings {
+            ranking_rules: Setting::Set(vec!["words".to_string(), "typo".to_string()]),
+            stop_words: Setting::Set(["the".to_string()].into()),
+            distinct_attribute: Setting::Reset,
+            ..Default::default()
+        };
+        let after = Settings {
+            ranking_rules: Setting::Set(vec!["typo".to_string(), "words".to_string()]),
+            stop_words: Setting::Set(["the".to_string()].into()),
+            distinct_attribute: Setting::Set("sku".to_string()),
+            ..Default::default()
+        };
+
+        let diff = settings_diff(&before, &after);
+        assert_eq!(
+            serde_json::to_value(diff).unwrap(),
+            json!({
+                "distinctAttribute": { "before": null, "after": "sku" },
+                "rankingRules": { "before": ["words", "typo"], "after": ["typo", "words"] },
+            })
+        );
+        assert!(settings_diff(&after, &after).is_empty());
+    }
+}
@@ -0,0 +1,78 @@
+//! The built-in stop words presets and the parser of the plain text stop words lists.
+//!
+//! The lists follow the format of the Snowball stop words files: one word per line, the
+//! text following a `|` or a `#` being a comment.
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub enum StopWordsPreset {
+    De,
+    En,
+    Es,
+    Fr,
+    It,
+    Nl,
+    Pt,
+}
+
+impl StopWordsPreset {
+    /// Returns the stop words of the language of the preset.
+    pub fn words(&self) -> BTreeSet<String> {
+        let list = match self {
+            StopWordsPreset::De => include_str!("stop_words/de.txt"),
+            StopWordsPreset::En => include_str!("stop_words/en.txt"),
+            StopWordsPreset::Es => include_str!("stop_words/es.txt"),
+            StopWordsPreset::Fr => include_str!("stop_words/fr.txt"),
+            StopWordsPreset::It => include_str!("stop_words/it.txt"),
+            StopWordsPreset::Nl => include_str!("stop_words/nl.txt"),
+            StopWordsPreset::Pt => include_str!("stop_words/pt.txt"),
+        };
+        parse_stop_words(list)
+    }
+}
+
+/// Parses a list of stop words, one word per line. The blank lines and the comments,
+/// introduced by a `|` or a `#`, are ignored.
+pub fn parse_stop_words(text: &str) -> BTreeSet<String> {
+    text.lines()
+        .map(|line| line.split(|c| c == '|' || c == '#').next().unwrap_or_default().trim())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_stop_words_lists() {
+        let text =
+            "# a comment\nThe\n\n  and   | a snowball comment\nof#trailing\n|only a comment\n";
+        let words = parse_stop_words(text);
+        assert_eq!(words, BTreeSet::from(["and", "of", "the"].map(String::from)));
+    }
+
+    #[test]
+    fn presets_are_not_empty() {
+        for preset in [
+            StopWordsPreset::De,
+            StopWordsPreset::En,
+            StopWordsPreset::Es,
+            StopWordsPreset::Fr,
+            StopWordsPreset::It,
+            StopWordsPreset::Nl,
+            StopWordsPreset::Pt,
+        ] {
+            let words = preset.words();
+            assert!(words.len() > 50, "{:?}", preset);
+            assert!(words.iter().all(|word| !word.contains(char::is_whitespace)), "{:?}", preset);
+        }
+        assert!(StopWordsPreset::En.words().contains("the"));
+    }
+}
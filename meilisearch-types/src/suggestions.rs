@@ -0,0 +1,293 @@
+//! The query suggestions of an index, completing the prefix typed in a search bar.
+//!
+//! They're built from the words of the documents, weighted by the number of documents
+//! containing them, and from the popular queries, weighted by the number of times they
+//! were searched. Both are stored as fsts in the main database of the index and are rebuilt
+//! by the periodic `suggestionsRefresh` tasks, so they may lag behind the documents.
+
+use std::collections::HashSet;
+
+use fst::{Automaton, IntoStreamer, Map, MapBuilder, Streamer};
+use milli::heed::types::{ByteSlice, Str};
+use milli::heed::{self, RoTxn, RwTxn};
+use milli::Index;
+use serde::Serialize;
+
+use crate::error::{Code, ErrorCode};
+
+mod main_key {
+    pub const WORDS: &str = "meilisearch-suggestions-words";
+    pub const QUERIES: &str = "meilisearch-suggestions-queries";
+}
+
+type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Fst(#[from] fst::Error),
+    #[error(transparent)]
+    Heed(#[from] heed::Error),
+    #[error(transparent)]
+    Milli(#[from] milli::Error),
+}
+
+impl ErrorCode for Error {
+    fn error_code(&self) -> Code {
+        match self {
+            Error::Fst(_) => Code::Internal,
+            Error::Heed(e) => e.error_code(),
+            Error::Milli(e) => e.error_code(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SuggestionSource {
+    Query,
+    Word,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Suggestion {
+    pub suggestion: String,
+    pub source: SuggestionSource,
+    /// The number of searches of the query or the number of documents containing the word.
+    pub frequency: u64,
+}
+
+/// Rebuilds the suggestions from the words of the documents and from the given popular
+/// queries along with their number of searches.
+pub fn update_suggestions(
+    index: &Index,
+    wtxn: &mut RwTxn,
+    popular_queries: Vec<(String, u64)>,
+) -> Result<()> {
+    // 1. The words are already sorted in the database, the single letters are useless.
+    let mut words = MapBuilder::memory();
+    for result in index.word_docids.iter(wtxn)? {
+        let (word, docids) = result?;
+        if word.chars().nth(1).is_some() {
+            words.insert(word, docids.len())?;
+        }
+    }
+    let words = words.into_inner()?;
+
+    // 2. The queries must be sorted and deduplicated once normalized.
+    let mut popular_queries: Vec<_> = popular_queries
+        .into_iter()
+        .map(|(q, searches)| (normalize(&q), searches))
+        .filter(|(q, _)| !q.is_empty())
+        .collect();
+    popular_queries.sort_unstable();
+    popular_queries.dedup_by(|(q, searches), (kept, kept_searches)| {
+        let duplicate = q == kept;
+        if duplicate {
+            *kept_searches += *searches;
+        }
+        duplicate
+    });
+    let mut queries = MapBuilder::memory();
+    for (q, searches) in popular_queries {
+        queries.insert(q, searches)?;
+    }
+    let queries = queries.into_inner()?;
+
+    index.main.put::<_, Str, ByteSlice>(wtxn, main_key::WORDS, &words)?;
+    index.main.put::<_, Str, ByteSlice>(wtxn, main_key::QUERIES, &queries)?;
+
+    Ok(())
+}
+
+/// Returns the `limit` best completions of the prefix: the ones with the fewest typos first,
+/// then the popular queries before the words, then the most frequent first.
+///
+/// The number of typos allowed depends on the length of the prefix and follows the typo
+/// tolerance settings of the index. When the prefix doesn't end with a space its last word
+/// is also completed with the words of the documents.
+pub fn suggest(index: &Index, rtxn: &RoTxn, prefix: &str, limit: usize) -> Result<Vec<Suggestion>> {
+    let authorize_typos = index.authorize_typos(rtxn)?;
+    let one_typo = index.min_word_len_one_typo(rtxn)? as usize;
+    let two_typos = index.min_word_len_two_typos(rtxn)? as usize;
+    let max_typos = |text: &str| match text.chars().count() {
+        _ if !authorize_typos => 0,
+        len if len < one_typo => 0,
+        len if len < two_typos => 1,
+        _ => 2,
+    };
+
+    let completes_last_word = !prefix.ends_with(char::is_whitespace);
+    let prefix = normalize(prefix);
+    // (typos, source, frequency, suggestion)
+    let mut candidates = Vec::new();
+
+    // 1. The popular queries starting with the whole prefix.
+    if let Some(queries) = fst_map(index, rtxn, main_key::QUERIES)? {
+        let automaton = PrefixLevenshtein::new(&prefix, max_typos(&prefix));
+        let mut stream = queries.search(&automaton).into_stream();
+        while let Some((query, searches)) = stream.next() {
+            let query = String::from_utf8_lossy(query).into_owned();
+            candidates.push((automaton.distance(&query), SuggestionSource::Query, searches, query));
+        }
+    }
+
+    // 2. The words of the documents completing the last word of the prefix.
+    if completes_last_word && !prefix.is_empty() {
+        if let Some(words) = fst_map(index, rtxn, main_key::WORDS)? {
+            let (head, last_word) = match prefix.rsplit_once(' ') {
+                Some((head, last_word)) => (Some(head), last_word),
+                None => (None, prefix.as_str()),
+            };
+            let automaton = PrefixLevenshtein::new(last_word, max_typos(last_word));
+            let mut stream = words.search(&automaton).into_stream();
+            while let Some((word, docs)) = stream.next() {
+                let word = String::from_utf8_lossy(word);
+                let typos = automaton.distance(&word);
+                let suggestion = match head {
+                    Some(head) => format!("{} {}", head, word),
+                    None => word.into_owned(),
+                };
+                candidates.push((typos, SuggestionSource::Word, docs, suggestion));
+            }
+        }
+    }
+
+    candidates.sort_by(|(lt, ls, lf, lq), (rt, rs, rf, rq)| {
+        lt.cmp(rt).then(ls.cmp(rs)).then(rf.cmp(lf)).then(lq.cmp(rq))
+    });
+
+    let mut seen = HashSet::new();
+    Ok(candidates
+        .into_iter()
+        .filter(|(_, _, _, suggestion)| seen.insert(suggestion.clone()))
+        .take(limit)
+        .map(|(_, source, frequency, suggestion)| Suggestion { suggestion, source, frequency })
+        .collect())
+}
+
+/// Lowercases the text and collapses its whitespaces.
+fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+fn fst_map<'t>(index: &Index, rtxn: &'t RoTxn, key: &str) -> Result<Option<Map<&'t [u8]>>> {
+    match index.main.get::<_, Str, ByteSlice>(rtxn, key)? {
+        Some(bytes) => Ok(Some(Map::new(bytes)?)),
+        None => Ok(None),
+    }
+}
+
+/// Matches the keys starting with a text at most `max_typos` typos away from the prefix,
+/// the typos being counted in characters and not in bytes.
+struct PrefixLevenshtein {
+    prefix: Vec<char>,
+    max_typos: usize,
+}
+
+#[derive(Clone)]
+struct LevenshteinState {
+    /// The number of typos between every start of the prefix and the key read so far.
+    row: Vec<usize>,
+    /// The fewest typos between the whole prefix and a start of the key read so far.
+    distance: usize,
+    /// The bytes read of a character that isn't complete yet.
+    pending: Vec<u8>,
+}
+
+impl PrefixLevenshtein {
+    fn new(prefix: &str, max_typos: usize) -> Self {
+        PrefixLevenshtein { prefix: prefix.chars().collect(), max_typos }
+    }
+
+    /// Returns the fewest typos between the prefix and a start of the key.
+    fn distance(&self, key: &str) -> usize {
+        key.chars().fold(self.start(), |state, c| self.step(&state, c)).distance
+    }
+
+    fn step(&self, state: &LevenshteinState, c: char) -> LevenshteinState {
+        let mut row = Vec::with_capacity(state.row.len());
+        row.push(state.row[0] + 1);
+        for (i, &p) in self.prefix.iter().enumerate() {
+            let substitution = state.row[i] + (p != c) as usize;
+            let insertion = state.row[i + 1] + 1;
+            let deletion = row[i] + 1;
+            row.push(substitution.min(insertion).min(deletion));
+        }
+        let distance = state.distance.min(row[self.prefix.len()]);
+        LevenshteinState { row, distance, pending: Vec::new() }
+    }
+}
+
+impl Automaton for PrefixLevenshtein {
+    type State = LevenshteinState;
+
+    fn start(&self) -> LevenshteinState {
+        let row: Vec<_> = (0..=self.prefix.len()).collect();
+        LevenshteinState { distance: row[self.prefix.len()], row, pending: Vec::new() }
+    }
+
+    fn is_match(&self, state: &LevenshteinState) -> bool {
+        state.distance <= self.max_typos
+    }
+
+    fn can_match(&self, state: &LevenshteinState) -> bool {
+        state.row.iter().any(|&typos| typos <= self.max_typos) || state.distance <= self.max_typos
+    }
+
+    fn will_always_match(&self, state: &LevenshteinState) -> bool {
+        state.distance <= self.max_typos
+    }
+
+    fn accept(&self, state: &LevenshteinState, byte: u8) -> LevenshteinState {
+        let mut pending = state.pending.clone();
+        pending.push(byte);
+        match std::str::from_utf8(&pending) {
+            Ok(c) => self.step(state, c.chars().next().unwrap_or(char::REPLACEMENT_CHARACTER)),
+            // the character continues in the next bytes.
+            Err(e) if e.error_len().is_none() => LevenshteinState { pending, ..state.clone() },
+            Err(_) => self.step(state, char::REPLACEMENT_CHARACTER),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn prefix_levenshtein_distance() {
+        let automaton = PrefixLevenshtein::new("hary", 1);
+        assert_eq!(automaton.distance("harry potter"), 1);
+        assert_eq!(automaton.distance("hary"), 0);
+        assert_eq!(automaton.distance("ha"), 2);
+        assert_eq!(PrefixLevenshtein::new("éco", 0).distance("école"), 0);
+        assert_eq!(PrefixLevenshtein::new("eco", 1).distance("école"), 1);
+    }
+
+    #[test]
+    fn prefix_levenshtein_automaton() {
+        let map = Map::from_iter([("harry", 1), ("hermione", 2), ("école", 3)]).unwrap();
+        let search = |prefix: &str, max_typos| {
+            let automaton = PrefixLevenshtein::new(prefix, max_typos);
+            let mut stream = map.search(&automaton).into_stream();
+            let mut keys = Vec::new();
+            while let Some((key, _)) = stream.next() {
+                keys.push(String::from_utf8(key.to_vec()).unwrap());
+            }
+            keys
+        };
+        assert_eq!(search("har", 0), ["harry"]);
+        assert_eq!(search("hery", 1), ["hermione"]);
+        assert_eq!(search("hary", 1), ["harry"]);
+        assert_eq!(search("éc", 0), ["école"]);
+        assert_eq!(search("ec", 1), ["école"]);
+        assert_eq!(search("", 0), ["harry", "hermione", "école"]);
+    }
+
+    #[test]
+    fn normalize_queries() {
+        assert_eq!(normalize("  Harry   Potter "), "harry potter");
+    }
+}
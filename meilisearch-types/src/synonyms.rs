@@ -0,0 +1,162 @@
+//! The parser of the synonyms files in the Solr format, also used by Elasticsearch.
+//!
+//! Each line is either a list of equivalent words, `a, b, c`, where every word is a synonym
+//! of the others, or an explicit mapping, `a, b => c, d`, where the words on the left are
+//! replaced by the words on the right. The text following a `#` is a comment and the `,`,
+//! `=>`, `#` and `\` characters can be escaped with a `\`.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::mem;
+
+use crate::error::{Code, ErrorCode};
+
+#[derive(Debug, thiserror::Error)]
+#[error("The synonyms are invalid at line {line}: {message}.")]
+pub struct SynonymsFormatError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl ErrorCode for SynonymsFormatError {
+    fn error_code(&self) -> Code {
+        Code::BadRequest
+    }
+}
+
+/// Parses a synonyms file in the Solr format, the synonyms of a word appearing on several
+/// lines are merged.
+pub fn parse_synonyms(text: &str) -> Result<BTreeMap<String, Vec<String>>, SynonymsFormatError> {
+    let mut synonyms: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+
+    for (i, line) in text.lines().enumerate() {
+        let error = |message: &str| SynonymsFormatError { line: i + 1, message: message.into() };
+        let (left, right) = split_line(line).map_err(error)?;
+
+        if left.iter().chain(right.iter().flatten()).any(String::is_empty) {
+            return Err(error("a synonym is empty"));
+        }
+        match right {
+            Some(right) => {
+                for word in &left {
+                    let words = synonyms.entry(word.clone()).or_default();
+                    words.extend(right.iter().filter(|synonym| *synonym != word).cloned());
+                }
+            }
+            // the words are synonyms of each other, a single word has no synonym.
+            None => {
+                for word in &left {
+                    let words = synonyms.entry(word.clone()).or_default();
+                    words.extend(left.iter().filter(|synonym| *synonym != word).cloned());
+                }
+            }
+        }
+    }
+
+    Ok(synonyms
+        .into_iter()
+        .filter(|(_, words)| !words.is_empty())
+        .map(|(word, words)| (word, words.into_iter().collect()))
+        .collect())
+}
+
+/// Splits a line into the words on the left of the `=>` and, if any, the words on its right.
+/// A blank line or a line only made of a comment has no words.
+fn split_line(line: &str) -> Result<(Vec<String>, Option<Vec<String>>), &'static str> {
+    let mut left = Vec::new();
+    let mut right: Option<Vec<String>> = None;
+    let mut word = String::new();
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(escaped) => word.push(escaped),
+                None => return Err("it ends with an unescaped `\\`"),
+            },
+            '#' => break,
+            ',' => right.as_mut().unwrap_or(&mut left).push(normalize(&mem::take(&mut word))),
+            '=' if chars.peek() == Some(&'>') => {
+                chars.next();
+                if right.is_some() {
+                    return Err("it contains more than one `=>`");
+                }
+                left.push(normalize(&mem::take(&mut word)));
+                right = Some(Vec::new());
+            }
+            c => word.push(c),
+        }
+    }
+
+    let word = normalize(&word);
+    match right.as_mut() {
+        Some(right) => right.push(word),
+        None if left.is_empty() && word.is_empty() => (),
+        None => left.push(word),
+    }
+
+    Ok((left, right))
+}
+
+/// Trims the word and collapses its whitespaces.
+fn normalize(word: &str) -> String {
+    word.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn synonyms(entries: &[(&str, &[&str])]) -> BTreeMap<String, Vec<String>> {
+        entries
+            .iter()
+            .map(|(word, words)| (word.to_string(), words.iter().map(|w| w.to_string()).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn parse_solr_synonyms() {
+        let text = "# a comment\n\
+            \n\
+            tv, television ,  telly\n\
+            i-pod, i pod => ipod # trailing comment\n\
+            sea biscuit => seabiscuit\n\
+            tv, screen\n\
+            alone\n\
+            a\\,b => c\\=>d, e\\#f\n";
+
+        assert_eq!(
+            parse_synonyms(text).unwrap(),
+            synonyms(&[
+                ("a,b", &["c=>d", "e#f"]),
+                ("i pod", &["ipod"]),
+                ("i-pod", &["ipod"]),
+                ("screen", &["tv"]),
+                ("sea biscuit", &["seabiscuit"]),
+                ("telly", &["television", "tv"]),
+                ("television", &["telly", "tv"]),
+                ("tv", &["screen", "telly", "television"]),
+            ])
+        );
+    }
+
+    #[test]
+    fn invalid_solr_synonyms() {
+        let error = parse_synonyms("tv, television\na => b => c\n").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The synonyms are invalid at line 2: it contains more than one `=>`."
+        );
+
+        let error = parse_synonyms("tv,, television").unwrap_err();
+        assert_eq!(error.to_string(), "The synonyms are invalid at line 1: a synonym is empty.");
+
+        let error = parse_synonyms("=> television").unwrap_err();
+        assert_eq!(error.to_string(), "The synonyms are invalid at line 1: a synonym is empty.");
+
+        let error = parse_synonyms("tv\\").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The synonyms are invalid at line 1: it ends with an unescaped `\\`."
+        );
+    }
+}
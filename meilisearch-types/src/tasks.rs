@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::fmt::{Display, Write};
 use std::str::FromStr;
 
@@ -43,6 +43,7 @@ impl Task {
         match &self.kind {
             DumpCreation { .. }
             | SnapshotCreation
+            | SuggestionsRefresh
             | TaskCancelation { .. }
             | TaskDeletion { .. }
             | IndexSwap { .. } => None,
@@ -75,7 +76,12 @@ impl Task {
             | KindWithContent::TaskCancelation { .. }
             | KindWithContent::TaskDeletion { .. }
             | KindWithContent::DumpCreation { .. }
-            | KindWithContent::SnapshotCreation => None,
+            | KindWithContent::SnapshotCreation
+            | KindWithContent::SuggestionsRefresh => None,
+            KindWithContent::DumpImport { source, .. } => match source {
+                DumpSource::Upload { content_file } => Some(*content_file),
+                DumpSource::Path { .. } => None,
+            },
         }
     }
 }
@@ -129,8 +135,17 @@ pub enum KindWithContent {
     DumpCreation {
         keys: Vec<Key>,
         instance_uid: Option<InstanceUid>,
+        #[serde(default)]
+        content: DumpContent,
+    },
+    DumpImport {
+        source: DumpSource,
+        /// The new uids of the dumped indexes, the other indexes keep their uid.
+        renames: BTreeMap<String, String>,
+        on_conflict: ConflictPolicy,
     },
     SnapshotCreation,
+    SuggestionsRefresh,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -139,6 +154,71 @@ pub struct IndexSwap {
     pub indexes: (String, String),
 }
 
+/// Describes what a dump contains, by default everything.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct DumpContent {
+    /// The uids of the dumped indexes, a uid ending with `*` matches all the indexes starting
+    /// with the same prefix. `None` means that all the indexes are dumped.
+    #[serde(default)]
+    pub indexes: Option<Vec<String>>,
+    #[serde(default = "default_true")]
+    pub tasks: bool,
+    #[serde(default = "default_true")]
+    pub keys: bool,
+}
+
+impl Default for DumpContent {
+    fn default() -> Self {
+        DumpContent { indexes: None, tasks: true, keys: true }
+    }
+}
+
+impl DumpContent {
+    /// Returns `true` if the index is part of the dump.
+    pub fn contains_index(&self, index: &str) -> bool {
+        match &self.indexes {
+            None => true,
+            Some(patterns) => patterns.iter().any(|pattern| match pattern.strip_suffix('*') {
+                Some(prefix) => index.starts_with(prefix),
+                None => pattern == index,
+            }),
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Where the dump imported by a [`KindWithContent::DumpImport`] is read from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DumpSource {
+    /// The dump was uploaded and stored as an update file.
+    Upload { content_file: Uuid },
+    /// The path of the dump in the dump directory.
+    Path { path: String },
+}
+
+/// What to do when an imported index has the same uid as an existing index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConflictPolicy {
+    /// Fail the import without importing anything.
+    Fail,
+    /// Replace the settings and the documents of the existing index.
+    Overwrite,
+    /// Keep the existing index and don't import the dumped one.
+    Skip,
+}
+
+impl Default for ConflictPolicy {
+    fn default() -> Self {
+        ConflictPolicy::Fail
+    }
+}
+
 impl KindWithContent {
     pub fn as_kind(&self) -> Kind {
         match self {
@@ -153,7 +233,9 @@ impl KindWithContent {
             KindWithContent::TaskCancelation { .. } => Kind::TaskCancelation,
             KindWithContent::TaskDeletion { .. } => Kind::TaskDeletion,
             KindWithContent::DumpCreation { .. } => Kind::DumpCreation,
+            KindWithContent::DumpImport { .. } => Kind::DumpImport,
             KindWithContent::SnapshotCreation => Kind::SnapshotCreation,
+            KindWithContent::SuggestionsRefresh => Kind::SuggestionsRefresh,
         }
     }
 
@@ -161,8 +243,11 @@ impl KindWithContent {
         use KindWithContent::*;
 
         match self {
+            // the uids of the imported indexes are only known once the dump has been read.
             DumpCreation { .. }
+            | DumpImport { .. }
             | SnapshotCreation
+            | SuggestionsRefresh
             | TaskCancelation { .. }
             | TaskDeletion { .. } => vec![],
             DocumentAdditionOrUpdate { index_uid, .. }
@@ -223,7 +308,10 @@ impl KindWithContent {
                 original_filter: query.clone(),
             }),
             KindWithContent::DumpCreation { .. } => Some(Details::Dump { dump_uid: None }),
-            KindWithContent::SnapshotCreation => None,
+            KindWithContent::DumpImport { .. } => {
+                Some(Details::DumpImport { imported_indexes: None, skipped_indexes: None })
+            }
+            KindWithContent::SnapshotCreation | KindWithContent::SuggestionsRefresh => None,
         }
     }
 
@@ -266,7 +354,10 @@ impl KindWithContent {
                 original_filter: query.clone(),
             }),
             KindWithContent::DumpCreation { .. } => Some(Details::Dump { dump_uid: None }),
-            KindWithContent::SnapshotCreation => None,
+            KindWithContent::DumpImport { .. } => {
+                Some(Details::DumpImport { imported_indexes: None, skipped_indexes: None })
+            }
+            KindWithContent::SnapshotCreation | KindWithContent::SuggestionsRefresh => None,
         }
     }
 }
@@ -304,7 +395,10 @@ impl From<&KindWithContent> for Option<Details> {
                 original_filter: query.clone(),
             }),
             KindWithContent::DumpCreation { .. } => Some(Details::Dump { dump_uid: None }),
-            KindWithContent::SnapshotCreation => None,
+            KindWithContent::DumpImport { .. } => {
+                Some(Details::DumpImport { imported_indexes: None, skipped_indexes: None })
+            }
+            KindWithContent::SnapshotCreation | KindWithContent::SuggestionsRefresh => None,
         }
     }
 }
@@ -374,7 +468,9 @@ pub enum Kind {
     TaskCancelation,
     TaskDeletion,
     DumpCreation,
+    DumpImport,
     SnapshotCreation,
+    SuggestionsRefresh,
 }
 
 impl Kind {
@@ -390,7 +486,9 @@ impl Kind {
             | Kind::TaskCancelation
             | Kind::TaskDeletion
             | Kind::DumpCreation
-            | Kind::SnapshotCreation => false,
+            | Kind::DumpImport
+            | Kind::SnapshotCreation
+            | Kind::SuggestionsRefresh => false,
         }
     }
 }
@@ -407,7 +505,9 @@ impl Display for Kind {
             Kind::TaskCancelation => write!(f, "taskCancelation"),
             Kind::TaskDeletion => write!(f, "taskDeletion"),
             Kind::DumpCreation => write!(f, "dumpCreation"),
+            Kind::DumpImport => write!(f, "dumpImport"),
             Kind::SnapshotCreation => write!(f, "snapshotCreation"),
+            Kind::SuggestionsRefresh => write!(f, "suggestionsRefresh"),
         }
     }
 }
@@ -435,8 +535,12 @@ impl FromStr for Kind {
             Ok(Kind::TaskDeletion)
         } else if kind.eq_ignore_ascii_case("dumpCreation") {
             Ok(Kind::DumpCreation)
+        } else if kind.eq_ignore_ascii_case("dumpImport") {
+            Ok(Kind::DumpImport)
         } else if kind.eq_ignore_ascii_case("snapshotCreation") {
             Ok(Kind::SnapshotCreation)
+        } else if kind.eq_ignore_ascii_case("suggestionsRefresh") {
+            Ok(Kind::SuggestionsRefresh)
         } else {
             Err(ResponseError::from_msg(
                 format!(
@@ -467,6 +571,7 @@ pub enum Details {
     TaskCancelation { matched_tasks: u64, canceled_tasks: Option<u64>, original_filter: String },
     TaskDeletion { matched_tasks: u64, deleted_tasks: Option<u64>, original_filter: String },
     Dump { dump_uid: Option<String> },
+    DumpImport { imported_indexes: Option<Vec<String>>, skipped_indexes: Option<Vec<String>> },
     IndexSwap { swaps: Vec<IndexSwap> },
 }
 
@@ -484,6 +589,7 @@ impl Details {
             Self::SettingsUpdate { .. }
             | Self::IndexInfo { .. }
             | Self::Dump { .. }
+            | Self::DumpImport { .. }
             | Self::IndexSwap { .. } => (),
         }
 
@@ -544,7 +650,7 @@ pub fn serialize_duration<S: Serializer>(
 
 #[cfg(test)]
 mod tests {
-    use super::Details;
+    use super::{Details, DumpContent};
     use crate::heed::types::SerdeJson;
     use crate::heed::{BytesDecode, BytesEncode};
 
@@ -560,4 +666,23 @@ mod tests {
         meili_snap::snapshot!(format!("{:?}", details), @r###"TaskDeletion { matched_tasks: 1, deleted_tasks: None, original_filter: "hello" }"###);
         meili_snap::snapshot!(format!("{:?}", deserialised), @r###"TaskDeletion { matched_tasks: 1, deleted_tasks: None, original_filter: "hello" }"###);
     }
+
+    #[test]
+    fn dump_content_contains_index() {
+        let content = DumpContent::default();
+        assert!(content.contains_index("movies"));
+
+        let content = DumpContent {
+            indexes: Some(vec!["movies".to_string(), "products-*".to_string()]),
+            ..DumpContent::default()
+        };
+        assert!(content.contains_index("movies"));
+        assert!(!content.contains_index("movies2"));
+        assert!(content.contains_index("products-fr"));
+        assert!(content.contains_index("products-"));
+        assert!(!content.contains_index("products"));
+
+        let content: DumpContent = serde_json::from_str("{}").unwrap();
+        assert_eq!(content, DumpContent::default());
+    }
 }
@@ -0,0 +1,364 @@
+//! The vectors given by the users in the reserved `_vectors` field of their documents.
+//!
+//! They're stored in the main database of the index along with an approximate nearest
+//! neighbours structure: the vectors are hashed with random hyperplanes in several tables
+//! and the documents of the buckets of the query vector are the candidates of a search.
+
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+use milli::heed::types::{OwnedType, Str};
+use milli::heed::{self, BytesDecode, BytesEncode, RoTxn, RwTxn};
+use milli::{DocumentId, Index, RoaringBitmapCodec};
+use roaring::RoaringBitmap;
+use serde_json::Value;
+
+use crate::error::{Code, ErrorCode};
+
+/// The reserved field holding the vectors of a document.
+pub const RESERVED_VECTORS_FIELD_NAME: &str = "_vectors";
+
+/// The number of hash tables, the more tables the better the recall.
+const NUMBER_OF_TABLES: usize = 8;
+/// The number of hyperplanes of each table, the more hyperplanes the smaller the buckets.
+const NUMBER_OF_HYPERPLANES: usize = 8;
+
+mod main_key {
+    pub const DIMENSIONS: &str = "meilisearch-vectors-dimensions";
+    pub const DOCIDS: &str = "meilisearch-vectors-docids";
+    pub const DOCUMENT_PREFIX: &str = "meilisearch-vectors-document-";
+    pub const BUCKET_PREFIX: &str = "meilisearch-vectors-bucket-";
+}
+
+type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("The `_vectors` field of the document with the internal id `{0}` is invalid. It must be an array of numbers or an array of arrays of numbers.")]
+    InvalidVectorsField(DocumentId),
+    #[error("The vectors of an index must all have the same number of dimensions, expected {expected} but found {found}.")]
+    InvalidVectorDimensions { expected: usize, found: usize },
+    #[error(transparent)]
+    Heed(#[from] heed::Error),
+    #[error(transparent)]
+    Milli(#[from] milli::Error),
+}
+
+impl ErrorCode for Error {
+    fn error_code(&self) -> Code {
+        match self {
+            Error::InvalidVectorsField(_) => Code::InvalidVectorsField,
+            Error::InvalidVectorDimensions { .. } => Code::InvalidVectorsField,
+            Error::Heed(e) => e.error_code(),
+            Error::Milli(e) => e.error_code(),
+        }
+    }
+}
+
+/// Updates the vectors store after an update of the documents: the vectors of the deleted
+/// documents are removed and the ones of the `updated` documents are read from their
+/// `_vectors` field again. The other documents are left untouched.
+pub fn update_vectors(index: &Index, wtxn: &mut RwTxn, updated: &RoaringBitmap) -> Result<()> {
+    let documents_ids = index.documents_ids(wtxn)?;
+    let mut docids = vectors_docids(index, wtxn)?;
+    let mut buckets = Buckets::default();
+
+    // 1. Remove the previous vectors of the deleted and updated documents from their buckets.
+    let stale = (&docids - &documents_ids) | (&docids & updated);
+    for docid in &stale {
+        let key = document_key(docid);
+        let vectors = index.main.get::<_, Str, VectorsCodec>(wtxn, &key)?.unwrap_or_default();
+        for vector in &vectors {
+            for table in 0..NUMBER_OF_TABLES {
+                buckets.get_mut(index, wtxn, table, hash(table, vector))?.remove(docid);
+            }
+        }
+        index.main.delete::<_, Str>(wtxn, &key)?;
+    }
+    docids -= stale;
+
+    // 2. Store the new vectors of the updated documents and hash them in the buckets.
+    let mut dimensions = if docids.is_empty() { None } else { dimensions(index, wtxn)? };
+    let updated = updated & &documents_ids;
+    let field_id = index.fields_ids_map(wtxn)?.id(RESERVED_VECTORS_FIELD_NAME);
+    if let Some(field_id) = field_id.filter(|_| !updated.is_empty()) {
+        let mut vectors_by_document = Vec::new();
+        for (docid, obkv) in index.documents(wtxn, &updated)? {
+            let value = match obkv.get(field_id) {
+                Some(value) => {
+                    serde_json::from_slice(value).map_err(|_| Error::InvalidVectorsField(docid))?
+                }
+                None => continue,
+            };
+            let vectors = parse_vectors(value).ok_or(Error::InvalidVectorsField(docid))?;
+            for vector in &vectors {
+                let expected = *dimensions.get_or_insert(vector.len());
+                if expected != vector.len() {
+                    return Err(Error::InvalidVectorDimensions { expected, found: vector.len() });
+                }
+            }
+            if !vectors.is_empty() {
+                vectors_by_document.push((docid, vectors));
+            }
+        }
+
+        for (docid, vectors) in vectors_by_document {
+            for vector in &vectors {
+                for table in 0..NUMBER_OF_TABLES {
+                    buckets.get_mut(index, wtxn, table, hash(table, vector))?.insert(docid);
+                }
+            }
+            index.main.put::<_, Str, VectorsCodec>(wtxn, &document_key(docid), &vectors)?;
+            docids.insert(docid);
+        }
+    }
+
+    // 3. Write the modified buckets back.
+    for ((table, hash), bucket) in buckets.0 {
+        let key = bucket_key(table, hash);
+        if bucket.is_empty() {
+            index.main.delete::<_, Str>(wtxn, &key)?;
+        } else {
+            index.main.put::<_, Str, RoaringBitmapCodec>(wtxn, &key, &bucket)?;
+        }
+    }
+    match dimensions.filter(|_| !docids.is_empty()) {
+        Some(dimensions) => {
+            index.main.put::<_, Str, RoaringBitmapCodec>(wtxn, main_key::DOCIDS, &docids)?;
+            index.main.put::<_, Str, OwnedType<u32>>(
+                wtxn,
+                main_key::DIMENSIONS,
+                &(dimensions as u32),
+            )?;
+        }
+        None => {
+            index.main.delete::<_, Str>(wtxn, main_key::DOCIDS)?;
+            index.main.delete::<_, Str>(wtxn, main_key::DIMENSIONS)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The buckets modified by an update, read from the index the first time they're modified.
+#[derive(Default)]
+struct Buckets(HashMap<(usize, u32), RoaringBitmap>);
+
+impl Buckets {
+    fn get_mut(
+        &mut self,
+        index: &Index,
+        rtxn: &RoTxn,
+        table: usize,
+        hash: u32,
+    ) -> heed::Result<&mut RoaringBitmap> {
+        match self.0.entry((table, hash)) {
+            Entry::Occupied(entry) => Ok(entry.into_mut()),
+            Entry::Vacant(entry) => {
+                let bucket = index
+                    .main
+                    .get::<_, Str, RoaringBitmapCodec>(rtxn, &bucket_key(table, hash))?
+                    .unwrap_or_default();
+                Ok(entry.insert(bucket))
+            }
+        }
+    }
+}
+
+/// A heed codec for the vectors of a document: their number of dimensions followed by
+/// all their components, in little endian.
+pub struct VectorsCodec;
+
+impl<'a> BytesDecode<'a> for VectorsCodec {
+    type DItem = Vec<Vec<f32>>;
+
+    fn bytes_decode(bytes: &'a [u8]) -> Option<Self::DItem> {
+        let dimensions = u32::from_le_bytes(bytes.get(..4)?.try_into().ok()?) as usize;
+        if dimensions == 0 {
+            return Some(Vec::new());
+        }
+        let vectors = bytes[4..].chunks_exact(dimensions * 4);
+        if !vectors.remainder().is_empty() {
+            return None;
+        }
+        let component = |bytes: &[u8]| f32::from_le_bytes(bytes.try_into().unwrap());
+        Some(vectors.map(|vector| vector.chunks_exact(4).map(component).collect()).collect())
+    }
+}
+
+impl<'a> BytesEncode<'a> for VectorsCodec {
+    type EItem = [Vec<f32>];
+
+    fn bytes_encode(vectors: &'a Self::EItem) -> Option<Cow<'a, [u8]>> {
+        let dimensions = vectors.first().map_or(0, Vec::len);
+        let mut bytes = Vec::with_capacity(4 + vectors.len() * dimensions * 4);
+        bytes.extend_from_slice(&(dimensions as u32).to_le_bytes());
+        for vector in vectors {
+            if vector.len() != dimensions {
+                return None;
+            }
+            vector.iter().for_each(|component| bytes.extend_from_slice(&component.to_le_bytes()));
+        }
+        Some(Cow::Owned(bytes))
+    }
+}
+
+/// Returns the number of dimensions of the vectors of the index, if there is any vector.
+pub fn dimensions(index: &Index, rtxn: &RoTxn) -> heed::Result<Option<usize>> {
+    let dimensions = index.main.get::<_, Str, OwnedType<u32>>(rtxn, main_key::DIMENSIONS)?;
+    Ok(dimensions.map(|dimensions| dimensions as usize))
+}
+
+/// Returns the ids of the documents with at least one vector.
+pub fn vectors_docids(index: &Index, rtxn: &RoTxn) -> heed::Result<RoaringBitmap> {
+    Ok(index.main.get::<_, Str, RoaringBitmapCodec>(rtxn, main_key::DOCIDS)?.unwrap_or_default())
+}
+
+/// Returns the `limit` candidates the most similar to the query vector, the most similar first,
+/// along with their cosine similarity. The query vector must have the dimensions of the index.
+///
+/// The candidates are first looked for in the buckets of the query vector and its closest
+/// buckets, all the candidates are compared when there isn't enough of them in the buckets.
+pub fn nearest_neighbors(
+    index: &Index,
+    rtxn: &RoTxn,
+    query: &[f32],
+    candidates: &RoaringBitmap,
+    limit: usize,
+) -> heed::Result<Vec<(DocumentId, f32)>> {
+    let candidates = candidates & vectors_docids(index, rtxn)?;
+
+    let mut approximate = RoaringBitmap::new();
+    for table in 0..NUMBER_OF_TABLES {
+        let query_hash = hash(table, query);
+        // the bucket of the query vector and the ones at one hyperplane of it.
+        let probes = (0..NUMBER_OF_HYPERPLANES).map(|bit| query_hash ^ (1 << bit));
+        for probe in std::iter::once(query_hash).chain(probes) {
+            if let Some(bucket) =
+                index.main.get::<_, Str, RoaringBitmapCodec>(rtxn, &bucket_key(table, probe))?
+            {
+                approximate |= bucket;
+            }
+        }
+    }
+    approximate &= &candidates;
+    let candidates = if approximate.len() as usize >= limit { approximate } else { candidates };
+
+    let mut neighbors = Vec::with_capacity(candidates.len() as usize);
+    for docid in candidates {
+        let vectors =
+            index.main.get::<_, Str, VectorsCodec>(rtxn, &document_key(docid))?.unwrap_or_default();
+        let similarity = vectors
+            .iter()
+            .map(|vector| cosine_similarity(query, vector))
+            .fold(f32::NEG_INFINITY, f32::max);
+        neighbors.push((docid, similarity));
+    }
+    neighbors.sort_by(|(_, left), (_, right)| right.partial_cmp(left).unwrap_or(Ordering::Equal));
+    neighbors.truncate(limit);
+
+    Ok(neighbors)
+}
+
+/// Parses either a single vector or an array of vectors, they must all be made of finite numbers.
+pub fn parse_vectors(value: Value) -> Option<Vec<Vec<f32>>> {
+    let parse_vector = |value: &Value| -> Option<Vec<f32>> {
+        value
+            .as_array()?
+            .iter()
+            .map(|n| n.as_f64().map(|n| n as f32).filter(|n| n.is_finite()))
+            .collect()
+    };
+
+    match value {
+        Value::Null => Some(Vec::new()),
+        Value::Array(ref values) if values.iter().all(Value::is_array) => {
+            values.iter().map(parse_vector).collect()
+        }
+        value => parse_vector(&value).map(|vector| vec![vector]),
+    }
+}
+
+fn cosine_similarity(left: &[f32], right: &[f32]) -> f32 {
+    let dot: f32 = left.iter().zip(right).map(|(l, r)| l * r).sum();
+    let norm = |vector: &[f32]| vector.iter().map(|n| n * n).sum::<f32>().sqrt();
+    let norms = norm(left) * norm(right);
+    if norms == 0.0 {
+        0.0
+    } else {
+        dot / norms
+    }
+}
+
+/// Returns on which side of each hyperplane of the table the vector is.
+fn hash(table: usize, vector: &[f32]) -> u32 {
+    let mut hash = 0;
+    for bit in 0..NUMBER_OF_HYPERPLANES {
+        let seed = (table * NUMBER_OF_HYPERPLANES + bit) as u64;
+        let dot: f32 =
+            vector.iter().enumerate().map(|(i, n)| n * hyperplane_component(seed, i)).sum();
+        if dot >= 0.0 {
+            hash |= 1 << bit;
+        }
+    }
+    hash
+}
+
+/// Returns a pseudo-random component in `[-1, 1]` of the normal of a hyperplane,
+/// the same seed always gives the same hyperplane.
+fn hyperplane_component(seed: u64, dimension: usize) -> f32 {
+    // splitmix64
+    let mut z = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ (dimension as u64).wrapping_add(1);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    (z as f64 / u64::MAX as f64 * 2.0 - 1.0) as f32
+}
+
+fn document_key(docid: DocumentId) -> String {
+    format!("{}{}", main_key::DOCUMENT_PREFIX, docid)
+}
+
+fn bucket_key(table: usize, hash: u32) -> String {
+    format!("{}{}-{}", main_key::BUCKET_PREFIX, table, hash)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_vectors_field() {
+        assert_eq!(parse_vectors(serde_json::json!([1, 2.5])), Some(vec![vec![1.0, 2.5]]));
+        assert_eq!(
+            parse_vectors(serde_json::json!([[1, 2], [3, 4]])),
+            Some(vec![vec![1.0, 2.0], vec![3.0, 4.0]])
+        );
+        assert_eq!(parse_vectors(serde_json::json!(null)), Some(Vec::new()));
+        assert_eq!(parse_vectors(serde_json::json!([1, "2"])), None);
+        assert_eq!(parse_vectors(serde_json::json!({ "a": 1 })), None);
+    }
+
+    #[test]
+    fn vectors_codec() {
+        let vectors = vec![vec![1.0, -2.5, 0.0], vec![0.1, 0.2, 0.3]];
+        let bytes = VectorsCodec::bytes_encode(&vectors).unwrap();
+        assert_eq!(bytes.len(), 4 + 6 * 4);
+        assert_eq!(VectorsCodec::bytes_decode(&bytes), Some(vectors));
+
+        assert!(VectorsCodec::bytes_encode(&[vec![1.0], vec![1.0, 2.0]]).is_none());
+        assert_eq!(VectorsCodec::bytes_decode(&[1, 0]), None);
+    }
+
+    #[test]
+    fn close_vectors_share_buckets() {
+        let a = [1.0, 0.9, 0.1];
+        let b = [0.98, 0.91, 0.12];
+        let shared = (0..NUMBER_OF_TABLES).filter(|&table| hash(table, &a) == hash(table, &b));
+        assert!(shared.count() > 0);
+        assert!(cosine_similarity(&a, &b) > 0.99);
+        assert_eq!(cosine_similarity(&a, &[0.0, 0.0, 0.0]), 0.0);
+    }
+}
@@ -24,6 +24,8 @@ impl SearchAggregator {
     }
 
     pub fn succeed(&mut self, _: &dyn Any) {}
+
+    pub fn succeed_from_cache(&mut self) {}
 }
 
 impl MockAnalytics {
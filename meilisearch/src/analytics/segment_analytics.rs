@@ -217,18 +217,24 @@ struct Infos {
     db_path: bool,
     import_dump: bool,
     dump_dir: bool,
+    s3_storage: bool,
     ignore_missing_dump: bool,
     ignore_dump_if_db_exists: bool,
     import_snapshot: bool,
     schedule_snapshot: bool,
     snapshot_dir: bool,
     snapshot_interval_sec: u64,
+    incremental_snapshots: bool,
+    snapshot_retention: usize,
     ignore_missing_snapshot: bool,
     ignore_snapshot_if_db_exists: bool,
     http_addr: bool,
     max_index_size: Byte,
     max_task_db_size: Byte,
     http_payload_size_limit: Byte,
+    search_cache_size: Byte,
+    enable_search_analytics: bool,
+    suggestions_refresh_interval_sec: u64,
     log_level: String,
     max_indexing_memory: MaxMemory,
     max_indexing_threads: MaxThreads,
@@ -251,10 +257,14 @@ impl From<Opt> for Infos {
             db_path,
             http_addr,
             master_key: _,
+            master_encryption_key: _,
             env,
             max_index_size,
             max_task_db_size,
             http_payload_size_limit,
+            search_cache_size,
+            enable_search_analytics,
+            suggestions_refresh_interval_sec,
             ssl_cert_path,
             ssl_key_path,
             ssl_auth_path,
@@ -268,10 +278,16 @@ impl From<Opt> for Infos {
             snapshot_dir,
             schedule_snapshot,
             snapshot_interval_sec,
+            incremental_snapshots,
+            snapshot_retention,
             import_dump,
             ignore_missing_dump,
             ignore_dump_if_db_exists,
             dump_dir,
+            s3_url,
+            s3_region: _,
+            s3_access_key: _,
+            s3_secret_key: _,
             log_level,
             indexer_options,
             config_file_path,
@@ -294,18 +310,24 @@ impl From<Opt> for Infos {
             db_path: db_path != PathBuf::from("./data.ms"),
             import_dump: import_dump.is_some(),
             dump_dir: dump_dir != PathBuf::from("dumps/"),
+            s3_storage: s3_url.is_some(),
             ignore_missing_dump,
             ignore_dump_if_db_exists,
             import_snapshot: import_snapshot.is_some(),
             schedule_snapshot,
             snapshot_dir: snapshot_dir != PathBuf::from("snapshots/"),
             snapshot_interval_sec,
+            incremental_snapshots,
+            snapshot_retention,
             ignore_missing_snapshot,
             ignore_snapshot_if_db_exists,
             http_addr: http_addr != default_http_addr(),
             max_index_size,
             max_task_db_size,
             http_payload_size_limit,
+            search_cache_size,
+            enable_search_analytics,
+            suggestions_refresh_interval_sec,
             log_level: log_level.to_string(),
             max_indexing_memory,
             max_indexing_threads,
@@ -474,6 +496,8 @@ pub struct SearchAggregator {
 
     // filter
     filter_with_geo_radius: bool,
+    filter_with_geo_bounding_box: bool,
+    filter_with_geo_polygon: bool,
     // every time a request has a filter, this field must be incremented by the number of terms it contains
     filter_sum_of_criteria_terms: usize,
     // every time a request has a filter, this field must be incremented by one
@@ -541,6 +565,8 @@ impl SearchAggregator {
 
             let stringified_filters = filter.to_string();
             ret.filter_with_geo_radius = stringified_filters.contains("_geoRadius(");
+            ret.filter_with_geo_bounding_box = stringified_filters.contains("_geoBoundingBox(");
+            ret.filter_with_geo_polygon = stringified_filters.contains("_geoPolygon(");
             ret.filter_sum_of_criteria_terms = RE.split(&stringified_filters).count();
         }
 
@@ -575,6 +601,11 @@ impl SearchAggregator {
         self.time_spent.push(result.processing_time_ms as usize);
     }
 
+    /// A search answered by the search cache, it isn't counted in the time spent.
+    pub fn succeed_from_cache(&mut self) {
+        self.total_succeeded = self.total_succeeded.saturating_add(1);
+    }
+
     /// Aggregate one [SearchAggregator] into another.
     pub fn aggregate(&mut self, mut other: Self) {
         if self.timestamp.is_none() {
@@ -600,6 +631,8 @@ impl SearchAggregator {
 
         // filter
         self.filter_with_geo_radius |= other.filter_with_geo_radius;
+        self.filter_with_geo_bounding_box |= other.filter_with_geo_bounding_box;
+        self.filter_with_geo_polygon |= other.filter_with_geo_polygon;
         self.filter_sum_of_criteria_terms =
             self.filter_sum_of_criteria_terms.saturating_add(other.filter_sum_of_criteria_terms);
         self.filter_total_number_of_criteria = self
@@ -667,6 +700,8 @@ impl SearchAggregator {
                 },
                 "filter": {
                    "with_geoRadius": self.filter_with_geo_radius,
+                   "with_geoBoundingBox": self.filter_with_geo_bounding_box,
+                   "with_geoPolygon": self.filter_with_geo_polygon,
                    "avg_criteria_number": format!("{:.2}", self.filter_sum_of_criteria_terms as f64 / self.filter_total_number_of_criteria as f64),
                    "most_used_syntax": self.used_syntax.iter().max_by_key(|(_, v)| *v).map(|(k, _)| json!(k)).unwrap_or_else(|| json!(null)),
                 },
@@ -5,6 +5,7 @@ use meilisearch_types::error::{Code, ErrorCode, ResponseError};
 use meilisearch_types::index_uid::IndexUidFormatError;
 use serde_json::Value;
 use tokio::task::JoinError;
+use uuid::Uuid;
 
 #[derive(Debug, thiserror::Error)]
 pub enum MeilisearchHttpError {
@@ -16,8 +17,12 @@ pub enum MeilisearchHttpError {
         .1.iter().map(|s| format!("`{}`", s)).collect::<Vec<_>>().join(", ")
     )]
     InvalidContentType(String, Vec<String>),
+    #[error("The text payload is invalid: {0}.")]
+    InvalidTextPayload(String),
     #[error("Document `{0}` not found.")]
     DocumentNotFound(String),
+    #[error("Version `{0}` of the settings not found. Only the last {1} versions are kept.")]
+    SettingsVersionNotFound(u32, usize),
     #[error("Invalid syntax for the filter parameter: `expected {}, found: {1}`.", .0.join(", "))]
     InvalidExpression(&'static [&'static str], Value),
     #[error("A {0} payload is missing.")]
@@ -28,6 +33,36 @@ pub enum MeilisearchHttpError {
         .0, .0.len()
     )]
     SwapIndexPayloadWrongLength(Vec<String>),
+    #[error("Invalid facet buckets for `{0}`: {1}.")]
+    InvalidFacetBuckets(String, String),
+    #[error("The attribute `{0}` can't be used to deduplicate or group the hits: {1}.")]
+    InvalidDistinct(String, String),
+    #[error("The dump path `{0}` is invalid. It must be relative to the dumps directory.")]
+    InvalidDumpPath(String),
+    #[error("Attribute `{0}` is not searchable. It can't be used in `attributesToSearchOn`.")]
+    InvalidAttributesToSearchOn(String),
+    #[error("The `vector` is invalid: {0}.")]
+    InvalidVector(String),
+    #[error("The `semanticRatio` must be between 0.0 and 1.0, found {0}.")]
+    InvalidSemanticRatio(f32),
+    #[error("The document id `{0}` is invalid. It must be a string or an integer.")]
+    InvalidDocumentId(String),
+    #[error("The `searchAfter` cursor is invalid: {0}.")]
+    InvalidSearchAfter(String),
+    #[error("The query is invalid: {0}.")]
+    InvalidQuery(String),
+    #[error("The geo filter `{0}` is invalid: {1}.")]
+    InvalidGeoFilter(String, String),
+    #[error("The `geoGridPrecision` must be between 1 and 12, found {0}.")]
+    InvalidGeoGridPrecision(usize),
+    #[error("The search analytics are disabled. Launch Meilisearch with `--enable-search-analytics` to enable them.")]
+    SearchAnalyticsDisabled,
+    #[error("The query uid `{0}` doesn't refer to a recent search.")]
+    UnknownQueryUid(Uuid),
+    #[error("The suggestions are built from all the documents of the index, they can't be restricted by the filter of a tenant token.")]
+    SuggestionsWithFilteredToken,
+    #[error("A dump must either be uploaded in the `dump` part of a multipart payload or be specified by its `path`.")]
+    InvalidDumpSource,
     #[error(transparent)]
     IndexUid(#[from] IndexUidFormatError),
     #[error(transparent)]
@@ -46,6 +81,8 @@ pub enum MeilisearchHttpError {
     DocumentFormat(#[from] DocumentFormatError),
     #[error(transparent)]
     Join(#[from] JoinError),
+    #[error(transparent)]
+    Suggestions(#[from] meilisearch_types::suggestions::Error),
 }
 
 impl ErrorCode for MeilisearchHttpError {
@@ -54,10 +91,27 @@ impl ErrorCode for MeilisearchHttpError {
             MeilisearchHttpError::MissingContentType(_) => Code::MissingContentType,
             MeilisearchHttpError::MissingPayload(_) => Code::MissingPayload,
             MeilisearchHttpError::InvalidContentType(_, _) => Code::InvalidContentType,
+            MeilisearchHttpError::InvalidTextPayload(_) => Code::MalformedPayload,
             MeilisearchHttpError::DocumentNotFound(_) => Code::DocumentNotFound,
+            MeilisearchHttpError::SettingsVersionNotFound(_, _) => Code::SettingsVersionNotFound,
             MeilisearchHttpError::InvalidExpression(_, _) => Code::Filter,
             MeilisearchHttpError::PayloadTooLarge => Code::PayloadTooLarge,
             MeilisearchHttpError::SwapIndexPayloadWrongLength(_) => Code::BadRequest,
+            MeilisearchHttpError::InvalidFacetBuckets(_, _) => Code::BadRequest,
+            MeilisearchHttpError::InvalidDistinct(_, _) => Code::BadRequest,
+            MeilisearchHttpError::InvalidDumpPath(_) => Code::BadRequest,
+            MeilisearchHttpError::InvalidDocumentId(_) => Code::InvalidDocumentId,
+            MeilisearchHttpError::InvalidAttributesToSearchOn(_) => Code::BadRequest,
+            MeilisearchHttpError::InvalidVector(_) => Code::BadRequest,
+            MeilisearchHttpError::InvalidSemanticRatio(_) => Code::BadRequest,
+            MeilisearchHttpError::InvalidSearchAfter(_) => Code::BadRequest,
+            MeilisearchHttpError::InvalidQuery(_) => Code::BadRequest,
+            MeilisearchHttpError::InvalidGeoFilter(_, _) => Code::Filter,
+            MeilisearchHttpError::InvalidGeoGridPrecision(_) => Code::BadRequest,
+            MeilisearchHttpError::InvalidDumpSource => Code::BadRequest,
+            MeilisearchHttpError::SearchAnalyticsDisabled => Code::BadRequest,
+            MeilisearchHttpError::UnknownQueryUid(_) => Code::BadRequest,
+            MeilisearchHttpError::SuggestionsWithFilteredToken => Code::BadRequest,
             MeilisearchHttpError::IndexUid(e) => e.error_code(),
             MeilisearchHttpError::SerdeJson(_) => Code::Internal,
             MeilisearchHttpError::HeedError(_) => Code::Internal,
@@ -67,6 +121,7 @@ impl ErrorCode for MeilisearchHttpError {
             MeilisearchHttpError::FileStore(_) => Code::Internal,
             MeilisearchHttpError::DocumentFormat(e) => e.error_code(),
             MeilisearchHttpError::Join(_) => Code::Internal,
+            MeilisearchHttpError::Suggestions(e) => e.error_code(),
         }
     }
 }
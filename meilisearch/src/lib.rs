@@ -14,7 +14,7 @@ pub mod metrics;
 pub mod route_metrics;
 
 use std::fs::File;
-use std::io::{BufReader, BufWriter};
+use std::io::Read;
 use std::path::Path;
 use std::sync::Arc;
 use std::thread;
@@ -30,16 +30,16 @@ use analytics::Analytics;
 use anyhow::bail;
 use error::PayloadError;
 use extractors::payload::PayloadConfig;
+use file_store::encryption::EncryptionKey;
 use http::header::CONTENT_TYPE;
+use index_scheduler::snapshot::restore_incremental_snapshot;
+use index_scheduler::storage::{LocalStorage, S3Credentials, S3Storage, Storage};
 use index_scheduler::{IndexScheduler, IndexSchedulerOptions};
 use log::error;
 use meilisearch_auth::AuthController;
-use meilisearch_types::milli::documents::{DocumentsBatchBuilder, DocumentsBatchReader};
-use meilisearch_types::milli::update::{IndexDocumentsConfig, IndexDocumentsMethod};
-use meilisearch_types::settings::apply_settings_to_builder;
 use meilisearch_types::tasks::KindWithContent;
 use meilisearch_types::versioning::{check_version_file, create_version_file};
-use meilisearch_types::{compression, milli, VERSION_FILE_NAME};
+use meilisearch_types::{compression, VERSION_FILE_NAME};
 pub use option::Opt;
 
 use crate::error::MeilisearchHttpError;
@@ -112,11 +112,14 @@ enum OnFailure {
 
 pub fn setup_meilisearch(opt: &Opt) -> anyhow::Result<(Arc<IndexScheduler>, AuthController)> {
     let empty_db = is_empty_db(&opt.db_path);
+    let encryption_key = opt.master_encryption_key.as_deref().map(EncryptionKey::new);
     let (index_scheduler, auth_controller) = if let Some(ref snapshot_path) = opt.import_snapshot {
-        let snapshot_path_exists = snapshot_path.exists();
+        let snapshot = ImportSource::new(opt, snapshot_path)?;
+        let snapshot_path_exists = snapshot.exists()?;
         // the db is empty and the snapshot exists, import it
         if empty_db && snapshot_path_exists {
-            match compression::from_tar_gz(snapshot_path, &opt.db_path) {
+            let imported = snapshot.import_snapshot(&opt.db_path, encryption_key.as_ref());
+            match imported {
                 Ok(()) => open_or_create_database_unchecked(opt, OnFailure::RemoveDb)?,
                 Err(e) => {
                     std::fs::remove_dir_all(&opt.db_path)?;
@@ -137,12 +140,22 @@ pub fn setup_meilisearch(opt: &Opt) -> anyhow::Result<(Arc<IndexScheduler>, Auth
             open_or_create_database(opt, empty_db)?
         }
     } else if let Some(ref path) = opt.import_dump {
-        let src_path_exists = path.exists();
+        let dump = ImportSource::new(opt, path)?;
+        let src_path_exists = dump.exists()?;
         // the db is empty and the dump exists, import it
         if empty_db && src_path_exists {
             let (mut index_scheduler, mut auth_controller) =
                 open_or_create_database_unchecked(opt, OnFailure::RemoveDb)?;
-            match import_dump(&opt.db_path, path, &mut index_scheduler, &mut auth_controller) {
+            let imported = outside_of_runtime(|| {
+                import_dump(
+                    &opt.db_path,
+                    &dump,
+                    encryption_key.as_ref(),
+                    &mut index_scheduler,
+                    &mut auth_controller,
+                )
+            });
+            match imported {
                 Ok(()) => (index_scheduler, auth_controller),
                 Err(e) => {
                     std::fs::remove_dir_all(&opt.db_path)?;
@@ -183,6 +196,21 @@ pub fn setup_meilisearch(opt: &Opt) -> anyhow::Result<(Arc<IndexScheduler>, Auth
             .unwrap();
     }
 
+    // We create a loop in a thread that registers suggestionsRefresh tasks
+    if opt.suggestions_refresh_interval_sec > 0 {
+        let refresh_delay = Duration::from_secs(opt.suggestions_refresh_interval_sec);
+        let index_scheduler = index_scheduler.clone();
+        thread::Builder::new()
+            .name(String::from("register-suggestions-refresh-tasks"))
+            .spawn(move || loop {
+                thread::sleep(refresh_delay);
+                if let Err(e) = index_scheduler.register(KindWithContent::SuggestionsRefresh) {
+                    error!("Error while registering suggestions refresh: {}", e);
+                }
+            })
+            .unwrap();
+    }
+
     Ok((index_scheduler, auth_controller))
 }
 
@@ -203,10 +231,17 @@ fn open_or_create_database_unchecked(
             indexes_path: opt.db_path.join("indexes"),
             snapshots_path: opt.snapshot_dir.clone(),
             dumps_path: opt.dump_dir.clone(),
+            snapshots_storage: storage(opt, &opt.snapshot_dir)?,
+            dumps_storage: storage(opt, &opt.dump_dir)?,
             task_db_size: opt.max_task_db_size.get_bytes() as usize,
             index_size: opt.max_index_size.get_bytes() as usize,
             indexer_config: (&opt.indexer_options).try_into()?,
             autobatching_enabled: true,
+            encryption_key: opt.master_encryption_key.as_deref().map(EncryptionKey::new),
+            incremental_snapshots: opt.incremental_snapshots,
+            snapshot_retention: opt.snapshot_retention,
+            search_cache_size: opt.search_cache_size.get_bytes() as usize,
+            search_analytics: opt.enable_search_analytics,
         })?)
     };
 
@@ -237,14 +272,100 @@ fn open_or_create_database(
     open_or_create_database_unchecked(opt, OnFailure::KeepDb)
 }
 
+/// Returns the storage on which the snapshots or the dumps are written. `dir` is either
+/// the local directory or, when an S3 url is provided, the prefix of the keys in the bucket.
+fn storage(opt: &Opt, dir: &Path) -> anyhow::Result<Arc<dyn Storage>> {
+    match opt.s3_url {
+        Some(ref url) => Ok(Arc::new(s3_storage(opt, url)?.with_prefix(&dir.to_string_lossy()))),
+        None => Ok(Arc::new(LocalStorage::new(dir))),
+    }
+}
+
+fn s3_storage(opt: &Opt, url: &str) -> anyhow::Result<S3Storage> {
+    let credentials = match (&opt.s3_access_key, &opt.s3_secret_key) {
+        (Some(access_key), Some(secret_key)) => {
+            Some(S3Credentials { access_key: access_key.clone(), secret_key: secret_key.clone() })
+        }
+        (None, None) => None,
+        _ => bail!("both `--s3-access-key` and `--s3-secret-key` must be provided"),
+    };
+    Ok(S3Storage::new(url, opt.s3_region.clone(), credentials)?)
+}
+
+/// Runs `f` on another thread so that the blocking http client of the
+/// S3 storage is never called from within the async runtime.
+fn outside_of_runtime<T: Send>(f: impl FnOnce() -> T + Send) -> T {
+    thread::scope(|s| s.spawn(f).join().unwrap_or_else(|e| std::panic::resume_unwind(e)))
+}
+
+/// A snapshot or a dump to import, read either from the local file system or,
+/// when its path starts with `s3://`, from the S3 bucket.
+enum ImportSource<'a> {
+    Local(&'a Path),
+    S3 { storage: S3Storage, key: String },
+}
+
+impl<'a> ImportSource<'a> {
+    fn new(opt: &Opt, path: &'a Path) -> anyhow::Result<ImportSource<'a>> {
+        match path.to_str().and_then(|path| path.strip_prefix("s3://")) {
+            Some(key) => match opt.s3_url {
+                Some(ref url) => {
+                    Ok(ImportSource::S3 { storage: s3_storage(opt, url)?, key: key.to_string() })
+                }
+                None => bail!("`--s3-url` must be provided to import {}", path.display()),
+            },
+            None => Ok(ImportSource::Local(path)),
+        }
+    }
+
+    fn exists(&self) -> anyhow::Result<bool> {
+        match self {
+            ImportSource::Local(path) => Ok(path.exists()),
+            // incremental snapshots are directories containing the generations
+            ImportSource::S3 { storage, key } => {
+                outside_of_runtime(|| Ok(storage.exists(key)? || !storage.list(key)?.is_empty()))
+            }
+        }
+    }
+
+    fn import_snapshot(
+        &self,
+        db_path: &Path,
+        encryption_key: Option<&EncryptionKey>,
+    ) -> anyhow::Result<()> {
+        match self {
+            // incremental snapshots are directories containing the generations
+            ImportSource::Local(path) if path.is_dir() => {
+                restore_incremental_snapshot(&LocalStorage::new(*path), "", db_path, encryption_key)
+            }
+            ImportSource::Local(path) => compression::from_tar_gz(path, db_path, encryption_key),
+            ImportSource::S3 { storage, key } => outside_of_runtime(|| {
+                if storage.list(key)?.is_empty() {
+                    compression::from_tar_gz_reader(storage.get(key)?, db_path, encryption_key)
+                } else {
+                    restore_incremental_snapshot(storage, key, db_path, encryption_key)
+                }
+            }),
+        }
+    }
+
+    fn reader(&self) -> anyhow::Result<Box<dyn Read + Send>> {
+        match self {
+            ImportSource::Local(path) => Ok(Box::new(File::open(path)?)),
+            ImportSource::S3 { storage, key } => Ok(storage.get(key)?),
+        }
+    }
+}
+
 fn import_dump(
     db_path: &Path,
-    dump_path: &Path,
+    dump: &ImportSource,
+    encryption_key: Option<&EncryptionKey>,
     index_scheduler: &mut IndexScheduler,
     auth: &mut AuthController,
 ) -> Result<(), anyhow::Error> {
-    let reader = File::open(dump_path)?;
-    let mut dump_reader = dump::DumpReader::open(reader)?;
+    let reader = dump.reader()?;
+    let mut dump_reader = dump::DumpReader::open(reader, encryption_key)?;
 
     if let Some(date) = dump_reader.date() {
         log::info!(
@@ -267,16 +388,23 @@ fn import_dump(
         let _ = std::fs::write(db_path.join("instance-uid"), instance_uid.to_string().as_bytes());
     };
 
-    // 2. Import the `Key`s.
-    let mut keys = Vec::new();
-    auth.raw_delete_all_keys()?;
-    for key in dump_reader.keys()? {
-        let key = key?;
-        auth.raw_insert_key(key.clone())?;
-        keys.push(key);
+    let content = dump_reader.content();
+    if let Some(ref indexes) = content.indexes {
+        log::info!("The dump only contains the indexes matching {:?}.", indexes);
     }
 
-    let indexer_config = index_scheduler.indexer_config();
+    // 2. Import the `Key`s, a dump without keys keeps the default ones.
+    let mut keys = Vec::new();
+    if content.keys {
+        auth.raw_delete_all_keys()?;
+        for key in dump_reader.keys()? {
+            let key = key?;
+            auth.raw_insert_key(key.clone())?;
+            keys.push(key);
+        }
+    } else {
+        log::info!("The dump doesn't contain any key, keeping the default keys.");
+    }
 
     // /!\ The tasks must be imported AFTER importing the indexes or else the scheduler might
     // try to process tasks while we're trying to import the indexes.
@@ -284,57 +412,14 @@ fn import_dump(
     // 3. Import the indexes.
     for index_reader in dump_reader.indexes()? {
         let mut index_reader = index_reader?;
-        let metadata = index_reader.metadata();
+        let metadata = index_reader.metadata().clone();
         log::info!("Importing index `{}`.", metadata.uid);
 
         let date = Some((metadata.created_at, metadata.updated_at));
         let index = index_scheduler.create_raw_index(&metadata.uid, date)?;
 
         let mut wtxn = index.write_txn()?;
-
-        let mut builder = milli::update::Settings::new(&mut wtxn, &index, indexer_config);
-        // 3.1 Import the primary key if there is one.
-        if let Some(ref primary_key) = metadata.primary_key {
-            builder.set_primary_key(primary_key.to_string());
-        }
-
-        // 3.2 Import the settings.
-        log::info!("Importing the settings.");
-        let settings = index_reader.settings()?;
-        apply_settings_to_builder(&settings, &mut builder);
-        builder.execute(|indexing_step| log::debug!("update: {:?}", indexing_step), || false)?;
-
-        // 3.3 Import the documents.
-        // 3.3.1 We need to recreate the grenad+obkv format accepted by the index.
-        log::info!("Importing the documents.");
-        let file = tempfile::tempfile()?;
-        let mut builder = DocumentsBatchBuilder::new(BufWriter::new(file));
-        for document in index_reader.documents()? {
-            builder.append_json_object(&document?)?;
-        }
-
-        // This flush the content of the batch builder.
-        let file = builder.into_inner()?.into_inner()?;
-
-        // 3.3.2 We feed it to the milli index.
-        let reader = BufReader::new(file);
-        let reader = DocumentsBatchReader::from_reader(reader)?;
-
-        let builder = milli::update::IndexDocuments::new(
-            &mut wtxn,
-            &index,
-            indexer_config,
-            IndexDocumentsConfig {
-                update_method: IndexDocumentsMethod::ReplaceDocuments,
-                ..Default::default()
-            },
-            |indexing_step| log::debug!("update: {:?}", indexing_step),
-            || false,
-        )?;
-
-        let (builder, user_result) = builder.add_documents(reader)?;
-        log::info!("{} documents found.", user_result?);
-        builder.execute()?;
+        index_scheduler.import_dumped_index(&mut wtxn, &index, &mut index_reader)?;
         wtxn.commit()?;
         log::info!("All documents successfully imported.");
     }
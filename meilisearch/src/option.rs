@@ -26,12 +26,16 @@ const POSSIBLE_ENV: [&str; 2] = ["development", "production"];
 const MEILI_DB_PATH: &str = "MEILI_DB_PATH";
 const MEILI_HTTP_ADDR: &str = "MEILI_HTTP_ADDR";
 const MEILI_MASTER_KEY: &str = "MEILI_MASTER_KEY";
+const MEILI_MASTER_ENCRYPTION_KEY: &str = "MEILI_MASTER_ENCRYPTION_KEY";
 const MEILI_ENV: &str = "MEILI_ENV";
 #[cfg(all(not(debug_assertions), feature = "analytics"))]
 const MEILI_NO_ANALYTICS: &str = "MEILI_NO_ANALYTICS";
 const MEILI_MAX_INDEX_SIZE: &str = "MEILI_MAX_INDEX_SIZE";
 const MEILI_MAX_TASK_DB_SIZE: &str = "MEILI_MAX_TASK_DB_SIZE";
 const MEILI_HTTP_PAYLOAD_SIZE_LIMIT: &str = "MEILI_HTTP_PAYLOAD_SIZE_LIMIT";
+const MEILI_SEARCH_CACHE_SIZE: &str = "MEILI_SEARCH_CACHE_SIZE";
+const MEILI_ENABLE_SEARCH_ANALYTICS: &str = "MEILI_ENABLE_SEARCH_ANALYTICS";
+const MEILI_SUGGESTIONS_REFRESH_INTERVAL_SEC: &str = "MEILI_SUGGESTIONS_REFRESH_INTERVAL_SEC";
 const MEILI_SSL_CERT_PATH: &str = "MEILI_SSL_CERT_PATH";
 const MEILI_SSL_KEY_PATH: &str = "MEILI_SSL_KEY_PATH";
 const MEILI_SSL_AUTH_PATH: &str = "MEILI_SSL_AUTH_PATH";
@@ -45,10 +49,16 @@ const MEILI_IGNORE_SNAPSHOT_IF_DB_EXISTS: &str = "MEILI_IGNORE_SNAPSHOT_IF_DB_EX
 const MEILI_SNAPSHOT_DIR: &str = "MEILI_SNAPSHOT_DIR";
 const MEILI_SCHEDULE_SNAPSHOT: &str = "MEILI_SCHEDULE_SNAPSHOT";
 const MEILI_SNAPSHOT_INTERVAL_SEC: &str = "MEILI_SNAPSHOT_INTERVAL_SEC";
+const MEILI_INCREMENTAL_SNAPSHOTS: &str = "MEILI_INCREMENTAL_SNAPSHOTS";
+const MEILI_SNAPSHOT_RETENTION: &str = "MEILI_SNAPSHOT_RETENTION";
 const MEILI_IMPORT_DUMP: &str = "MEILI_IMPORT_DUMP";
 const MEILI_IGNORE_MISSING_DUMP: &str = "MEILI_IGNORE_MISSING_DUMP";
 const MEILI_IGNORE_DUMP_IF_DB_EXISTS: &str = "MEILI_IGNORE_DUMP_IF_DB_EXISTS";
 const MEILI_DUMP_DIR: &str = "MEILI_DUMP_DIR";
+const MEILI_S3_URL: &str = "MEILI_S3_URL";
+const MEILI_S3_REGION: &str = "MEILI_S3_REGION";
+const MEILI_S3_ACCESS_KEY: &str = "MEILI_S3_ACCESS_KEY";
+const MEILI_S3_SECRET_KEY: &str = "MEILI_S3_SECRET_KEY";
 const MEILI_LOG_LEVEL: &str = "MEILI_LOG_LEVEL";
 const MEILI_GENERATE_MASTER_KEY: &str = "MEILI_GENERATE_MASTER_KEY";
 #[cfg(feature = "metrics")]
@@ -61,9 +71,13 @@ const DEFAULT_ENV: &str = "development";
 const DEFAULT_MAX_INDEX_SIZE: &str = "100 GiB";
 const DEFAULT_MAX_TASK_DB_SIZE: &str = "100 GiB";
 const DEFAULT_HTTP_PAYLOAD_SIZE_LIMIT: &str = "100 MB";
+const DEFAULT_SEARCH_CACHE_SIZE: &str = "0 B";
 const DEFAULT_SNAPSHOT_DIR: &str = "snapshots/";
 const DEFAULT_SNAPSHOT_INTERVAL_SEC: u64 = 86400;
+const DEFAULT_SNAPSHOT_RETENTION: usize = 1;
+const DEFAULT_SUGGESTIONS_REFRESH_INTERVAL_SEC: u64 = 3600;
 const DEFAULT_DUMP_DIR: &str = "dumps/";
+const DEFAULT_S3_REGION: &str = "us-east-1";
 
 const MEILI_MAX_INDEXING_MEMORY: &str = "MEILI_MAX_INDEXING_MEMORY";
 const MEILI_MAX_INDEXING_THREADS: &str = "MEILI_MAX_INDEXING_THREADS";
@@ -141,6 +155,13 @@ pub struct Opt {
     #[clap(long, env = MEILI_MASTER_KEY)]
     pub master_key: Option<String>,
 
+    /// Sets the key used to encrypt the update files, dumps and snapshots at rest.
+    ///
+    /// The key should be a long, randomly generated string. The same key must be provided to import
+    /// the dumps and snapshots it encrypted.
+    #[clap(long, env = MEILI_MASTER_ENCRYPTION_KEY)]
+    pub master_encryption_key: Option<String>,
+
     /// Configures the instance's environment. Value must be either `production` or `development`.
     #[clap(long, env = MEILI_ENV, default_value_t = default_env(), value_parser = POSSIBLE_ENV)]
     #[serde(default = "default_env")]
@@ -173,6 +194,27 @@ pub struct Opt {
     #[serde(default = "default_http_payload_size_limit")]
     pub http_payload_size_limit: Byte,
 
+    /// Sets the maximum size of the in-memory cache of the search responses. Value must be given in bytes
+    /// or explicitly stating a base unit (for instance: 104857600, '100Mb', or '100 MiB'). The cache is
+    /// disabled by default.
+    #[clap(long, env = MEILI_SEARCH_CACHE_SIZE, default_value_t = default_search_cache_size())]
+    #[serde(default = "default_search_cache_size")]
+    pub search_cache_size: Byte,
+
+    /// Records the searches and their clicks and conversions in memory, they're available on the
+    /// `/indexes/{indexUid}/analytics` route. The searches then return a `queryUid` to which the
+    /// events sent to the `/events` route refer, the cached responses get a new one.
+    #[clap(long, env = MEILI_ENABLE_SEARCH_ANALYTICS)]
+    #[serde(default)]
+    pub enable_search_analytics: bool,
+
+    /// Defines the interval between each refresh of the query suggestions of the indexes, they're
+    /// rebuilt from the words of the documents and the popular queries. Value must be given in seconds,
+    /// the suggestions are never refreshed when set to 0.
+    #[clap(long, env = MEILI_SUGGESTIONS_REFRESH_INTERVAL_SEC, default_value_t = default_suggestions_refresh_interval_sec())]
+    #[serde(default = "default_suggestions_refresh_interval_sec")]
+    pub suggestions_refresh_interval_sec: u64,
+
     /// Sets the server's SSL certificates.
     #[clap(long, env = MEILI_SSL_CERT_PATH, value_parser)]
     pub ssl_cert_path: Option<PathBuf>,
@@ -250,6 +292,19 @@ pub struct Opt {
     #[serde(default = "default_snapshot_interval_sec")]
     pub snapshot_interval_sec: u64,
 
+    /// Creates incremental snapshots when provided. Each snapshot is then stored as a new generation
+    /// in the `<db name>.snapshots` directory, reusing the indexes that didn't change since the previous one.
+    ///
+    /// Use `--import-snapshot` with this directory to import the most recent generation.
+    #[clap(long, env = MEILI_INCREMENTAL_SNAPSHOTS)]
+    #[serde(default)]
+    pub incremental_snapshots: bool,
+
+    /// Defines how many generations of the incremental snapshots are kept.
+    #[clap(long, env = MEILI_SNAPSHOT_RETENTION, default_value_t = default_snapshot_retention())]
+    #[serde(default = "default_snapshot_retention")]
+    pub snapshot_retention: usize,
+
     /// Imports the dump file located at the specified path. Path must point to a `.dump` file.
     /// If a database already exists, Meilisearch will throw an error and abort launch.
     #[clap(long, env = MEILI_IMPORT_DUMP, conflicts_with = "import_snapshot")]
@@ -277,6 +332,27 @@ pub struct Opt {
     #[serde(default = "default_dump_dir")]
     pub dump_dir: PathBuf,
 
+    /// Stores the snapshots and the dumps in the S3-compatible bucket at the specified url,
+    /// e.g. `https://s3.us-east-1.amazonaws.com/my-bucket`. The `--snapshot-dir` and `--dump-dir`
+    /// are then used as the prefixes of the keys in the bucket.
+    ///
+    /// Snapshots and dumps stored in the bucket can be imported by prefixing their key with `s3://`.
+    #[clap(long, env = MEILI_S3_URL)]
+    pub s3_url: Option<String>,
+
+    /// Sets the region of the S3-compatible bucket.
+    #[clap(long, env = MEILI_S3_REGION, default_value_t = default_s3_region())]
+    #[serde(default = "default_s3_region")]
+    pub s3_region: String,
+
+    /// Sets the access key used to authenticate to the S3-compatible bucket.
+    #[clap(long, env = MEILI_S3_ACCESS_KEY, requires = "s3_secret_key")]
+    pub s3_access_key: Option<String>,
+
+    /// Sets the secret key used to authenticate to the S3-compatible bucket.
+    #[clap(long, env = MEILI_S3_SECRET_KEY, requires = "s3_access_key")]
+    pub s3_secret_key: Option<String>,
+
     /// Defines how much detail should be present in Meilisearch's logs.
     ///
     /// Meilisearch currently supports six log levels, listed in order of increasing verbosity: OFF, ERROR, WARN, INFO, DEBUG, TRACE.
@@ -363,10 +439,14 @@ impl Opt {
             db_path,
             http_addr,
             master_key,
+            master_encryption_key,
             env,
             max_index_size,
             max_task_db_size,
             http_payload_size_limit,
+            search_cache_size,
+            enable_search_analytics,
+            suggestions_refresh_interval_sec,
             ssl_cert_path,
             ssl_key_path,
             ssl_auth_path,
@@ -377,7 +457,13 @@ impl Opt {
             snapshot_dir,
             schedule_snapshot,
             snapshot_interval_sec,
+            incremental_snapshots,
+            snapshot_retention,
             dump_dir,
+            s3_url,
+            s3_region,
+            s3_access_key,
+            s3_secret_key,
             log_level,
             indexer_options,
             import_snapshot: _,
@@ -398,6 +484,9 @@ impl Opt {
         if let Some(master_key) = master_key {
             export_to_env_if_not_present(MEILI_MASTER_KEY, master_key);
         }
+        if let Some(master_encryption_key) = master_encryption_key {
+            export_to_env_if_not_present(MEILI_MASTER_ENCRYPTION_KEY, master_encryption_key);
+        }
         export_to_env_if_not_present(MEILI_ENV, env);
         #[cfg(all(not(debug_assertions), feature = "analytics"))]
         {
@@ -409,6 +498,15 @@ impl Opt {
             MEILI_HTTP_PAYLOAD_SIZE_LIMIT,
             http_payload_size_limit.to_string(),
         );
+        export_to_env_if_not_present(MEILI_SEARCH_CACHE_SIZE, search_cache_size.to_string());
+        export_to_env_if_not_present(
+            MEILI_ENABLE_SEARCH_ANALYTICS,
+            enable_search_analytics.to_string(),
+        );
+        export_to_env_if_not_present(
+            MEILI_SUGGESTIONS_REFRESH_INTERVAL_SEC,
+            suggestions_refresh_interval_sec.to_string(),
+        );
         if let Some(ssl_cert_path) = ssl_cert_path {
             export_to_env_if_not_present(MEILI_SSL_CERT_PATH, ssl_cert_path);
         }
@@ -430,7 +528,22 @@ impl Opt {
             MEILI_SNAPSHOT_INTERVAL_SEC,
             snapshot_interval_sec.to_string(),
         );
+        export_to_env_if_not_present(
+            MEILI_INCREMENTAL_SNAPSHOTS,
+            incremental_snapshots.to_string(),
+        );
+        export_to_env_if_not_present(MEILI_SNAPSHOT_RETENTION, snapshot_retention.to_string());
         export_to_env_if_not_present(MEILI_DUMP_DIR, dump_dir);
+        if let Some(s3_url) = s3_url {
+            export_to_env_if_not_present(MEILI_S3_URL, s3_url);
+        }
+        export_to_env_if_not_present(MEILI_S3_REGION, s3_region);
+        if let Some(s3_access_key) = s3_access_key {
+            export_to_env_if_not_present(MEILI_S3_ACCESS_KEY, s3_access_key);
+        }
+        if let Some(s3_secret_key) = s3_secret_key {
+            export_to_env_if_not_present(MEILI_S3_SECRET_KEY, s3_secret_key);
+        }
         export_to_env_if_not_present(MEILI_LOG_LEVEL, log_level.to_string());
         #[cfg(feature = "metrics")]
         {
@@ -740,6 +853,10 @@ fn default_http_payload_size_limit() -> Byte {
     Byte::from_str(DEFAULT_HTTP_PAYLOAD_SIZE_LIMIT).unwrap()
 }
 
+fn default_search_cache_size() -> Byte {
+    Byte::from_str(DEFAULT_SEARCH_CACHE_SIZE).unwrap()
+}
+
 fn default_snapshot_dir() -> PathBuf {
     PathBuf::from(DEFAULT_SNAPSHOT_DIR)
 }
@@ -748,6 +865,18 @@ fn default_snapshot_interval_sec() -> u64 {
     DEFAULT_SNAPSHOT_INTERVAL_SEC
 }
 
+fn default_snapshot_retention() -> usize {
+    DEFAULT_SNAPSHOT_RETENTION
+}
+
+fn default_suggestions_refresh_interval_sec() -> u64 {
+    DEFAULT_SUGGESTIONS_REFRESH_INTERVAL_SEC
+}
+
+fn default_s3_region() -> String {
+    DEFAULT_S3_REGION.to_string()
+}
+
 fn default_dump_dir() -> PathBuf {
     PathBuf::from(DEFAULT_DUMP_DIR)
 }
@@ -1,33 +1,92 @@
+use std::collections::BTreeMap;
+use std::path::{Component, Path};
+use std::str::FromStr;
+
+use actix_multipart::{Field, Multipart, MultipartError};
+use actix_web::guard::{self, GuardContext};
+use actix_web::http::header::CONTENT_TYPE;
 use actix_web::web::Data;
 use actix_web::{web, HttpRequest, HttpResponse};
+use futures::StreamExt;
 use index_scheduler::IndexScheduler;
 use log::debug;
 use meilisearch_auth::AuthController;
 use meilisearch_types::error::ResponseError;
-use meilisearch_types::tasks::KindWithContent;
+use meilisearch_types::index_uid::IndexUid;
+use meilisearch_types::tasks::{ConflictPolicy, DumpContent, DumpSource, KindWithContent};
+use serde::Deserialize;
 use serde_json::json;
+use tempfile::tempfile;
+use tokio::fs::File;
+use tokio::io::{AsyncSeekExt, AsyncWriteExt, BufWriter};
+use uuid::Uuid;
 
 use crate::analytics::Analytics;
+use crate::error::{MeilisearchHttpError, PayloadError};
 use crate::extractors::authentication::policies::*;
-use crate::extractors::authentication::GuardedData;
+use crate::extractors::authentication::{AuthenticationError, GuardedData};
+use crate::extractors::payload::Payload;
 use crate::extractors::sequential_extractor::SeqHandler;
 use crate::routes::SummarizedTaskView;
 
+/// The maximum size of the `options` part of an uploaded dump.
+const MAX_DUMP_OPTIONS_SIZE: usize = 64 * 1024;
+
 pub fn configure(cfg: &mut web::ServiceConfig) {
-    cfg.service(web::resource("").route(web::post().to(SeqHandler(create_dump))));
+    cfg.service(web::resource("").route(web::post().to(SeqHandler(create_dump)))).service(
+        web::resource("/import")
+            .route(web::post().guard(guard::fn_guard(is_multipart)).to(SeqHandler(upload_dump)))
+            .route(web::post().to(SeqHandler(import_dump))),
+    );
+}
+
+/// The dumps are uploaded along with their options in a multipart payload.
+fn is_multipart(ctx: &GuardContext) -> bool {
+    ctx.head()
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .map_or(false, |content_type| content_type.starts_with("multipart/form-data"))
+}
+
+/// Ensures the patterns of the indexes to dump are valid index uids, optionally ending with `*`.
+fn validate_dump_content(content: &DumpContent) -> Result<(), MeilisearchHttpError> {
+    for pattern in content.indexes.iter().flatten() {
+        // `*` alone matches every index, otherwise the prefix must be a valid index uid.
+        let uid = pattern.strip_suffix('*').unwrap_or(pattern);
+        if !uid.is_empty() {
+            IndexUid::from_str(uid)?;
+        }
+    }
+    Ok(())
 }
 
 pub async fn create_dump(
     index_scheduler: GuardedData<ActionPolicy<{ actions::DUMPS_CREATE }>, Data<IndexScheduler>>,
     auth_controller: GuardedData<ActionPolicy<{ actions::DUMPS_CREATE }>, AuthController>,
+    body: web::Json<Option<DumpContent>>,
     req: HttpRequest,
     analytics: web::Data<dyn Analytics>,
 ) -> Result<HttpResponse, ResponseError> {
-    analytics.publish("Dump Created".to_string(), json!({}), Some(&req));
+    // a `null` payload dumps everything.
+    let content = body.into_inner().unwrap_or_default();
+    validate_dump_content(&content)?;
+
+    analytics.publish(
+        "Dump Created".to_string(),
+        json!({
+            "indexes": content.indexes.as_ref().map(|indexes| indexes.len()),
+            "tasks": content.tasks,
+            "keys": content.keys,
+        }),
+        Some(&req),
+    );
 
+    let keys = if content.keys { auth_controller.list_keys()? } else { Vec::new() };
     let task = KindWithContent::DumpCreation {
-        keys: auth_controller.list_keys()?,
+        keys,
         instance_uid: analytics.instance_uid().cloned(),
+        content,
     };
     let task: SummarizedTaskView =
         tokio::task::spawn_blocking(move || index_scheduler.register(task)).await??.into();
@@ -35,3 +94,220 @@ pub async fn create_dump(
     debug!("returns: {:?}", task);
     Ok(HttpResponse::Accepted().json(task))
 }
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct DumpImportRequest {
+    /// The path of the dump in the dumps directory, must be omitted when the dump is uploaded.
+    #[serde(default)]
+    path: Option<String>,
+    /// The new uids of the dumped indexes.
+    #[serde(default)]
+    renames: BTreeMap<String, String>,
+    #[serde(default)]
+    on_conflict: ConflictPolicy,
+}
+
+impl DumpImportRequest {
+    fn into_kind(
+        self,
+        content_file: Option<Uuid>,
+    ) -> Result<KindWithContent, MeilisearchHttpError> {
+        let source = match (self.path, content_file) {
+            (Some(path), None) => {
+                // we only accept the dumps stored in the dumps directory.
+                let is_valid = !path.is_empty()
+                    && Path::new(&path).components().all(|c| matches!(c, Component::Normal(_)));
+                if !is_valid {
+                    return Err(MeilisearchHttpError::InvalidDumpPath(path));
+                }
+                DumpSource::Path { path }
+            }
+            (None, Some(content_file)) => DumpSource::Upload { content_file },
+            _ => return Err(MeilisearchHttpError::InvalidDumpSource),
+        };
+        for (uid, new_uid) in &self.renames {
+            IndexUid::from_str(uid)?;
+            IndexUid::from_str(new_uid)?;
+        }
+        Ok(KindWithContent::DumpImport {
+            source,
+            renames: self.renames,
+            on_conflict: self.on_conflict,
+        })
+    }
+}
+
+/// Imports a dump already stored in the dumps directory.
+pub async fn import_dump(
+    index_scheduler: GuardedData<ActionPolicy<{ actions::DUMPS_IMPORT }>, Data<IndexScheduler>>,
+    body: web::Json<DumpImportRequest>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    check_import_authorization(&index_scheduler)?;
+    register_dump_import(index_scheduler, body.into_inner(), None, &req, analytics).await
+}
+
+/// Imports a dump uploaded along with its options in a multipart payload.
+pub async fn upload_dump(
+    index_scheduler: GuardedData<ActionPolicy<{ actions::DUMPS_IMPORT }>, Data<IndexScheduler>>,
+    body: Payload,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    check_import_authorization(&index_scheduler)?;
+
+    // The size of the payload is limited like the other payloads.
+    let body = body.map(|chunk| {
+        chunk.map_err(|e| match e {
+            MeilisearchHttpError::PayloadTooLarge => actix_web::error::PayloadError::Overflow,
+            e => actix_web::error::PayloadError::Io(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                e.to_string(),
+            )),
+        })
+    });
+    let (request, content_file) =
+        receive_multipart(index_scheduler.clone(), Multipart::new(req.headers(), body)).await?;
+    register_dump_import(index_scheduler, request, content_file, &req, analytics).await
+}
+
+/// The indexes of a dump are only known once it is read, the key must give access to all of them.
+fn check_import_authorization(index_scheduler: &IndexScheduler) -> Result<(), ResponseError> {
+    if !index_scheduler.filters().search_rules.is_index_authorized("*") {
+        return Err(AuthenticationError::InvalidToken.into());
+    }
+    Ok(())
+}
+
+async fn register_dump_import(
+    index_scheduler: GuardedData<ActionPolicy<{ actions::DUMPS_IMPORT }>, Data<IndexScheduler>>,
+    request: DumpImportRequest,
+    content_file: Option<Uuid>,
+    req: &HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    analytics.publish(
+        "Dump Imported".to_string(),
+        json!({
+            "upload": content_file.is_some(),
+            "renames": request.renames.len(),
+            "on_conflict": request.on_conflict,
+        }),
+        Some(req),
+    );
+
+    let task = match request.into_kind(content_file) {
+        Ok(task) => task,
+        Err(e) => {
+            if let Some(uuid) = content_file {
+                index_scheduler.delete_update_file(uuid)?;
+            }
+            return Err(e.into());
+        }
+    };
+
+    let scheduler = index_scheduler.clone();
+    let task = match tokio::task::spawn_blocking(move || scheduler.register(task)).await? {
+        Ok(task) => task,
+        Err(e) => {
+            if let Some(uuid) = content_file {
+                index_scheduler.delete_update_file(uuid)?;
+            }
+            return Err(e.into());
+        }
+    };
+    let task: SummarizedTaskView = task.into();
+
+    debug!("returns: {:?}", task);
+    Ok(HttpResponse::Accepted().json(task))
+}
+
+/// Reads the `options` and the uploaded `dump` parts of a multipart payload,
+/// the dump is stored in a new update file.
+async fn receive_multipart(
+    index_scheduler: Data<IndexScheduler>,
+    mut multipart: Multipart,
+) -> Result<(DumpImportRequest, Option<Uuid>), MeilisearchHttpError> {
+    let mut request = None;
+    let mut content_file = None;
+
+    while let Some(field) = multipart.next().await {
+        let field = field.map_err(multipart_error)?;
+        let name = field.content_disposition().get_name().map(String::from);
+        let result = match name.as_deref() {
+            Some("options") => receive_options(field).await.map(|options| request = Some(options)),
+            Some("dump") if content_file.is_none() => {
+                receive_dump(&index_scheduler, field).await.map(|uuid| content_file = Some(uuid))
+            }
+            // the other parts are ignored.
+            _ => Ok(()),
+        };
+
+        if let Err(e) = result {
+            if let Some(uuid) = content_file {
+                index_scheduler.delete_update_file(uuid)?;
+            }
+            return Err(e);
+        }
+    }
+
+    Ok((request.unwrap_or_default(), content_file))
+}
+
+/// Reads the `options` part of a multipart payload, it can't be larger than
+/// `MAX_DUMP_OPTIONS_SIZE`.
+async fn receive_options(mut field: Field) -> Result<DumpImportRequest, MeilisearchHttpError> {
+    let mut bytes = web::BytesMut::new();
+    while let Some(chunk) = field.next().await {
+        let chunk = chunk.map_err(multipart_error)?;
+        if bytes.len() + chunk.len() > MAX_DUMP_OPTIONS_SIZE {
+            return Err(MeilisearchHttpError::PayloadTooLarge);
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    serde_json::from_slice(&bytes).map_err(|e| PayloadError::MalformedPayload(e).into())
+}
+
+/// The payloads over the size limit are reported as such.
+fn multipart_error(error: MultipartError) -> MeilisearchHttpError {
+    match error {
+        MultipartError::Payload(actix_web::error::PayloadError::Overflow) => {
+            MeilisearchHttpError::PayloadTooLarge
+        }
+        e => PayloadError::ReceivePayload(e.to_string().into()).into(),
+    }
+}
+
+/// Streams the uploaded dump in a temporary file before copying it to a new update file.
+async fn receive_dump(
+    index_scheduler: &Data<IndexScheduler>,
+    mut field: Field,
+) -> Result<Uuid, MeilisearchHttpError> {
+    let temp_file = tempfile().map_err(|e| PayloadError::ReceivePayload(Box::new(e)))?;
+    let mut buffer = BufWriter::new(File::from_std(temp_file));
+
+    while let Some(chunk) = field.next().await {
+        let chunk = chunk.map_err(multipart_error)?;
+        buffer.write_all(&chunk).await.map_err(|e| PayloadError::ReceivePayload(Box::new(e)))?;
+    }
+    buffer.flush().await.map_err(|e| PayloadError::ReceivePayload(Box::new(e)))?;
+    buffer
+        .seek(std::io::SeekFrom::Start(0))
+        .await
+        .map_err(|e| PayloadError::ReceivePayload(Box::new(e)))?;
+
+    let mut read_file = buffer.into_inner().into_std().await;
+    let (uuid, mut update_file) = index_scheduler.create_update_file()?;
+    tokio::task::spawn_blocking(move || -> Result<(), MeilisearchHttpError> {
+        std::io::copy(&mut read_file, &mut update_file)
+            .map_err(|e| PayloadError::ReceivePayload(Box::new(e)))?;
+        // we NEED to persist the file here because we moved the `update_file` in another task.
+        update_file.persist()?;
+        Ok(())
+    })
+    .await??;
+
+    Ok(uuid)
+}
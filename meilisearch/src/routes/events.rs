@@ -0,0 +1,55 @@
+use actix_web::web::Data;
+use actix_web::{web, HttpRequest, HttpResponse};
+use index_scheduler::search_analytics::SearchEventKind;
+use index_scheduler::IndexScheduler;
+use meilisearch_types::error::ResponseError;
+use serde::Deserialize;
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::analytics::Analytics;
+use crate::error::MeilisearchHttpError;
+use crate::extractors::authentication::policies::*;
+use crate::extractors::authentication::{AuthenticationError, GuardedData};
+use crate::extractors::sequential_extractor::SeqHandler;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("").route(web::post().to(SeqHandler(send_event))));
+}
+
+/// An event following a search, such as a click on one of its hits.
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SearchEvent {
+    event_type: SearchEventKind,
+    /// The `queryUid` returned by the search.
+    query_uid: Uuid,
+}
+
+pub async fn send_event(
+    index_scheduler: GuardedData<ActionPolicy<{ actions::SEARCH }>, Data<IndexScheduler>>,
+    params: web::Json<SearchEvent>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let SearchEvent { event_type, query_uid } = params.into_inner();
+    analytics.publish(
+        "Search Event Sent".to_string(),
+        json!({ "event_type": format!("{:?}", event_type) }),
+        Some(&req),
+    );
+
+    let search_analytics = index_scheduler.search_analytics();
+    if !search_analytics.is_enabled() {
+        return Err(MeilisearchHttpError::SearchAnalyticsDisabled.into());
+    }
+    let index_uid = search_analytics
+        .index_of(query_uid)
+        .ok_or(MeilisearchHttpError::UnknownQueryUid(query_uid))?;
+    if !index_scheduler.filters().search_rules.is_index_authorized(&index_uid) {
+        return Err(AuthenticationError::InvalidToken.into());
+    }
+    search_analytics.record_event(query_uid, event_type);
+
+    Ok(HttpResponse::NoContent().finish())
+}
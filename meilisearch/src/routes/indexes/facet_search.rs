@@ -0,0 +1,52 @@
+use actix_web::web::Data;
+use actix_web::{web, HttpRequest, HttpResponse};
+use index_scheduler::IndexScheduler;
+use log::debug;
+use meilisearch_types::error::ResponseError;
+use serde_json::json;
+
+use super::search::add_search_rules;
+use crate::analytics::Analytics;
+use crate::extractors::authentication::policies::*;
+use crate::extractors::authentication::GuardedData;
+use crate::extractors::sequential_extractor::SeqHandler;
+use crate::search::{perform_facet_search, FacetSearchQuery};
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("").route(web::post().to(SeqHandler(search))));
+}
+
+pub async fn search(
+    index_scheduler: GuardedData<ActionPolicy<{ actions::SEARCH }>, Data<IndexScheduler>>,
+    index_uid: web::Path<String>,
+    params: web::Json<FacetSearchQuery>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let mut query = params.into_inner();
+    debug!("facet search called with params: {:?}", query);
+
+    // Tenant token search_rules.
+    if let Some(search_rules) =
+        index_scheduler.filters().search_rules.get_index_search_rules(&index_uid)
+    {
+        add_search_rules(&mut query.filter, search_rules);
+    }
+
+    analytics.publish(
+        "Facet Searched".to_string(),
+        json!({
+            "facet_query": query.facet_query.is_some(),
+            "q": query.q.is_some(),
+            "filter": query.filter.is_some(),
+        }),
+        Some(&req),
+    );
+
+    let index = index_scheduler.index(&index_uid)?;
+    let search_result =
+        tokio::task::spawn_blocking(move || perform_facet_search(&index, query)).await??;
+
+    debug!("returns: {:?}", search_result);
+    Ok(HttpResponse::Ok().json(search_result))
+}
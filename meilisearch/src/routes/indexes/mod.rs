@@ -10,15 +10,19 @@ use serde::{Deserialize, Serialize};
 use serde_json::json;
 use time::OffsetDateTime;
 
-use super::{Pagination, SummarizedTaskView};
+use super::{Pagination, SummarizedTaskView, PAGINATION_DEFAULT_LIMIT};
 use crate::analytics::Analytics;
+use crate::error::MeilisearchHttpError;
 use crate::extractors::authentication::policies::*;
 use crate::extractors::authentication::{AuthenticationError, GuardedData};
 use crate::extractors::sequential_extractor::SeqHandler;
 
 pub mod documents;
+pub mod facet_search;
 pub mod search;
 pub mod settings;
+pub mod similar;
+pub mod suggestions;
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg.service(
@@ -35,8 +39,14 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
                     .route(web::delete().to(SeqHandler(delete_index))),
             )
             .service(web::resource("/stats").route(web::get().to(SeqHandler(get_index_stats))))
+            .service(
+                web::resource("/analytics").route(web::get().to(SeqHandler(get_index_analytics))),
+            )
             .service(web::scope("/documents").configure(documents::configure))
             .service(web::scope("/search").configure(search::configure))
+            .service(web::scope("/facet-search").configure(facet_search::configure))
+            .service(web::scope("/similar").configure(similar::configure))
+            .service(web::scope("/suggestions").configure(suggestions::configure))
             .service(web::scope("/settings").configure(settings::configure)),
     );
 }
@@ -212,3 +222,36 @@ impl IndexStats {
         })
     }
 }
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct IndexAnalyticsQuery {
+    /// The number of top queries to return.
+    #[serde(default = "PAGINATION_DEFAULT_LIMIT")]
+    limit: usize,
+}
+
+pub async fn get_index_analytics(
+    index_scheduler: GuardedData<ActionPolicy<{ actions::STATS_GET }>, Data<IndexScheduler>>,
+    index_uid: web::Path<String>,
+    params: web::Query<IndexAnalyticsQuery>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    analytics.publish(
+        "Index Analytics Seen".to_string(),
+        json!({ "limit": params.limit }),
+        Some(&req),
+    );
+
+    let search_analytics = index_scheduler.search_analytics();
+    if !search_analytics.is_enabled() {
+        return Err(MeilisearchHttpError::SearchAnalyticsDisabled.into());
+    }
+    // the index must exist.
+    index_scheduler.index(&index_uid)?;
+    let index_analytics = search_analytics.index_analytics(&index_uid, params.limit);
+
+    debug!("returns: {:?}", index_analytics);
+    Ok(HttpResponse::Ok().json(index_analytics))
+}
@@ -1,3 +1,7 @@
+use std::collections::BTreeSet;
+use std::time::Instant;
+
+use actix_web::http::header::ContentType;
 use actix_web::web::Data;
 use actix_web::{web, HttpRequest, HttpResponse};
 use index_scheduler::IndexScheduler;
@@ -6,16 +10,17 @@ use meilisearch_auth::IndexSearchRules;
 use meilisearch_types::error::ResponseError;
 use serde::Deserialize;
 use serde_cs::vec::CS;
-use serde_json::Value;
+use serde_json::{json, Map, Value};
 
 use crate::analytics::{Analytics, SearchAggregator};
+use crate::error::MeilisearchHttpError;
 use crate::extractors::authentication::policies::*;
 use crate::extractors::authentication::GuardedData;
 use crate::extractors::sequential_extractor::SeqHandler;
 use crate::search::{
-    perform_search, MatchingStrategy, SearchQuery, DEFAULT_CROP_LENGTH, DEFAULT_CROP_MARKER,
-    DEFAULT_HIGHLIGHT_POST_TAG, DEFAULT_HIGHLIGHT_PRE_TAG, DEFAULT_SEARCH_LIMIT,
-    DEFAULT_SEARCH_OFFSET,
+    perform_search, FacetQuery, HitsInfo, MatchingStrategy, SearchQuery, DEFAULT_CROP_LENGTH,
+    DEFAULT_CROP_MARKER, DEFAULT_HIGHLIGHT_POST_TAG, DEFAULT_HIGHLIGHT_PRE_TAG,
+    DEFAULT_SEARCH_LIMIT, DEFAULT_SEARCH_OFFSET,
 };
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
@@ -37,15 +42,23 @@ pub struct SearchQueryGet {
     page: Option<usize>,
     hits_per_page: Option<usize>,
     attributes_to_retrieve: Option<CS<String>>,
+    attributes_to_search_on: Option<CS<String>>,
     attributes_to_crop: Option<CS<String>>,
     #[serde(default = "DEFAULT_CROP_LENGTH")]
     crop_length: usize,
+    snippets_per_attribute: Option<usize>,
     attributes_to_highlight: Option<CS<String>>,
     filter: Option<String>,
     sort: Option<String>,
     #[serde(default = "Default::default")]
     show_matches_position: bool,
     facets: Option<CS<String>>,
+    distinct: Option<String>,
+    geo_grid_precision: Option<usize>,
+    search_after: Option<String>,
+    vector: Option<CS<f32>>,
+    semantic_ratio: Option<f32>,
+    search_cutoff_ms: Option<u64>,
     #[serde(default = "DEFAULT_HIGHLIGHT_PRE_TAG")]
     highlight_pre_tag: String,
     #[serde(default = "DEFAULT_HIGHLIGHT_POST_TAG")]
@@ -73,13 +86,23 @@ impl From<SearchQueryGet> for SearchQuery {
             page: other.page,
             hits_per_page: other.hits_per_page,
             attributes_to_retrieve: other.attributes_to_retrieve.map(|o| o.into_iter().collect()),
+            attributes_to_search_on: other.attributes_to_search_on.map(|o| o.into_iter().collect()),
             attributes_to_crop: other.attributes_to_crop.map(|o| o.into_iter().collect()),
             crop_length: other.crop_length,
+            snippets_per_attribute: other.snippets_per_attribute,
             attributes_to_highlight: other.attributes_to_highlight.map(|o| o.into_iter().collect()),
             filter,
             sort: other.sort.map(|attr| fix_sort_query_parameters(&attr)),
             show_matches_position: other.show_matches_position,
-            facets: other.facets.map(|o| o.into_iter().collect()),
+            facets: other.facets.map(|o| o.into_iter().map(FacetQuery::Name).collect()),
+            facet_buckets: None,
+            distinct: other.distinct,
+            group_by: None,
+            geo_grid_precision: other.geo_grid_precision,
+            search_after: other.search_after,
+            vector: other.vector.map(|o| o.into_iter().collect()),
+            semantic_ratio: other.semantic_ratio,
+            search_cutoff_ms: other.search_cutoff_ms,
             highlight_pre_tag: other.highlight_pre_tag,
             highlight_post_tag: other.highlight_post_tag,
             crop_marker: other.crop_marker,
@@ -88,9 +111,113 @@ impl From<SearchQueryGet> for SearchQuery {
     }
 }
 
-/// Incorporate search rules in search query
-fn add_search_rules(query: &mut SearchQuery, rules: IndexSearchRules) {
-    query.filter = match (query.filter.take(), rules.filter) {
+/// Performs the search, unless its response is in the search cache. The responses are cached
+/// by the search query and the search rules of the caller.
+async fn cached_search(
+    index_scheduler: &IndexScheduler,
+    index_uid: &str,
+    query: SearchQuery,
+    search_rules: Option<&IndexSearchRules>,
+    aggregate: &mut SearchAggregator,
+) -> Result<HttpResponse, ResponseError> {
+    let before_search = Instant::now();
+    let index = index_scheduler.index(index_uid)?;
+    let q = query.q.clone();
+
+    let search_analytics = index_scheduler.search_analytics();
+    let search_cache = index_scheduler.search_cache();
+    // The responses are cached without their `queryUid`, each search is recorded on its own.
+    let cache_key = search_cache.is_enabled().then(|| cache_key(&query, search_rules));
+    if let Some(ref key) = cache_key {
+        if let Some(response) = search_cache.get(index_uid, key) {
+            aggregate.succeed_from_cache();
+            debug!("returns a cached response");
+            if !search_analytics.is_enabled() {
+                return Ok(HttpResponse::Ok()
+                    .content_type(ContentType::json())
+                    .body(response.to_vec()));
+            }
+            let mut response: Map<String, Value> =
+                serde_json::from_slice(&response).map_err(MeilisearchHttpError::from)?;
+            let total_hits = response
+                .get("totalHits")
+                .or_else(|| response.get("estimatedTotalHits"))
+                .and_then(Value::as_u64)
+                .unwrap_or_default();
+            let query_uid = search_analytics.record_search(
+                index_uid,
+                q.as_deref().unwrap_or_default(),
+                total_hits,
+                before_search.elapsed(),
+            );
+            response.insert("queryUid".to_string(), json!(query_uid));
+            return Ok(HttpResponse::Ok().json(response));
+        }
+    }
+    // The generation is read before the search to never cache a response outdated by a batch.
+    let generation = search_cache.generation(index_uid);
+
+    let search_result = tokio::task::spawn_blocking(move || perform_search(&index, query)).await?;
+    if let Ok(ref search_result) = search_result {
+        aggregate.succeed(search_result);
+    }
+    let mut search_result = search_result?;
+
+    let query_uid = search_analytics.is_enabled().then(|| {
+        let total_hits = match search_result.hits_info {
+            HitsInfo::Pagination { total_hits, .. } => total_hits,
+            HitsInfo::OffsetLimit { estimated_total_hits, .. } => estimated_total_hits,
+        };
+        search_analytics.record_search(
+            index_uid,
+            q.as_deref().unwrap_or_default(),
+            total_hits as u64,
+            before_search.elapsed(),
+        )
+    });
+
+    debug!("returns: {:?}", search_result);
+    // A degraded response depends on the load of the machine, it's never cached.
+    if let Some(key) = cache_key.filter(|_| !search_result.degraded) {
+        let response = serde_json::to_vec(&search_result).map_err(MeilisearchHttpError::from)?;
+        if query_uid.is_none() {
+            search_cache.insert(index_uid, key, generation, response.clone());
+            return Ok(HttpResponse::Ok().content_type(ContentType::json()).body(response));
+        }
+        search_cache.insert(index_uid, key, generation, response);
+    }
+    search_result.query_uid = query_uid;
+    Ok(HttpResponse::Ok().json(search_result))
+}
+
+/// Returns the key of the search in the search cache. It is the same whatever the route, the
+/// order of the parameters, of the attributes to highlight and of the keys of the filter objects.
+fn cache_key(query: &SearchQuery, search_rules: Option<&IndexSearchRules>) -> String {
+    let mut query = query.clone();
+    let attributes_to_highlight: Option<BTreeSet<_>> =
+        query.attributes_to_highlight.take().map(|attributes| attributes.into_iter().collect());
+    query.filter = query.filter.map(canonical_json);
+    let rules_filter = search_rules.and_then(|rules| rules.filter.clone()).map(canonical_json);
+    format!("{:?} {:?} {:?}", query, attributes_to_highlight, rules_filter)
+}
+
+/// Sorts the keys of the objects of the value, they keep their insertion order otherwise.
+fn canonical_json(value: Value) -> Value {
+    match value {
+        Value::Object(object) => {
+            let mut entries: Vec<_> =
+                object.into_iter().map(|(key, value)| (key, canonical_json(value))).collect();
+            entries.sort_by(|(left, _), (right, _)| left.cmp(right));
+            Value::Object(entries.into_iter().collect())
+        }
+        Value::Array(values) => Value::Array(values.into_iter().map(canonical_json).collect()),
+        value => value,
+    }
+}
+
+/// Incorporate search rules in the filter of a search query
+pub fn add_search_rules(filter: &mut Option<Value>, rules: IndexSearchRules) {
+    *filter = match (filter.take(), rules.filter) {
         (None, rules_filter) => rules_filter,
         (filter, None) => filter,
         (Some(filter), Some(rules_filter)) => {
@@ -144,25 +271,19 @@ pub async fn search_with_url_query(
     let mut query: SearchQuery = params.into_inner().into();
 
     // Tenant token search_rules.
-    if let Some(search_rules) =
-        index_scheduler.filters().search_rules.get_index_search_rules(&index_uid)
-    {
-        add_search_rules(&mut query, search_rules);
+    let search_rules = index_scheduler.filters().search_rules.get_index_search_rules(&index_uid);
+    if let Some(ref search_rules) = search_rules {
+        add_search_rules(&mut query.filter, search_rules.clone());
     }
 
     let mut aggregate = SearchAggregator::from_query(&query, &req);
 
-    let index = index_scheduler.index(&index_uid)?;
-    let search_result = tokio::task::spawn_blocking(move || perform_search(&index, query)).await?;
-    if let Ok(ref search_result) = search_result {
-        aggregate.succeed(search_result);
-    }
+    let response =
+        cached_search(&index_scheduler, &index_uid, query, search_rules.as_ref(), &mut aggregate)
+            .await;
     analytics.get_search(aggregate);
 
-    let search_result = search_result?;
-
-    debug!("returns: {:?}", search_result);
-    Ok(HttpResponse::Ok().json(search_result))
+    response
 }
 
 pub async fn search_with_post(
@@ -176,31 +297,46 @@ pub async fn search_with_post(
     debug!("search called with params: {:?}", query);
 
     // Tenant token search_rules.
-    if let Some(search_rules) =
-        index_scheduler.filters().search_rules.get_index_search_rules(&index_uid)
-    {
-        add_search_rules(&mut query, search_rules);
+    let search_rules = index_scheduler.filters().search_rules.get_index_search_rules(&index_uid);
+    if let Some(ref search_rules) = search_rules {
+        add_search_rules(&mut query.filter, search_rules.clone());
     }
 
     let mut aggregate = SearchAggregator::from_query(&query, &req);
 
-    let index = index_scheduler.index(&index_uid)?;
-    let search_result = tokio::task::spawn_blocking(move || perform_search(&index, query)).await?;
-    if let Ok(ref search_result) = search_result {
-        aggregate.succeed(search_result);
-    }
+    let response =
+        cached_search(&index_scheduler, &index_uid, query, search_rules.as_ref(), &mut aggregate)
+            .await;
     analytics.post_search(aggregate);
 
-    let search_result = search_result?;
-
-    debug!("returns: {:?}", search_result);
-    Ok(HttpResponse::Ok().json(search_result))
+    response
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
+    #[test]
+    fn test_cache_key() {
+        let query = |query: Value| -> SearchQuery { serde_json::from_value(query).unwrap() };
+        let left = query(json!({
+            "q": "carol",
+            "attributesToHighlight": ["title", "overview", "genres"],
+            "filter": { "or": ["genres = Drama", "genres = Comedy"], "not": "year < 2000" },
+        }));
+        let right = query(json!({
+            "filter": { "not": "year < 2000", "or": ["genres = Drama", "genres = Comedy"] },
+            "attributesToHighlight": ["genres", "title", "overview"],
+            "q": "carol",
+        }));
+        assert_eq!(cache_key(&left, None), cache_key(&right, None));
+
+        let rules = IndexSearchRules { filter: Some(json!("genres = Drama")) };
+        assert_ne!(cache_key(&left, None), cache_key(&left, Some(&rules)));
+        let other = query(json!({ "q": "carol", "filter": ["genres = Comedy", "genres = Drama"] }));
+        assert_ne!(cache_key(&left, None), cache_key(&other, None));
+    }
+
     #[test]
     fn test_fix_sort_query_parameters() {
         let sort = fix_sort_query_parameters("_geoPoint(12, 13):asc");
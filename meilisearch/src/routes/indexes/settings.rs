@@ -1,21 +1,34 @@
+use std::collections::BTreeMap;
+
+use actix_web::http::header::CONTENT_TYPE;
 use actix_web::web::Data;
-use actix_web::{web, HttpRequest, HttpResponse};
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use bstr::ByteSlice;
+use futures::StreamExt;
 use index_scheduler::IndexScheduler;
 use log::debug;
 use meilisearch_types::error::ResponseError;
 use meilisearch_types::index_uid::IndexUid;
-use meilisearch_types::settings::{settings, Settings, Unchecked};
-use meilisearch_types::tasks::KindWithContent;
+use meilisearch_types::settings::{settings, FacetValuesSort, Settings, Unchecked};
+use meilisearch_types::settings_history::{
+    settings_diff, settings_history, settings_version, SettingDiff, SettingsVersion,
+    MAX_SETTINGS_VERSIONS,
+};
+use meilisearch_types::tasks::{KindWithContent, TaskId};
+use serde::{Deserialize, Serialize};
 use serde_json::json;
+use time::OffsetDateTime;
 
 use crate::analytics::Analytics;
+use crate::error::MeilisearchHttpError;
 use crate::extractors::authentication::policies::*;
 use crate::extractors::authentication::GuardedData;
+use crate::extractors::payload::Payload;
 use crate::routes::SummarizedTaskView;
 
 #[macro_export]
 macro_rules! make_setting_route {
-    ($route:literal, $update_verb:ident, $type:ty, $attr:ident, $camelcase_attr:literal, $analytics_var:ident, $analytics:expr) => {
+    ($route:literal, $update_verb:ident, $type:ty, $attr:ident, $camelcase_attr:literal, $analytics_var:ident, $analytics:expr $(, text: $text_verb:ident => $parse_text:expr)?) => {
         pub mod $attr {
             use actix_web::web::Data;
             use actix_web::{web, HttpRequest, HttpResponse, Resource};
@@ -97,6 +110,47 @@ macro_rules! make_setting_route {
                 Ok(HttpResponse::Accepted().json(task))
             }
 
+            $(
+            /// Replaces the setting by the one parsed from a `text/plain` payload.
+            pub async fn update_from_text(
+                index_scheduler: GuardedData<
+                    ActionPolicy<{ actions::SETTINGS_UPDATE }>,
+                    Data<IndexScheduler>,
+                >,
+                index_uid: actix_web::web::Path<String>,
+                body: $crate::extractors::payload::Payload,
+                req: HttpRequest,
+                $analytics_var: web::Data<dyn Analytics>,
+            ) -> std::result::Result<HttpResponse, ResponseError> {
+                let text = $crate::routes::indexes::settings::read_text_payload(&req, body).await?;
+                let body: $type = ($parse_text)(text.as_str())?;
+                let body = Some(body);
+
+                $analytics(&body, &req);
+
+                let new_settings = Settings {
+                    $attr: body.map_or(Setting::Reset, Setting::Set),
+                    ..Default::default()
+                };
+
+                let allow_index_creation = index_scheduler.filters().allow_index_creation;
+                let index_uid = IndexUid::try_from(index_uid.into_inner())?.into_inner();
+                let task = KindWithContent::SettingsUpdate {
+                    index_uid,
+                    new_settings: Box::new(new_settings),
+                    is_deletion: false,
+                    allow_index_creation,
+                };
+                let task: SummarizedTaskView =
+                    tokio::task::spawn_blocking(move || index_scheduler.register(task))
+                        .await??
+                        .into();
+
+                debug!("returns: {:?}", task);
+                Ok(HttpResponse::Accepted().json(task))
+            }
+            )?
+
             pub async fn get(
                 index_scheduler: GuardedData<
                     ActionPolicy<{ actions::SETTINGS_GET }>,
@@ -119,6 +173,7 @@ macro_rules! make_setting_route {
                 Resource::new($route)
                     .route(web::get().to(SeqHandler(get)))
                     .route(web::$update_verb().to(SeqHandler(update)))
+                    $(.route(web::$text_verb().to(SeqHandler(update_from_text))))?
                     .route(web::delete().to(SeqHandler(delete)))
             }
         }
@@ -278,6 +333,34 @@ make_setting_route!(
             }),
             Some(req),
         );
+    },
+    text: patch => |text: &str| -> Result<_, ResponseError> {
+        Ok(meilisearch_types::stop_words::parse_stop_words(text))
+    }
+);
+
+make_setting_route!(
+    "/stop-words-presets",
+    put,
+    std::collections::BTreeSet<meilisearch_types::stop_words::StopWordsPreset>,
+    stop_words_presets,
+    "stopWordsPresets",
+    analytics,
+    |presets: &Option<
+        std::collections::BTreeSet<meilisearch_types::stop_words::StopWordsPreset>,
+    >,
+     req: &HttpRequest| {
+        use serde_json::json;
+
+        analytics.publish(
+            "StopWordsPresets Updated".to_string(),
+            json!({
+                "stop_words_presets": {
+                    "presets": presets,
+                },
+            }),
+            Some(req),
+        );
     }
 );
 
@@ -300,6 +383,9 @@ make_setting_route!(
             }),
             Some(req),
         );
+    },
+    text: patch => |text: &str| -> Result<_, ResponseError> {
+        Ok(meilisearch_types::synonyms::parse_synonyms(text)?)
     }
 );
 
@@ -367,6 +453,9 @@ make_setting_route!(
             json!({
                 "faceting": {
                     "max_values_per_facet": setting.as_ref().and_then(|s| s.max_values_per_facet.set()),
+                    "sort_facet_values_by_star_count": setting.as_ref().and_then(|s| {
+                        s.sort_facet_values_by.as_ref().set().map(|s| s.get("*") == Some(&FacetValuesSort::Count))
+                    }),
                 },
             }),
             Some(req),
@@ -396,6 +485,50 @@ make_setting_route!(
     }
 );
 
+make_setting_route!(
+    "/rules",
+    put,
+    Vec<meilisearch_types::settings::QueryRule>,
+    rules,
+    "rules",
+    analytics,
+    |setting: &Option<Vec<meilisearch_types::settings::QueryRule>>, req: &HttpRequest| {
+        use serde_json::json;
+
+        analytics.publish(
+            "Rules Updated".to_string(),
+            json!({
+                "rules": {
+                    "total": setting.as_ref().map(|rules| rules.len()),
+                },
+            }),
+            Some(req),
+        );
+    }
+);
+
+make_setting_route!(
+    "/search-cutoff-ms",
+    put,
+    u64,
+    search_cutoff_ms,
+    "searchCutoffMs",
+    analytics,
+    |setting: &Option<u64>, req: &HttpRequest| {
+        use serde_json::json;
+
+        analytics.publish(
+            "Search Cutoff Updated".to_string(),
+            json!({
+                "search_cutoff_ms": {
+                    "search_cutoff_ms": setting,
+                },
+            }),
+            Some(req),
+        );
+    }
+);
+
 macro_rules! generate_configure {
     ($($mod:ident),*) => {
         pub fn configure(cfg: &mut web::ServiceConfig) {
@@ -405,6 +538,10 @@ macro_rules! generate_configure {
                 .route(web::patch().to(SeqHandler(update_all)))
                 .route(web::get().to(SeqHandler(get_all)))
                 .route(web::delete().to(SeqHandler(delete_all))))
+                .service(web::resource("/history").route(web::get().to(SeqHandler(get_history))))
+                .service(
+                    web::resource("/rollback/{version}").route(web::post().to(SeqHandler(rollback))),
+                )
                 $(.service($mod::resources()))*;
         }
     };
@@ -417,13 +554,48 @@ generate_configure!(
     searchable_attributes,
     distinct_attribute,
     stop_words,
+    stop_words_presets,
     synonyms,
     ranking_rules,
     typo_tolerance,
     pagination,
-    faceting
+    faceting,
+    rules,
+    search_cutoff_ms
 );
 
+/// Reads the payload of a setting given as plain text, it must have the `text/plain`
+/// content type.
+pub async fn read_text_payload(
+    req: &HttpRequest,
+    mut body: Payload,
+) -> Result<String, MeilisearchHttpError> {
+    let accepted = vec![mime::TEXT_PLAIN.to_string()];
+    match req.mime_type() {
+        Ok(Some(mime)) if mime.type_() == mime::TEXT && mime.subtype() == mime::PLAIN => (),
+        Ok(Some(mime)) => {
+            return Err(MeilisearchHttpError::InvalidContentType(
+                mime.essence_str().to_string(),
+                accepted,
+            ))
+        }
+        Ok(None) => return Err(MeilisearchHttpError::MissingContentType(accepted)),
+        Err(_) => {
+            let content_type = req.headers().get(CONTENT_TYPE);
+            return Err(MeilisearchHttpError::InvalidContentType(
+                content_type.map(|c| c.as_bytes().as_bstr().to_string()).unwrap_or_default(),
+                accepted,
+            ));
+        }
+    }
+
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.next().await {
+        bytes.extend_from_slice(&chunk?);
+    }
+    String::from_utf8(bytes).map_err(|e| MeilisearchHttpError::InvalidTextPayload(e.to_string()))
+}
+
 pub async fn update_all(
     index_scheduler: GuardedData<ActionPolicy<{ actions::SETTINGS_UPDATE }>, Data<IndexScheduler>>,
     index_uid: web::Path<String>,
@@ -449,6 +621,12 @@ pub async fn update_all(
                 "total": new_settings.searchable_attributes.as_ref().set().map(|searchable| searchable.len()),
                 "with_wildcard": new_settings.searchable_attributes.as_ref().set().map(|searchable| searchable.iter().any(|searchable| searchable == "*")),
             },
+            "rules": {
+                "total": new_settings.rules.as_ref().set().map(|rules| rules.len()),
+            },
+            "search_cutoff_ms": {
+                "search_cutoff_ms": new_settings.search_cutoff_ms.as_ref().set(),
+            },
             "displayed_attributes": {
                 "total": new_settings.displayed_attributes.as_ref().set().map(|displayed| displayed.len()),
                 "with_wildcard": new_settings.displayed_attributes.as_ref().set().map(|displayed| displayed.iter().any(|displayed| displayed == "*")),
@@ -500,6 +678,11 @@ pub async fn update_all(
                     .as_ref()
                     .set()
                     .and_then(|s| s.max_values_per_facet.as_ref().set()),
+                "sort_facet_values_by_star_count": new_settings.faceting
+                    .as_ref()
+                    .set()
+                    .and_then(|s| s.sort_facet_values_by.as_ref().set())
+                    .map(|s| s.get("*") == Some(&FacetValuesSort::Count)),
             },
             "pagination": {
                 "max_total_hits": new_settings.pagination
@@ -510,6 +693,9 @@ pub async fn update_all(
             "stop_words": {
                 "total": new_settings.stop_words.as_ref().set().map(|stop_words| stop_words.len()),
             },
+            "stop_words_presets": {
+                "presets": new_settings.stop_words_presets.as_ref().set(),
+            },
             "synonyms": {
                 "total": new_settings.synonyms.as_ref().set().map(|synonyms| synonyms.len()),
             },
@@ -563,3 +749,76 @@ pub async fn delete_all(
     debug!("returns: {:?}", task);
     Ok(HttpResponse::Accepted().json(task))
 }
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SettingsVersionView {
+    version: u32,
+    task_uids: Vec<TaskId>,
+    #[serde(with = "time::serde::rfc3339")]
+    updated_at: OffsetDateTime,
+    settings: Settings<Unchecked>,
+    /// The settings changed since the previous version, unknown for the oldest version.
+    diff: Option<BTreeMap<String, SettingDiff>>,
+}
+
+pub async fn get_history(
+    index_scheduler: GuardedData<ActionPolicy<{ actions::SETTINGS_GET }>, Data<IndexScheduler>>,
+    index_uid: web::Path<String>,
+) -> Result<HttpResponse, ResponseError> {
+    let index = index_scheduler.index(&index_uid)?;
+    let rtxn = index.read_txn()?;
+    let history = settings_history(&index, &rtxn)?;
+
+    let mut previous_settings = None;
+    let mut results = Vec::with_capacity(history.len());
+    for SettingsVersion { version, task_uids, updated_at, settings } in history {
+        let diff = previous_settings.map(|previous| settings_diff(&previous, &settings));
+        previous_settings = Some(settings.clone());
+        results.push(SettingsVersionView { version, task_uids, updated_at, settings, diff });
+    }
+    // the most recent versions first, like the tasks.
+    results.reverse();
+
+    debug!("returns: {:?}", results);
+    Ok(HttpResponse::Ok().json(json!({ "results": results })))
+}
+
+#[derive(Deserialize)]
+pub struct SettingsVersionParam {
+    index_uid: String,
+    version: u32,
+}
+
+pub async fn rollback(
+    index_scheduler: GuardedData<ActionPolicy<{ actions::SETTINGS_UPDATE }>, Data<IndexScheduler>>,
+    params: web::Path<SettingsVersionParam>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let SettingsVersionParam { index_uid, version } = params.into_inner();
+
+    let index = index_scheduler.index(&index_uid)?;
+    let rtxn = index.read_txn()?;
+    let new_settings = settings_version(&index, &rtxn, version)?
+        .map(|settings_version| settings_version.settings)
+        .ok_or(MeilisearchHttpError::SettingsVersionNotFound(version, MAX_SETTINGS_VERSIONS))?;
+    drop(rtxn);
+
+    analytics.publish("Settings Rolled Back".to_string(), json!({}), Some(&req));
+
+    // The whole settings of the version are applied, which restores them all.
+    let allow_index_creation = index_scheduler.filters().allow_index_creation;
+    let index_uid = IndexUid::try_from(index_uid)?.into_inner();
+    let task = KindWithContent::SettingsUpdate {
+        index_uid,
+        new_settings: Box::new(new_settings),
+        is_deletion: false,
+        allow_index_creation,
+    };
+    let task: SummarizedTaskView =
+        tokio::task::spawn_blocking(move || index_scheduler.register(task)).await??.into();
+
+    debug!("returns: {:?}", task);
+    Ok(HttpResponse::Accepted().json(task))
+}
@@ -0,0 +1,52 @@
+use actix_web::web::Data;
+use actix_web::{web, HttpRequest, HttpResponse};
+use index_scheduler::IndexScheduler;
+use log::debug;
+use meilisearch_types::error::ResponseError;
+use serde_json::json;
+
+use super::search::add_search_rules;
+use crate::analytics::Analytics;
+use crate::extractors::authentication::policies::*;
+use crate::extractors::authentication::GuardedData;
+use crate::extractors::sequential_extractor::SeqHandler;
+use crate::search::{perform_similar, SimilarQuery};
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("").route(web::post().to(SeqHandler(similar))));
+}
+
+pub async fn similar(
+    index_scheduler: GuardedData<ActionPolicy<{ actions::SEARCH }>, Data<IndexScheduler>>,
+    index_uid: web::Path<String>,
+    params: web::Json<SimilarQuery>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let mut query = params.into_inner();
+    debug!("similar called with params: {:?}", query);
+
+    // Tenant token search_rules.
+    if let Some(search_rules) =
+        index_scheduler.filters().search_rules.get_index_search_rules(&index_uid)
+    {
+        add_search_rules(&mut query.filter, search_rules);
+    }
+
+    analytics.publish(
+        "Similar Searched".to_string(),
+        json!({
+            "filter": query.filter.is_some(),
+            "limit": query.limit,
+            "attributes_to_retrieve": query.attributes_to_retrieve.is_some(),
+        }),
+        Some(&req),
+    );
+
+    let index = index_scheduler.index(&index_uid)?;
+    let similar_result =
+        tokio::task::spawn_blocking(move || perform_similar(&index, query)).await??;
+
+    debug!("returns: {:?}", similar_result);
+    Ok(HttpResponse::Ok().json(similar_result))
+}
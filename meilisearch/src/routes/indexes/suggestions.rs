@@ -0,0 +1,50 @@
+use actix_web::web::Data;
+use actix_web::{web, HttpRequest, HttpResponse};
+use index_scheduler::IndexScheduler;
+use log::debug;
+use meilisearch_types::error::ResponseError;
+use serde_json::json;
+
+use crate::analytics::Analytics;
+use crate::error::MeilisearchHttpError;
+use crate::extractors::authentication::policies::*;
+use crate::extractors::authentication::GuardedData;
+use crate::extractors::sequential_extractor::SeqHandler;
+use crate::search::{perform_suggest, SuggestQuery};
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("").route(web::post().to(SeqHandler(suggest))));
+}
+
+pub async fn suggest(
+    index_scheduler: GuardedData<ActionPolicy<{ actions::SEARCH }>, Data<IndexScheduler>>,
+    index_uid: web::Path<String>,
+    params: web::Json<SuggestQuery>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let query = params.into_inner();
+    debug!("suggest called with params: {:?}", query);
+
+    // Tenant token search_rules, the words of the filtered out documents must not be suggested.
+    if let Some(search_rules) =
+        index_scheduler.filters().search_rules.get_index_search_rules(&index_uid)
+    {
+        if search_rules.filter.is_some() {
+            return Err(MeilisearchHttpError::SuggestionsWithFilteredToken.into());
+        }
+    }
+
+    analytics.publish(
+        "Suggestions Fetched".to_string(),
+        json!({ "limit": query.limit }),
+        Some(&req),
+    );
+
+    let index = index_scheduler.index(&index_uid)?;
+    let suggest_result =
+        tokio::task::spawn_blocking(move || perform_suggest(&index, query)).await??;
+
+    debug!("returns: {:?}", suggest_result);
+    Ok(HttpResponse::Ok().json(suggest_result))
+}
@@ -2,6 +2,7 @@ use std::collections::BTreeMap;
 
 use actix_web::web::Data;
 use actix_web::{web, HttpRequest, HttpResponse};
+use index_scheduler::search_cache::SearchCacheStats;
 use index_scheduler::{IndexScheduler, Query};
 use log::debug;
 use meilisearch_types::error::ResponseError;
@@ -19,6 +20,7 @@ use crate::extractors::authentication::GuardedData;
 
 mod api_key;
 mod dump;
+mod events;
 pub mod indexes;
 mod swap_indexes;
 pub mod tasks;
@@ -28,6 +30,7 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         .service(web::resource("/health").route(web::get().to(get_health)))
         .service(web::scope("/keys").configure(api_key::configure))
         .service(web::scope("/dumps").configure(dump::configure))
+        .service(web::scope("/events").configure(events::configure))
         .service(web::resource("/stats").route(web::get().to(get_stats)))
         .service(web::resource("/version").route(web::get().to(get_version)))
         .service(web::scope("/indexes").configure(indexes::configure))
@@ -247,6 +250,9 @@ pub struct Stats {
     #[serde(serialize_with = "time::serde::rfc3339::option::serialize")]
     pub last_update: Option<OffsetDateTime>,
     pub indexes: BTreeMap<String, IndexStats>,
+    /// The hits and misses of the search cache, when it's enabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search_cache: Option<SearchCacheStats>,
 }
 
 async fn get_stats(
@@ -294,7 +300,9 @@ pub fn create_all_stats(
 
         indexes.insert(name, stats);
     }
-    let stats = Stats { database_size, last_update: last_task, indexes };
+    let search_cache = index_scheduler.search_cache();
+    let search_cache = search_cache.is_enabled().then(|| search_cache.stats());
+    let stats = Stats { database_size, last_update: last_task, indexes, search_cache };
     Ok(stats)
 }
 
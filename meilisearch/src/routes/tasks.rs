@@ -100,6 +100,10 @@ pub struct DetailsView {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub dump_uid: Option<Option<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub imported_indexes: Option<Option<Vec<String>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skipped_indexes: Option<Option<Vec<String>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(flatten)]
     pub settings: Option<Box<Settings<Unchecked>>>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -152,6 +156,11 @@ impl From<Details> for DetailsView {
             Details::Dump { dump_uid } => {
                 DetailsView { dump_uid: Some(dump_uid), ..DetailsView::default() }
             }
+            Details::DumpImport { imported_indexes, skipped_indexes } => DetailsView {
+                imported_indexes: Some(imported_indexes),
+                skipped_indexes: Some(skipped_indexes),
+                ..DetailsView::default()
+            },
             Details::IndexSwap { swaps } => {
                 DetailsView { swaps: Some(swaps), ..Default::default() }
             }
@@ -912,12 +921,12 @@ mod tests {
     #[test]
     fn deserialize_task_filter_types() {
         {
-            let json = r#" { "types": "documentAdditionOrUpdate,documentDeletion,settingsUpdate,indexCreation,indexDeletion,indexUpdate,indexSwap,taskCancelation,taskDeletion,dumpCreation,snapshotCreation" }"#;
+            let json = r#" { "types": "documentAdditionOrUpdate,documentDeletion,settingsUpdate,indexCreation,indexDeletion,indexUpdate,indexSwap,taskCancelation,taskDeletion,dumpCreation,dumpImport,snapshotCreation,suggestionsRefresh" }"#;
             let query = serde_json::from_str::<TaskDeletionOrCancelationQueryRaw>(json)
                 .unwrap()
                 .validate()
                 .unwrap();
-            snapshot!(format!("{:?}", query.common.types.unwrap()), @"[DocumentAdditionOrUpdate, DocumentDeletion, SettingsUpdate, IndexCreation, IndexDeletion, IndexUpdate, IndexSwap, TaskCancelation, TaskDeletion, DumpCreation, SnapshotCreation]");
+            snapshot!(format!("{:?}", query.common.types.unwrap()), @"[DocumentAdditionOrUpdate, DocumentDeletion, SettingsUpdate, IndexCreation, IndexDeletion, IndexUpdate, IndexSwap, TaskCancelation, TaskDeletion, DumpCreation, DumpImport, SnapshotCreation, SuggestionsRefresh]");
         }
         {
             let json = r#" { "types": "settingsUpdate" } "#;
@@ -933,7 +942,7 @@ mod tests {
                 .unwrap()
                 .validate()
                 .unwrap_err();
-            snapshot!(format!("{err}"), @"Task type `createIndex` is invalid. Available task types are `documentAdditionOrUpdate`, `documentDeletion`, `settingsUpdate`, `indexCreation`, `indexDeletion`, `indexUpdate`, `indexSwap`, `taskCancelation`, `taskDeletion`, `dumpCreation`, `snapshotCreation`");
+            snapshot!(format!("{err}"), @"Task type `createIndex` is invalid. Available task types are `documentAdditionOrUpdate`, `documentDeletion`, `settingsUpdate`, `indexCreation`, `indexDeletion`, `indexUpdate`, `indexSwap`, `taskCancelation`, `taskDeletion`, `dumpCreation`, `dumpImport`, `snapshotCreation`, `suggestionsRefresh`");
         }
     }
     #[test]
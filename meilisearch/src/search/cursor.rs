@@ -0,0 +1,229 @@
+//! The `searchAfter` cursors, the position of the last hit of a page.
+
+use std::cmp::Ordering;
+
+use hmac::{Hmac, Mac};
+use meilisearch_types::heed::RoTxn;
+use meilisearch_types::{milli, Document};
+use milli::{AscDesc, DocumentId, FieldsIdsMap, Index, Member};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::Sha256;
+
+use super::{field_value, full_document};
+use crate::error::MeilisearchHttpError;
+
+/// The number of hits fetched around a `searchAfter` cursor to find its last hit back
+/// when documents were added or removed before it since the previous page.
+const SEARCH_AFTER_WINDOW: usize = 10;
+
+/// The key signing the cursors, the cursors of a previous run of Meilisearch are invalid.
+static CURSOR_KEY: Lazy<[u8; 32]> = Lazy::new(rand::random);
+
+/// The position of the last hit of a page, sent back to the user as an opaque string.
+///
+/// The cursors are signed so that they can't be forged to skip the `maxTotalHits`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SearchCursor {
+    /// The number of hits up to and including the last hit.
+    offset: usize,
+    /// The primary key of the last hit.
+    id: String,
+    /// The values of the sort criteria of the last hit.
+    sort: Vec<Value>,
+}
+
+impl SearchCursor {
+    /// Returns the cursor following the given last hit of a page, or `None` when the
+    /// following hits are beyond the `maxTotalHits`.
+    pub fn after(
+        index: &Index,
+        rtxn: &RoTxn,
+        fields_ids_map: &FieldsIdsMap,
+        sort: &[AscDesc],
+        offset: usize,
+        last_id: DocumentId,
+        max_total_hits: usize,
+    ) -> Result<Option<Self>, MeilisearchHttpError> {
+        if offset >= max_total_hits {
+            return Ok(None);
+        }
+        let document = full_document(index, rtxn, fields_ids_map, last_id)?;
+        let id = index
+            .primary_key(rtxn)?
+            .and_then(|primary_key| document.get(primary_key))
+            .map(|id| match id {
+                Value::String(id) => id.clone(),
+                id => id.to_string(),
+            })
+            .unwrap_or_default();
+        Ok(Some(SearchCursor { offset, id, sort: sort_values(sort, &document) }))
+    }
+
+    pub fn encode(&self) -> String {
+        let bytes = serde_json::to_vec(self).expect("a cursor is always serializable");
+        let signature = mac(&bytes).finalize().into_bytes();
+        format!(
+            "{}.{}",
+            base64::encode_config(bytes, base64::URL_SAFE_NO_PAD),
+            base64::encode_config(signature, base64::URL_SAFE_NO_PAD)
+        )
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self, MeilisearchHttpError> {
+        let invalid = || {
+            MeilisearchHttpError::InvalidSearchAfter("it was not returned by a search".to_string())
+        };
+        let (bytes, signature) = cursor.split_once('.').ok_or_else(invalid)?;
+        let bytes = base64::decode_config(bytes, base64::URL_SAFE_NO_PAD).map_err(|_| invalid())?;
+        let signature =
+            base64::decode_config(signature, base64::URL_SAFE_NO_PAD).map_err(|_| invalid())?;
+        mac(&bytes).verify_slice(&signature).map_err(|_| invalid())?;
+        serde_json::from_slice(&bytes).map_err(|_| invalid())
+    }
+
+    /// Returns the number of sort criteria of the search that returned the cursor.
+    pub fn sort_len(&self) -> usize {
+        self.sort.len()
+    }
+
+    /// Returns the offset and the limit of the hits to fetch from milli, a window around
+    /// the last hit to find it back even if it moved since the previous page.
+    ///
+    /// The hits following the cursor are bound by the `maxTotalHits` like the offset.
+    pub fn window(&self, limit: usize, max_total_hits: usize) -> (usize, usize) {
+        let limit = limit.min(max_total_hits.saturating_sub(self.offset));
+        if limit == 0 {
+            return (self.offset, 0);
+        }
+        let window_offset = self.offset.saturating_sub(SEARCH_AFTER_WINDOW);
+        (window_offset, limit.saturating_add(2 * SEARCH_AFTER_WINDOW))
+    }
+
+    /// Returns the hits of the window that follow the last hit of the cursor, along with
+    /// the offset of the first of them.
+    #[allow(clippy::too_many_arguments)]
+    pub fn documents_after(
+        &self,
+        index: &Index,
+        rtxn: &RoTxn,
+        fields_ids_map: &FieldsIdsMap,
+        sort: &[AscDesc],
+        window_offset: usize,
+        documents_ids: Vec<DocumentId>,
+        limit: usize,
+        max_total_hits: usize,
+    ) -> Result<(usize, Vec<DocumentId>), MeilisearchHttpError> {
+        let last_id = index.external_documents_ids(rtxn)?.get(&self.id);
+        let skip = match last_id.and_then(|id| documents_ids.iter().position(|&docid| docid == id))
+        {
+            Some(position) => position + 1,
+            // The last hit was deleted or moved out of the window,
+            // we skip the hits that are sorted before it.
+            None if !sort.is_empty() => {
+                let mut skip = 0;
+                for &docid in &documents_ids {
+                    let document = full_document(index, rtxn, fields_ids_map, docid)?;
+                    let values = sort_values(sort, &document);
+                    if compare_sort_values(sort, &values, &self.sort) == Ordering::Greater {
+                        break;
+                    }
+                    skip += 1;
+                }
+                skip
+            }
+            None => self.offset.saturating_sub(window_offset),
+        };
+
+        let offset = window_offset + skip;
+        let limit = limit.min(max_total_hits.saturating_sub(offset));
+        let documents_ids = documents_ids.into_iter().skip(skip).take(limit).collect();
+        Ok((offset, documents_ids))
+    }
+}
+
+fn mac(bytes: &[u8]) -> Hmac<Sha256> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(&*CURSOR_KEY).expect("HMAC accepts keys of any size");
+    mac.update(bytes);
+    mac
+}
+
+/// Returns the value of each sort criterion for the given document,
+/// the distance to the point for the geo criteria.
+fn sort_values(sort: &[AscDesc], document: &Document) -> Vec<Value> {
+    sort.iter()
+        .map(|criterion| match criterion {
+            AscDesc::Asc(Member::Field(field)) | AscDesc::Desc(Member::Field(field)) => {
+                field_value(document, field)
+            }
+            AscDesc::Asc(Member::Geo(point)) | AscDesc::Desc(Member::Geo(point)) => {
+                let geo_point = document.get("_geo").unwrap_or(&Value::Null);
+                match geo_point["lat"].as_f64().zip(geo_point["lng"].as_f64()) {
+                    Some((lat, lng)) => {
+                        json!(milli::distance_between_two_points(point, &[lat, lng]))
+                    }
+                    None => Value::Null,
+                }
+            }
+        })
+        .collect()
+}
+
+/// Compares the sort values of two hits, the missing values are always sorted last.
+fn compare_sort_values(sort: &[AscDesc], left: &[Value], right: &[Value]) -> Ordering {
+    for ((criterion, left), right) in sort.iter().zip(left).zip(right) {
+        let ordering = match (left, right) {
+            (Value::Null, Value::Null) => Ordering::Equal,
+            (Value::Null, _) => return Ordering::Greater,
+            (_, Value::Null) => return Ordering::Less,
+            (Value::Number(left), Value::Number(right)) => {
+                left.as_f64().partial_cmp(&right.as_f64()).unwrap_or(Ordering::Equal)
+            }
+            (Value::String(left), Value::String(right)) => {
+                left.to_lowercase().cmp(&right.to_lowercase())
+            }
+            _ => Ordering::Equal,
+        };
+        let ordering = match criterion {
+            AscDesc::Asc(_) => ordering,
+            AscDesc::Desc(_) => ordering.reverse(),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_signed_cursor() {
+        let cursor = SearchCursor { offset: 20, id: "42".to_string(), sort: vec![json!(1984)] };
+        let encoded = cursor.encode();
+        assert_eq!(SearchCursor::decode(&encoded).unwrap(), cursor);
+
+        // the offset of the cursor can't be changed
+        let (_, signature) = encoded.split_once('.').unwrap();
+        let forged = SearchCursor { offset: 2000, ..cursor };
+        let bytes = serde_json::to_vec(&forged).unwrap();
+        let forged =
+            format!("{}.{}", base64::encode_config(bytes, base64::URL_SAFE_NO_PAD), signature);
+        assert!(SearchCursor::decode(&forged).is_err());
+
+        assert!(SearchCursor::decode(encoded.split_once('.').unwrap().0).is_err());
+        assert!(SearchCursor::decode("not a cursor").is_err());
+    }
+
+    #[test]
+    fn test_cursor_window() {
+        let cursor = SearchCursor { offset: 20, id: "42".to_string(), sort: Vec::new() };
+        assert_eq!(cursor.window(5, 1000), (10, 25));
+        assert_eq!(cursor.window(5, 22), (10, 22));
+        assert_eq!(cursor.window(5, 20), (20, 0));
+    }
+}
@@ -0,0 +1,304 @@
+//! The search-time `distinct` and `groupBy`, computed over all the candidates of a search.
+
+use std::collections::{HashMap, HashSet};
+
+use meilisearch_types::heed::types::{ByteSlice, Str};
+use meilisearch_types::heed::{self, BytesDecode, RoTxn};
+use meilisearch_types::milli::heed_codec::facet::{FacetGroupKey, FieldDocIdFacetStringCodec};
+use meilisearch_types::milli::{DocumentId, FieldId, Index};
+use roaring::RoaringBitmap;
+use serde_json::{json, Value};
+
+use super::{facet_number_range, facet_number_values, FacetRange};
+use crate::error::MeilisearchHttpError;
+
+/// Identifies a facet value, the strings are normalized.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum FacetKey {
+    String(String),
+    Number(u64),
+}
+
+/// Deduplicates or groups the hits by the values of an attribute, the `distinct` and the
+/// `groupBy` of a search. The documents are identified by their first value of the attribute,
+/// the documents without value are never deduplicated.
+pub struct HitsDistinct {
+    /// `None` when no document has the attribute yet.
+    field_id: Option<FieldId>,
+    /// The maximum number of hits in each group of a `groupBy`, `None` for a `distinct`.
+    hits_per_group: Option<usize>,
+}
+
+/// The candidates of a search split by the values of the attribute.
+pub struct DistinctCandidates {
+    candidates: RoaringBitmap,
+    /// The candidates of each value, the candidates without value are under `None`.
+    docids: HashMap<Option<FacetKey>, RoaringBitmap>,
+}
+
+/// The ranked hits deduplicated or grouped by value.
+pub struct DistinctHits {
+    /// The best ranked hit of each value, or the hits of the groups one group after the other.
+    pub documents_ids: Vec<DocumentId>,
+    /// One candidate per value for a `distinct`, all the candidates for a `groupBy`.
+    pub candidates: RoaringBitmap,
+    pub groups: Option<HitsGroups>,
+}
+
+pub struct HitsGroups {
+    /// The groups of the ranked hits, in the order of their best hit.
+    pub groups: Vec<HitsGroup>,
+    /// The number of groups among all the candidates.
+    pub total: usize,
+}
+
+pub struct HitsGroup {
+    pub value: Value,
+    pub total_hits: u64,
+    /// The best hits of the group.
+    pub documents_ids: Vec<DocumentId>,
+}
+
+impl HitsDistinct {
+    pub fn new(
+        index: &Index,
+        rtxn: &RoTxn,
+        attribute: &str,
+        hits_per_group: Option<usize>,
+    ) -> Result<Self, MeilisearchHttpError> {
+        if !index.faceted_fields(rtxn)?.contains(attribute) {
+            return Err(MeilisearchHttpError::InvalidDistinct(
+                attribute.to_string(),
+                "it must be filterable or sortable".to_string(),
+            ));
+        }
+        Ok(HitsDistinct { field_id: index.fields_ids_map(rtxn)?.id(attribute), hits_per_group })
+    }
+
+    fn key(
+        &self,
+        index: &Index,
+        rtxn: &RoTxn,
+        document_id: DocumentId,
+    ) -> Result<Option<(FacetKey, Value)>, MeilisearchHttpError> {
+        match self.field_id {
+            Some(field_id) => first_facet_value(index, rtxn, field_id, document_id),
+            None => Ok(None),
+        }
+    }
+
+    /// Splits the candidates by value, once for all the windows of ranked hits.
+    pub fn candidates(
+        &self,
+        index: &Index,
+        rtxn: &RoTxn,
+        candidates: &RoaringBitmap,
+    ) -> Result<DistinctCandidates, MeilisearchHttpError> {
+        let mut docids = HashMap::<Option<FacetKey>, RoaringBitmap>::new();
+        let mut remaining = candidates.clone();
+        if let Some(field_id) = self.field_id {
+            // the values are visited in the order of `first_facet_value`, the strings then the
+            // numbers in ascending order, so that each candidate is under its first value.
+            let strings = FacetGroupKey { field_id, level: 0, left_bound: "" }..FacetGroupKey {
+                field_id,
+                level: 1,
+                left_bound: "",
+            };
+            for result in index.facet_id_string_docids.range(rtxn, &strings)? {
+                let (key, value) = result?;
+                let key = FacetKey::String(key.left_bound.to_string());
+                split_off(&mut docids, &mut remaining, key, value.bitmap);
+            }
+            let numbers = facet_number_range(field_id, &FacetRange { from: None, to: None });
+            for result in index.facet_id_f64_docids.range(rtxn, &numbers)? {
+                let (key, value) = result?;
+                let key = FacetKey::Number(key.left_bound.to_bits());
+                split_off(&mut docids, &mut remaining, key, value.bitmap);
+            }
+        }
+        if !remaining.is_empty() {
+            docids.insert(None, remaining);
+        }
+        Ok(DistinctCandidates { candidates: candidates.clone(), docids })
+    }
+
+    /// Deduplicates or groups the ranked hits, or returns `None` when they can't fill the first
+    /// `count` hits or groups while other hits are ranked after them.
+    pub fn apply(
+        &self,
+        index: &Index,
+        rtxn: &RoTxn,
+        candidates: &DistinctCandidates,
+        documents_ids: Vec<DocumentId>,
+        count: usize,
+        exhausted: bool,
+    ) -> Result<Option<DistinctHits>, MeilisearchHttpError> {
+        let hits_per_group = match self.hits_per_group {
+            Some(hits_per_group) => hits_per_group,
+            None => {
+                let documents_ids = self.dedup(index, rtxn, documents_ids)?;
+                if !exhausted && documents_ids.len() < count.min(candidates.hits_len()) {
+                    return Ok(None);
+                }
+                return Ok(Some(DistinctHits {
+                    candidates: candidates.kept(&documents_ids),
+                    documents_ids,
+                    groups: None,
+                }));
+            }
+        };
+
+        let groups = self.groups(index, rtxn, candidates, &documents_ids, hits_per_group)?;
+        let count = count.min(candidates.docids.len());
+        let complete = groups.len() >= count
+            && groups[..count].iter().all(|group| {
+                group.documents_ids.len() as u64 >= group.total_hits.min(hits_per_group as u64)
+            });
+        if !exhausted && !complete {
+            return Ok(None);
+        }
+        Ok(Some(DistinctHits {
+            documents_ids: Vec::new(),
+            candidates: candidates.candidates.clone(),
+            groups: Some(HitsGroups { groups, total: candidates.docids.len() }),
+        }))
+    }
+
+    /// Keeps the best ranked hit of each value.
+    fn dedup(
+        &self,
+        index: &Index,
+        rtxn: &RoTxn,
+        documents_ids: Vec<DocumentId>,
+    ) -> Result<Vec<DocumentId>, MeilisearchHttpError> {
+        let mut seen = HashSet::new();
+        let mut kept_ids = Vec::new();
+        for document_id in documents_ids {
+            match self.key(index, rtxn, document_id)? {
+                Some((key, _)) if !seen.insert(key) => (),
+                _ => kept_ids.push(document_id),
+            }
+        }
+        Ok(kept_ids)
+    }
+
+    /// Groups the ranked hits by value, keeping the `hits_per_group` best hits of each group.
+    fn groups(
+        &self,
+        index: &Index,
+        rtxn: &RoTxn,
+        candidates: &DistinctCandidates,
+        documents_ids: &[DocumentId],
+        hits_per_group: usize,
+    ) -> Result<Vec<HitsGroup>, MeilisearchHttpError> {
+        let mut groups: Vec<HitsGroup> = Vec::new();
+        let mut positions = HashMap::<Option<FacetKey>, usize>::new();
+        for &document_id in documents_ids {
+            let (key, value) = match self.key(index, rtxn, document_id)? {
+                Some((key, value)) => (Some(key), value),
+                None => (None, Value::Null),
+            };
+            let position = *positions.entry(key.clone()).or_insert_with(|| {
+                groups.push(HitsGroup {
+                    value,
+                    total_hits: candidates.docids.get(&key).map_or(0, RoaringBitmap::len),
+                    documents_ids: Vec::new(),
+                });
+                groups.len() - 1
+            });
+            let group = &mut groups[position];
+            if group.documents_ids.len() < hits_per_group {
+                group.documents_ids.push(document_id);
+            }
+        }
+        Ok(groups)
+    }
+}
+
+impl DistinctCandidates {
+    /// The number of distinct hits among the candidates.
+    fn hits_len(&self) -> usize {
+        self.docids
+            .iter()
+            .map(|(key, docids)| if key.is_some() { 1 } else { docids.len() as usize })
+            .sum()
+    }
+
+    /// Keeps one candidate per value, its best ranked hit if any.
+    fn kept(&self, documents_ids: &[DocumentId]) -> RoaringBitmap {
+        let ranked: RoaringBitmap = documents_ids.iter().copied().collect();
+        let mut kept = ranked.clone();
+        for (key, docids) in &self.docids {
+            match key {
+                None => kept |= docids,
+                Some(_) if docids.is_disjoint(&ranked) => kept.extend(docids.min()),
+                Some(_) => (),
+            }
+        }
+        kept
+    }
+}
+
+/// Moves the remaining candidates having the value under its key.
+fn split_off(
+    docids: &mut HashMap<Option<FacetKey>, RoaringBitmap>,
+    remaining: &mut RoaringBitmap,
+    key: FacetKey,
+    value_docids: RoaringBitmap,
+) {
+    let value_docids = value_docids & &*remaining;
+    if !value_docids.is_empty() {
+        *remaining -= &value_docids;
+        docids.insert(Some(key), value_docids);
+    }
+}
+
+/// Returns the first value of the field for the document, the strings before the numbers,
+/// along with a key identifying the value.
+fn first_facet_value(
+    index: &Index,
+    rtxn: &RoTxn,
+    field_id: FieldId,
+    document_id: DocumentId,
+) -> Result<Option<(FacetKey, Value)>, MeilisearchHttpError> {
+    let mut prefix = field_id.to_be_bytes().to_vec();
+    prefix.extend_from_slice(&document_id.to_be_bytes());
+
+    let mut iter = index
+        .field_id_docid_facet_strings
+        .remap_types::<ByteSlice, Str>()
+        .prefix_iter(rtxn, &prefix)?;
+    if let Some(result) = iter.next() {
+        let (key, original) = result?;
+        let (_, _, normalized) =
+            FieldDocIdFacetStringCodec::bytes_decode(key).ok_or(heed::Error::Decoding)?;
+        return Ok(Some((FacetKey::String(normalized.to_string()), json!(original))));
+    }
+
+    let value = facet_number_values(index, rtxn, field_id, document_id)?.into_iter().next();
+    Ok(value.map(|value| (FacetKey::Number(value.to_bits()), json!(value))))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_distinct_candidates() {
+        let key = |value: &str| Some(FacetKey::String(value.to_string()));
+        let candidates = DistinctCandidates {
+            candidates: (0..10).collect(),
+            docids: HashMap::from([
+                (key("shoe"), (0..4).collect()),
+                (key("shirt"), (4..7).collect()),
+                (None, (7..10).collect()),
+            ]),
+        };
+
+        // the documents without value are never deduplicated
+        assert_eq!(candidates.hits_len(), 5);
+        // the ranked hit of a value is kept instead of its first document
+        assert_eq!(candidates.kept(&[2]), RoaringBitmap::from_iter([2, 4, 7, 8, 9]));
+        assert_eq!(candidates.kept(&[]), RoaringBitmap::from_iter([0, 4, 7, 8, 9]));
+    }
+}
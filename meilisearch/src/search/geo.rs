@@ -0,0 +1,432 @@
+//! The `_geoBoundingBox` and `_geoPolygon` filters, and the `geoGrid` of the hits.
+
+use std::collections::BTreeMap;
+
+use meilisearch_types::heed::RoTxn;
+use meilisearch_types::milli::{self, Index};
+use regex::Regex;
+use roaring::RoaringBitmap;
+use rstar::AABB;
+use serde_json::Value;
+
+use crate::error::MeilisearchHttpError;
+
+/// The margin around the envelopes of the shapes, so that the rounding of the coordinates of
+/// the points on their sides doesn't leave them out.
+const ENVELOPE_MARGIN: f64 = 1e-9;
+
+/// A `_geoBoundingBox` or `_geoPolygon` filter expression.
+#[derive(Debug, Clone, PartialEq)]
+enum GeoShape {
+    /// The top right and the bottom left corners, the box crosses the antimeridian when
+    /// its right side is west of its left side.
+    BoundingBox { top_right: [f64; 2], bottom_left: [f64; 2] },
+    /// The vertices of the polygon, it's closed by joining the last one to the first one.
+    Polygon(Vec<[f64; 2]>),
+}
+
+/// The geo shapes of a filter along with the rest of the filter.
+///
+/// The shapes must be whole expressions of the filter: the filter itself, one of the strings
+/// of its array or one of its arrays of strings when they're all shapes.
+pub struct GeoFilter {
+    filter: Option<Value>,
+    /// The documents must be in one of the shapes of every group.
+    shapes: Vec<Vec<GeoShape>>,
+}
+
+impl GeoShape {
+    /// Returns `None` when the expression isn't a geo shape.
+    fn parse(expr: &str) -> Result<Option<Self>, MeilisearchHttpError> {
+        lazy_static::lazy_static! {
+            static ref NAME_REGEX: Regex = Regex::new(r"_geo(BoundingBox|Polygon)\s*\(").unwrap();
+            static ref SHAPE_REGEX: Regex =
+                Regex::new(r"^\s*(_geoBoundingBox|_geoPolygon)\s*\((.*)\)\s*$").unwrap();
+            static ref POINT_REGEX: Regex =
+                Regex::new(r"\[\s*([+\-]?[[:digit:].]+)\s*,\s*([+\-]?[[:digit:].]+)\s*\]").unwrap();
+        };
+        if !NAME_REGEX.is_match(expr) {
+            return Ok(None);
+        }
+        let invalid = |reason: &str| {
+            MeilisearchHttpError::InvalidGeoFilter(expr.to_string(), reason.to_string())
+        };
+
+        let captures = SHAPE_REGEX.captures(expr).ok_or_else(|| {
+            invalid(
+                "it must be a whole expression of the filter, combined with the others in an array",
+            )
+        })?;
+        let arguments = &captures[2];
+        let mut points = Vec::new();
+        for capture in POINT_REGEX.captures_iter(arguments) {
+            let lat: f64 = capture[1].parse().map_err(|_| invalid("a latitude isn't a number"))?;
+            let lng: f64 = capture[2].parse().map_err(|_| invalid("a longitude isn't a number"))?;
+            if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lng) {
+                return Err(invalid("the latitudes must be between -90 and 90 and the longitudes between -180 and 180"));
+            }
+            points.push([lat, lng]);
+        }
+        if POINT_REGEX.replace_all(arguments, "").chars().any(|c| c != ',' && !c.is_whitespace()) {
+            return Err(invalid("the points must be written `[lat, lng]` and separated by commas"));
+        }
+
+        match (&captures[1], points.as_slice()) {
+            ("_geoBoundingBox", &[top_right, bottom_left]) => {
+                if top_right[0] < bottom_left[0] {
+                    return Err(invalid("the top right corner is south of the bottom left corner"));
+                }
+                Ok(Some(GeoShape::BoundingBox { top_right, bottom_left }))
+            }
+            ("_geoBoundingBox", _) => {
+                Err(invalid("it must have a top right and a bottom left corners"))
+            }
+            (_, points) if points.len() < 3 => Err(invalid("it must have at least 3 points")),
+            (_, points) => Ok(Some(GeoShape::Polygon(points.to_vec()))),
+        }
+    }
+
+    fn contains(&self, [lat, lng]: [f64; 2]) -> bool {
+        match self {
+            GeoShape::BoundingBox { top_right: [top, right], bottom_left: [bottom, left] } => {
+                let in_lng = if left <= right {
+                    *left <= lng && lng <= *right
+                } else {
+                    *left <= lng || lng <= *right
+                };
+                *bottom <= lat && lat <= *top && in_lng
+            }
+            // counts the sides of the polygon crossed by a ray going east from the point.
+            GeoShape::Polygon(vertices) => {
+                let mut inside = false;
+                let mut previous = vertices[vertices.len() - 1];
+                for &vertex in vertices {
+                    let ([lat_a, lng_a], [lat_b, lng_b]) = (vertex, previous);
+                    if (lat_a > lat) != (lat_b > lat)
+                        && lng < (lng_b - lng_a) * (lat - lat_a) / (lat_b - lat_a) + lng_a
+                    {
+                        inside = !inside;
+                    }
+                    previous = vertex;
+                }
+                inside
+            }
+        }
+    }
+
+    /// Returns the top right and the bottom left corners of the smallest box around the shape.
+    fn bounding_box(&self) -> ([f64; 2], [f64; 2]) {
+        match self {
+            GeoShape::BoundingBox { top_right, bottom_left } => (*top_right, *bottom_left),
+            GeoShape::Polygon(vertices) => {
+                let (mut top_right, mut bottom_left) = ([f64::MIN; 2], [f64::MAX; 2]);
+                for &[lat, lng] in vertices {
+                    top_right = [top_right[0].max(lat), top_right[1].max(lng)];
+                    bottom_left = [bottom_left[0].min(lat), bottom_left[1].min(lng)];
+                }
+                (top_right, bottom_left)
+            }
+        }
+    }
+
+    /// Returns the filter of milli matching the documents in the bounding box of the shape,
+    /// which are exactly the documents in the shape for a bounding box.
+    fn milli_filter(&self) -> String {
+        let ([top, right], [bottom, left]) = self.bounding_box();
+        let lng = if left <= right {
+            format!("_geo.lng >= {} AND _geo.lng <= {}", left, right)
+        } else {
+            format!("(_geo.lng >= {} OR _geo.lng <= {})", left, right)
+        };
+        format!("_geo.lat >= {} AND _geo.lat <= {} AND {}", bottom, top, lng)
+    }
+
+    /// Returns the envelope of the bounding box of the shape among the points of the r-tree of
+    /// milli, which are on the unit sphere.
+    fn envelope(&self) -> AABB<[f64; 3]> {
+        let ([top, right], [bottom, left]) = self.bounding_box();
+        let in_lng = |lng: f64| {
+            if left <= right {
+                left <= lng && lng <= right
+            } else {
+                left <= lng || lng <= right
+            }
+        };
+
+        // Each coordinate is a function of the latitude times a function of the longitude, they
+        // are the furthest on the sides of the box or where the box crosses the axes.
+        let mut lats = vec![bottom, top];
+        if bottom < 0.0 && 0.0 < top {
+            lats.push(0.0);
+        }
+        let mut lngs = vec![left, right];
+        lngs.extend([-180.0, -90.0, 0.0, 90.0, 180.0].into_iter().filter(|lng| in_lng(*lng)));
+
+        let (mut lower, mut upper) = ([f64::MAX; 3], [f64::MIN; 3]);
+        for &lat in &lats {
+            for &lng in &lngs {
+                let point = milli::lat_lng_to_xyz(&[lat, lng]);
+                for ((low, high), coordinate) in lower.iter_mut().zip(&mut upper).zip(point) {
+                    *low = low.min(coordinate - ENVELOPE_MARGIN);
+                    *high = high.max(coordinate + ENVELOPE_MARGIN);
+                }
+            }
+        }
+        AABB::from_corners(lower, upper)
+    }
+}
+
+impl GeoFilter {
+    pub fn extract(
+        index: &Index,
+        rtxn: &RoTxn,
+        filter: Option<&Value>,
+    ) -> Result<Self, MeilisearchHttpError> {
+        // like `_geoRadius`, the shapes can only be used when `_geo` is filterable.
+        let parse = |expr: &str| -> Result<Option<GeoShape>, MeilisearchHttpError> {
+            let shape = GeoShape::parse(expr)?;
+            if shape.is_some() && !index.filterable_fields(rtxn)?.contains("_geo") {
+                return Err(MeilisearchHttpError::InvalidGeoFilter(
+                    expr.to_string(),
+                    "the `_geo` attribute must be in the `filterableAttributes`".to_string(),
+                ));
+            }
+            Ok(shape)
+        };
+
+        let mut geo_filter = GeoFilter { filter: None, shapes: Vec::new() };
+        match filter {
+            Some(Value::String(expr)) => match parse(expr)? {
+                Some(shape) => geo_filter.shapes.push(vec![shape]),
+                None => geo_filter.filter = filter.cloned(),
+            },
+            Some(Value::Array(elements)) => {
+                let mut rest = Vec::new();
+                for element in elements {
+                    let ors: Vec<_> = match element {
+                        Value::String(_) => vec![element],
+                        Value::Array(ors) => ors.iter().collect(),
+                        _ => Vec::new(),
+                    };
+                    let mut shapes = Vec::new();
+                    for or in &ors {
+                        if let Some(shape) = or.as_str().map_or(Ok(None), parse)? {
+                            shapes.push(shape);
+                        }
+                    }
+                    if shapes.is_empty() {
+                        rest.push(element.clone());
+                    } else if shapes.len() == ors.len() {
+                        geo_filter.shapes.push(shapes);
+                    } else {
+                        return Err(MeilisearchHttpError::InvalidGeoFilter(
+                            element.to_string(),
+                            "the geo shapes can only be ORed with other geo shapes".to_string(),
+                        ));
+                    }
+                }
+                if !rest.is_empty() {
+                    geo_filter.filter = Some(Value::Array(rest));
+                }
+            }
+            _ => geo_filter.filter = filter.cloned(),
+        }
+        Ok(geo_filter)
+    }
+
+    /// Returns the filter given to milli, the rest of the filter along with the bounding boxes
+    /// of the shapes, so that milli only ranks the documents around the shapes.
+    pub fn milli_filter(&self) -> Option<Value> {
+        if self.shapes.is_empty() {
+            return self.filter.clone();
+        }
+        let mut elements = match self.filter {
+            Some(Value::Array(ref elements)) => elements.clone(),
+            Some(ref filter) => vec![filter.clone()],
+            None => Vec::new(),
+        };
+        for shapes in &self.shapes {
+            let ors: Vec<_> =
+                shapes.iter().map(|shape| format!("({})", shape.milli_filter())).collect();
+            elements.push(Value::String(ors.join(" OR ")));
+        }
+        Some(Value::Array(elements))
+    }
+
+    /// Returns the documents in the shapes of the groups having a polygon, or `None` if there
+    /// is no polygon: the filter of milli already matches the bounding boxes exactly. Only the
+    /// points of the r-tree in the envelopes of the shapes are looked at.
+    pub fn docids(
+        &self,
+        index: &Index,
+        rtxn: &RoTxn,
+    ) -> Result<Option<RoaringBitmap>, MeilisearchHttpError> {
+        let groups: Vec<_> = self
+            .shapes
+            .iter()
+            .filter(|shapes| shapes.iter().any(|shape| matches!(shape, GeoShape::Polygon(_))))
+            .collect();
+        if groups.is_empty() {
+            return Ok(None);
+        }
+        let rtree = match index.geo_rtree(rtxn)? {
+            Some(rtree) => rtree,
+            None => return Ok(Some(RoaringBitmap::new())),
+        };
+
+        let mut docids: Option<RoaringBitmap> = None;
+        for shapes in groups {
+            let mut group_docids = RoaringBitmap::new();
+            for shape in shapes {
+                for point in rtree.locate_in_envelope(&shape.envelope()) {
+                    let (docid, lat_lng) = point.data;
+                    if shape.contains(lat_lng) {
+                        group_docids.insert(docid);
+                    }
+                }
+            }
+            docids = Some(match docids {
+                Some(docids) => docids & group_docids,
+                None => group_docids,
+            });
+        }
+        Ok(docids)
+    }
+}
+
+/// The length of the longest geohash, about 4 centimeters wide.
+pub const MAX_GEOHASH_PRECISION: usize = 12;
+
+/// Returns the number of documents in each geohash cell of the given precision.
+pub fn compute_geo_grid(
+    index: &Index,
+    rtxn: &RoTxn,
+    precision: usize,
+    candidates: &RoaringBitmap,
+) -> Result<BTreeMap<String, u64>, MeilisearchHttpError> {
+    let mut cells = BTreeMap::<String, RoaringBitmap>::new();
+    if let Some(rtree) = index.geo_rtree(rtxn)? {
+        for point in rtree.iter() {
+            let (docid, [lat, lng]) = point.data;
+            if candidates.contains(docid) {
+                cells.entry(geohash(lat, lng, precision)).or_default().insert(docid);
+            }
+        }
+    }
+    Ok(cells.into_iter().map(|(cell, docids)| (cell, docids.len())).collect())
+}
+
+/// Encodes the point as a geohash of `precision` characters, alternating the longitude and
+/// the latitude bits.
+fn geohash(lat: f64, lng: f64, precision: usize) -> String {
+    const BASE32: &[u8] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+    let (mut lat_range, mut lng_range) = ((-90.0, 90.0), (-180.0, 180.0));
+    let mut hash = String::with_capacity(precision);
+    let (mut bits, mut bits_count, mut is_lng) = (0, 0, true);
+    while hash.len() < precision {
+        let (range, value): (&mut (f64, f64), f64) =
+            if is_lng { (&mut lng_range, lng) } else { (&mut lat_range, lat) };
+        let middle = (range.0 + range.1) / 2.0;
+        bits <<= 1;
+        if value >= middle {
+            bits |= 1;
+            range.0 = middle;
+        } else {
+            range.1 = middle;
+        }
+        is_lng = !is_lng;
+        bits_count += 1;
+        if bits_count == 5 {
+            hash.push(BASE32[bits] as char);
+            bits = 0;
+            bits_count = 0;
+        }
+    }
+    hash
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_geohash() {
+        assert_eq!(geohash(57.64911, 10.40744, 11), "u4pruydqqvj");
+        assert_eq!(geohash(48.8566, 2.3522, 5), "u09tv");
+    }
+
+    #[test]
+    fn test_geo_shapes() {
+        let shape = |expr: &str| GeoShape::parse(expr).unwrap().unwrap();
+
+        let paris = [48.8566, 2.3522];
+        let lille = [50.6292, 3.0573];
+        let france = shape("_geoBoundingBox([51.1, 8.2], [42.3, -4.8])");
+        assert!(france.contains(paris) && france.contains(lille));
+        assert!(!france.contains([40.4168, -3.7038]));
+
+        // the box crosses the antimeridian
+        let pacific = shape("_geoBoundingBox([10, -170], [-10, 170])");
+        assert!(pacific.contains([0.0, 179.0]) && pacific.contains([0.0, -179.0]));
+        assert!(!pacific.contains([0.0, 0.0]));
+
+        let triangle = shape("_geoPolygon([49, 2], [49, 3], [51, 3])");
+        assert!(!triangle.contains(paris));
+        assert!(triangle.contains([49.5, 2.9]));
+
+        assert!(GeoShape::parse("genre = horror").unwrap().is_none());
+        assert!(GeoShape::parse("_geoPolygon([49, 2], [49, 3])").is_err());
+        assert!(GeoShape::parse("_geoBoundingBox([42.3, -4.8], [51.1, 8.2])").is_err());
+        assert!(GeoShape::parse("genre = horror AND _geoBoundingBox([1, 1], [0, 0])").is_err());
+    }
+
+    #[test]
+    fn test_geo_envelope() {
+        let shape = |expr: &str| GeoShape::parse(expr).unwrap().unwrap();
+        let contains = |shape: &GeoShape, lat_lng: [f64; 2]| {
+            let [x, y, z] = milli::lat_lng_to_xyz(&lat_lng);
+            let envelope = shape.envelope();
+            let (lower, upper) = (envelope.lower(), envelope.upper());
+            (lower[0]..=upper[0]).contains(&x)
+                && (lower[1]..=upper[1]).contains(&y)
+                && (lower[2]..=upper[2]).contains(&z)
+        };
+
+        // the points in the shapes and on their sides are in their envelopes
+        let france = shape("_geoBoundingBox([51.1, 8.2], [42.3, -4.8])");
+        for lat_lng in [[48.8566, 2.3522], [51.1, 8.2], [42.3, -4.8], [51.1, 0.0], [46.0, -4.8]] {
+            assert!(contains(&france, lat_lng));
+        }
+        assert!(!contains(&france, [40.4168, -3.7038]));
+
+        let equator = shape("_geoBoundingBox([10, 100], [-10, 80])");
+        assert!(contains(&equator, [0.0, 90.0]));
+        assert!(!contains(&equator, [0.0, -90.0]));
+
+        let pacific = shape("_geoBoundingBox([10, -170], [-10, 170])");
+        assert!(contains(&pacific, [0.0, 180.0]) && contains(&pacific, [5.0, -175.0]));
+        assert!(!contains(&pacific, [0.0, 0.0]));
+
+        let triangle = shape("_geoPolygon([49, 2], [49, 3], [51, 3])");
+        assert!(contains(&triangle, [50.0, 2.5]));
+        assert!(!contains(&triangle, [48.8566, 2.3522]));
+    }
+
+    #[test]
+    fn test_geo_milli_filter() {
+        let shape = |expr: &str| GeoShape::parse(expr).unwrap().unwrap();
+
+        assert_eq!(
+            shape("_geoBoundingBox([51.1, 8.2], [42.3, -4.8])").milli_filter(),
+            "_geo.lat >= 42.3 AND _geo.lat <= 51.1 AND _geo.lng >= -4.8 AND _geo.lng <= 8.2"
+        );
+        assert_eq!(
+            shape("_geoBoundingBox([10, -170], [-10, 170])").milli_filter(),
+            "_geo.lat >= -10 AND _geo.lat <= 10 AND (_geo.lng >= 170 OR _geo.lng <= -170)"
+        );
+        assert_eq!(
+            shape("_geoPolygon([49, 2], [49, 3], [51, 3])").milli_filter(),
+            "_geo.lat >= 49 AND _geo.lat <= 51 AND _geo.lng >= 2 AND _geo.lng <= 3"
+        );
+    }
+}
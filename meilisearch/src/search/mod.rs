@@ -0,0 +1,2095 @@
+use std::cell::Cell;
+use std::cmp::{min, Ordering};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::ops::Bound;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use either::Either;
+use indexmap::IndexMap;
+use meilisearch_types::heed::types::ByteSlice;
+use meilisearch_types::heed::{self, BytesDecode, RoTxn};
+use meilisearch_types::settings::{self, FacetValuesSort, DEFAULT_PAGINATION_MAX_TOTAL_HITS};
+use meilisearch_types::suggestions::{self, Suggestion};
+use meilisearch_types::{milli, Document};
+use milli::heed_codec::facet::{FacetGroupKey, FacetGroupValue, FieldDocIdFacetF64Codec};
+use milli::tokenizer::TokenizerBuilder;
+use milli::{
+    AscDesc, DocumentId, FieldId, FieldsIdsMap, Filter, FormatOptions, Index, MatchBounds,
+    MatcherBuilder, MatchingWords, SortError, TermsMatchingStrategy, DEFAULT_VALUES_PER_FACET,
+};
+use regex::Regex;
+use roaring::RoaringBitmap;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use time::{Date, Month, OffsetDateTime};
+use uuid::Uuid;
+
+use self::cursor::SearchCursor;
+use self::distinct::{DistinctHits, HitsDistinct};
+use self::geo::{compute_geo_grid, GeoFilter, MAX_GEOHASH_PRECISION};
+use self::operators::{attributes_docids, ParsedQuery};
+use self::rules::FiredRules;
+use self::vectors::VectorSearch;
+use crate::error::MeilisearchHttpError;
+
+mod cursor;
+mod distinct;
+mod geo;
+mod operators;
+mod rules;
+mod vectors;
+
+type MatchesPosition = BTreeMap<String, Vec<MatchBounds>>;
+
+pub const DEFAULT_SEARCH_OFFSET: fn() -> usize = || 0;
+pub const DEFAULT_SEARCH_LIMIT: fn() -> usize = || 20;
+pub const DEFAULT_CROP_LENGTH: fn() -> usize = || 10;
+pub const DEFAULT_CROP_MARKER: fn() -> String = || "…".to_string();
+pub const DEFAULT_HIGHLIGHT_PRE_TAG: fn() -> String = || "<em>".to_string();
+pub const DEFAULT_HIGHLIGHT_POST_TAG: fn() -> String = || "</em>".to_string();
+pub const DEFAULT_SEMANTIC_RATIO: f32 = 0.5;
+pub const DEFAULT_HITS_PER_GROUP: fn() -> usize = || 3;
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SearchQuery {
+    pub q: Option<String>,
+    #[serde(default = "DEFAULT_SEARCH_OFFSET")]
+    pub offset: usize,
+    #[serde(default = "DEFAULT_SEARCH_LIMIT")]
+    pub limit: usize,
+    pub page: Option<usize>,
+    pub hits_per_page: Option<usize>,
+    pub attributes_to_retrieve: Option<BTreeSet<String>>,
+    /// Only returns the hits containing a word of the query in these searchable attributes.
+    pub attributes_to_search_on: Option<Vec<String>>,
+    pub attributes_to_crop: Option<Vec<String>>,
+    #[serde(default = "DEFAULT_CROP_LENGTH")]
+    pub crop_length: usize,
+    /// Returns up to this number of snippets of every cropped attribute in `_snippets`.
+    pub snippets_per_attribute: Option<usize>,
+    pub attributes_to_highlight: Option<HashSet<String>>,
+    // Default to false
+    #[serde(default = "Default::default")]
+    pub show_matches_position: bool,
+    pub filter: Option<Value>,
+    pub sort: Option<Vec<String>>,
+    pub facets: Option<Vec<FacetQuery>>,
+    pub facet_buckets: Option<BTreeMap<String, FacetBuckets>>,
+    /// Only returns the best hit of each value of this attribute, like the `distinctAttribute`.
+    pub distinct: Option<String>,
+    /// Returns the hits grouped by the values of an attribute in `groups`, a page of groups.
+    pub group_by: Option<GroupBy>,
+    /// Returns the number of hits in each geohash cell of this precision in `geoGrid`.
+    pub geo_grid_precision: Option<usize>,
+    pub search_after: Option<String>,
+    /// Returns the nearest neighbours of this vector, blended with the hits of `q` if any.
+    pub vector: Option<Vec<f32>>,
+    /// The weight of the vector compared to `q`, from `0.0` (`q` only) to `1.0` (vector only).
+    pub semantic_ratio: Option<f32>,
+    /// Overrides the `searchCutoffMs` setting of the index.
+    pub search_cutoff_ms: Option<u64>,
+    #[serde(default = "DEFAULT_HIGHLIGHT_PRE_TAG")]
+    pub highlight_pre_tag: String,
+    #[serde(default = "DEFAULT_HIGHLIGHT_POST_TAG")]
+    pub highlight_post_tag: String,
+    #[serde(default = "DEFAULT_CROP_MARKER")]
+    pub crop_marker: String,
+    #[serde(default)]
+    pub matching_strategy: MatchingStrategy,
+}
+
+impl SearchQuery {
+    pub fn is_finite_pagination(&self) -> bool {
+        self.page.or(self.hits_per_page).is_some()
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum MatchingStrategy {
+    /// Remove query words from last to first
+    Last,
+    /// All query words are mandatory
+    All,
+}
+
+impl Default for MatchingStrategy {
+    fn default() -> Self {
+        Self::Last
+    }
+}
+
+/// A facet to compute the distribution of, either by name or with options on its values.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum FacetQuery {
+    Name(String),
+    Options(FacetOptions),
+}
+
+impl FacetQuery {
+    pub fn name(&self) -> &str {
+        match self {
+            FacetQuery::Name(name) => name,
+            FacetQuery::Options(options) => &options.name,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct FacetOptions {
+    pub name: String,
+    /// Overrides the `faceting.sortFacetValuesBy` setting of the index.
+    pub sort_by: Option<FacetValuesSort>,
+    /// Overrides the `faceting.maxValuesPerFacet` setting of the index.
+    pub max_values: Option<usize>,
+    /// Values always returned, even when they're cut by `maxValues`.
+    #[serde(default)]
+    pub include: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct GroupBy {
+    pub attribute: String,
+    /// The maximum number of hits returned in each group.
+    #[serde(default = "DEFAULT_HITS_PER_GROUP")]
+    pub hits_per_group: usize,
+}
+
+/// How the numeric values of a facet are split in buckets.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum FacetBuckets {
+    /// Buckets of the same width, starting at a multiple of the step.
+    Step(f64),
+    /// The values are unix timestamps and are split by calendar interval.
+    Interval(CalendarInterval),
+    /// Explicit ranges, a document can be counted in several overlapping ranges.
+    Ranges(Vec<FacetRange>),
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum CalendarInterval {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+/// A range of values including `from` and excluding `to`, unbounded when missing.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct FacetRange {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<f64>,
+}
+
+impl From<MatchingStrategy> for TermsMatchingStrategy {
+    fn from(other: MatchingStrategy) -> Self {
+        match other {
+            MatchingStrategy::Last => Self::Last,
+            MatchingStrategy::All => Self::All,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct SearchHit {
+    #[serde(flatten)]
+    pub document: Document,
+    #[serde(rename = "_formatted", skip_serializing_if = "Document::is_empty")]
+    pub formatted: Document,
+    #[serde(rename = "_matchesPosition", skip_serializing_if = "Option::is_none")]
+    pub matches_position: Option<MatchesPosition>,
+    #[serde(rename = "_snippets", skip_serializing_if = "Document::is_empty")]
+    pub snippets: Document,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResult {
+    pub hits: Vec<SearchHit>,
+    pub query: String,
+    pub processing_time_ms: u128,
+    #[serde(flatten)]
+    pub hits_info: HitsInfo,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub facet_distribution: Option<BTreeMap<String, IndexMap<String, u64>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub facet_stats: Option<BTreeMap<String, FacetStats>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub facet_buckets: Option<BTreeMap<String, Vec<FacetBucket>>>,
+    /// The hits grouped by `groupBy`, the `hits` are then empty.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub groups: Option<Vec<SearchGroup>>,
+    /// The number of hits in each geohash cell.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub geo_grid: Option<BTreeMap<String, u64>>,
+    /// The cursor to send back as `searchAfter` to get the following hits.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_search_after: Option<String>,
+    /// The uids of the query rules applied to the search.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub applied_rules: Vec<String>,
+    /// The uid of the search in the search analytics, to which its events must refer.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query_uid: Option<Uuid>,
+    /// Whether the search reached its cutoff and returned the best hits found until then.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub degraded: bool,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchGroup {
+    /// The value of the attribute shared by the hits, `null` for the hits without value.
+    pub value: Value,
+    /// The number of documents of the group matching the search.
+    pub total_hits: u64,
+    pub hits: Vec<SearchHit>,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct FacetStats {
+    pub min: f64,
+    pub max: f64,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct FacetBucket {
+    #[serde(flatten)]
+    pub range: FacetRange,
+    pub count: u64,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum HitsInfo {
+    #[serde(rename_all = "camelCase")]
+    Pagination { hits_per_page: usize, page: usize, total_pages: usize, total_hits: usize },
+    #[serde(rename_all = "camelCase")]
+    OffsetLimit { limit: usize, offset: usize, estimated_total_hits: usize },
+}
+
+/// The instant past which a search stops fetching hits and returns the best ones it has found,
+/// it's checked between the successive searches made to milli.
+struct Deadline {
+    instant: Option<Instant>,
+    reached: Cell<bool>,
+}
+
+impl Deadline {
+    fn new(start: Instant, cutoff_ms: Option<u64>) -> Self {
+        let instant = cutoff_ms.map(|cutoff_ms| start + Duration::from_millis(cutoff_ms));
+        Deadline { instant, reached: Cell::new(false) }
+    }
+
+    /// Returns whether the deadline has passed, and remembers it to flag the search as degraded.
+    fn is_past(&self) -> bool {
+        let is_past = self.instant.map_or(false, |instant| Instant::now() >= instant);
+        if is_past {
+            self.reached.set(true);
+        }
+        is_past
+    }
+
+    /// Whether the deadline had passed when more hits could still have been fetched.
+    fn reached(&self) -> bool {
+        self.reached.get()
+    }
+}
+
+/// Performs the search. Past the `searchCutoffMs` of the query or of the index, it stops
+/// fetching hits and returns the best ones found so far, flagged as `degraded`.
+pub fn perform_search(
+    index: &Index,
+    query: SearchQuery,
+) -> Result<SearchResult, MeilisearchHttpError> {
+    let before_search = Instant::now();
+    let rtxn = index.read_txn()?;
+
+    let search_cutoff_ms = match query.search_cutoff_ms {
+        Some(search_cutoff_ms) => Some(search_cutoff_ms),
+        None => settings::search_cutoff_ms(index, &rtxn)?,
+    };
+    let deadline = Deadline::new(before_search, search_cutoff_ms);
+
+    let vector = VectorSearch::new(index, &rtxn, &query)?;
+    let semantic_only = vector.as_ref().map_or(false, VectorSearch::semantic_only);
+
+    // The operators are removed from the query given to milli.
+    let parsed_query = match query.q {
+        Some(ref q) if !semantic_only => {
+            Some(ParsedQuery::parse(q, &index.fields_ids_map(&rtxn)?)?)
+        }
+        _ => None,
+    };
+    let milli_query = parsed_query.as_ref().map_or("", ParsedQuery::milli_query);
+
+    let is_finite_pagination = query.is_finite_pagination();
+
+    let max_total_hits = index
+        .pagination_max_total_hits(&rtxn)
+        .map_err(milli::Error::from)?
+        .unwrap_or(DEFAULT_PAGINATION_MAX_TOTAL_HITS);
+
+    let cursor = query.search_after.as_deref().map(SearchCursor::decode).transpose()?;
+    if cursor.is_some() && (is_finite_pagination || query.offset != 0) {
+        return Err(MeilisearchHttpError::InvalidSearchAfter(
+            "it can't be used along with `offset`, `page` or `hitsPerPage`".to_string(),
+        ));
+    }
+    if cursor.is_some() && query.vector.is_some() {
+        return Err(MeilisearchHttpError::InvalidSearchAfter(
+            "it can't be used along with `vector`".to_string(),
+        ));
+    }
+
+    let fired_rules = FiredRules::new(index, &rtxn, query.q.as_deref(), query.filter.as_ref())?;
+    let filter = fired_rules.filter(query.filter.clone());
+
+    // The `distinctAttribute` of the index is applied by milli and can't be replaced.
+    let distinct = match (&query.distinct, &query.group_by) {
+        (Some(_), Some(group_by)) => {
+            return Err(MeilisearchHttpError::InvalidDistinct(
+                group_by.attribute.clone(),
+                "`groupBy` can't be used along with `distinct`".to_string(),
+            ))
+        }
+        (Some(attribute), None) => match index.distinct_field(&rtxn)? {
+            Some(field) if field == attribute.as_str() => None,
+            Some(field) => {
+                return Err(MeilisearchHttpError::InvalidDistinct(
+                    attribute.clone(),
+                    format!("the hits of the index are already deduplicated by `{}`", field),
+                ))
+            }
+            None => Some(HitsDistinct::new(index, &rtxn, attribute, None)?),
+        },
+        (None, Some(group_by)) => Some(HitsDistinct::new(
+            index,
+            &rtxn,
+            &group_by.attribute,
+            Some(group_by.hits_per_group),
+        )?),
+        (None, None) => None,
+    };
+    if cursor.is_some() && distinct.is_some() {
+        return Err(MeilisearchHttpError::InvalidSearchAfter(
+            "it can't be used along with `distinct` and `groupBy`".to_string(),
+        ));
+    }
+
+    // The bounding boxes of the geo shapes are given to milli, the hits are restricted to the
+    // documents in the polygons before they're counted and paginated.
+    let geo_filter = GeoFilter::extract(index, &rtxn, filter.as_ref())?;
+    let geo_docids = geo_filter.docids(index, &rtxn)?;
+    if cursor.is_some() && geo_docids.is_some() {
+        return Err(MeilisearchHttpError::InvalidSearchAfter(
+            "it can't be used along with `_geoPolygon` filters".to_string(),
+        ));
+    }
+
+    let query_operators = match parsed_query {
+        Some(ref parsed_query) => parsed_query.operators(index, &rtxn)?,
+        None => None,
+    };
+    if cursor.is_some() && query_operators.is_some() {
+        return Err(MeilisearchHttpError::InvalidSearchAfter(
+            "it can't be used along with the `OR`, `-` and `attribute:` operators".to_string(),
+        ));
+    }
+
+    // The hits are restricted to the documents containing a word of the query in the attributes
+    // to search on, along with the documents in the polygons.
+    let attributes_docids = match query.attributes_to_search_on {
+        Some(ref attributes) => attributes_docids(index, &rtxn, milli_query, attributes)?,
+        None => None,
+    };
+    if cursor.is_some() && attributes_docids.is_some() {
+        return Err(MeilisearchHttpError::InvalidSearchAfter(
+            "it can't be used along with `attributesToSearchOn`".to_string(),
+        ));
+    }
+    let universe = match (geo_docids, attributes_docids) {
+        (Some(geo_docids), Some(attributes_docids)) => Some(geo_docids & attributes_docids),
+        (geo_docids, attributes_docids) => geo_docids.or(attributes_docids),
+    };
+
+    let sort = match query.sort {
+        Some(ref sort) => match sort.iter().map(|s| AscDesc::from_str(s)).collect() {
+            Ok(sorts) => sorts,
+            Err(asc_desc_error) => {
+                return Err(milli::Error::from(SortError::from(asc_desc_error)).into())
+            }
+        },
+        None => Vec::new(),
+    };
+
+    if cursor.as_ref().map_or(false, |cursor| cursor.sort_len() != sort.len()) {
+        return Err(MeilisearchHttpError::InvalidSearchAfter(
+            "it was returned by a search with a different `sort`".to_string(),
+        ));
+    }
+
+    // compute the offset on the limit depending on the pagination mode.
+    let (offset, limit) = if is_finite_pagination {
+        let limit = query.hits_per_page.unwrap_or_else(DEFAULT_SEARCH_LIMIT);
+        let page = query.page.unwrap_or(1);
+
+        // page 0 gives a limit of 0 forcing Meilisearch to return no document.
+        page.checked_sub(1).map_or((0, 0), |p| (limit * p, limit))
+    } else {
+        (query.offset, query.limit)
+    };
+
+    // Make sure that a user can't get more documents than the hard limit,
+    // we align that on the offset too, and on the offset of the cursor.
+    let (offset, limit) = match cursor {
+        Some(ref cursor) => cursor.window(limit, max_total_hits),
+        None => {
+            let offset = min(offset, max_total_hits);
+            (offset, min(limit, max_total_hits.saturating_sub(offset)))
+        }
+    };
+
+    // The hits are filtered and reordered among the first hits of milli. The cursors follow
+    // the ranking of milli, only the hidden documents of the rules are removed from their hits.
+    let reranked = cursor.is_none() && (distinct.is_some() || fired_rules.reranks());
+    // The hits are restricted to some documents while they're fetched from milli.
+    let restricted = query_operators.is_some() || universe.is_some();
+    // More hits are fetched to replace the hidden documents.
+    let hidden = fired_rules.hidden_len();
+    let (search_offset, search_limit) = if reranked {
+        (0, max_total_hits.max(offset + limit) + hidden)
+    } else if vector.is_some() {
+        // The hits of the query are blended with the nearest neighbours from the first one.
+        (0, offset + limit)
+    } else if restricted {
+        (0, offset + limit + hidden)
+    } else {
+        (offset, limit + hidden)
+    };
+
+    let milli_filter = match geo_filter.milli_filter() {
+        Some(ref filter) => parse_filter(filter)?,
+        None => None,
+    };
+    let new_search = |q: &str| {
+        let mut search = index.search(&rtxn);
+        if !q.is_empty() {
+            search.query(q);
+        }
+        search.terms_matching_strategy(query.matching_strategy.clone().into());
+        search.exhaustive_number_hits(is_finite_pagination);
+        if let Some(ref filter) = milli_filter {
+            search.filter(filter.clone());
+        }
+        if query.sort.is_some() {
+            search.sort_criteria(sort.clone());
+        }
+        search
+    };
+
+    // The hits are deduplicated or grouped over a window of ranked hits twice as large each time
+    // it can't fill the page, the values of the candidates are only looked up once.
+    let (mut search_limit, mut vector_limit) = (search_limit, offset + limit + hidden);
+    let mut distinct_candidates = None;
+    let (documents_ids, candidates, matching_words, groups) = loop {
+        let (documents_ids, candidates, matching_words) = match (&query_operators, &universe) {
+            (Some(query_operators), universe) => {
+                query_operators.execute(&new_search, universe.as_ref(), search_limit, &deadline)?
+            }
+            (None, Some(universe)) => {
+                ranked_hits(&mut new_search(milli_query), universe, search_limit, &deadline)?
+            }
+            (None, None) => {
+                let mut search = new_search(milli_query);
+                search.offset(search_offset);
+                search.limit(search_limit);
+                let milli::SearchResult { documents_ids, matching_words, candidates, .. } =
+                    search.execute()?;
+                (documents_ids, candidates, matching_words)
+            }
+        };
+        let exhausted = documents_ids.len() < search_limit;
+
+        let (documents_ids, candidates) = match vector {
+            Some(ref vector) => vector.apply(
+                index,
+                &rtxn,
+                filter.as_ref(),
+                documents_ids,
+                candidates,
+                vector_limit,
+            )?,
+            None => (documents_ids, candidates),
+        };
+        let exhausted = exhausted && (vector.is_none() || documents_ids.len() < vector_limit);
+
+        let (documents_ids, candidates) = fired_rules.apply(
+            index,
+            &rtxn,
+            filter.as_ref(),
+            documents_ids,
+            candidates,
+            reranked,
+        )?;
+
+        let distinct = match distinct {
+            Some(ref distinct) => distinct,
+            None => break (documents_ids, candidates, matching_words, None),
+        };
+        let distinct_candidates = match distinct_candidates {
+            Some(ref distinct_candidates) => distinct_candidates,
+            None => distinct_candidates.insert(distinct.candidates(index, &rtxn, &candidates)?),
+        };
+        let count = offset + limit;
+        // Past the deadline, the window isn't widened anymore.
+        let exhausted = exhausted || deadline.is_past();
+        if let Some(DistinctHits { documents_ids, candidates, groups }) =
+            distinct.apply(index, &rtxn, distinct_candidates, documents_ids, count, exhausted)?
+        {
+            break (documents_ids, candidates, matching_words, groups);
+        }
+        search_limit = search_limit.saturating_mul(2);
+        vector_limit = vector_limit.saturating_mul(2);
+    };
+
+    // The pages of a `groupBy` are made of groups instead of hits.
+    let (groups, number_of_groups) = match groups {
+        Some(groups) => {
+            let number_of_groups = groups.total;
+            let groups: Vec<_> = groups.groups.into_iter().skip(offset).take(limit).collect();
+            (Some(groups), Some(number_of_groups))
+        }
+        None => (None, None),
+    };
+
+    let documents_ids = match groups {
+        Some(ref groups) => {
+            groups.iter().flat_map(|group| group.documents_ids.iter().copied()).collect()
+        }
+        None if reranked || vector.is_some() || restricted => {
+            documents_ids.into_iter().skip(offset).take(limit).collect()
+        }
+        None => documents_ids,
+    };
+
+    let fields_ids_map = index.fields_ids_map(&rtxn).unwrap();
+
+    let (offset, documents_ids) = match cursor {
+        Some(ref cursor) => cursor.documents_after(
+            index,
+            &rtxn,
+            &fields_ids_map,
+            &sort,
+            offset,
+            documents_ids,
+            query.limit,
+            max_total_hits,
+        )?,
+        None => (offset, documents_ids),
+    };
+
+    let next_offset = offset + documents_ids.len();
+    let next_search_after = match documents_ids.last() {
+        Some(&last_id)
+            if !is_finite_pagination
+                && groups.is_none()
+                && (next_offset as u64) < candidates.len() =>
+        {
+            SearchCursor::after(
+                index,
+                &rtxn,
+                &fields_ids_map,
+                &sort,
+                next_offset,
+                last_id,
+                max_total_hits,
+            )?
+            .map(|cursor| cursor.encode())
+        }
+        _ => None,
+    };
+
+    let displayed_ids = index
+        .displayed_fields_ids(&rtxn)?
+        .map(|fields| fields.into_iter().collect::<BTreeSet<_>>())
+        .unwrap_or_else(|| fields_ids_map.iter().map(|(id, _)| id).collect());
+
+    let fids = |attrs: &BTreeSet<String>| {
+        let mut ids = BTreeSet::new();
+        for attr in attrs {
+            if attr == "*" {
+                ids = displayed_ids.clone();
+                break;
+            }
+
+            if let Some(id) = fields_ids_map.id(attr) {
+                ids.insert(id);
+            }
+        }
+        ids
+    };
+
+    // The attributes to retrieve are the ones explicitly marked as to retrieve (all by default),
+    // but these attributes must be also be present
+    // - in the fields_ids_map
+    // - in the the displayed attributes
+    let to_retrieve_ids: BTreeSet<_> = query
+        .attributes_to_retrieve
+        .as_ref()
+        .map(fids)
+        .unwrap_or_else(|| displayed_ids.clone())
+        .intersection(&displayed_ids)
+        .cloned()
+        .collect();
+
+    let attr_to_highlight = query.attributes_to_highlight.unwrap_or_default();
+
+    let attr_to_crop = query.attributes_to_crop.unwrap_or_default();
+
+    // Attributes in `formatted_options` correspond to the attributes that will be in `_formatted`
+    // These attributes are:
+    // - the attributes asked to be highlighted or cropped (with `attributesToCrop` or `attributesToHighlight`)
+    // - the attributes asked to be retrieved: these attributes will not be highlighted/cropped
+    // But these attributes must be also present in displayed attributes
+    let formatted_options = compute_formatted_options(
+        &attr_to_highlight,
+        &attr_to_crop,
+        query.crop_length,
+        &to_retrieve_ids,
+        &fields_ids_map,
+        &displayed_ids,
+    );
+
+    let snippet_options = query.snippets_per_attribute.map(|count| SnippetOptions {
+        count,
+        crop_marker: query.crop_marker.clone(),
+        highlight_pre_tag: query.highlight_pre_tag.clone(),
+        highlight_post_tag: query.highlight_post_tag.clone(),
+    });
+
+    let tokenizer = TokenizerBuilder::default().build();
+
+    let mut formatter_builder = MatcherBuilder::new(matching_words, tokenizer);
+    formatter_builder.crop_marker(query.crop_marker);
+    formatter_builder.highlight_prefix(query.highlight_pre_tag);
+    formatter_builder.highlight_suffix(query.highlight_post_tag);
+
+    let mut documents = Vec::new();
+
+    let documents_iter = index.documents(&rtxn, documents_ids)?;
+
+    for (_id, obkv) in documents_iter {
+        // First generate a document with all the displayed fields
+        let displayed_document = make_document(&displayed_ids, &fields_ids_map, obkv)?;
+
+        // select the attributes to retrieve
+        let attributes_to_retrieve = to_retrieve_ids
+            .iter()
+            .map(|&fid| fields_ids_map.name(fid).expect("Missing field name"));
+        let mut document =
+            permissive_json_pointer::select_values(&displayed_document, attributes_to_retrieve);
+
+        let (matches_position, formatted) = format_fields(
+            &displayed_document,
+            &fields_ids_map,
+            &formatter_builder,
+            &formatted_options,
+            query.show_matches_position,
+            &displayed_ids,
+        )?;
+
+        let snippets = match snippet_options {
+            Some(ref options) => compute_snippets(
+                &displayed_document,
+                &fields_ids_map,
+                &formatter_builder,
+                &formatted_options,
+                options,
+            ),
+            None => Document::new(),
+        };
+
+        if let Some(sort) = query.sort.as_ref() {
+            insert_geo_distance(sort, &mut document);
+        }
+
+        let hit = SearchHit { document, formatted, matches_position, snippets };
+        documents.push(hit);
+    }
+
+    let (documents, groups) = match groups {
+        Some(groups) => {
+            let mut documents = documents.into_iter();
+            let groups = groups
+                .into_iter()
+                .map(|group| SearchGroup {
+                    value: group.value,
+                    total_hits: group.total_hits,
+                    hits: documents.by_ref().take(group.documents_ids.len()).collect(),
+                })
+                .collect();
+            (Vec::new(), Some(groups))
+        }
+        None => (documents, None),
+    };
+
+    let number_of_hits = min(number_of_groups.unwrap_or(candidates.len() as usize), max_total_hits);
+    let hits_info = if is_finite_pagination {
+        let hits_per_page = query.hits_per_page.unwrap_or_else(DEFAULT_SEARCH_LIMIT);
+        // If hit_per_page is 0, then pages can't be computed and so we respond 0.
+        let total_pages = (number_of_hits + hits_per_page.saturating_sub(1))
+            .checked_div(hits_per_page)
+            .unwrap_or(0);
+
+        HitsInfo::Pagination {
+            hits_per_page,
+            page: query.page.unwrap_or(1),
+            total_pages,
+            total_hits: number_of_hits,
+        }
+    } else {
+        HitsInfo::OffsetLimit { limit: query.limit, offset, estimated_total_hits: number_of_hits }
+    };
+
+    let facet_distribution = match query.facets {
+        Some(ref facets) => Some(compute_facet_distribution(index, &rtxn, facets, &candidates)?),
+        None => None,
+    };
+
+    let facet_stats = match query.facets {
+        Some(ref facets) => {
+            let fields: Vec<_> = facets.iter().map(|facet| facet.name().to_string()).collect();
+            Some(compute_facet_stats(index, &rtxn, &fields, &candidates)?)
+        }
+        None => None,
+    };
+
+    let facet_buckets = match query.facet_buckets {
+        Some(ref facet_buckets) => {
+            let max_values_by_facet = index
+                .max_values_per_facet(&rtxn)
+                .map_err(milli::Error::from)?
+                .unwrap_or(DEFAULT_VALUES_PER_FACET);
+            Some(compute_facet_buckets(
+                index,
+                &rtxn,
+                facet_buckets,
+                &candidates,
+                max_values_by_facet,
+            )?)
+        }
+        None => None,
+    };
+
+    let geo_grid = match query.geo_grid_precision {
+        Some(precision) if !(1..=MAX_GEOHASH_PRECISION).contains(&precision) => {
+            return Err(MeilisearchHttpError::InvalidGeoGridPrecision(precision))
+        }
+        Some(precision) => Some(compute_geo_grid(index, &rtxn, precision, &candidates)?),
+        None => None,
+    };
+
+    let result = SearchResult {
+        hits: documents,
+        hits_info,
+        query: query.q.clone().unwrap_or_default(),
+        processing_time_ms: before_search.elapsed().as_millis(),
+        facet_distribution,
+        facet_stats,
+        facet_buckets,
+        groups,
+        geo_grid,
+        next_search_after,
+        applied_rules: fired_rules.into_uids(),
+        query_uid: None,
+        degraded: deadline.reached(),
+    };
+    Ok(result)
+}
+
+/// Returns the first `count` hits of the search among the allowed documents, along with the
+/// candidates of the search among them. The hits are fetched from milli by chunks of growing
+/// size until enough of them are allowed, or until the deadline has passed.
+fn ranked_hits(
+    search: &mut milli::Search,
+    allowed: &RoaringBitmap,
+    count: usize,
+    deadline: &Deadline,
+) -> Result<(Vec<DocumentId>, RoaringBitmap, MatchingWords), MeilisearchHttpError> {
+    // The allowed candidates tell when the walk can stop.
+    search.exhaustive_number_hits(true);
+
+    let mut documents_ids = Vec::new();
+    let mut offset = 0;
+    let mut chunk = count.max(DEFAULT_SEARCH_LIMIT());
+    loop {
+        search.offset(offset);
+        search.limit(chunk);
+        let milli::SearchResult { documents_ids: hits, matching_words, candidates, .. } =
+            search.execute()?;
+        let candidates = candidates & allowed;
+        let is_last_chunk = hits.len() < chunk;
+        documents_ids.extend(hits.into_iter().filter(|docid| allowed.contains(*docid)));
+
+        if count == 0
+            || documents_ids.len() >= count
+            || documents_ids.len() as u64 >= candidates.len()
+            || is_last_chunk
+            || deadline.is_past()
+        {
+            documents_ids.truncate(count);
+            return Ok((documents_ids, candidates, matching_words));
+        }
+        offset += chunk;
+        chunk = chunk.saturating_mul(2);
+    }
+}
+
+/// Returns the documents matching the filter, or all the documents if there is no filter.
+fn filtered_documents(
+    index: &Index,
+    rtxn: &RoTxn,
+    filter: Option<&Value>,
+) -> Result<RoaringBitmap, MeilisearchHttpError> {
+    let geo_filter = GeoFilter::extract(index, rtxn, filter)?;
+    let milli_filter = match geo_filter.milli_filter() {
+        Some(ref filter) => parse_filter(filter)?,
+        None => None,
+    };
+    let documents_ids = match milli_filter {
+        Some(filter) => {
+            let mut search = index.search(rtxn);
+            search.filter(filter);
+            search.exhaustive_number_hits(true);
+            search.limit(0);
+            search.execute()?.candidates
+        }
+        None => index.documents_ids(rtxn)?,
+    };
+    match geo_filter.docids(index, rtxn)? {
+        Some(geo_docids) => Ok(documents_ids & geo_docids),
+        None => Ok(documents_ids),
+    }
+}
+
+/// Returns all the fields of a document, displayed or not.
+fn full_document(
+    index: &Index,
+    rtxn: &RoTxn,
+    fields_ids_map: &FieldsIdsMap,
+    docid: DocumentId,
+) -> Result<Document, MeilisearchHttpError> {
+    let all_fields = fields_ids_map.iter().map(|(id, _)| id).collect();
+    match index.documents(rtxn, Some(docid))?.pop() {
+        Some((_, obkv)) => make_document(&all_fields, fields_ids_map, obkv),
+        None => Ok(Document::new()),
+    }
+}
+
+/// Returns the value of a possibly nested field of a document.
+fn field_value(document: &Document, field: &str) -> Value {
+    if let Some(value) = document.get(field) {
+        return value.clone();
+    }
+    let mut value = Value::Object(permissive_json_pointer::select_values(document, [field]));
+    for key in field.split('.') {
+        value = match value {
+            Value::Object(mut object) => object.remove(key).unwrap_or(Value::Null),
+            _ => Value::Null,
+        };
+    }
+    value
+}
+
+/// Computes the distribution of the requested facets among the candidates.
+/// The values of each facet are sorted and truncated according to the options of the
+/// query, falling back on the options of the `*` facet and then on the index settings.
+fn compute_facet_distribution(
+    index: &Index,
+    rtxn: &RoTxn,
+    facets: &[FacetQuery],
+    candidates: &RoaringBitmap,
+) -> Result<BTreeMap<String, IndexMap<String, u64>>, MeilisearchHttpError> {
+    let max_values_by_facet = index
+        .max_values_per_facet(rtxn)
+        .map_err(milli::Error::from)?
+        .unwrap_or(DEFAULT_VALUES_PER_FACET);
+    let sort_facet_values_by = settings::sort_facet_values_by(index, rtxn)?;
+
+    let options_of = |name: &str| {
+        facets
+            .iter()
+            .find(|facet| facet.name() == name)
+            .or_else(|| facets.iter().find(|facet| facet.name() == "*"))
+            .and_then(|facet| match facet {
+                FacetQuery::Name(_) => None,
+                FacetQuery::Options(options) => Some(options),
+            })
+    };
+
+    let mut facet_distribution = index.facets_distribution(rtxn);
+    facet_distribution.max_values_per_facet(max_values_by_facet);
+    if facets.iter().all(|facet| facet.name() != "*") {
+        facet_distribution.facets(facets.iter().map(|facet| facet.name()));
+    }
+    let distribution = facet_distribution.candidates(candidates.clone()).execute()?;
+
+    let mut result = BTreeMap::new();
+    for (name, values) in distribution {
+        let options = options_of(&name);
+        let sort_by = options
+            .and_then(|options| options.sort_by)
+            .or_else(|| sort_facet_values_by.get(&name).copied())
+            .or_else(|| sort_facet_values_by.get("*").copied())
+            .unwrap_or_default();
+        let max_values =
+            options.and_then(|options| options.max_values).unwrap_or(max_values_by_facet);
+        let include = options.map_or(&[][..], |options| &options.include[..]);
+
+        // The default distribution already holds the first values in alphanumerical order.
+        if sort_by == FacetValuesSort::Alpha
+            && max_values <= max_values_by_facet
+            && include.is_empty()
+        {
+            result.insert(name, values.into_iter().take(max_values).collect());
+            continue;
+        }
+
+        let mut facet_distribution = index.facets_distribution(rtxn);
+        let mut all_values = facet_distribution
+            .facets([&name])
+            .max_values_per_facet(usize::MAX)
+            .candidates(candidates.clone())
+            .execute()?
+            .remove(&name)
+            .unwrap_or_default();
+
+        let mut values: Vec<_> =
+            all_values.iter().map(|(value, count)| (value.clone(), *count)).collect();
+        if sort_by == FacetValuesSort::Count {
+            // The sort is stable, the values with the same count stay in alphanumerical order.
+            values.sort_by(|(_, left), (_, right)| right.cmp(left));
+        }
+        values.truncate(max_values);
+
+        let mut values: IndexMap<_, _> = values.into_iter().collect();
+        for value in include {
+            if !values.contains_key(value) {
+                let count = all_values.remove(value).unwrap_or(0);
+                values.insert(value.clone(), count);
+            }
+        }
+        result.insert(name, values);
+    }
+
+    Ok(result)
+}
+
+/// Returns the numeric values of the given field for the given document.
+fn facet_number_values(
+    index: &Index,
+    rtxn: &RoTxn,
+    field_id: FieldId,
+    document_id: DocumentId,
+) -> Result<Vec<f64>, MeilisearchHttpError> {
+    let mut prefix = field_id.to_be_bytes().to_vec();
+    prefix.extend_from_slice(&document_id.to_be_bytes());
+
+    let mut values = Vec::new();
+    let iter =
+        index.field_id_docid_facet_f64s.remap_key_type::<ByteSlice>().prefix_iter(rtxn, &prefix)?;
+    for result in iter {
+        let (key, ()) = result?;
+        let (_, _, value) =
+            FieldDocIdFacetF64Codec::bytes_decode(key).ok_or(heed::Error::Decoding)?;
+        values.push(value);
+    }
+    Ok(values)
+}
+
+/// Returns the ids of the filterable fields matching the requested facets.
+fn facet_fields_ids(
+    index: &Index,
+    rtxn: &RoTxn,
+    facets: &[String],
+) -> Result<BTreeMap<String, FieldId>, MeilisearchHttpError> {
+    let fields_ids_map = index.fields_ids_map(rtxn)?;
+    let filterable_fields = index.filterable_fields(rtxn)?;
+    let all_facets = facets.iter().any(|facet| facet == "*");
+
+    Ok(fields_ids_map
+        .iter()
+        .filter(|(_, name)| {
+            filterable_fields.iter().any(|field| milli::is_faceted_by(name, field))
+                && (all_facets || facets.iter().any(|facet| facet == name))
+        })
+        .map(|(id, name)| (name.to_string(), id))
+        .collect())
+}
+
+/// Returns the bounds of the level 0 keys of the numeric facet database holding the values of
+/// the field within the range, the keys of the higher levels group several values.
+fn facet_number_range(
+    field_id: FieldId,
+    range: &FacetRange,
+) -> (Bound<FacetGroupKey<f64>>, Bound<FacetGroupKey<f64>>) {
+    let key = |left_bound| FacetGroupKey { field_id, level: 0, left_bound };
+    let start = Bound::Included(key(range.from.unwrap_or(f64::MIN)));
+    let end = match range.to {
+        Some(to) => Bound::Excluded(key(to)),
+        None => Bound::Included(key(f64::MAX)),
+    };
+    (start, end)
+}
+
+/// Returns the first value of the numeric facet entries shared by one of the candidates.
+fn first_value_among(
+    entries: impl Iterator<Item = heed::Result<(FacetGroupKey<f64>, FacetGroupValue)>>,
+    candidates: &RoaringBitmap,
+) -> heed::Result<Option<f64>> {
+    for result in entries {
+        let (key, value) = result?;
+        if !value.bitmap.is_disjoint(candidates) {
+            return Ok(Some(key.left_bound));
+        }
+    }
+    Ok(None)
+}
+
+/// Computes the minimum and maximum numeric values of each facet among the candidates.
+/// The facets without any numeric value are ignored.
+fn compute_facet_stats(
+    index: &Index,
+    rtxn: &RoTxn,
+    facets: &[String],
+    candidates: &RoaringBitmap,
+) -> Result<BTreeMap<String, FacetStats>, MeilisearchHttpError> {
+    let mut facet_stats = BTreeMap::new();
+    for (name, field_id) in facet_fields_ids(index, rtxn, facets)? {
+        let values = facet_number_range(field_id, &FacetRange { from: None, to: None });
+        let min = first_value_among(index.facet_id_f64_docids.range(rtxn, &values)?, candidates)?;
+        let max =
+            first_value_among(index.facet_id_f64_docids.rev_range(rtxn, &values)?, candidates)?;
+        if let (Some(min), Some(max)) = (min, max) {
+            facet_stats.insert(name, FacetStats { min, max });
+        }
+    }
+    Ok(facet_stats)
+}
+
+/// Counts the candidates in each bucket of the requested facets.
+/// Only the non-empty buckets are returned, in ascending order.
+fn compute_facet_buckets(
+    index: &Index,
+    rtxn: &RoTxn,
+    facet_buckets: &BTreeMap<String, FacetBuckets>,
+    candidates: &RoaringBitmap,
+    max_buckets_per_facet: usize,
+) -> Result<BTreeMap<String, Vec<FacetBucket>>, MeilisearchHttpError> {
+    let fields_ids_map = index.fields_ids_map(rtxn)?;
+    let filterable_fields = index.filterable_fields(rtxn)?;
+
+    let mut result = BTreeMap::new();
+    for (name, buckets) in facet_buckets {
+        if !filterable_fields.iter().any(|field| milli::is_faceted_by(name, field)) {
+            return Err(MeilisearchHttpError::InvalidFacetBuckets(
+                name.clone(),
+                "the attribute is not filterable".to_string(),
+            ));
+        }
+        if let FacetBuckets::Step(step) = buckets {
+            if !step.is_finite() || *step <= 0.0 {
+                return Err(MeilisearchHttpError::InvalidFacetBuckets(
+                    name.clone(),
+                    format!("the step must be a positive number, got `{}`", step),
+                ));
+            }
+        }
+        let field_id = match fields_ids_map.id(name) {
+            Some(field_id) => field_id,
+            None => {
+                result.insert(name.clone(), Vec::new());
+                continue;
+            }
+        };
+
+        // the buckets are identified by the start of their range, or by their index for
+        // the explicit ranges, and contain the candidates in them. A document is counted
+        // only once per bucket even if it has several values in it.
+        let docids = match buckets {
+            FacetBuckets::Ranges(ranges) => {
+                let mut docids = BTreeMap::new();
+                for (i, range) in ranges.iter().enumerate() {
+                    let values = facet_number_range(field_id, range);
+                    let mut range_docids = RoaringBitmap::new();
+                    for result in index.facet_id_f64_docids.range(rtxn, &values)? {
+                        let (_, value) = result?;
+                        range_docids |= value.bitmap & candidates;
+                    }
+                    if !range_docids.is_empty() {
+                        docids.insert(i as i64, (*range, range_docids));
+                    }
+                }
+                docids
+            }
+            FacetBuckets::Step(step) => {
+                let step = *step;
+                value_buckets(
+                    index,
+                    rtxn,
+                    name,
+                    field_id,
+                    candidates,
+                    max_buckets_per_facet,
+                    |value| {
+                        let key = (value / step).floor() as i64;
+                        let from = key as f64 * step;
+                        Some((key, FacetRange { from: Some(from), to: Some(from + step) }))
+                    },
+                )?
+            }
+            FacetBuckets::Interval(interval) => value_buckets(
+                index,
+                rtxn,
+                name,
+                field_id,
+                candidates,
+                max_buckets_per_facet,
+                |value| {
+                    let range = calendar_range(value, *interval)?;
+                    Some((range.from.unwrap_or_default() as i64, range))
+                },
+            )?,
+        };
+
+        let buckets = docids
+            .into_values()
+            .map(|(range, docids)| FacetBucket { range, count: docids.len() })
+            .collect();
+        result.insert(name.clone(), buckets);
+    }
+    Ok(result)
+}
+
+/// Splits the candidates in the buckets returned by `bucket` for each value of the field.
+/// Returns an error if there are more than `max_buckets` non-empty buckets.
+fn value_buckets(
+    index: &Index,
+    rtxn: &RoTxn,
+    name: &str,
+    field_id: FieldId,
+    candidates: &RoaringBitmap,
+    max_buckets: usize,
+    bucket: impl Fn(f64) -> Option<(i64, FacetRange)>,
+) -> Result<BTreeMap<i64, (FacetRange, RoaringBitmap)>, MeilisearchHttpError> {
+    let values = facet_number_range(field_id, &FacetRange { from: None, to: None });
+    let mut docids: BTreeMap<i64, (FacetRange, RoaringBitmap)> = BTreeMap::new();
+    for result in index.facet_id_f64_docids.range(rtxn, &values)? {
+        let (key, value) = result?;
+        let value_docids = value.bitmap & candidates;
+        if value_docids.is_empty() {
+            continue;
+        }
+        if let Some((key, range)) = bucket(key.left_bound) {
+            docids.entry(key).or_insert((range, RoaringBitmap::new())).1 |= value_docids;
+        }
+        if docids.len() > max_buckets {
+            return Err(MeilisearchHttpError::InvalidFacetBuckets(
+                name.to_string(),
+                format!(
+                    "there are more than {} buckets, the maximum number of values per facet",
+                    max_buckets
+                ),
+            ));
+        }
+    }
+    Ok(docids)
+}
+
+/// Returns the calendar interval containing the given unix timestamp.
+fn calendar_range(timestamp: f64, interval: CalendarInterval) -> Option<FacetRange> {
+    let date = OffsetDateTime::from_unix_timestamp(timestamp.floor() as i64).ok()?.date();
+    let (start, end) = match interval {
+        CalendarInterval::Day => (date, date.next_day()?),
+        CalendarInterval::Week => {
+            let start =
+                date - time::Duration::days(date.weekday().number_days_from_monday() as i64);
+            (start, start + time::Duration::days(7))
+        }
+        CalendarInterval::Month => {
+            let start = date.replace_day(1).ok()?;
+            let end = match start.month() {
+                Month::December => Date::from_calendar_date(start.year() + 1, Month::January, 1),
+                month => Date::from_calendar_date(start.year(), month.next(), 1),
+            };
+            (start, end.ok()?)
+        }
+        CalendarInterval::Year => (
+            Date::from_calendar_date(date.year(), Month::January, 1).ok()?,
+            Date::from_calendar_date(date.year() + 1, Month::January, 1).ok()?,
+        ),
+    };
+    let timestamp = |date: Date| date.midnight().assume_utc().unix_timestamp() as f64;
+    Some(FacetRange { from: Some(timestamp(start)), to: Some(timestamp(end)) })
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct FacetSearchQuery {
+    pub facet_name: String,
+    pub facet_query: Option<String>,
+    pub q: Option<String>,
+    pub filter: Option<Value>,
+    #[serde(default)]
+    pub matching_strategy: MatchingStrategy,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct FacetValueHit {
+    pub value: String,
+    pub count: u64,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FacetSearchResult {
+    pub facet_hits: Vec<FacetValueHit>,
+    pub facet_query: Option<String>,
+    pub processing_time_ms: u128,
+}
+
+pub fn perform_facet_search(
+    index: &Index,
+    query: FacetSearchQuery,
+) -> Result<FacetSearchResult, MeilisearchHttpError> {
+    let before_search = Instant::now();
+    let rtxn = index.read_txn()?;
+
+    // 1. Only keep the documents matching the main query and filter, if any.
+    let candidates = if query.q.is_some() || query.filter.is_some() {
+        let mut search = index.search(&rtxn);
+        if let Some(ref q) = query.q {
+            search.query(q);
+        }
+        search.terms_matching_strategy(query.matching_strategy.clone().into());
+        let geo_filter = GeoFilter::extract(index, &rtxn, query.filter.as_ref())?;
+        if let Some(ref filter) = geo_filter.milli_filter() {
+            if let Some(facets) = parse_filter(filter)? {
+                search.filter(facets);
+            }
+        }
+        search.exhaustive_number_hits(true);
+        search.limit(0);
+        match geo_filter.docids(index, &rtxn)? {
+            Some(geo_docids) => search.execute()?.candidates & geo_docids,
+            None => search.execute()?.candidates,
+        }
+    } else {
+        index.documents_ids(&rtxn)?
+    };
+
+    // 2. Retrieve every value of the facet along with its number of documents.
+    let mut facet_distribution = index.facets_distribution(&rtxn);
+    let mut distribution = facet_distribution
+        .facets([&query.facet_name])
+        .max_values_per_facet(usize::MAX)
+        .candidates(candidates)
+        .execute()?;
+    let values = distribution.remove(&query.facet_name).unwrap_or_default();
+
+    // 3. Keep the values matching the facet query, with the typo tolerance of the index.
+    let authorize_typos = index.authorize_typos(&rtxn)?;
+    let min_len_one_typo = index.min_word_len_one_typo(&rtxn)? as usize;
+    let min_len_two_typos = index.min_word_len_two_typos(&rtxn)? as usize;
+    let allowed_typos = |word: &[char]| match word.len() {
+        _ if !authorize_typos => 0,
+        len if len < min_len_one_typo => 0,
+        len if len < min_len_two_typos => 1,
+        _ => 2,
+    };
+
+    let tokenizer = TokenizerBuilder::default().build();
+    let words = |text: &str| -> Vec<Vec<char>> {
+        tokenizer
+            .tokenize(text)
+            .filter(|token| token.is_word())
+            .map(|token| token.lemma().chars().collect())
+            .collect()
+    };
+    let query_words = query.facet_query.as_deref().map(&words).unwrap_or_default();
+
+    let mut facet_hits: Vec<_> = values
+        .into_iter()
+        .filter(|(value, _)| {
+            let value_words = words(value);
+            // every word of the facet query must be the prefix of a word of the value.
+            query_words.iter().all(|query_word| {
+                value_words.iter().any(|value_word| {
+                    prefix_distance(query_word, value_word) <= allowed_typos(query_word)
+                })
+            })
+        })
+        .map(|(value, count)| FacetValueHit { value, count })
+        .collect();
+
+    // 4. Return the most frequent values first.
+    facet_hits.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+    let max_values_per_facet = index
+        .max_values_per_facet(&rtxn)
+        .map_err(milli::Error::from)?
+        .unwrap_or(DEFAULT_VALUES_PER_FACET);
+    facet_hits.truncate(max_values_per_facet);
+
+    Ok(FacetSearchResult {
+        facet_hits,
+        facet_query: query.facet_query,
+        processing_time_ms: before_search.elapsed().as_millis(),
+    })
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SuggestQuery {
+    /// The prefix typed by the user, the suggestions complete it.
+    pub q: String,
+    #[serde(default = "DEFAULT_SEARCH_LIMIT")]
+    pub limit: usize,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SuggestResult {
+    pub suggestions: Vec<Suggestion>,
+    pub q: String,
+    pub processing_time_ms: u128,
+}
+
+pub fn perform_suggest(
+    index: &Index,
+    query: SuggestQuery,
+) -> Result<SuggestResult, MeilisearchHttpError> {
+    let before_search = Instant::now();
+    let rtxn = index.read_txn()?;
+    let suggestions = suggestions::suggest(index, &rtxn, &query.q, query.limit)?;
+
+    Ok(SuggestResult {
+        suggestions,
+        q: query.q,
+        processing_time_ms: before_search.elapsed().as_millis(),
+    })
+}
+
+/// The maximum number of terms of the source document used to find similar documents.
+const MAX_SIMILAR_TERMS: usize = 10;
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SimilarQuery {
+    /// The primary key of the source document, a string or an integer.
+    pub id: Value,
+    #[serde(default = "DEFAULT_SEARCH_OFFSET")]
+    pub offset: usize,
+    #[serde(default = "DEFAULT_SEARCH_LIMIT")]
+    pub limit: usize,
+    pub filter: Option<Value>,
+    pub attributes_to_retrieve: Option<BTreeSet<String>>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SimilarResult {
+    pub hits: Vec<Document>,
+    pub id: String,
+    pub processing_time_ms: u128,
+    #[serde(flatten)]
+    pub hits_info: HitsInfo,
+}
+
+pub fn perform_similar(
+    index: &Index,
+    query: SimilarQuery,
+) -> Result<SimilarResult, MeilisearchHttpError> {
+    let before_search = Instant::now();
+    let rtxn = index.read_txn()?;
+
+    let id = match query.id {
+        Value::String(id) => id,
+        Value::Number(id) if id.is_u64() || id.is_i64() => id.to_string(),
+        id => return Err(MeilisearchHttpError::InvalidDocumentId(id.to_string())),
+    };
+    let source_id = index
+        .external_documents_ids(&rtxn)?
+        .get(&id)
+        .ok_or_else(|| MeilisearchHttpError::DocumentNotFound(id.clone()))?;
+
+    // 1. Extract the text of the searchable attributes of the source document.
+    let fields_ids_map = index.fields_ids_map(&rtxn)?;
+    let source = full_document(index, &rtxn, &fields_ids_map, source_id)?;
+    let mut texts = Vec::new();
+    match index.searchable_fields(&rtxn)? {
+        Some(searchable_fields) => {
+            for field in searchable_fields {
+                collect_texts(&field_value(&source, field), &mut texts);
+            }
+        }
+        None => source.values().for_each(|value| collect_texts(value, &mut texts)),
+    }
+
+    // 2. Keep the most distinctive terms, the frequent ones in the source document
+    //    which are rare among the other documents.
+    let stop_words = index.stop_words(&rtxn)?;
+    let mut tokenizer_builder = TokenizerBuilder::default();
+    if let Some(ref stop_words) = stop_words {
+        tokenizer_builder.stop_words(stop_words);
+    }
+    let tokenizer = tokenizer_builder.build();
+
+    let mut term_frequencies = BTreeMap::<String, usize>::new();
+    for text in texts {
+        for token in tokenizer.tokenize(&text).filter(|token| token.is_word()) {
+            if token.lemma().chars().count() > 1 {
+                *term_frequencies.entry(token.lemma().to_string()).or_default() += 1;
+            }
+        }
+    }
+
+    let number_of_documents = index.number_of_documents(&rtxn)? as f64;
+    let mut terms = Vec::new();
+    for (term, frequency) in term_frequencies {
+        let document_frequency =
+            index.word_docids.get(&rtxn, &term)?.map_or(0, |docids| docids.len());
+        // a term only found in the source document can't bring any similar document.
+        if document_frequency > 1 {
+            let weight = frequency as f64 * (number_of_documents / document_frequency as f64).ln();
+            terms.push((term, weight));
+        }
+    }
+    terms.sort_by(|(_, left), (_, right)| right.partial_cmp(left).unwrap_or(Ordering::Equal));
+    terms.truncate(MAX_SIMILAR_TERMS);
+
+    // 3. Rank the documents by the sum of the weights of the terms they contain, the source
+    //    document aside. The documents sharing the most distinctive terms come first.
+    let max_total_hits = index
+        .pagination_max_total_hits(&rtxn)
+        .map_err(milli::Error::from)?
+        .unwrap_or(DEFAULT_PAGINATION_MAX_TOTAL_HITS);
+    let offset = min(query.offset, max_total_hits);
+    let limit = min(query.limit, max_total_hits.saturating_sub(offset));
+
+    let mut allowed = filtered_documents(index, &rtxn, query.filter.as_ref())?;
+    allowed.remove(source_id);
+    let mut scores = HashMap::<DocumentId, f64>::new();
+    for (term, weight) in terms {
+        if let Some(docids) = index.word_docids.get(&rtxn, &term)? {
+            for docid in docids & &allowed {
+                *scores.entry(docid).or_default() += weight;
+            }
+        }
+    }
+    let number_of_hits = scores.len();
+    let mut scores: Vec<_> = scores.into_iter().collect();
+    scores.sort_unstable_by(|(left_id, left), (right_id, right)| {
+        right.partial_cmp(left).unwrap_or(Ordering::Equal).then(left_id.cmp(right_id))
+    });
+    let documents_ids: Vec<_> =
+        scores.into_iter().skip(offset).take(limit).map(|(docid, _)| docid).collect();
+
+    // 4. Retrieve the attributes to retrieve among the displayed ones.
+    let displayed_ids = index
+        .displayed_fields_ids(&rtxn)?
+        .map(|fields| fields.into_iter().collect::<BTreeSet<_>>())
+        .unwrap_or_else(|| fields_ids_map.iter().map(|(id, _)| id).collect());
+    let to_retrieve_ids: BTreeSet<_> = match query.attributes_to_retrieve {
+        Some(ref attributes) if !attributes.contains("*") => attributes
+            .iter()
+            .filter_map(|attribute| fields_ids_map.id(attribute))
+            .filter(|id| displayed_ids.contains(id))
+            .collect(),
+        _ => displayed_ids,
+    };
+
+    let mut hits = Vec::new();
+    for (_id, obkv) in index.documents(&rtxn, documents_ids)? {
+        hits.push(make_document(&to_retrieve_ids, &fields_ids_map, obkv)?);
+    }
+
+    Ok(SimilarResult {
+        hits,
+        id,
+        processing_time_ms: before_search.elapsed().as_millis(),
+        hits_info: HitsInfo::OffsetLimit {
+            limit: query.limit,
+            offset,
+            estimated_total_hits: min(number_of_hits, max_total_hits),
+        },
+    })
+}
+
+/// Collects all the strings of a json value, nested or not.
+fn collect_texts(value: &Value, texts: &mut Vec<String>) {
+    match value {
+        Value::String(text) => texts.push(text.clone()),
+        Value::Array(values) => values.iter().for_each(|value| collect_texts(value, texts)),
+        Value::Object(object) => object.values().for_each(|value| collect_texts(value, texts)),
+        _ => (),
+    }
+}
+
+/// Returns the smallest number of typos between `query` and any prefix of `word`.
+fn prefix_distance(query: &[char], word: &[char]) -> usize {
+    // `row[j]` is the distance between the first `j` chars of the query
+    // and the prefix of the word we're currently looking at.
+    let mut row: Vec<usize> = (0..=query.len()).collect();
+    let mut best = row[query.len()];
+    for (i, word_char) in word.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for j in 1..=query.len() {
+            let above = row[j];
+            row[j] = if query[j - 1] == *word_char {
+                diagonal
+            } else {
+                1 + diagonal.min(row[j - 1]).min(above)
+            };
+            diagonal = above;
+        }
+        best = best.min(row[query.len()]);
+    }
+    best
+}
+
+fn insert_geo_distance(sorts: &[String], document: &mut Document) {
+    lazy_static::lazy_static! {
+        static ref GEO_REGEX: Regex =
+            Regex::new(r"_geoPoint\(\s*([[:digit:].\-]+)\s*,\s*([[:digit:].\-]+)\s*\)").unwrap();
+    };
+    if let Some(capture_group) = sorts.iter().find_map(|sort| GEO_REGEX.captures(sort)) {
+        // TODO: TAMO: milli encountered an internal error, what do we want to do?
+        let base = [capture_group[1].parse().unwrap(), capture_group[2].parse().unwrap()];
+        let geo_point = &document.get("_geo").unwrap_or(&json!(null));
+        if let Some((lat, lng)) = geo_point["lat"].as_f64().zip(geo_point["lng"].as_f64()) {
+            let distance = milli::distance_between_two_points(&base, &[lat, lng]);
+            document.insert("_geoDistance".to_string(), json!(distance.round() as usize));
+        }
+    }
+}
+
+fn compute_formatted_options(
+    attr_to_highlight: &HashSet<String>,
+    attr_to_crop: &[String],
+    query_crop_length: usize,
+    to_retrieve_ids: &BTreeSet<FieldId>,
+    fields_ids_map: &FieldsIdsMap,
+    displayed_ids: &BTreeSet<FieldId>,
+) -> BTreeMap<FieldId, FormatOptions> {
+    let mut formatted_options = BTreeMap::new();
+
+    add_highlight_to_formatted_options(
+        &mut formatted_options,
+        attr_to_highlight,
+        fields_ids_map,
+        displayed_ids,
+    );
+
+    add_crop_to_formatted_options(
+        &mut formatted_options,
+        attr_to_crop,
+        query_crop_length,
+        fields_ids_map,
+        displayed_ids,
+    );
+
+    // Should not return `_formatted` if no valid attributes to highlight/crop
+    if !formatted_options.is_empty() {
+        add_non_formatted_ids_to_formatted_options(&mut formatted_options, to_retrieve_ids);
+    }
+
+    formatted_options
+}
+
+fn add_highlight_to_formatted_options(
+    formatted_options: &mut BTreeMap<FieldId, FormatOptions>,
+    attr_to_highlight: &HashSet<String>,
+    fields_ids_map: &FieldsIdsMap,
+    displayed_ids: &BTreeSet<FieldId>,
+) {
+    for attr in attr_to_highlight {
+        let new_format = FormatOptions { highlight: true, crop: None };
+
+        if attr == "*" {
+            for id in displayed_ids {
+                formatted_options.insert(*id, new_format);
+            }
+            break;
+        }
+
+        if let Some(id) = fields_ids_map.id(attr) {
+            if displayed_ids.contains(&id) {
+                formatted_options.insert(id, new_format);
+            }
+        }
+    }
+}
+
+fn add_crop_to_formatted_options(
+    formatted_options: &mut BTreeMap<FieldId, FormatOptions>,
+    attr_to_crop: &[String],
+    crop_length: usize,
+    fields_ids_map: &FieldsIdsMap,
+    displayed_ids: &BTreeSet<FieldId>,
+) {
+    for attr in attr_to_crop {
+        let mut split = attr.rsplitn(2, ':');
+        let (attr_name, attr_len) = match split.next().zip(split.next()) {
+            Some((len, name)) => {
+                let crop_len = len.parse::<usize>().unwrap_or(crop_length);
+                (name, crop_len)
+            }
+            None => (attr.as_str(), crop_length),
+        };
+
+        if attr_name == "*" {
+            for id in displayed_ids {
+                formatted_options
+                    .entry(*id)
+                    .and_modify(|f| f.crop = Some(attr_len))
+                    .or_insert(FormatOptions { highlight: false, crop: Some(attr_len) });
+            }
+        }
+
+        if let Some(id) = fields_ids_map.id(attr_name) {
+            if displayed_ids.contains(&id) {
+                formatted_options
+                    .entry(id)
+                    .and_modify(|f| f.crop = Some(attr_len))
+                    .or_insert(FormatOptions { highlight: false, crop: Some(attr_len) });
+            }
+        }
+    }
+}
+
+fn add_non_formatted_ids_to_formatted_options(
+    formatted_options: &mut BTreeMap<FieldId, FormatOptions>,
+    to_retrieve_ids: &BTreeSet<FieldId>,
+) {
+    for id in to_retrieve_ids {
+        formatted_options.entry(*id).or_insert(FormatOptions { highlight: false, crop: None });
+    }
+}
+
+fn make_document(
+    displayed_attributes: &BTreeSet<FieldId>,
+    field_ids_map: &FieldsIdsMap,
+    obkv: obkv::KvReaderU16,
+) -> Result<Document, MeilisearchHttpError> {
+    let mut document = serde_json::Map::new();
+
+    // recreate the original json
+    for (key, value) in obkv.iter() {
+        let value = serde_json::from_slice(value)?;
+        let key = field_ids_map.name(key).expect("Missing field name").to_string();
+
+        document.insert(key, value);
+    }
+
+    // select the attributes to retrieve
+    let displayed_attributes = displayed_attributes
+        .iter()
+        .map(|&fid| field_ids_map.name(fid).expect("Missing field name"));
+
+    let document = permissive_json_pointer::select_values(&document, displayed_attributes);
+    Ok(document)
+}
+
+fn format_fields<'a, A: AsRef<[u8]>>(
+    document: &Document,
+    field_ids_map: &FieldsIdsMap,
+    builder: &MatcherBuilder<'a, A>,
+    formatted_options: &BTreeMap<FieldId, FormatOptions>,
+    compute_matches: bool,
+    displayable_ids: &BTreeSet<FieldId>,
+) -> Result<(Option<MatchesPosition>, Document), MeilisearchHttpError> {
+    let mut matches_position = compute_matches.then(BTreeMap::new);
+    let mut document = document.clone();
+
+    // select the attributes to retrieve
+    let displayable_names =
+        displayable_ids.iter().map(|&fid| field_ids_map.name(fid).expect("Missing field name"));
+    permissive_json_pointer::map_leaf_values(&mut document, displayable_names, |key, value| {
+        // To get the formatting option of each key we need to see all the rules that applies
+        // to the value and merge them together. eg. If a user said he wanted to highlight `doggo`
+        // and crop `doggo.name`. `doggo.name` needs to be highlighted + cropped while `doggo.age` is only
+        // highlighted.
+        let format = formatted_options
+            .iter()
+            .filter(|(field, _option)| {
+                let name = field_ids_map.name(**field).unwrap();
+                milli::is_faceted_by(name, key) || milli::is_faceted_by(key, name)
+            })
+            .map(|(_, option)| *option)
+            .reduce(|acc, option| acc.merge(option));
+        let mut infos = Vec::new();
+
+        *value = format_value(std::mem::take(value), builder, format, &mut infos, compute_matches);
+
+        if let Some(matches) = matches_position.as_mut() {
+            if !infos.is_empty() {
+                matches.insert(key.to_owned(), infos);
+            }
+        }
+    });
+
+    let selectors = formatted_options
+        .keys()
+        // This unwrap must be safe since we got the ids from the fields_ids_map just
+        // before.
+        .map(|&fid| field_ids_map.name(fid).unwrap());
+    let document = permissive_json_pointer::select_values(&document, selectors);
+
+    Ok((matches_position, document))
+}
+
+fn format_value<'a, A: AsRef<[u8]>>(
+    value: Value,
+    builder: &MatcherBuilder<'a, A>,
+    format_options: Option<FormatOptions>,
+    infos: &mut Vec<MatchBounds>,
+    compute_matches: bool,
+) -> Value {
+    match value {
+        Value::String(old_string) => {
+            let mut matcher = builder.build(&old_string);
+            if compute_matches {
+                let matches = matcher.matches();
+                infos.extend_from_slice(&matches[..]);
+            }
+
+            match format_options {
+                Some(format_options) => {
+                    let value = matcher.format(format_options);
+                    Value::String(value.into_owned())
+                }
+                None => Value::String(old_string),
+            }
+        }
+        Value::Array(values) => Value::Array(
+            values
+                .into_iter()
+                .map(|v| {
+                    format_value(
+                        v,
+                        builder,
+                        format_options.map(|format_options| FormatOptions {
+                            highlight: format_options.highlight,
+                            crop: None,
+                        }),
+                        infos,
+                        compute_matches,
+                    )
+                })
+                .collect(),
+        ),
+        Value::Object(object) => Value::Object(
+            object
+                .into_iter()
+                .map(|(k, v)| {
+                    (
+                        k,
+                        format_value(
+                            v,
+                            builder,
+                            format_options.map(|format_options| FormatOptions {
+                                highlight: format_options.highlight,
+                                crop: None,
+                            }),
+                            infos,
+                            compute_matches,
+                        ),
+                    )
+                })
+                .collect(),
+        ),
+        Value::Number(number) => {
+            let s = number.to_string();
+
+            let mut matcher = builder.build(&s);
+            if compute_matches {
+                let matches = matcher.matches();
+                infos.extend_from_slice(&matches[..]);
+            }
+
+            match format_options {
+                Some(format_options) => {
+                    let value = matcher.format(format_options);
+                    Value::String(value.into_owned())
+                }
+                None => Value::Number(number),
+            }
+        }
+        value => value,
+    }
+}
+
+/// How the `_snippets` of the cropped attributes are built.
+struct SnippetOptions {
+    /// The maximum number of snippets of every attribute.
+    count: usize,
+    crop_marker: String,
+    highlight_pre_tag: String,
+    highlight_post_tag: String,
+}
+
+/// Returns the snippets of the cropped string attributes of the document.
+fn compute_snippets<'a, A: AsRef<[u8]>>(
+    document: &Document,
+    field_ids_map: &FieldsIdsMap,
+    builder: &MatcherBuilder<'a, A>,
+    formatted_options: &BTreeMap<FieldId, FormatOptions>,
+    options: &SnippetOptions,
+) -> Document {
+    let mut snippets = Document::new();
+    for (&field_id, format_options) in formatted_options {
+        let crop_length = match format_options.crop {
+            Some(crop_length) => crop_length,
+            None => continue,
+        };
+        let name = field_ids_map.name(field_id).expect("Missing field name");
+        if let Some(Value::String(text)) = document.get(name) {
+            let matches = builder.build(text).matches().to_vec();
+            let text_snippets =
+                snippets_of_text(text, &matches, crop_length, format_options.highlight, options);
+            snippets.insert(name.to_string(), json!(text_snippets));
+        }
+    }
+    snippets
+}
+
+/// Returns the `options.count` windows of `crop_length` words of the text containing the most
+/// matches, the densest first. The windows never overlap and the text is only cropped to its
+/// first words when nothing matches.
+fn snippets_of_text(
+    text: &str,
+    matches: &[MatchBounds],
+    crop_length: usize,
+    highlight: bool,
+    options: &SnippetOptions,
+) -> Vec<String> {
+    let tokenizer = TokenizerBuilder::default().build();
+    let words: Vec<_> = tokenizer
+        .tokenize(text)
+        .filter(|token| token.is_word())
+        .map(|token| (token.byte_start, token.byte_end))
+        .collect();
+    let window_length = min(crop_length, words.len());
+    if window_length == 0 || options.count == 0 {
+        return Vec::new();
+    }
+
+    // 1. The windows centered on and starting with every matching word,
+    //    along with their number of matches.
+    let matching_words: Vec<_> = matches
+        .iter()
+        .map(|bounds| words.partition_point(|&(_, end)| end <= bounds.start))
+        .filter(|&word| word < words.len())
+        .collect();
+    let last_start = words.len() - window_length;
+    let mut windows: Vec<_> = matching_words
+        .iter()
+        .flat_map(|&word| [word.saturating_sub((window_length - 1) / 2), word])
+        .map(|start| {
+            let start = start.min(last_start);
+            let end = start + window_length;
+            let density =
+                matching_words.iter().filter(|&&word| start <= word && word < end).count();
+            (start, density)
+        })
+        .collect();
+    if windows.is_empty() {
+        windows.push((0, 0));
+    }
+    windows.sort_by(|(ls, ld), (rs, rd)| rd.cmp(ld).then(ls.cmp(rs)));
+    windows.dedup();
+
+    // 2. The densest windows that don't overlap the ones already selected.
+    let mut selected: Vec<usize> = Vec::new();
+    for (start, _) in windows {
+        if selected.len() == options.count {
+            break;
+        }
+        if selected
+            .iter()
+            .all(|&other| start + window_length <= other || other + window_length <= start)
+        {
+            selected.push(start);
+        }
+    }
+
+    // 3. The text of the windows, highlighted if the attribute is.
+    selected
+        .into_iter()
+        .map(|start| {
+            let end = start + window_length;
+            let (from, to) = (words[start].0, words[end - 1].1);
+            let mut snippet = String::new();
+            if start > 0 {
+                snippet.push_str(&options.crop_marker);
+            }
+            let mut position = from;
+            if highlight {
+                for bounds in
+                    matches.iter().filter(|bounds| from <= bounds.start && bounds.start < to)
+                {
+                    // the overlapping matches are highlighted as one.
+                    if bounds.start < position {
+                        continue;
+                    }
+                    let match_end = min(bounds.start + bounds.length, to);
+                    snippet.push_str(&text[position..bounds.start]);
+                    snippet.push_str(&options.highlight_pre_tag);
+                    snippet.push_str(&text[bounds.start..match_end]);
+                    snippet.push_str(&options.highlight_post_tag);
+                    position = match_end;
+                }
+            }
+            snippet.push_str(&text[position..to]);
+            if end < words.len() {
+                snippet.push_str(&options.crop_marker);
+            }
+            snippet
+        })
+        .collect()
+}
+
+fn parse_filter(facets: &Value) -> Result<Option<Filter>, MeilisearchHttpError> {
+    match facets {
+        Value::String(expr) => {
+            let condition = Filter::from_str(expr)?;
+            Ok(condition)
+        }
+        Value::Array(arr) => parse_filter_array(arr),
+        v => Err(MeilisearchHttpError::InvalidExpression(&["Array"], v.clone())),
+    }
+}
+
+fn parse_filter_array(arr: &[Value]) -> Result<Option<Filter>, MeilisearchHttpError> {
+    let mut ands = Vec::new();
+    for value in arr {
+        match value {
+            Value::String(s) => ands.push(Either::Right(s.as_str())),
+            Value::Array(arr) => {
+                let mut ors = Vec::new();
+                for value in arr {
+                    match value {
+                        Value::String(s) => ors.push(s.as_str()),
+                        v => {
+                            return Err(MeilisearchHttpError::InvalidExpression(
+                                &["String"],
+                                v.clone(),
+                            ))
+                        }
+                    }
+                }
+                ands.push(Either::Left(ors));
+            }
+            v => {
+                return Err(MeilisearchHttpError::InvalidExpression(
+                    &["String", "[String]"],
+                    v.clone(),
+                ))
+            }
+        }
+    }
+
+    Ok(Filter::from_array(ands)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_prefix_distance() {
+        let distance = |query: &str, word: &str| {
+            prefix_distance(&query.chars().collect::<Vec<_>>(), &word.chars().collect::<Vec<_>>())
+        };
+        assert_eq!(distance("", "adidas"), 0);
+        assert_eq!(distance("adi", "adidas"), 0);
+        assert_eq!(distance("adidas", "adidas"), 0);
+        assert_eq!(distance("adisas", "adidas"), 1);
+        assert_eq!(distance("adudas", "adidas"), 1);
+        assert_eq!(distance("adidass", "adidas"), 1);
+        assert_eq!(distance("nike", "adidas"), 4);
+    }
+
+    #[test]
+    fn test_insert_geo_distance() {
+        let value: Document = serde_json::from_str(
+            r#"{
+              "_geo": {
+                "lat": 50.629973371633746,
+                "lng": 3.0569447399419567
+              },
+              "city": "Lille",
+              "id": "1"
+            }"#,
+        )
+        .unwrap();
+
+        let sorters = &["_geoPoint(50.629973371633746,3.0569447399419567):desc".to_string()];
+        let mut document = value.clone();
+        insert_geo_distance(sorters, &mut document);
+        assert_eq!(document.get("_geoDistance"), Some(&json!(0)));
+
+        let sorters = &["_geoPoint(50.629973371633746, 3.0569447399419567):asc".to_string()];
+        let mut document = value.clone();
+        insert_geo_distance(sorters, &mut document);
+        assert_eq!(document.get("_geoDistance"), Some(&json!(0)));
+
+        let sorters =
+            &["_geoPoint(   50.629973371633746   ,  3.0569447399419567   ):desc".to_string()];
+        let mut document = value.clone();
+        insert_geo_distance(sorters, &mut document);
+        assert_eq!(document.get("_geoDistance"), Some(&json!(0)));
+
+        let sorters = &[
+            "prix:asc",
+            "villeneuve:desc",
+            "_geoPoint(50.629973371633746, 3.0569447399419567):asc",
+            "ubu:asc",
+        ]
+        .map(|s| s.to_string());
+        let mut document = value.clone();
+        insert_geo_distance(sorters, &mut document);
+        assert_eq!(document.get("_geoDistance"), Some(&json!(0)));
+
+        // only the first geoPoint is used to compute the distance
+        let sorters = &[
+            "chien:desc",
+            "_geoPoint(50.629973371633746, 3.0569447399419567):asc",
+            "pangolin:desc",
+            "_geoPoint(100.0, -80.0):asc",
+            "chat:asc",
+        ]
+        .map(|s| s.to_string());
+        let mut document = value.clone();
+        insert_geo_distance(sorters, &mut document);
+        assert_eq!(document.get("_geoDistance"), Some(&json!(0)));
+
+        // there was no _geoPoint so nothing is inserted in the document
+        let sorters = &["chien:asc".to_string()];
+        let mut document = value;
+        insert_geo_distance(sorters, &mut document);
+        assert_eq!(document.get("_geoDistance"), None);
+    }
+}
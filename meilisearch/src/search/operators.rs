@@ -0,0 +1,455 @@
+//! The operators of the extended syntax of `q`: the quoted phrases, the terms excluded with `-`,
+//! the terms restricted to an attribute with `attribute:` and the terms joined by `OR`.
+
+use std::collections::BTreeMap;
+
+use meilisearch_types::heed::types::ByteSlice;
+use meilisearch_types::heed::{self, BytesDecode, RoTxn};
+use meilisearch_types::milli;
+use milli::tokenizer::TokenizerBuilder;
+use milli::{
+    DocumentId, FieldId, FieldsIdsMap, Index, MatchingWords, StrBEU32Codec, TermsMatchingStrategy,
+};
+use roaring::RoaringBitmap;
+
+use super::{ranked_hits, Deadline};
+use crate::error::MeilisearchHttpError;
+
+/// The maximum number of combinations of the alternatives joined by `OR`, each of them is a
+/// search of its own.
+const MAX_QUERY_BRANCHES: usize = 16;
+
+/// A word or a quoted phrase of a query, possibly restricted to an attribute with `attribute:`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct QueryClause {
+    /// The word, or the phrase along with its quotes.
+    term: String,
+    attribute: Option<String>,
+}
+
+/// A query written with the extended syntax of `q`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedQuery {
+    /// The positive terms of the query, without any operator, highlighted in the hits.
+    milli_query: String,
+    /// The groups of clauses joined by `OR`, a lonely clause is a group of its own.
+    groups: Vec<Vec<QueryClause>>,
+    excluded: Vec<QueryClause>,
+}
+
+/// The searches of a query with operators.
+pub struct QueryOperators {
+    /// The query of each combination of the alternatives joined by `OR`, along with the
+    /// documents it's restricted to by its `attribute:` clauses and the excluded terms.
+    branches: Vec<(String, RoaringBitmap)>,
+    /// The positive terms of all the branches.
+    milli_query: String,
+}
+
+impl ParsedQuery {
+    /// Parses the query, a word looking like `attribute:term` is kept as is when the attribute
+    /// isn't a field of the index.
+    pub fn parse(q: &str, fields_ids_map: &FieldsIdsMap) -> Result<Self, MeilisearchHttpError> {
+        enum Item {
+            Clause(QueryClause),
+            Excluded(QueryClause),
+            Or,
+        }
+
+        let mut items = Vec::new();
+        let mut rest = q.trim_start();
+        while !rest.is_empty() {
+            let excluded =
+                rest.starts_with('-') && rest[1..].starts_with(|c: char| !c.is_whitespace());
+            if excluded {
+                rest = &rest[1..];
+            }
+
+            let (clause, remaining) = if rest.starts_with('"') {
+                let (phrase, remaining) = split_phrase(rest)?;
+                (QueryClause { term: phrase.to_string(), attribute: None }, remaining)
+            } else {
+                let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                let word = &rest[..end];
+                let is_field = |attribute: &str| {
+                    fields_ids_map.iter().any(|(_, field)| milli::is_faceted_by(field, attribute))
+                };
+                match word.split_once(':') {
+                    Some((attribute, term)) if !term.is_empty() && is_field(attribute) => {
+                        let rest = &rest[attribute.len() + 1..];
+                        let attribute = Some(attribute.to_string());
+                        if rest.starts_with('"') {
+                            let (phrase, remaining) = split_phrase(rest)?;
+                            (QueryClause { term: phrase.to_string(), attribute }, remaining)
+                        } else {
+                            (QueryClause { term: term.to_string(), attribute }, &rest[term.len()..])
+                        }
+                    }
+                    _ => (QueryClause { term: word.to_string(), attribute: None }, &rest[end..]),
+                }
+            };
+
+            items.push(match clause {
+                clause if excluded => Item::Excluded(clause),
+                QueryClause { ref term, attribute: None } if term == "OR" => Item::Or,
+                clause => Item::Clause(clause),
+            });
+            rest = remaining.trim_start();
+        }
+
+        let mut groups: Vec<Vec<QueryClause>> = Vec::new();
+        let mut excluded = Vec::new();
+        let mut previous_is_or = false;
+        let mut items = items.into_iter().peekable();
+        while let Some(item) = items.next() {
+            match item {
+                Item::Clause(clause) if previous_is_or => {
+                    groups.last_mut().unwrap().push(clause);
+                    previous_is_or = false;
+                }
+                Item::Clause(clause) => groups.push(vec![clause]),
+                Item::Excluded(clause) if !previous_is_or => excluded.push(clause),
+                Item::Or
+                    if !groups.is_empty()
+                        && !previous_is_or
+                        && matches!(items.peek(), Some(Item::Clause(_))) =>
+                {
+                    previous_is_or = true;
+                }
+                Item::Or | Item::Excluded(_) => {
+                    return Err(MeilisearchHttpError::InvalidQuery(
+                        "`OR` must be between two terms".to_string(),
+                    ))
+                }
+            }
+        }
+
+        let milli_query = groups
+            .iter()
+            .flatten()
+            .map(|clause| clause.term.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        Ok(ParsedQuery { milli_query, groups, excluded })
+    }
+
+    pub fn milli_query(&self) -> &str {
+        &self.milli_query
+    }
+
+    /// Returns the searches of the query, or `None` if it's a plain query that milli can
+    /// answer by itself.
+    ///
+    /// Each combination of the alternatives joined by `OR` is searched on its own. A clause
+    /// restricted to an attribute must always match, such a clause and the excluded words are
+    /// looked for without typos.
+    pub fn operators(
+        &self,
+        index: &Index,
+        rtxn: &RoTxn,
+    ) -> Result<Option<QueryOperators>, MeilisearchHttpError> {
+        let has_or = self.groups.iter().any(|group| group.len() > 1);
+        let has_attribute = self.groups.iter().flatten().any(|clause| clause.attribute.is_some());
+        if !has_or && !has_attribute && self.excluded.is_empty() {
+            return Ok(None);
+        }
+
+        let mut allowed = index.documents_ids(rtxn)?;
+        for clause in &self.excluded {
+            allowed -= clause_docids(index, rtxn, clause, true)?;
+        }
+
+        let mut branches = vec![(Vec::new(), allowed)];
+        for group in &self.groups {
+            if branches.len() * group.len() > MAX_QUERY_BRANCHES {
+                return Err(MeilisearchHttpError::InvalidQuery(format!(
+                    "it has more than {} combinations of terms joined by `OR`",
+                    MAX_QUERY_BRANCHES
+                )));
+            }
+            let mut alternatives = Vec::new();
+            for clause in group {
+                let restriction = match clause.attribute {
+                    Some(_) => Some(clause_docids(index, rtxn, clause, false)?),
+                    None => None,
+                };
+                alternatives.push((clause, restriction));
+            }
+            branches = branches
+                .into_iter()
+                .flat_map(|(terms, allowed)| {
+                    alternatives.iter().map(move |(clause, restriction)| {
+                        let mut terms = terms.clone();
+                        terms.push(clause.term.as_str());
+                        match restriction {
+                            Some(restriction) => (terms, allowed.clone() & restriction),
+                            None => (terms, allowed.clone()),
+                        }
+                    })
+                })
+                .collect();
+        }
+
+        let branches =
+            branches.into_iter().map(|(terms, allowed)| (terms.join(" "), allowed)).collect();
+        Ok(Some(QueryOperators { branches, milli_query: self.milli_query.clone() }))
+    }
+}
+
+impl QueryOperators {
+    /// Returns the first `count` hits of the query among the documents of the universe, if any,
+    /// along with its candidates. The hits of the searches of the branches are merged by rank,
+    /// the documents they aren't allowed to return are skipped before the hits are counted and
+    /// paginated. The branches left once the deadline has passed aren't searched.
+    pub(super) fn execute<'t>(
+        &self,
+        new_search: impl Fn(&str) -> milli::Search<'t>,
+        universe: Option<&RoaringBitmap>,
+        count: usize,
+        deadline: &Deadline,
+    ) -> Result<(Vec<DocumentId>, RoaringBitmap, MatchingWords), MeilisearchHttpError> {
+        let mut branches_hits = Vec::new();
+        let mut candidates = RoaringBitmap::new();
+        for (query, allowed) in &self.branches {
+            if !branches_hits.is_empty() && deadline.is_past() {
+                break;
+            }
+            let allowed = match universe {
+                Some(universe) => allowed & universe,
+                None => allowed.clone(),
+            };
+            let (hits, branch_candidates, _) =
+                ranked_hits(&mut new_search(query), &allowed, count, deadline)?;
+            branches_hits.push(hits);
+            candidates |= branch_candidates;
+        }
+
+        // The words of all the branches are highlighted.
+        let mut search = new_search(&self.milli_query);
+        search.limit(0);
+        let matching_words = search.execute()?.matching_words;
+
+        Ok((merge_by_rank(&branches_hits, count), candidates, matching_words))
+    }
+}
+
+/// Merges the hits of several searches, a hit is ranked by its best rank among the searches.
+fn merge_by_rank(branches_hits: &[Vec<DocumentId>], count: usize) -> Vec<DocumentId> {
+    let mut merged = Vec::new();
+    let mut seen = RoaringBitmap::new();
+    let max_len = branches_hits.iter().map(Vec::len).max().unwrap_or_default();
+    for rank in 0..max_len {
+        for hits in branches_hits {
+            match hits.get(rank) {
+                Some(&docid) if seen.insert(docid) => merged.push(docid),
+                _ => (),
+            }
+        }
+    }
+    merged.truncate(count);
+    merged
+}
+
+/// Returns the documents containing the word, or a word starting with it, in each field.
+fn word_fields_docids(
+    index: &Index,
+    rtxn: &RoTxn,
+    word: &str,
+    is_prefix: bool,
+) -> Result<BTreeMap<FieldId, RoaringBitmap>, MeilisearchHttpError> {
+    let mut fields_docids = BTreeMap::<FieldId, RoaringBitmap>::new();
+    let iter = index
+        .word_position_docids
+        .remap_key_type::<ByteSlice>()
+        .prefix_iter(rtxn, word.as_bytes())?;
+    for result in iter {
+        let (key, docids) = result?;
+        let (key_word, position) = StrBEU32Codec::bytes_decode(key).ok_or(heed::Error::Decoding)?;
+        if is_prefix || key_word == word {
+            let (field_id, _) = milli::relative_from_absolute_position(position);
+            *fields_docids.entry(field_id).or_default() |= docids;
+        }
+    }
+    Ok(fields_docids)
+}
+
+/// Returns the fields of the attribute, the attribute itself and its nested fields, or `None`
+/// when the attribute isn't searchable.
+fn searchable_fields_of(
+    index: &Index,
+    rtxn: &RoTxn,
+    attribute: &str,
+) -> Result<Option<Vec<FieldId>>, MeilisearchHttpError> {
+    let is_searchable = index
+        .searchable_fields(rtxn)?
+        .map_or(true, |fields| fields.iter().any(|field| milli::is_faceted_by(attribute, field)));
+    if !is_searchable {
+        return Ok(None);
+    }
+    let fields_ids_map = index.fields_ids_map(rtxn)?;
+    Ok(Some(
+        fields_ids_map
+            .iter()
+            .filter(|(_, name)| milli::is_faceted_by(name, attribute))
+            .map(|(id, _)| id)
+            .collect(),
+    ))
+}
+
+/// Returns the documents containing a word of the query in one of the attributes, or `None`
+/// when the query has no word. The last word is a prefix unless the query ends with a space,
+/// the words are looked for without typos like the clauses restricted to an attribute.
+pub fn attributes_docids(
+    index: &Index,
+    rtxn: &RoTxn,
+    q: &str,
+    attributes: &[String],
+) -> Result<Option<RoaringBitmap>, MeilisearchHttpError> {
+    let mut field_ids = Vec::new();
+    for attribute in attributes {
+        match searchable_fields_of(index, rtxn, attribute)? {
+            Some(attribute_field_ids) => field_ids.extend(attribute_field_ids),
+            None => {
+                return Err(MeilisearchHttpError::InvalidAttributesToSearchOn(attribute.clone()))
+            }
+        }
+    }
+
+    let tokenizer = TokenizerBuilder::default().build();
+    let words: Vec<_> = tokenizer
+        .tokenize(q)
+        .filter(|token| token.is_word())
+        .map(|token| token.lemma().to_string())
+        .collect();
+    if words.is_empty() {
+        return Ok(None);
+    }
+    let last_is_prefix = !q.ends_with(char::is_whitespace);
+
+    let mut docids = RoaringBitmap::new();
+    for (i, word) in words.iter().enumerate() {
+        let is_prefix = last_is_prefix && i == words.len() - 1;
+        let fields_docids = word_fields_docids(index, rtxn, word, is_prefix)?;
+        for field_id in &field_ids {
+            if let Some(field_docids) = fields_docids.get(field_id) {
+                docids |= field_docids;
+            }
+        }
+    }
+    Ok(Some(docids))
+}
+
+/// Splits the quoted phrase at the start of the text from the rest of the text.
+fn split_phrase(text: &str) -> Result<(&str, &str), MeilisearchHttpError> {
+    match text[1..].find('"') {
+        Some(end) => Ok(text.split_at(end + 2)),
+        None => Err(MeilisearchHttpError::InvalidQuery(format!(
+            "the quote of `{}` is never closed",
+            text
+        ))),
+    }
+}
+
+/// Returns the documents matching the clause, the exact words only when `exact`.
+/// A clause restricted to an attribute must contain all its exact words in the attribute.
+fn clause_docids(
+    index: &Index,
+    rtxn: &RoTxn,
+    clause: &QueryClause,
+    exact: bool,
+) -> Result<RoaringBitmap, MeilisearchHttpError> {
+    let attribute = match clause.attribute {
+        Some(ref attribute) => attribute,
+        None => {
+            let mut search = index.search(rtxn);
+            if exact && !clause.term.starts_with('"') {
+                search.query(format!("\"{}\"", clause.term));
+            } else {
+                search.query(&clause.term);
+            }
+            search.terms_matching_strategy(TermsMatchingStrategy::All);
+            search.exhaustive_number_hits(true);
+            search.limit(0);
+            return Ok(search.execute()?.candidates);
+        }
+    };
+
+    let field_ids = match searchable_fields_of(index, rtxn, attribute)? {
+        Some(field_ids) => field_ids,
+        None => {
+            return Err(MeilisearchHttpError::InvalidQuery(format!(
+                "attribute `{}` is not searchable",
+                attribute
+            )))
+        }
+    };
+
+    let tokenizer = TokenizerBuilder::default().build();
+    let mut docids: Option<RoaringBitmap> = None;
+    for token in tokenizer.tokenize(&clause.term).filter(|token| token.is_word()) {
+        let fields_docids = word_fields_docids(index, rtxn, token.lemma(), false)?;
+        let word_docids = field_ids
+            .iter()
+            .filter_map(|field_id| fields_docids.get(field_id))
+            .fold(RoaringBitmap::new(), |acc, field_docids| acc | field_docids);
+        docids = Some(match docids {
+            Some(docids) => docids & word_docids,
+            None => word_docids,
+        });
+    }
+    Ok(docids.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn clause(term: &str, attribute: Option<&str>) -> QueryClause {
+        QueryClause { term: term.to_string(), attribute: attribute.map(str::to_string) }
+    }
+
+    #[test]
+    fn test_parse_query() {
+        let mut fields_ids_map = FieldsIdsMap::new();
+        fields_ids_map.insert("title");
+
+        let query =
+            ParsedQuery::parse("\"dark tower\" title:king -it stephen", &fields_ids_map).unwrap();
+        assert_eq!(query.milli_query(), "\"dark tower\" king stephen");
+        assert_eq!(
+            query.groups,
+            vec![
+                vec![clause("\"dark tower\"", None)],
+                vec![clause("king", Some("title"))],
+                vec![clause("stephen", None)],
+            ]
+        );
+        assert_eq!(query.excluded, vec![clause("it", None)]);
+
+        let query = ParsedQuery::parse("horror OR thriller novel", &fields_ids_map).unwrap();
+        assert_eq!(
+            query.groups,
+            vec![
+                vec![clause("horror", None), clause("thriller", None)],
+                vec![clause("novel", None)]
+            ]
+        );
+
+        // the attribute isn't a field of the index
+        let query = ParsedQuery::parse("author:king", &fields_ids_map).unwrap();
+        assert_eq!(query.groups, vec![vec![clause("author:king", None)]]);
+
+        assert!(ParsedQuery::parse("OR horror", &fields_ids_map).is_err());
+        assert!(ParsedQuery::parse("horror OR -thriller", &fields_ids_map).is_err());
+        assert!(ParsedQuery::parse("\"dark tower", &fields_ids_map).is_err());
+    }
+
+    #[test]
+    fn test_merge_by_rank() {
+        let branches_hits = [vec![1, 2, 3, 4], vec![5, 2, 6], vec![]];
+        assert_eq!(merge_by_rank(&branches_hits, 10), [1, 5, 2, 3, 6, 4]);
+        assert_eq!(merge_by_rank(&branches_hits, 3), [1, 5, 2]);
+        assert!(merge_by_rank(&[], 3).is_empty());
+    }
+}
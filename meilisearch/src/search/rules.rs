@@ -0,0 +1,279 @@
+//! The query rules of an index, applied to the searches meeting their conditions.
+
+use meilisearch_types::heed::RoTxn;
+use meilisearch_types::milli::{DocumentId, Index};
+use meilisearch_types::settings::{self, RuleConditions};
+use roaring::RoaringBitmap;
+use serde_json::Value;
+
+use super::filtered_documents;
+use crate::error::MeilisearchHttpError;
+
+/// The consequences of the query rules whose conditions are met by a search.
+pub struct FiredRules {
+    uids: Vec<String>,
+    /// The filters of the rules, they must all match.
+    filter: Option<Value>,
+    /// The documents matching a boost filter of the rules.
+    boosted: Option<RoaringBitmap>,
+    hidden: RoaringBitmap,
+    /// The pinned documents along with their position, starting at 0.
+    pinned: Vec<(usize, DocumentId)>,
+}
+
+impl FiredRules {
+    pub fn new(
+        index: &Index,
+        rtxn: &RoTxn,
+        q: Option<&str>,
+        filter: Option<&Value>,
+    ) -> Result<Self, MeilisearchHttpError> {
+        let mut fired_rules = FiredRules {
+            uids: Vec::new(),
+            filter: None,
+            boosted: None,
+            hidden: RoaringBitmap::new(),
+            pinned: Vec::new(),
+        };
+
+        let rules = settings::rules(index, rtxn)?;
+        let rules: Vec<_> = rules
+            .into_iter()
+            .filter(|rule| conditions_are_met(&rule.conditions, q, filter))
+            .collect();
+        if rules.is_empty() {
+            return Ok(fired_rules);
+        }
+
+        let external_documents_ids = index.external_documents_ids(rtxn)?;
+        let mut filters = Vec::new();
+        for rule in rules {
+            let consequences = rule.consequences;
+            if let Some(filter) = consequences.filter {
+                filters.push(Value::String(filter));
+            }
+            if let Some(boost) = consequences.boost {
+                let boosted = filtered_documents(index, rtxn, Some(&Value::String(boost)))?;
+                *fired_rules.boosted.get_or_insert_with(RoaringBitmap::new) |= boosted;
+            }
+            // the unknown documents are ignored.
+            for id in consequences.hide {
+                if let Some(docid) = external_documents_ids.get(id) {
+                    fired_rules.hidden.insert(docid);
+                }
+            }
+            for pinned in consequences.pin {
+                if let Some(docid) = external_documents_ids.get(pinned.id) {
+                    fired_rules.pinned.push((pinned.position.saturating_sub(1), docid));
+                }
+            }
+            fired_rules.uids.push(rule.uid);
+        }
+        if !filters.is_empty() {
+            fired_rules.filter = Some(Value::Array(filters));
+        }
+        fired_rules.pinned.sort_by_key(|(position, _)| *position);
+
+        Ok(fired_rules)
+    }
+
+    /// Returns the filter of the search along with the filters of the rules.
+    pub fn filter(&self, filter: Option<Value>) -> Option<Value> {
+        match self.filter.clone() {
+            Some(rules_filter) => Some(and_filters(filter, rules_filter)),
+            None => filter,
+        }
+    }
+
+    /// Whether the hits must be reordered, the hidden documents being removed from them.
+    pub fn reranks(&self) -> bool {
+        self.boosted.is_some() || !self.hidden.is_empty() || !self.pinned.is_empty()
+    }
+
+    /// The number of hidden documents, more hits must be fetched to replace them.
+    pub fn hidden_len(&self) -> usize {
+        self.hidden.len() as usize
+    }
+
+    /// The uids of the rules, in the order they were given.
+    pub fn into_uids(self) -> Vec<String> {
+        self.uids
+    }
+
+    /// Applies the rules to the hits of a search with the given filter, see [`Self::rerank`].
+    pub fn apply(
+        &self,
+        index: &Index,
+        rtxn: &RoTxn,
+        filter: Option<&Value>,
+        documents_ids: Vec<DocumentId>,
+        candidates: RoaringBitmap,
+        reranked: bool,
+    ) -> Result<(Vec<DocumentId>, RoaringBitmap), MeilisearchHttpError> {
+        let pinned_universe = match filter {
+            Some(filter) if !self.pinned.is_empty() => {
+                Some(filtered_documents(index, rtxn, Some(filter))?)
+            }
+            _ => None,
+        };
+        Ok(self.rerank(documents_ids, candidates, reranked, pinned_universe.as_ref()))
+    }
+
+    /// Removes the hidden documents from the hits and, when `reranked`, moves the boosted
+    /// documents first and inserts the pinned documents matching the filter at their position.
+    fn rerank(
+        &self,
+        documents_ids: Vec<DocumentId>,
+        candidates: RoaringBitmap,
+        reranked: bool,
+        pinned_universe: Option<&RoaringBitmap>,
+    ) -> (Vec<DocumentId>, RoaringBitmap) {
+        let mut documents_ids: Vec<_> =
+            documents_ids.into_iter().filter(|docid| !self.hidden.contains(*docid)).collect();
+        let mut candidates = candidates - &self.hidden;
+        if !reranked {
+            return (documents_ids, candidates);
+        }
+
+        if let Some(ref boosted) = self.boosted {
+            // the sort is stable, the ranking is kept among the boosted documents.
+            documents_ids.sort_by_key(|docid| !boosted.contains(*docid));
+        }
+
+        let pinned: Vec<_> = self
+            .pinned
+            .iter()
+            .filter(|(_, docid)| !self.hidden.contains(*docid))
+            .filter(|(_, docid)| pinned_universe.map_or(true, |universe| universe.contains(*docid)))
+            .collect();
+        documents_ids.retain(|docid| !pinned.iter().any(|(_, pinned)| pinned == docid));
+        for &&(position, docid) in &pinned {
+            if !documents_ids.contains(&docid) {
+                documents_ids.insert(position.min(documents_ids.len()), docid);
+                candidates.insert(docid);
+            }
+        }
+
+        (documents_ids, candidates)
+    }
+}
+
+/// Whether a search meets all the conditions of a rule.
+fn conditions_are_met(
+    conditions: &RuleConditions,
+    q: Option<&str>,
+    filter: Option<&Value>,
+) -> bool {
+    let q = normalize_words(q.unwrap_or_default());
+    if let Some(ref query_is) = conditions.query_is {
+        if q != normalize_words(query_is) {
+            return false;
+        }
+    }
+    if let Some(ref query_contains) = conditions.query_contains {
+        let query_contains = normalize_words(query_contains);
+        if query_contains.is_empty()
+            || !format!(" {} ", q).contains(&format!(" {} ", query_contains))
+        {
+            return false;
+        }
+    }
+    if let Some(ref filter_active) = conditions.filter_active {
+        let filter_active = normalize_words(filter_active);
+        let mut expressions = Vec::new();
+        if let Some(filter) = filter {
+            filter_expressions(filter, &mut expressions);
+        }
+        if !expressions
+            .iter()
+            .any(|expression| normalize_words(expression).contains(&filter_active))
+        {
+            return false;
+        }
+    }
+    true
+}
+
+/// Lowercases the words of a string and separates them with a single space.
+fn normalize_words(s: &str) -> String {
+    s.split_whitespace().map(str::to_lowercase).collect::<Vec<_>>().join(" ")
+}
+
+/// Collects the expressions of a filter, whatever their nesting in arrays.
+fn filter_expressions<'a>(filter: &'a Value, expressions: &mut Vec<&'a str>) {
+    match filter {
+        Value::String(expression) => expressions.push(expression),
+        Value::Array(filters) => {
+            filters.iter().for_each(|filter| filter_expressions(filter, expressions))
+        }
+        _ => (),
+    }
+}
+
+/// Returns a filter matching both filters.
+fn and_filters(filter: Option<Value>, other: Value) -> Value {
+    let filter = match filter {
+        Some(Value::Array(filter)) => filter,
+        Some(filter) => vec![filter],
+        None => Vec::new(),
+    };
+    let other = match other {
+        Value::Array(other) => other,
+        other => vec![other],
+    };
+    Value::Array([filter, other].concat())
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_conditions() {
+        let conditions = |value: Value| serde_json::from_value::<RuleConditions>(value).unwrap();
+
+        let query_is = conditions(json!({ "queryIs": "IPhone  case" }));
+        assert!(conditions_are_met(&query_is, Some("iphone case"), None));
+        assert!(!conditions_are_met(&query_is, Some("iphone case blue"), None));
+
+        let query_contains = conditions(json!({ "queryContains": "case" }));
+        assert!(conditions_are_met(&query_contains, Some("iphone case blue"), None));
+        assert!(!conditions_are_met(&query_contains, Some("iphone cases"), None));
+        assert!(!conditions_are_met(&query_contains, None, None));
+
+        let filter_active = conditions(json!({ "filterActive": "category = phone" }));
+        let filter = json!(["price < 100", ["category = PHONE", "category = tablet"]]);
+        assert!(conditions_are_met(&filter_active, None, Some(&filter)));
+        assert!(!conditions_are_met(&filter_active, None, Some(&json!("price < 100"))));
+    }
+
+    #[test]
+    fn test_rerank() {
+        let fired_rules = FiredRules {
+            uids: vec!["rule".to_string()],
+            filter: Some(json!(["category = phone"])),
+            boosted: Some(RoaringBitmap::from_iter([3])),
+            hidden: RoaringBitmap::from_iter([2]),
+            pinned: vec![(0, 5), (1, 6)],
+        };
+        assert_eq!(
+            fired_rules.filter(Some(json!("price < 100"))),
+            Some(json!(["price < 100", "category = phone"]))
+        );
+
+        let candidates = RoaringBitmap::from_iter([1, 2, 3, 4]);
+        let (documents_ids, candidates) =
+            fired_rules.rerank(vec![1, 2, 3, 4], candidates.clone(), false, None);
+        assert_eq!(documents_ids, [1, 3, 4]);
+        assert_eq!(candidates, RoaringBitmap::from_iter([1, 3, 4]));
+
+        // the document 6 doesn't match the filter of the search.
+        let universe = RoaringBitmap::from_iter([1, 2, 3, 4, 5]);
+        let (documents_ids, candidates) =
+            fired_rules.rerank(vec![1, 2, 3, 4], candidates, true, Some(&universe));
+        assert_eq!(documents_ids, [5, 3, 1, 4]);
+        assert_eq!(candidates, RoaringBitmap::from_iter([1, 3, 4, 5]));
+    }
+}
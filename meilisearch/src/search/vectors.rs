@@ -0,0 +1,140 @@
+//! The vector and hybrid searches, the vectors are stored by `meilisearch_types::vectors`.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use meilisearch_types::heed::RoTxn;
+use meilisearch_types::milli::{DocumentId, Index};
+use meilisearch_types::vectors;
+use roaring::RoaringBitmap;
+use serde_json::Value;
+
+use super::{filtered_documents, SearchQuery, DEFAULT_SEMANTIC_RATIO};
+use crate::error::MeilisearchHttpError;
+
+/// The `vector` of a search along with its `semanticRatio`.
+pub struct VectorSearch<'a> {
+    vector: &'a [f32],
+    semantic_ratio: f32,
+    /// Whether the hits of `q` are ignored, only the nearest neighbours are returned.
+    semantic_only: bool,
+}
+
+impl<'a> VectorSearch<'a> {
+    /// Returns `None` when there is no vector or when it's ignored, given along with a query
+    /// with a `semanticRatio` of 0.
+    pub fn new(
+        index: &Index,
+        rtxn: &RoTxn,
+        query: &'a SearchQuery,
+    ) -> Result<Option<Self>, MeilisearchHttpError> {
+        let semantic_ratio = query.semantic_ratio.unwrap_or(DEFAULT_SEMANTIC_RATIO);
+        if !(0.0..=1.0).contains(&semantic_ratio) {
+            return Err(MeilisearchHttpError::InvalidSemanticRatio(semantic_ratio));
+        }
+        let vector = match query.vector {
+            Some(ref vector) => vector,
+            None => return Ok(None),
+        };
+        match vectors::dimensions(index, rtxn)? {
+            Some(dimensions) if dimensions != vector.len() => {
+                return Err(MeilisearchHttpError::InvalidVector(format!(
+                    "the vectors of the index have {} dimensions but it has {}",
+                    dimensions,
+                    vector.len()
+                )))
+            }
+            _ if vector.iter().any(|n| !n.is_finite()) => {
+                return Err(MeilisearchHttpError::InvalidVector(
+                    "it must only contain finite numbers".to_string(),
+                ))
+            }
+            _ => (),
+        }
+
+        // The query is ignored when the ratio is 1.
+        match query.q {
+            Some(_) if semantic_ratio == 0.0 => Ok(None),
+            Some(_) => Ok(Some(VectorSearch {
+                vector,
+                semantic_ratio,
+                semantic_only: semantic_ratio == 1.0,
+            })),
+            None => Ok(Some(VectorSearch { vector, semantic_ratio, semantic_only: true })),
+        }
+    }
+
+    pub fn semantic_only(&self) -> bool {
+        self.semantic_only
+    }
+
+    /// Returns the first `limit` hits, the hits of milli blended with the nearest neighbours
+    /// of the vector among the documents matching the filter, along with the candidates.
+    pub fn apply(
+        &self,
+        index: &Index,
+        rtxn: &RoTxn,
+        filter: Option<&Value>,
+        documents_ids: Vec<DocumentId>,
+        candidates: RoaringBitmap,
+        limit: usize,
+    ) -> Result<(Vec<DocumentId>, RoaringBitmap), MeilisearchHttpError> {
+        // The placeholder search already returns every document matching the filter.
+        let universe = if self.semantic_only {
+            candidates.clone()
+        } else {
+            filtered_documents(index, rtxn, filter)?
+        };
+        let neighbors = vectors::nearest_neighbors(index, rtxn, self.vector, &universe, limit)?;
+        let vectors_candidates = universe & vectors::vectors_docids(index, rtxn)?;
+
+        if self.semantic_only {
+            Ok((neighbors.into_iter().map(|(docid, _)| docid).collect(), vectors_candidates))
+        } else {
+            Ok((
+                blend_hits(&documents_ids, &neighbors, self.semantic_ratio),
+                candidates | vectors_candidates,
+            ))
+        }
+    }
+}
+
+/// Merges the hits of the query with the nearest neighbours of the vector. A hit is scored by
+/// the weighted sum of its rank among the hits of the query and its similarity to the vector.
+fn blend_hits(
+    keyword_hits: &[DocumentId],
+    semantic_hits: &[(DocumentId, f32)],
+    semantic_ratio: f32,
+) -> Vec<DocumentId> {
+    let mut scores = HashMap::<DocumentId, f32>::new();
+    let keyword_len = keyword_hits.len() as f32;
+    for (rank, docid) in keyword_hits.iter().enumerate() {
+        *scores.entry(*docid).or_default() +=
+            (1.0 - semantic_ratio) * (1.0 - rank as f32 / keyword_len);
+    }
+    for (docid, similarity) in semantic_hits {
+        *scores.entry(*docid).or_default() += semantic_ratio * (similarity + 1.0) / 2.0;
+    }
+
+    let mut hits: Vec<_> = scores.into_iter().collect();
+    hits.sort_by(|(left_id, left), (right_id, right)| {
+        right.partial_cmp(left).unwrap_or(Ordering::Equal).then(left_id.cmp(right_id))
+    });
+    hits.into_iter().map(|(docid, _)| docid).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_blend_hits() {
+        let keyword_hits = [1, 2, 3];
+        let semantic_hits = [(4, 1.0), (3, 0.9), (1, -1.0)];
+
+        assert_eq!(blend_hits(&keyword_hits, &semantic_hits, 0.0)[..3], [1, 2, 3]);
+        assert_eq!(blend_hits(&keyword_hits, &semantic_hits, 1.0)[..2], [4, 3]);
+        // the third keyword hit is also very close to the vector.
+        assert_eq!(blend_hits(&keyword_hits, &semantic_hits, 0.5)[0], 3);
+    }
+}
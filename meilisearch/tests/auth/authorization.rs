@@ -13,6 +13,10 @@ pub static AUTHORIZATIONS: Lazy<HashMap<(&'static str, &'static str), HashSet<&'
         let mut authorizations = hashmap! {
             ("POST",    "/indexes/products/search") =>                         hashset!{"search", "*"},
             ("GET",     "/indexes/products/search") =>                         hashset!{"search", "*"},
+            ("POST",    "/indexes/products/facet-search") =>                   hashset!{"search", "*"},
+            ("POST",    "/indexes/products/similar") =>                        hashset!{"search", "*"},
+            ("POST",    "/indexes/products/suggestions") =>                    hashset!{"search", "*"},
+            ("POST",    "/events") =>                                          hashset!{"search", "*"},
             ("POST",    "/indexes/products/documents") =>                      hashset!{"documents.add", "documents.*", "*"},
             ("GET",     "/indexes/products/documents") =>                      hashset!{"documents.get", "documents.*", "*"},
             ("GET",     "/indexes/products/documents/0") =>                    hashset!{"documents.get", "documents.*", "*"},
@@ -35,10 +39,15 @@ pub static AUTHORIZATIONS: Lazy<HashMap<(&'static str, &'static str), HashSet<&'
             ("GET",     "/indexes/products/settings/searchable-attributes") => hashset!{"settings.get", "settings.*", "*"},
             ("GET",     "/indexes/products/settings/sortable-attributes") =>   hashset!{"settings.get", "settings.*", "*"},
             ("GET",     "/indexes/products/settings/stop-words") =>            hashset!{"settings.get", "settings.*", "*"},
+            ("GET",     "/indexes/products/settings/stop-words-presets") =>    hashset!{"settings.get", "settings.*", "*"},
+            ("GET",     "/indexes/products/settings/history") =>               hashset!{"settings.get", "settings.*", "*"},
             ("GET",     "/indexes/products/settings/synonyms") =>              hashset!{"settings.get", "settings.*", "*"},
             ("DELETE",  "/indexes/products/settings") =>                       hashset!{"settings.update", "settings.*", "*"},
             ("PATCH",   "/indexes/products/settings") =>                       hashset!{"settings.update", "settings.*", "*"},
+            ("POST",    "/indexes/products/settings/rollback/1") =>            hashset!{"settings.update", "settings.*", "*"},
             ("PATCH",   "/indexes/products/settings/typo-tolerance") =>        hashset!{"settings.update", "settings.*", "*"},
+            ("PATCH",   "/indexes/products/settings/stop-words") =>            hashset!{"settings.update", "settings.*", "*"},
+            ("PATCH",   "/indexes/products/settings/synonyms") =>              hashset!{"settings.update", "settings.*", "*"},
             ("PUT",     "/indexes/products/settings/displayed-attributes") =>  hashset!{"settings.update", "settings.*", "*"},
             ("PUT",     "/indexes/products/settings/distinct-attribute") =>    hashset!{"settings.update", "settings.*", "*"},
             ("PUT",     "/indexes/products/settings/filterable-attributes") => hashset!{"settings.update", "settings.*", "*"},
@@ -46,10 +55,13 @@ pub static AUTHORIZATIONS: Lazy<HashMap<(&'static str, &'static str), HashSet<&'
             ("PUT",     "/indexes/products/settings/searchable-attributes") => hashset!{"settings.update", "settings.*", "*"},
             ("PUT",     "/indexes/products/settings/sortable-attributes") =>   hashset!{"settings.update", "settings.*", "*"},
             ("PUT",     "/indexes/products/settings/stop-words") =>            hashset!{"settings.update", "settings.*", "*"},
+            ("PUT",     "/indexes/products/settings/stop-words-presets") =>    hashset!{"settings.update", "settings.*", "*"},
             ("PUT",     "/indexes/products/settings/synonyms") =>              hashset!{"settings.update", "settings.*", "*"},
             ("GET",     "/indexes/products/stats") =>                          hashset!{"stats.get", "stats.*", "*"},
+            ("GET",     "/indexes/products/analytics") =>                      hashset!{"stats.get", "stats.*", "*"},
             ("GET",     "/stats") =>                                           hashset!{"stats.get", "stats.*", "*"},
             ("POST",    "/dumps") =>                                           hashset!{"dumps.create", "dumps.*", "*"},
+            ("POST",    "/dumps/import") =>                                    hashset!{"dumps.import", "dumps.*", "*"},
             ("GET",     "/version") =>                                         hashset!{"version", "*"},
             ("PATCH",   "/keys/mykey/") =>                                     hashset!{"keys.update", "*"},
             ("GET",     "/keys/mykey/") =>                                     hashset!{"keys.get", "*"},
@@ -182,7 +194,11 @@ async fn access_authorized_master_key() {
 #[actix_rt::test]
 async fn access_authorized_restricted_index() {
     let mut server = Server::new_auth().await;
-    for ((method, route), actions) in AUTHORIZATIONS.iter() {
+    for ((method, route), actions) in AUTHORIZATIONS
+        .iter()
+        // importing a dump requires an access to all the indexes.
+        .filter(|((_, route), _)| *route != "/dumps/import")
+    {
         for action in actions {
             // create a new API key letting only the needed action.
             server.use_api_key("MASTER_KEY");
@@ -215,6 +231,31 @@ async fn access_authorized_restricted_index() {
     }
 }
 
+#[actix_rt::test]
+async fn error_import_dump_restricted_index() {
+    let mut server = Server::new_auth().await;
+    for indexes in [json!(["products"]), json!(["*"])] {
+        server.use_api_key("MASTER_KEY");
+        let content = json!({
+            "indexes": indexes,
+            "actions": ["dumps.import"],
+            "expiresAt": (OffsetDateTime::now_utc() + Duration::hours(1)).format(&Rfc3339).unwrap(),
+        });
+        let (response, code) = server.add_api_key(content).await;
+        assert_eq!(201, code, "{:?}", &response);
+
+        server.use_api_key(response["key"].as_str().unwrap());
+        let (response, code) = server.dummy_request("POST", "/dumps/import").await;
+        if indexes == json!(["*"]) {
+            assert_ne!(response, INVALID_RESPONSE.clone());
+            assert_ne!(code, 403);
+        } else {
+            assert_eq!(response, INVALID_RESPONSE.clone());
+            assert_eq!(code, 403);
+        }
+    }
+}
+
 #[actix_rt::test]
 async fn access_authorized_no_index_restriction() {
     let mut server = Server::new_auth().await;
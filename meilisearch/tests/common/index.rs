@@ -185,6 +185,11 @@ impl Index<'_> {
         self.service.get(url).await
     }
 
+    pub async fn analytics(&self) -> (Value, StatusCode) {
+        let url = format!("/indexes/{}/analytics", urlencode(self.uid.as_ref()));
+        self.service.get(url).await
+    }
+
     /// Performs both GET and POST search queries
     pub async fn search(
         &self,
@@ -210,6 +215,21 @@ impl Index<'_> {
         self.service.post_encoded(url, query, self.encoder).await
     }
 
+    pub async fn facet_search(&self, query: Value) -> (Value, StatusCode) {
+        let url = format!("/indexes/{}/facet-search", urlencode(self.uid.as_ref()));
+        self.service.post_encoded(url, query, self.encoder).await
+    }
+
+    pub async fn similar(&self, query: Value) -> (Value, StatusCode) {
+        let url = format!("/indexes/{}/similar", urlencode(self.uid.as_ref()));
+        self.service.post_encoded(url, query, self.encoder).await
+    }
+
+    pub async fn suggestions(&self, query: Value) -> (Value, StatusCode) {
+        let url = format!("/indexes/{}/suggestions", urlencode(self.uid.as_ref()));
+        self.service.post_encoded(url, query, self.encoder).await
+    }
+
     pub async fn search_get(&self, query: Value) -> (Value, StatusCode) {
         let params = yaup::to_string(&query).unwrap();
         let url = format!("/indexes/{}/search?{}", urlencode(self.uid.as_ref()), params);
@@ -10,6 +10,7 @@ use byte_unit::{Byte, ByteUnit};
 use clap::Parser;
 use meilisearch::option::{IndexerOpts, MaxMemory, Opt};
 use meilisearch::{analytics, create_app, setup_meilisearch};
+use meilisearch_types::tasks::KindWithContent;
 use once_cell::sync::Lazy;
 use serde_json::{json, Value};
 use tempfile::TempDir;
@@ -144,6 +145,18 @@ impl Server {
         self.service.post("/dumps", json!(null)).await
     }
 
+    pub async fn create_dump_with(&self, value: Value) -> (Value, StatusCode) {
+        self.service.post("/dumps", value).await
+    }
+
+    pub async fn import_dump(&self, value: Value) -> (Value, StatusCode) {
+        self.service.post("/dumps/import", value).await
+    }
+
+    pub async fn send_event(&self, value: Value) -> (Value, StatusCode) {
+        self.service.post("/events", value).await
+    }
+
     pub async fn index_swap(&self, value: Value) -> (Value, StatusCode) {
         self.service.post("/swap-indexes", value).await
     }
@@ -179,6 +192,13 @@ impl Server {
         let url = format!("/tasks/{}", update_id);
         self.service.get(url).await
     }
+
+    /// Registers a suggestions refresh like the periodic scheduler does, and returns its uid.
+    pub fn register_suggestions_refresh(&self) -> u64 {
+        let task =
+            self.service.index_scheduler.register(KindWithContent::SuggestionsRefresh).unwrap();
+        task.uid as u64
+    }
 }
 
 pub fn default_settings(dir: impl AsRef<Path>) -> Opt {
@@ -82,6 +82,19 @@ impl Service {
         self.request(req).await
     }
 
+    /// Send a test patch request from a text body, with a `content-type:text/plain` header.
+    pub async fn patch_text(
+        &self,
+        url: impl AsRef<str>,
+        body: impl AsRef<str>,
+    ) -> (Value, StatusCode) {
+        let req = test::TestRequest::patch()
+            .uri(url.as_ref())
+            .set_payload(body.as_ref().to_string())
+            .insert_header(("content-type", "text/plain"));
+        self.request(req).await
+    }
+
     pub async fn delete(&self, url: impl AsRef<str>) -> (Value, StatusCode) {
         let req = test::TestRequest::delete().uri(url.as_ref());
         self.request(req).await
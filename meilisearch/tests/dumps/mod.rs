@@ -1,5 +1,7 @@
 mod data;
 
+use actix_web::test;
+use byte_unit::{Byte, ByteUnit};
 use meilisearch::Opt;
 use serde_json::json;
 
@@ -35,7 +37,7 @@ async fn import_dump_v1_movie_raw() {
     assert_eq!(code, 200);
     assert_eq!(
         settings,
-        json!({"displayedAttributes": ["*"], "searchableAttributes": ["*"], "filterableAttributes": [], "sortableAttributes": [], "rankingRules": ["typo", "words", "proximity", "attribute", "exactness"], "stopWords": [], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": {"oneTypo": 5, "twoTypos": 9}, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100 }, "pagination": { "maxTotalHits": 1000 } })
+        json!({"displayedAttributes": ["*"], "searchableAttributes": ["*"], "filterableAttributes": [], "sortableAttributes": [], "rankingRules": ["typo", "words", "proximity", "attribute", "exactness"], "stopWords": [], "stopWordsPresets": [], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": {"oneTypo": 5, "twoTypos": 9}, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100, "sortFacetValuesBy": { "*": "alpha" } }, "pagination": { "maxTotalHits": 1000 }, "rules": [], "searchCutoffMs": null })
     );
 
     let (tasks, code) = index.list_tasks().await;
@@ -98,7 +100,7 @@ async fn import_dump_v1_movie_with_settings() {
     assert_eq!(code, 200);
     assert_eq!(
         settings,
-        json!({ "displayedAttributes": ["genres", "id", "overview", "poster", "release_date", "title"], "searchableAttributes": ["title", "overview"], "filterableAttributes": ["genres"], "sortableAttributes": [], "rankingRules": ["typo", "words", "proximity", "attribute", "exactness"], "stopWords": ["of", "the"], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": { "oneTypo": 5, "twoTypos": 9 }, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100 }, "pagination": { "maxTotalHits": 1000 } })
+        json!({ "displayedAttributes": ["genres", "id", "overview", "poster", "release_date", "title"], "searchableAttributes": ["title", "overview"], "filterableAttributes": ["genres"], "sortableAttributes": [], "rankingRules": ["typo", "words", "proximity", "attribute", "exactness"], "stopWords": ["of", "the"], "stopWordsPresets": [], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": { "oneTypo": 5, "twoTypos": 9 }, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100, "sortFacetValuesBy": { "*": "alpha" } }, "pagination": { "maxTotalHits": 1000 }, "rules": [], "searchCutoffMs": null })
     );
 
     let (tasks, code) = index.list_tasks().await;
@@ -161,7 +163,7 @@ async fn import_dump_v1_rubygems_with_settings() {
     assert_eq!(code, 200);
     assert_eq!(
         settings,
-        json!({"displayedAttributes": ["description", "id", "name", "summary", "total_downloads", "version"], "searchableAttributes": ["name", "summary"], "filterableAttributes": ["version"], "sortableAttributes": [], "rankingRules": ["typo", "words", "fame:desc", "proximity", "attribute", "exactness", "total_downloads:desc"], "stopWords": [], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": {"oneTypo": 5, "twoTypos": 9}, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100 }, "pagination": { "maxTotalHits": 1000 }})
+        json!({"displayedAttributes": ["description", "id", "name", "summary", "total_downloads", "version"], "searchableAttributes": ["name", "summary"], "filterableAttributes": ["version"], "sortableAttributes": [], "rankingRules": ["typo", "words", "fame:desc", "proximity", "attribute", "exactness", "total_downloads:desc"], "stopWords": [], "stopWordsPresets": [], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": {"oneTypo": 5, "twoTypos": 9}, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100, "sortFacetValuesBy": { "*": "alpha" } }, "pagination": { "maxTotalHits": 1000 }, "rules": [], "searchCutoffMs": null})
     );
 
     let (tasks, code) = index.list_tasks().await;
@@ -222,7 +224,7 @@ async fn import_dump_v2_movie_raw() {
     assert_eq!(code, 200);
     assert_eq!(
         settings,
-        json!({"displayedAttributes": ["*"], "searchableAttributes": ["*"], "filterableAttributes": [], "sortableAttributes": [], "rankingRules": ["words", "typo", "proximity", "attribute", "exactness"], "stopWords": [], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": {"oneTypo": 5, "twoTypos": 9}, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100 }, "pagination": { "maxTotalHits": 1000 } })
+        json!({"displayedAttributes": ["*"], "searchableAttributes": ["*"], "filterableAttributes": [], "sortableAttributes": [], "rankingRules": ["words", "typo", "proximity", "attribute", "exactness"], "stopWords": [], "stopWordsPresets": [], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": {"oneTypo": 5, "twoTypos": 9}, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100, "sortFacetValuesBy": { "*": "alpha" } }, "pagination": { "maxTotalHits": 1000 }, "rules": [], "searchCutoffMs": null })
     );
 
     let (tasks, code) = index.list_tasks().await;
@@ -285,7 +287,7 @@ async fn import_dump_v2_movie_with_settings() {
     assert_eq!(code, 200);
     assert_eq!(
         settings,
-        json!({ "displayedAttributes": ["title", "genres", "overview", "poster", "release_date"], "searchableAttributes": ["title", "overview"], "filterableAttributes": ["genres"], "sortableAttributes": [], "rankingRules": ["words", "typo", "proximity", "attribute", "exactness"], "stopWords": ["of", "the"], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": { "oneTypo": 5, "twoTypos": 9 }, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100 }, "pagination": { "maxTotalHits": 1000 } })
+        json!({ "displayedAttributes": ["title", "genres", "overview", "poster", "release_date"], "searchableAttributes": ["title", "overview"], "filterableAttributes": ["genres"], "sortableAttributes": [], "rankingRules": ["words", "typo", "proximity", "attribute", "exactness"], "stopWords": ["of", "the"], "stopWordsPresets": [], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": { "oneTypo": 5, "twoTypos": 9 }, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100, "sortFacetValuesBy": { "*": "alpha" } }, "pagination": { "maxTotalHits": 1000 }, "rules": [], "searchCutoffMs": null })
     );
 
     let (tasks, code) = index.list_tasks().await;
@@ -348,7 +350,7 @@ async fn import_dump_v2_rubygems_with_settings() {
     assert_eq!(code, 200);
     assert_eq!(
         settings,
-        json!({"displayedAttributes": ["name", "summary", "description", "version", "total_downloads"], "searchableAttributes": ["name", "summary"], "filterableAttributes": ["version"], "sortableAttributes": [], "rankingRules": ["typo", "words", "fame:desc", "proximity", "attribute", "exactness", "total_downloads:desc"], "stopWords": [], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": {"oneTypo": 5, "twoTypos": 9}, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100 }, "pagination": { "maxTotalHits": 1000 }})
+        json!({"displayedAttributes": ["name", "summary", "description", "version", "total_downloads"], "searchableAttributes": ["name", "summary"], "filterableAttributes": ["version"], "sortableAttributes": [], "rankingRules": ["typo", "words", "fame:desc", "proximity", "attribute", "exactness", "total_downloads:desc"], "stopWords": [], "stopWordsPresets": [], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": {"oneTypo": 5, "twoTypos": 9}, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100, "sortFacetValuesBy": { "*": "alpha" } }, "pagination": { "maxTotalHits": 1000 }, "rules": [], "searchCutoffMs": null})
     );
 
     let (tasks, code) = index.list_tasks().await;
@@ -409,7 +411,7 @@ async fn import_dump_v3_movie_raw() {
     assert_eq!(code, 200);
     assert_eq!(
         settings,
-        json!({"displayedAttributes": ["*"], "searchableAttributes": ["*"], "filterableAttributes": [], "sortableAttributes": [], "rankingRules": ["words", "typo", "proximity", "attribute", "exactness"], "stopWords": [], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": {"oneTypo": 5, "twoTypos": 9}, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100 }, "pagination": { "maxTotalHits": 1000 } })
+        json!({"displayedAttributes": ["*"], "searchableAttributes": ["*"], "filterableAttributes": [], "sortableAttributes": [], "rankingRules": ["words", "typo", "proximity", "attribute", "exactness"], "stopWords": [], "stopWordsPresets": [], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": {"oneTypo": 5, "twoTypos": 9}, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100, "sortFacetValuesBy": { "*": "alpha" } }, "pagination": { "maxTotalHits": 1000 }, "rules": [], "searchCutoffMs": null })
     );
 
     let (tasks, code) = index.list_tasks().await;
@@ -472,7 +474,7 @@ async fn import_dump_v3_movie_with_settings() {
     assert_eq!(code, 200);
     assert_eq!(
         settings,
-        json!({ "displayedAttributes": ["title", "genres", "overview", "poster", "release_date"], "searchableAttributes": ["title", "overview"], "filterableAttributes": ["genres"], "sortableAttributes": [], "rankingRules": ["words", "typo", "proximity", "attribute", "exactness"], "stopWords": ["of", "the"], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": { "oneTypo": 5, "twoTypos": 9 }, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100 }, "pagination": { "maxTotalHits": 1000 } })
+        json!({ "displayedAttributes": ["title", "genres", "overview", "poster", "release_date"], "searchableAttributes": ["title", "overview"], "filterableAttributes": ["genres"], "sortableAttributes": [], "rankingRules": ["words", "typo", "proximity", "attribute", "exactness"], "stopWords": ["of", "the"], "stopWordsPresets": [], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": { "oneTypo": 5, "twoTypos": 9 }, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100, "sortFacetValuesBy": { "*": "alpha" } }, "pagination": { "maxTotalHits": 1000 }, "rules": [], "searchCutoffMs": null })
     );
 
     let (tasks, code) = index.list_tasks().await;
@@ -535,7 +537,7 @@ async fn import_dump_v3_rubygems_with_settings() {
     assert_eq!(code, 200);
     assert_eq!(
         settings,
-        json!({"displayedAttributes": ["name", "summary", "description", "version", "total_downloads"], "searchableAttributes": ["name", "summary"], "filterableAttributes": ["version"], "sortableAttributes": [], "rankingRules": ["typo", "words", "fame:desc", "proximity", "attribute", "exactness", "total_downloads:desc"], "stopWords": [], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": {"oneTypo": 5, "twoTypos": 9}, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100 }, "pagination": { "maxTotalHits": 1000 } })
+        json!({"displayedAttributes": ["name", "summary", "description", "version", "total_downloads"], "searchableAttributes": ["name", "summary"], "filterableAttributes": ["version"], "sortableAttributes": [], "rankingRules": ["typo", "words", "fame:desc", "proximity", "attribute", "exactness", "total_downloads:desc"], "stopWords": [], "stopWordsPresets": [], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": {"oneTypo": 5, "twoTypos": 9}, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100, "sortFacetValuesBy": { "*": "alpha" } }, "pagination": { "maxTotalHits": 1000 }, "rules": [], "searchCutoffMs": null })
     );
 
     let (tasks, code) = index.list_tasks().await;
@@ -596,7 +598,7 @@ async fn import_dump_v4_movie_raw() {
     assert_eq!(code, 200);
     assert_eq!(
         settings,
-        json!({ "displayedAttributes": ["*"], "searchableAttributes": ["*"], "filterableAttributes": [], "sortableAttributes": [], "rankingRules": ["words", "typo", "proximity", "attribute", "exactness"], "stopWords": [], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": {"oneTypo": 5, "twoTypos": 9}, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100 }, "pagination": { "maxTotalHits": 1000 } })
+        json!({ "displayedAttributes": ["*"], "searchableAttributes": ["*"], "filterableAttributes": [], "sortableAttributes": [], "rankingRules": ["words", "typo", "proximity", "attribute", "exactness"], "stopWords": [], "stopWordsPresets": [], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": {"oneTypo": 5, "twoTypos": 9}, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100, "sortFacetValuesBy": { "*": "alpha" } }, "pagination": { "maxTotalHits": 1000 }, "rules": [], "searchCutoffMs": null })
     );
 
     let (tasks, code) = index.list_tasks().await;
@@ -659,7 +661,7 @@ async fn import_dump_v4_movie_with_settings() {
     assert_eq!(code, 200);
     assert_eq!(
         settings,
-        json!({ "displayedAttributes": ["title", "genres", "overview", "poster", "release_date"], "searchableAttributes": ["title", "overview"], "filterableAttributes": ["genres"], "sortableAttributes": [], "rankingRules": ["words", "typo", "proximity", "attribute", "exactness"], "stopWords": ["of", "the"], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": { "oneTypo": 5, "twoTypos": 9 }, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100 }, "pagination": { "maxTotalHits": 1000 } })
+        json!({ "displayedAttributes": ["title", "genres", "overview", "poster", "release_date"], "searchableAttributes": ["title", "overview"], "filterableAttributes": ["genres"], "sortableAttributes": [], "rankingRules": ["words", "typo", "proximity", "attribute", "exactness"], "stopWords": ["of", "the"], "stopWordsPresets": [], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": { "oneTypo": 5, "twoTypos": 9 }, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100, "sortFacetValuesBy": { "*": "alpha" } }, "pagination": { "maxTotalHits": 1000 }, "rules": [], "searchCutoffMs": null })
     );
 
     let (tasks, code) = index.list_tasks().await;
@@ -722,7 +724,7 @@ async fn import_dump_v4_rubygems_with_settings() {
     assert_eq!(code, 200);
     assert_eq!(
         settings,
-        json!({ "displayedAttributes": ["name", "summary", "description", "version", "total_downloads"], "searchableAttributes": ["name", "summary"], "filterableAttributes": ["version"], "sortableAttributes": [], "rankingRules": ["typo", "words", "fame:desc", "proximity", "attribute", "exactness", "total_downloads:desc"], "stopWords": [], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": {"oneTypo": 5, "twoTypos": 9}, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100 }, "pagination": { "maxTotalHits": 1000 } })
+        json!({ "displayedAttributes": ["name", "summary", "description", "version", "total_downloads"], "searchableAttributes": ["name", "summary"], "filterableAttributes": ["version"], "sortableAttributes": [], "rankingRules": ["typo", "words", "fame:desc", "proximity", "attribute", "exactness", "total_downloads:desc"], "stopWords": [], "stopWordsPresets": [], "synonyms": {}, "distinctAttribute": null, "typoTolerance": {"enabled": true, "minWordSizeForTypos": {"oneTypo": 5, "twoTypos": 9}, "disableOnWords": [], "disableOnAttributes": [] }, "faceting": { "maxValuesPerFacet": 100, "sortFacetValuesBy": { "*": "alpha" } }, "pagination": { "maxTotalHits": 1000 }, "rules": [], "searchCutoffMs": null })
     );
 
     let (tasks, code) = index.list_tasks().await;
@@ -817,3 +819,144 @@ async fn import_dump_v5() {
 
     assert_eq!(key["name"], "my key");
 }
+
+#[actix_rt::test]
+#[cfg_attr(target_os = "windows", ignore)]
+async fn create_and_import_partial_dump() {
+    let temp = tempfile::tempdir().unwrap();
+    let options = default_settings(temp.path());
+    let dump_dir = options.dump_dir.clone();
+    let server = Server::new_with_options(options).await.unwrap();
+
+    for uid in ["movies", "products-fr", "products-en"] {
+        let (task, code) = server.index(uid).create(None).await;
+        assert_eq!(code, 202);
+        server.wait_task(task["taskUid"].as_u64().unwrap()).await;
+    }
+
+    let (response, code) =
+        server.create_dump_with(json!({ "indexes": ["products-"], "tasks": 1 })).await;
+    assert_eq!(code, 400, "{}", response);
+    let (response, code) = server.create_dump_with(json!({ "indexes": ["products!*"] })).await;
+    assert_eq!(code, 400, "{}", response);
+
+    let (task, code) =
+        server.create_dump_with(json!({ "indexes": ["products-*"], "tasks": false })).await;
+    assert_eq!(code, 202);
+    let task = server.wait_task(task["taskUid"].as_u64().unwrap()).await;
+    assert_eq!(task["status"], json!("succeeded"), "{}", task);
+    let dump_uid = task["details"]["dumpUid"].as_str().unwrap();
+
+    let temp = tempfile::tempdir().unwrap();
+    let options = Opt {
+        import_dump: Some(dump_dir.join(format!("{}.dump", dump_uid))),
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await.unwrap();
+
+    let (indexes, code) = server.list_indexes(None, None).await;
+    assert_eq!(code, 200);
+    let uids: Vec<_> =
+        indexes["results"].as_array().unwrap().iter().map(|index| &index["uid"]).collect();
+    assert_eq!(uids, [&json!("products-en"), &json!("products-fr")]);
+
+    let (tasks, code) = server.tasks().await;
+    assert_eq!(code, 200);
+    assert_eq!(tasks["results"], json!([]));
+}
+
+#[actix_rt::test]
+#[cfg_attr(target_os = "windows", ignore)]
+async fn import_dump_in_running_instance() {
+    let temp = tempfile::tempdir().unwrap();
+    let server = Server::new_with_options(default_settings(temp.path())).await.unwrap();
+
+    let index = server.index("movies");
+    let (task, code) = index.add_documents(json!([{ "id": 1, "title": "Carol" }]), None).await;
+    assert_eq!(code, 202);
+    server.wait_task(task["taskUid"].as_u64().unwrap()).await;
+
+    let (task, code) = server.create_dump().await;
+    assert_eq!(code, 202);
+    let task = server.wait_task(task["taskUid"].as_u64().unwrap()).await;
+    let path = format!("{}.dump", task["details"]["dumpUid"].as_str().unwrap());
+
+    let (response, code) = server.import_dump(json!({ "path": "../data.ms" })).await;
+    assert_eq!(code, 400, "{}", response);
+    let (response, code) = server.import_dump(json!({})).await;
+    assert_eq!(code, 400, "{}", response);
+
+    // by default the import fails when an index already exists.
+    let (task, code) = server.import_dump(json!({ "path": path })).await;
+    assert_eq!(code, 202);
+    let task = server.wait_task(task["taskUid"].as_u64().unwrap()).await;
+    assert_eq!(task["status"], json!("failed"), "{}", task);
+    assert_eq!(task["error"]["code"], json!("index_already_exists"), "{}", task);
+
+    let (task, code) = server.import_dump(json!({ "path": path, "onConflict": "skip" })).await;
+    assert_eq!(code, 202);
+    let task = server.wait_task(task["taskUid"].as_u64().unwrap()).await;
+    assert_eq!(task["status"], json!("succeeded"), "{}", task);
+    assert_eq!(task["details"]["importedIndexes"], json!([]));
+    assert_eq!(task["details"]["skippedIndexes"], json!(["movies"]));
+
+    let (task, code) =
+        server.import_dump(json!({ "path": path, "renames": { "movies": "films" } })).await;
+    assert_eq!(code, 202);
+    let task = server.wait_task(task["taskUid"].as_u64().unwrap()).await;
+    assert_eq!(task["status"], json!("succeeded"), "{}", task);
+    assert_eq!(task["details"]["importedIndexes"], json!(["films"]));
+
+    let (document, code) = server.index("films").get_document(1, None).await;
+    assert_eq!(code, 200);
+    assert_eq!(document, json!({ "id": 1, "title": "Carol" }));
+    let (_, code) = server.index("movies").get_document(1, None).await;
+    assert_eq!(code, 200);
+
+    let (task, code) =
+        server.import_dump(json!({ "path": path, "renames": { "series": "shows" } })).await;
+    assert_eq!(code, 202);
+    let task = server.wait_task(task["taskUid"].as_u64().unwrap()).await;
+    assert_eq!(task["error"]["code"], json!("index_not_found"), "{}", task);
+}
+
+#[actix_rt::test]
+async fn uploaded_dumps_are_limited_in_size() {
+    let temp = tempfile::tempdir().unwrap();
+    let mut options = default_settings(temp.path());
+    options.http_payload_size_limit = Byte::from_unit(100.0, ByteUnit::KiB).unwrap();
+    let server = Server::new_with_options(options).await.unwrap();
+
+    let upload = |options: String, dump: Vec<u8>| {
+        let mut body = Vec::new();
+        body.extend_from_slice(
+            b"--boundary\r\nContent-Disposition: form-data; name=\"options\"\r\n\r\n",
+        );
+        body.extend_from_slice(options.as_bytes());
+        body.extend_from_slice(b"\r\n--boundary\r\nContent-Disposition: form-data; name=\"dump\"; filename=\"a.dump\"\r\n\r\n");
+        body.extend_from_slice(&dump);
+        body.extend_from_slice(b"\r\n--boundary--\r\n");
+        test::TestRequest::post()
+            .uri("/dumps/import")
+            .insert_header(("content-type", "multipart/form-data; boundary=boundary"))
+            .set_payload(body)
+    };
+
+    // the options part can't be larger than 64KiB
+    let renames: serde_json::Map<_, _> =
+        (0..3500).map(|i| (format!("index{}", i), json!(format!("new{}", i)))).collect();
+    let options = json!({ "renames": renames }).to_string();
+    let (response, code) = server.service.request(upload(options, Vec::new())).await;
+    assert_eq!(code, 413, "{}", response);
+    assert_eq!(response["code"], json!("payload_too_large"), "{}", response);
+
+    // the whole payload can't be larger than the payload size limit
+    let (response, code) =
+        server.service.request(upload("{}".to_string(), vec![0; 200 * 1024])).await;
+    assert_eq!(code, 413, "{}", response);
+    assert_eq!(response["code"], json!("payload_too_large"), "{}", response);
+
+    let (tasks, code) = server.tasks().await;
+    assert_eq!(code, 200);
+    assert_eq!(tasks["results"], json!([]));
+}
@@ -0,0 +1,114 @@
+use byte_unit::{Byte, ByteUnit};
+use meilisearch::Opt;
+use serde_json::json;
+
+use crate::common::server::default_settings;
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn record_searches_and_events() {
+    let temp = tempfile::tempdir().unwrap();
+    let options = Opt { enable_search_analytics: true, ..default_settings(temp.path()) };
+    let server = Server::new_with_options(options).await.unwrap();
+    let index = server.index("test");
+
+    index.add_documents(json!([{ "id": 1, "title": "Carol" }]), None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = index.search_post(json!({ "q": "Carol" })).await;
+    assert_eq!(code, 200, "{}", response);
+    let query_uid = response["queryUid"].clone();
+    assert!(query_uid.is_string(), "{}", response);
+
+    let (response, code) = index.search_get(json!({ "q": "carol " })).await;
+    assert_eq!(code, 200, "{}", response);
+    let (response, code) = index.search_post(json!({ "q": "batman" })).await;
+    assert_eq!(code, 200, "{}", response);
+
+    let (response, code) =
+        server.send_event(json!({ "eventType": "click", "queryUid": query_uid })).await;
+    assert_eq!(code, 204, "{}", response);
+    let (response, code) =
+        server.send_event(json!({ "eventType": "conversion", "queryUid": query_uid })).await;
+    assert_eq!(code, 204, "{}", response);
+
+    let (response, code) = index.analytics().await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["totalSearches"], json!(3));
+    assert_eq!(response["totalZeroHitSearches"], json!(1));
+    assert_eq!(response["clicks"], json!(1));
+    assert_eq!(response["conversions"], json!(1));
+    let top: Vec<_> = response["topQueries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|query| (query["q"].clone(), query["searches"].clone(), query["clicks"].clone()))
+        .collect();
+    assert_eq!(top, [(json!("carol"), json!(2), json!(1)), (json!("batman"), json!(1), json!(0))]);
+    assert_eq!(response["topZeroHitQueries"][0]["q"], json!("batman"));
+
+    let (response, code) = server
+        .send_event(
+            json!({ "eventType": "click", "queryUid": "4b4e9a2e-1f8b-4c4e-8f8e-1f1e1f1e1f1e" }),
+        )
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    let (response, code) =
+        server.send_event(json!({ "eventType": "purchase", "queryUid": query_uid })).await;
+    assert_eq!(code, 400, "{}", response);
+}
+
+#[actix_rt::test]
+async fn search_analytics_are_disabled_by_default() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index.add_documents(json!([{ "id": 1, "title": "Carol" }]), None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = index.search_post(json!({ "q": "carol" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert!(response.get("queryUid").is_none(), "{}", response);
+
+    let (response, code) = index.analytics().await;
+    assert_eq!(code, 400, "{}", response);
+}
+
+#[actix_rt::test]
+async fn cached_searches_are_recorded() {
+    let temp = tempfile::tempdir().unwrap();
+    let options = Opt {
+        enable_search_analytics: true,
+        search_cache_size: Byte::from_unit(1.0, ByteUnit::MiB).unwrap(),
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await.unwrap();
+    let index = server.index("test");
+
+    index.add_documents(json!([{ "id": 1, "title": "Carol" }]), None).await;
+    index.wait_task(0).await;
+
+    let (first, code) = index.search_post(json!({ "q": "carol" })).await;
+    assert_eq!(code, 200, "{}", first);
+    let (second, code) = index.search_post(json!({ "q": "carol" })).await;
+    assert_eq!(code, 200, "{}", second);
+    assert!(first["queryUid"].is_string(), "{}", first);
+    assert!(second["queryUid"].is_string(), "{}", second);
+    assert_ne!(first["queryUid"], second["queryUid"]);
+    assert_eq!(first["hits"], second["hits"]);
+
+    let (stats, _) = server.stats().await;
+    assert_eq!(stats["searchCache"]["hits"], json!(1));
+    assert_eq!(stats["searchCache"]["entries"], json!(1));
+
+    // the events refer to the cached search too
+    let (response, code) =
+        server.send_event(json!({ "eventType": "click", "queryUid": second["queryUid"] })).await;
+    assert_eq!(code, 204, "{}", response);
+
+    let (response, code) = index.analytics().await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["totalSearches"], json!(2));
+    assert_eq!(response["totalZeroHitSearches"], json!(0));
+    assert_eq!(response["clicks"], json!(1));
+}
@@ -0,0 +1,63 @@
+use once_cell::sync::Lazy;
+use serde_json::{json, Value};
+
+use super::{hit_ids, sorted_hit_ids};
+use crate::common::Server;
+
+static DOCUMENTS: Lazy<Value> = Lazy::new(|| {
+    json!([
+        { "id": 1, "title": "The hobbit", "overview": "A dragon guards a treasure" },
+        { "id": 2, "title": "Dragon rider", "overview": "A boy and his friend" },
+        { "id": 3, "title": "Eragon", "overview": "A farm boy finds a dragon egg" },
+        { "id": 4, "title": "Dune", "overview": "A desert planet", "author": { "name": "dragon" } },
+    ])
+});
+
+#[actix_rt::test]
+async fn restrict_search_to_attributes() {
+    let server = Server::new().await;
+    let index = server.index("books");
+
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(0).await;
+
+    index
+        .search(json!({ "q": "dragon", "attributesToSearchOn": ["title"] }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(hit_ids(&response["hits"]), [2]);
+            assert_eq!(response["estimatedTotalHits"], json!(1));
+        })
+        .await;
+
+    // the last word is a prefix and the nested fields of an attribute are searched too
+    index
+        .search(
+            json!({ "q": "drag", "attributesToSearchOn": ["title", "author"] }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(sorted_hit_ids(&response["hits"]), [2, 4]);
+            },
+        )
+        .await;
+
+    // a placeholder search isn't restricted
+    index
+        .search(json!({ "attributesToSearchOn": ["title"] }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(sorted_hit_ids(&response["hits"]), [1, 2, 3, 4]);
+        })
+        .await;
+
+    index.update_settings(json!({ "searchableAttributes": ["title"] })).await;
+    index.wait_task(1).await;
+
+    let (response, code) =
+        index.search_post(json!({ "q": "dragon", "attributesToSearchOn": ["overview"] })).await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(
+        response["message"],
+        json!(
+            "Attribute `overview` is not searchable. It can't be used in `attributesToSearchOn`."
+        )
+    );
+}
@@ -0,0 +1,95 @@
+use byte_unit::{Byte, ByteUnit};
+use meilisearch::Opt;
+use serde_json::json;
+
+use crate::common::server::default_settings;
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn cached_searches_are_invalidated_by_a_batch() {
+    let temp = tempfile::tempdir().unwrap();
+    let options = Opt {
+        search_cache_size: Byte::from_unit(1.0, ByteUnit::MiB).unwrap(),
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await.unwrap();
+    let index = server.index("test");
+
+    index.add_documents(json!([{ "id": 1, "title": "Carol" }]), None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = index.search_post(json!({ "q": "carol" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"], json!([{ "id": 1, "title": "Carol" }]));
+
+    // the same query, sent through the other route, is answered by the cache
+    let (response, code) = index.search_get(json!({ "q": "carol" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"], json!([{ "id": 1, "title": "Carol" }]));
+
+    let (stats, _) = server.stats().await;
+    assert_eq!(stats["searchCache"]["hits"], json!(1));
+    assert_eq!(stats["searchCache"]["misses"], json!(1));
+    assert_eq!(stats["searchCache"]["entries"], json!(1));
+
+    index.add_documents(json!([{ "id": 2, "title": "Carol II" }]), None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = index.search_post(json!({ "q": "carol" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"].as_array().unwrap().len(), 2);
+
+    let (stats, _) = server.stats().await;
+    assert_eq!(stats["searchCache"]["hits"], json!(1));
+    assert_eq!(stats["searchCache"]["misses"], json!(2));
+}
+
+#[actix_rt::test]
+async fn search_cache_is_disabled_by_default() {
+    let server = Server::new().await;
+    let (stats, code) = server.stats().await;
+    assert_eq!(code, 200, "{}", stats);
+    assert!(stats.get("searchCache").is_none(), "{}", stats);
+}
+
+#[actix_rt::test]
+#[cfg_attr(target_os = "windows", ignore)]
+async fn cached_searches_are_invalidated_by_a_dump_import() {
+    let temp = tempfile::tempdir().unwrap();
+    let options = Opt {
+        search_cache_size: Byte::from_unit(1.0, ByteUnit::MiB).unwrap(),
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await.unwrap();
+    let index = server.index("test");
+
+    index.add_documents(json!([{ "id": 1, "title": "Carol" }]), None).await;
+    index.wait_task(0).await;
+
+    let (task, code) = server.create_dump().await;
+    assert_eq!(code, 202);
+    let task = server.wait_task(task["taskUid"].as_u64().unwrap()).await;
+    let path = format!("{}.dump", task["details"]["dumpUid"].as_str().unwrap());
+
+    index.add_documents(json!([{ "id": 2, "title": "Carol II" }]), None).await;
+    index.wait_task(2).await;
+
+    let (response, code) = index.search_post(json!({ "q": "carol" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"].as_array().unwrap().len(), 2);
+
+    // the import overwrites the index with its state at the time of the dump.
+    let (task, code) = server.import_dump(json!({ "path": path, "onConflict": "overwrite" })).await;
+    assert_eq!(code, 202);
+    let task = server.wait_task(task["taskUid"].as_u64().unwrap()).await;
+    assert_eq!(task["status"], json!("succeeded"), "{}", task);
+    assert_eq!(task["details"]["importedIndexes"], json!(["test"]));
+
+    let (response, code) = index.search_post(json!({ "q": "carol" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"], json!([{ "id": 1, "title": "Carol" }]));
+
+    let (stats, _) = server.stats().await;
+    assert_eq!(stats["searchCache"]["hits"], json!(0));
+    assert_eq!(stats["searchCache"]["misses"], json!(2));
+}
@@ -0,0 +1,122 @@
+use serde_json::json;
+
+use super::hit_ids;
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn degraded_search_past_the_cutoff() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .add_documents(
+            json!([
+                { "id": 1, "title": "Carol" },
+                { "id": 2, "title": "John" },
+            ]),
+            None,
+        )
+        .await;
+    index.wait_task(0).await;
+
+    index
+        .search(json!({ "q": "carol OR john", "searchCutoffMs": 60000 }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"].as_array().unwrap().len(), 2);
+            assert!(response.get("degraded").is_none(), "{}", response);
+        })
+        .await;
+
+    // past the deadline, the branches left after the first one aren't searched
+    index
+        .search(json!({ "q": "carol OR john", "searchCutoffMs": 0 }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"], json!([{ "id": 1, "title": "Carol" }]));
+            assert_eq!(response["degraded"], json!(true));
+        })
+        .await;
+
+    // a search made to milli at once is never cut
+    index
+        .search(json!({ "q": "carol", "searchCutoffMs": 0 }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"], json!([{ "id": 1, "title": "Carol" }]));
+            assert!(response.get("degraded").is_none(), "{}", response);
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn degraded_distinct_search_past_the_cutoff() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    // the ten best ranked documents are shoes, then come the shirts and the socks
+    let documents: Vec<_> = (1..=30)
+        .map(|id| {
+            let family = ["shoe", "shirt", "socks"][(id - 1) / 10];
+            json!({ "id": id, "name": "Running gear", "family": family, "rank": id })
+        })
+        .collect();
+    index
+        .update_settings(json!({
+            "filterableAttributes": ["family"],
+            "sortableAttributes": ["rank"],
+            "pagination": { "maxTotalHits": 5 }
+        }))
+        .await;
+    index.add_documents(json!(documents), None).await;
+    index.wait_task(1).await;
+
+    let query = |search_cutoff_ms: u64| {
+        json!({
+            "q": "running",
+            "sort": ["rank:asc"],
+            "distinct": "family",
+            "searchCutoffMs": search_cutoff_ms,
+        })
+    };
+
+    let (response, code) = index.search_post(query(60000)).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(hit_ids(&response["hits"]), [1, 11, 21]);
+    assert!(response.get("degraded").is_none(), "{}", response);
+
+    // the first window of hits only has shoes, it isn't widened past the deadline
+    let (response, code) = index.search_post(query(0)).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(hit_ids(&response["hits"]), [1]);
+    assert_eq!(response["degraded"], json!(true));
+}
+
+#[actix_rt::test]
+async fn search_cutoff_ms_setting() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index.update_settings(json!({ "searchCutoffMs": 0 })).await;
+    index
+        .add_documents(json!([{ "id": 1, "title": "Carol" }, { "id": 2, "title": "John" }]), None)
+        .await;
+    index.wait_task(1).await;
+
+    let (response, code) = index.settings().await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["searchCutoffMs"], json!(0));
+
+    let (response, code) = index.search_post(json!({ "q": "carol OR john" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["degraded"], json!(true));
+
+    // the cutoff of the query takes precedence over the setting
+    let (response, code) =
+        index.search_post(json!({ "q": "carol OR john", "searchCutoffMs": 60000 })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert!(response.get("degraded").is_none(), "{}", response);
+
+    index.update_settings(json!({ "searchCutoffMs": null })).await;
+    index.wait_task(2).await;
+
+    let (response, _) = index.settings().await;
+    assert_eq!(response["searchCutoffMs"], json!(null));
+}
@@ -0,0 +1,174 @@
+use once_cell::sync::Lazy;
+use serde_json::{json, Value};
+
+use super::hit_ids;
+use crate::common::Server;
+
+static DOCUMENTS: Lazy<Value> = Lazy::new(|| {
+    json!([
+        { "id": 1, "name": "Running shoe red", "family": "shoe", "rank": 1 },
+        { "id": 2, "name": "Running shoe blue", "family": "shoe", "rank": 2 },
+        { "id": 3, "name": "Running shoe green", "family": "shoe", "rank": 3 },
+        { "id": 4, "name": "Running shirt", "family": "shirt", "rank": 4 },
+        { "id": 5, "name": "Running shirt long sleeves", "family": "shirt", "rank": 5 },
+        { "id": 6, "name": "Running socks", "rank": 6 },
+    ])
+});
+
+#[actix_rt::test]
+async fn search_time_distinct() {
+    let server = Server::new().await;
+    let index = server.index("products");
+
+    index
+        .update_settings(
+            json!({ "filterableAttributes": ["family"], "sortableAttributes": ["rank"] }),
+        )
+        .await;
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+
+    index
+        .search(json!({ "q": "running", "sort": ["rank:asc"] }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(hit_ids(&response["hits"]), [1, 2, 3, 4, 5, 6]);
+        })
+        .await;
+
+    // the documents without family are kept
+    index
+        .search(
+            json!({ "q": "running", "sort": ["rank:asc"], "distinct": "family" }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(hit_ids(&response["hits"]), [1, 4, 6]);
+                assert_eq!(response["estimatedTotalHits"], json!(3));
+            },
+        )
+        .await;
+
+    let (response, code) = index.search_post(json!({ "q": "running", "distinct": "name" })).await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(
+        response["message"],
+        json!("The attribute `name` can't be used to deduplicate or group the hits: it must be filterable or sortable.")
+    );
+}
+
+#[actix_rt::test]
+async fn group_by() {
+    let server = Server::new().await;
+    let index = server.index("products");
+
+    index
+        .update_settings(
+            json!({ "filterableAttributes": ["family"], "sortableAttributes": ["rank"] }),
+        )
+        .await;
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = index
+        .search_post(json!({
+            "q": "running",
+            "sort": ["rank:asc"],
+            "groupBy": { "attribute": "family", "hitsPerGroup": 2 },
+            "attributesToRetrieve": ["id"],
+        }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"], json!([]));
+    assert_eq!(response["estimatedTotalHits"], json!(3));
+    assert_eq!(
+        response["groups"],
+        json!([
+            { "value": "shoe", "totalHits": 3, "hits": [{ "id": 1 }, { "id": 2 }] },
+            { "value": "shirt", "totalHits": 2, "hits": [{ "id": 4 }, { "id": 5 }] },
+            { "value": null, "totalHits": 1, "hits": [{ "id": 6 }] },
+        ])
+    );
+
+    // the pages are made of groups
+    let (response, code) = index
+        .search_post(json!({
+            "q": "running",
+            "sort": ["rank:asc"],
+            "groupBy": { "attribute": "family" },
+            "offset": 1,
+            "limit": 1,
+            "attributesToRetrieve": ["id"],
+        }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["groups"],
+        json!([{ "value": "shirt", "totalHits": 2, "hits": [{ "id": 4 }, { "id": 5 }] }])
+    );
+
+    let (response, code) = index
+        .search_post(
+            json!({ "q": "running", "groupBy": { "attribute": "family" }, "distinct": "family" }),
+        )
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(
+        response["message"],
+        json!("The attribute `family` can't be used to deduplicate or group the hits: `groupBy` can't be used along with `distinct`.")
+    );
+}
+
+#[actix_rt::test]
+async fn distinct_pages_are_reachable() {
+    let server = Server::new().await;
+    let index = server.index("products");
+
+    // the ten best ranked documents are shoes, then come the shirts and the socks
+    let documents: Vec<_> = (1..=30)
+        .map(|id| {
+            let family = ["shoe", "shirt", "socks"][(id - 1) / 10];
+            json!({ "id": id, "name": "Running gear", "family": family, "rank": id })
+        })
+        .collect();
+    index
+        .update_settings(json!({
+            "filterableAttributes": ["family"],
+            "sortableAttributes": ["rank"],
+            "pagination": { "maxTotalHits": 5 }
+        }))
+        .await;
+    index.add_documents(json!(documents), None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = index
+        .search_post(json!({
+            "q": "running",
+            "sort": ["rank:asc"],
+            "distinct": "family",
+            "hitsPerPage": 1,
+            "page": 3,
+        }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["totalHits"], json!(3));
+    assert_eq!(response["totalPages"], json!(3));
+    assert_eq!(hit_ids(&response["hits"]), [21]);
+
+    let (response, code) = index
+        .search_post(json!({
+            "q": "running",
+            "sort": ["rank:asc"],
+            "groupBy": { "attribute": "family", "hitsPerGroup": 2 },
+            "attributesToRetrieve": ["id"],
+        }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["estimatedTotalHits"], json!(3));
+    assert_eq!(
+        response["groups"],
+        json!([
+            { "value": "shoe", "totalHits": 10, "hits": [{ "id": 1 }, { "id": 2 }] },
+            { "value": "shirt", "totalHits": 10, "hits": [{ "id": 11 }, { "id": 12 }] },
+            { "value": "socks", "totalHits": 10, "hits": [{ "id": 21 }, { "id": 22 }] },
+        ])
+    );
+}
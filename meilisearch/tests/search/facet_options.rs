@@ -0,0 +1,104 @@
+use once_cell::sync::Lazy;
+use serde_json::{json, Value};
+
+use crate::common::Server;
+
+static DOCUMENTS: Lazy<Value> = Lazy::new(|| {
+    json!([
+        { "id": 1, "brand": "adidas", "color": ["blue", "white"] },
+        { "id": 2, "brand": "nike", "color": ["red", "white"] },
+        { "id": 3, "brand": "nike", "color": ["black"] },
+        { "id": 4, "brand": "puma", "color": ["white"] },
+        { "id": 5, "brand": "nike", "color": ["red"] },
+    ])
+});
+
+#[actix_rt::test]
+async fn per_facet_options() {
+    let server = Server::new().await;
+    let index = server.index("shoes");
+
+    index.update_settings(json!({ "filterableAttributes": ["brand", "color"] })).await;
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = index
+        .search_post(
+            json!({ "facets": ["brand", { "name": "color", "sortBy": "count", "maxValues": 2 }] }),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["facetDistribution"],
+        json!({
+            "brand": { "adidas": 1, "nike": 3, "puma": 1 },
+            "color": { "white": 3, "red": 2 },
+        })
+    );
+    let colors: Vec<_> =
+        response["facetDistribution"]["color"].as_object().unwrap().keys().collect();
+    assert_eq!(colors, ["white", "red"]);
+
+    let (response, code) = index
+        .search_post(
+            json!({ "facets": [{ "name": "color", "maxValues": 1, "include": ["red", "green"] }] }),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["facetDistribution"],
+        json!({ "color": { "black": 1, "red": 2, "green": 0 } })
+    );
+
+    let (response, code) =
+        index.search_post(json!({ "facets": [{ "name": "color", "unknown": true }] })).await;
+    assert_eq!(code, 400, "{}", response);
+}
+
+#[actix_rt::test]
+async fn sort_facet_values_by_setting() {
+    let server = Server::new().await;
+    let index = server.index("shoes");
+
+    index
+        .update_settings(json!({
+            "filterableAttributes": ["brand", "color"],
+            "faceting": { "sortFacetValuesBy": { "*": "count", "brand": "alpha" } },
+        }))
+        .await;
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = index.settings().await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["faceting"],
+        json!({ "maxValuesPerFacet": 100, "sortFacetValuesBy": { "*": "count", "brand": "alpha" } })
+    );
+
+    index
+        .search(json!({ "facets": ["*"] }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            let brands: Vec<_> =
+                response["facetDistribution"]["brand"].as_object().unwrap().keys().collect();
+            assert_eq!(brands, ["adidas", "nike", "puma"]);
+            let colors: Vec<_> =
+                response["facetDistribution"]["color"].as_object().unwrap().keys().collect();
+            assert_eq!(colors, ["white", "red", "black", "blue"]);
+        })
+        .await;
+
+    // the options of the query take precedence over the settings
+    let (response, code) =
+        index.search_post(json!({ "facets": [{ "name": "brand", "sortBy": "count" }] })).await;
+    assert_eq!(code, 200, "{}", response);
+    let brands: Vec<_> =
+        response["facetDistribution"]["brand"].as_object().unwrap().keys().collect();
+    assert_eq!(brands, ["nike", "adidas", "puma"]);
+
+    index.update_settings(json!({ "faceting": { "sortFacetValuesBy": null } })).await;
+    index.wait_task(2).await;
+
+    let (response, _) = index.settings().await;
+    assert_eq!(response["faceting"]["sortFacetValuesBy"], json!({ "*": "alpha" }));
+}
@@ -0,0 +1,89 @@
+use once_cell::sync::Lazy;
+use serde_json::{json, Value};
+
+use crate::common::Server;
+
+static DOCUMENTS: Lazy<Value> = Lazy::new(|| {
+    json!([
+        { "id": 1, "title": "Superstar", "brand": "Adidas", "color": "white" },
+        { "id": 2, "title": "Stan Smith", "brand": "Adidas", "color": "green" },
+        { "id": 3, "title": "Air Max", "brand": "Nike", "color": "white" },
+        { "id": 4, "title": "Air Force", "brand": "Nike", "color": "black" },
+        { "id": 5, "title": "Club C", "brand": "Reebok", "color": "white" },
+        { "id": 6, "title": "Gazelle", "brand": "Adidas Originals", "color": "blue" },
+    ])
+});
+
+#[actix_rt::test]
+async fn facet_search_by_prefix_and_with_typos() {
+    let server = Server::new().await;
+    let index = server.index("shoes");
+
+    index.update_settings(json!({ "filterableAttributes": ["brand", "color"] })).await;
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = index.facet_search(json!({ "facetName": "brand" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["facetHits"],
+        json!([
+            { "value": "Adidas", "count": 2 },
+            { "value": "Nike", "count": 2 },
+            { "value": "Adidas Originals", "count": 1 },
+            { "value": "Reebok", "count": 1 },
+        ])
+    );
+
+    let (response, code) =
+        index.facet_search(json!({ "facetName": "brand", "facetQuery": "adi" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["facetHits"],
+        json!([{ "value": "Adidas", "count": 2 }, { "value": "Adidas Originals", "count": 1 }])
+    );
+
+    let (response, code) =
+        index.facet_search(json!({ "facetName": "brand", "facetQuery": "origanals" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["facetHits"], json!([{ "value": "Adidas Originals", "count": 1 }]));
+}
+
+#[actix_rt::test]
+async fn facet_search_with_query_and_filter() {
+    let server = Server::new().await;
+    let index = server.index("shoes");
+
+    index.update_settings(json!({ "filterableAttributes": ["brand", "color"] })).await;
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+
+    let (response, code) =
+        index.facet_search(json!({ "facetName": "brand", "filter": "color = white" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["facetHits"],
+        json!([
+            { "value": "Adidas", "count": 1 },
+            { "value": "Nike", "count": 1 },
+            { "value": "Reebok", "count": 1 },
+        ])
+    );
+
+    let (response, code) = index.facet_search(json!({ "facetName": "brand", "q": "air" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["facetHits"], json!([{ "value": "Nike", "count": 2 }]));
+}
+
+#[actix_rt::test]
+async fn facet_search_on_non_filterable_attribute() {
+    let server = Server::new().await;
+    let index = server.index("shoes");
+
+    index.update_settings(json!({ "filterableAttributes": ["color"] })).await;
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = index.facet_search(json!({ "facetName": "brand" })).await;
+    assert_eq!(code, 400, "{}", response);
+}
@@ -0,0 +1,139 @@
+use once_cell::sync::Lazy;
+use serde_json::{json, Value};
+
+use crate::common::Server;
+
+static DOCUMENTS: Lazy<Value> = Lazy::new(|| {
+    json!([
+        // 2022-01-15, 2022-01-31 and 2022-03-02
+        { "id": 1, "title": "Superstar", "brand": "Adidas", "price": 80, "releasedAt": 1642204800 },
+        { "id": 2, "title": "Stan Smith", "brand": "Adidas", "price": 95.5, "releasedAt": 1643587200 },
+        { "id": 3, "title": "Air Max", "brand": "Nike", "price": [120, 125], "releasedAt": 1646179200 },
+        { "id": 4, "title": "Air Force", "brand": "Nike", "price": 110 },
+    ])
+});
+
+#[actix_rt::test]
+async fn facet_stats() {
+    let server = Server::new().await;
+    let index = server.index("shoes");
+
+    index.update_settings(json!({ "filterableAttributes": ["brand", "price"] })).await;
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+
+    index
+        .search(json!({ "facets": ["brand", "price"] }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["facetStats"], json!({ "price": { "min": 80.0, "max": 125.0 } }));
+        })
+        .await;
+
+    index
+        .search(json!({ "facets": ["*"], "filter": "brand = Adidas" }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["facetStats"], json!({ "price": { "min": 80.0, "max": 95.5 } }));
+        })
+        .await;
+
+    index
+        .search(json!({ "q": "air" }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert!(response.get("facetStats").is_none());
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn facet_buckets() {
+    let server = Server::new().await;
+    let index = server.index("shoes");
+
+    index.update_settings(json!({ "filterableAttributes": ["price", "releasedAt"] })).await;
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = index
+        .search_post(json!({
+            "facetBuckets": {
+                "price": { "step": 20 },
+                "releasedAt": { "interval": "month" },
+            }
+        }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["facetBuckets"],
+        json!({
+            "price": [
+                { "from": 80.0, "to": 100.0, "count": 2 },
+                { "from": 100.0, "to": 120.0, "count": 1 },
+                { "from": 120.0, "to": 140.0, "count": 1 },
+            ],
+            "releasedAt": [
+                { "from": 1640995200.0, "to": 1643673600.0, "count": 2 },
+                { "from": 1646092800.0, "to": 1648771200.0, "count": 1 },
+            ],
+        })
+    );
+
+    let (response, code) = index
+        .search_post(json!({
+            "q": "air",
+            "facetBuckets": { "price": { "ranges": [{ "to": 115 }, { "from": 115 }] } }
+        }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["facetBuckets"],
+        json!({ "price": [{ "to": 115.0, "count": 1 }, { "from": 115.0, "count": 1 }] })
+    );
+
+    let (response, code) =
+        index.search_post(json!({ "facetBuckets": { "price": { "step": 0 } } })).await;
+    assert_eq!(code, 400, "{}", response);
+    let (response, code) =
+        index.search_post(json!({ "facetBuckets": { "title": { "step": 10 } } })).await;
+    assert_eq!(code, 400, "{}", response);
+
+    // the buckets aren't truncated to the maximum number of values per facet.
+    index.update_settings(json!({ "faceting": { "maxValuesPerFacet": 2 } })).await;
+    index.wait_task(2).await;
+    let (response, code) =
+        index.search_post(json!({ "facetBuckets": { "price": { "step": 20 } } })).await;
+    assert_eq!(code, 400, "{}", response);
+}
+
+#[actix_rt::test]
+async fn facet_buckets_of_nested_fields() {
+    let server = Server::new().await;
+    let index = server.index("shoes");
+
+    index.update_settings(json!({ "filterableAttributes": ["stock"] })).await;
+    let documents = json!([
+        { "id": 1, "stock": { "count": 3 } },
+        { "id": 2, "stock": { "count": 12 } },
+        { "id": 3, "stock": { "count": [4, 8] } },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = index
+        .search_post(json!({
+            "facets": ["stock.count"],
+            "facetBuckets": { "stock.count": { "step": 5 } }
+        }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["facetStats"], json!({ "stock.count": { "min": 3.0, "max": 12.0 } }));
+    assert_eq!(
+        response["facetBuckets"],
+        json!({
+            "stock.count": [
+                { "from": 0.0, "to": 5.0, "count": 2 },
+                { "from": 5.0, "to": 10.0, "count": 1 },
+                { "from": 10.0, "to": 15.0, "count": 1 },
+            ]
+        })
+    );
+}
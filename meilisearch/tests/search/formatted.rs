@@ -442,3 +442,53 @@ async fn displayedattr_2_smol() {
         )
         .await;
 }
+
+#[actix_rt::test]
+async fn multiple_snippets() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([{
+        "id": 1,
+        "body": "the cat sat on the mat while the dog slept in the sun then a cat chased another cat near the old barn",
+    }]);
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    // the densest snippet comes first and the snippets never overlap.
+    index
+        .search(
+            json!({ "q": "cat", "attributesToCrop": ["body:4"], "attributesToHighlight": ["body"], "snippetsPerAttribute": 2 }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(
+                    response["hits"][0]["_snippets"],
+                    json!({
+                        "body": [
+                            "…<em>cat</em> chased another <em>cat</em>…",
+                            "the <em>cat</em> sat on…",
+                        ]
+                    })
+                );
+            },
+        )
+        .await;
+
+    // the snippets are kept within the attribute, a single match gives a single snippet.
+    index
+        .search(
+            json!({ "q": "barn", "attributesToCrop": ["body:3"], "snippetsPerAttribute": 3 }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["hits"][0]["_snippets"], json!({ "body": ["…the old barn"] }));
+            },
+        )
+        .await;
+
+    index
+        .search(json!({ "q": "cat", "attributesToCrop": ["body:4"] }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert!(response["hits"][0].get("_snippets").is_none(), "{}", response);
+        })
+        .await;
+}
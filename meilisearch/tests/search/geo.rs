@@ -0,0 +1,172 @@
+use once_cell::sync::Lazy;
+use serde_json::{json, Value};
+
+use super::sorted_hit_ids;
+use crate::common::Server;
+
+static DOCUMENTS: Lazy<Value> = Lazy::new(|| {
+    json!([
+        { "id": 1, "name": "Eiffel tower", "kind": "monument", "_geo": { "lat": 48.8584, "lng": 2.2945 } },
+        { "id": 2, "name": "Louvre", "kind": "museum", "_geo": { "lat": 48.8606, "lng": 2.3376 } },
+        { "id": 3, "name": "Lille belfry", "kind": "monument", "_geo": { "lat": 50.6366, "lng": 3.0635 } },
+        { "id": 4, "name": "Prado", "kind": "museum", "_geo": { "lat": 40.4138, "lng": -3.6921 } },
+        { "id": 5, "name": "Nowhere" },
+    ])
+});
+
+#[actix_rt::test]
+async fn geo_bounding_box_and_polygon() {
+    let server = Server::new().await;
+    let index = server.index("places");
+
+    index.update_settings(json!({ "filterableAttributes": ["_geo", "kind"] })).await;
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+
+    // France
+    index
+        .search(
+            json!({ "filter": "_geoBoundingBox([51.1, 8.2], [42.3, -4.8])" }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(sorted_hit_ids(&response["hits"]), [1, 2, 3]);
+                assert_eq!(response["estimatedTotalHits"], json!(3));
+            },
+        )
+        .await;
+
+    index
+        .search(
+            json!({ "filter": ["kind = museum", "_geoBoundingBox([51.1, 8.2], [42.3, -4.8])"] }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(sorted_hit_ids(&response["hits"]), [2]);
+            },
+        )
+        .await;
+
+    // a triangle around Paris, ORed with a box around Madrid
+    index
+        .search(
+            json!({ "filter": [[
+                "_geoPolygon([48.5, 2.0], [49.2, 2.0], [48.85, 3.0])",
+                "_geoBoundingBox([41, -3], [40, -4])"
+            ]] }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(sorted_hit_ids(&response["hits"]), [1, 2, 4]);
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn geo_shapes_restrict_the_hits_before_pagination() {
+    let server = Server::new().await;
+    let index = server.index("places");
+
+    // the places in Paris come after the ones in Madrid
+    let documents: Vec<_> = (1..=20)
+        .map(|id| {
+            let (lat, lng) = if id > 10 { (48.85, 2.35) } else { (40.41, -3.69) };
+            json!({ "id": id, "_geo": { "lat": lat, "lng": lng } })
+        })
+        .collect();
+    index
+        .update_settings(json!({
+            "filterableAttributes": ["_geo"],
+            "pagination": { "maxTotalHits": 5 }
+        }))
+        .await;
+    index.add_documents(json!(documents), None).await;
+    index.wait_task(1).await;
+
+    for filter in
+        ["_geoBoundingBox([49, 3], [48, 2])", "_geoPolygon([48.5, 2.0], [49.2, 2.0], [48.85, 3.0])"]
+    {
+        let (response, code) =
+            index.search_post(json!({ "filter": filter, "hitsPerPage": 3, "page": 2 })).await;
+        assert_eq!(code, 200, "{}", response);
+        assert_eq!(response["totalHits"], json!(5), "{}", response);
+        assert_eq!(response["totalPages"], json!(2), "{}", response);
+        let hits = response["hits"].as_array().unwrap();
+        assert_eq!(hits.len(), 2, "{}", response);
+        assert!(hits.iter().all(|hit| hit["id"].as_u64().unwrap() > 10), "{}", response);
+
+        let (response, code) = index.search_post(json!({ "filter": filter, "limit": 2 })).await;
+        assert_eq!(code, 200, "{}", response);
+        assert_eq!(response["estimatedTotalHits"], json!(5), "{}", response);
+        assert_eq!(response["hits"].as_array().unwrap().len(), 2, "{}", response);
+    }
+}
+
+#[actix_rt::test]
+async fn geo_grid() {
+    let server = Server::new().await;
+    let index = server.index("places");
+
+    index.update_settings(json!({ "filterableAttributes": ["_geo"] })).await;
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+
+    index
+        .search(json!({ "geoGridPrecision": 2 }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["geoGrid"], json!({ "ez": 1, "u0": 2, "u1": 1 }));
+        })
+        .await;
+
+    index
+        .search(json!({ "q": "louvre", "geoGridPrecision": 4 }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["geoGrid"], json!({ "u09t": 1 }));
+        })
+        .await;
+
+    let (response, code) = index.search_post(json!({ "geoGridPrecision": 13 })).await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(
+        response["message"],
+        json!("The `geoGridPrecision` must be between 1 and 12, found 13.")
+    );
+}
+
+#[actix_rt::test]
+async fn invalid_geo_shapes() {
+    let server = Server::new().await;
+    let index = server.index("places");
+
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(0).await;
+
+    let (response, code) =
+        index.search_post(json!({ "filter": "_geoBoundingBox([51.1, 8.2], [42.3, -4.8])" })).await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(
+        response["message"],
+        json!("The geo filter `_geoBoundingBox([51.1, 8.2], [42.3, -4.8])` is invalid: the `_geo` attribute must be in the `filterableAttributes`.")
+    );
+    assert_eq!(response["code"], json!("invalid_filter"));
+
+    index.update_settings(json!({ "filterableAttributes": ["_geo", "kind"] })).await;
+    index.wait_task(1).await;
+
+    let (response, code) = index
+        .search_post(
+            json!({ "filter": "kind = museum AND _geoPolygon([48, 2], [49, 2], [49, 3])" }),
+        )
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(
+        response["message"],
+        json!("The geo filter `kind = museum AND _geoPolygon([48, 2], [49, 2], [49, 3])` is invalid: it must be a whole expression of the filter, combined with the others in an array.")
+    );
+
+    let (response, code) =
+        index.search_post(json!({ "filter": "_geoPolygon([48, 2], [49, 2])" })).await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(
+        response["message"],
+        json!("The geo filter `_geoPolygon([48, 2], [49, 2])` is invalid: it must have at least 3 points.")
+    );
+}
@@ -1,9 +1,24 @@
 // This modules contains all the test concerning search. Each particular feature of the search
 // should be tested in its own module to isolate tests and keep the tests readable.
 
+mod analytics;
+mod attributes_to_search_on;
+mod cache;
+mod cutoff;
+mod distinct;
 mod errors;
+mod facet_options;
+mod facet_search;
+mod facet_stats;
 mod formatted;
+mod geo;
 mod pagination;
+mod query_operators;
+mod rules;
+mod search_after;
+mod similar;
+mod suggestions;
+mod vectors;
 
 use once_cell::sync::Lazy;
 use serde_json::{json, Value};
@@ -35,6 +50,18 @@ pub(self) static DOCUMENTS: Lazy<Value> = Lazy::new(|| {
     ])
 });
 
+/// Returns the ids of the hits, in their order.
+pub(self) fn hit_ids(hits: &Value) -> Vec<u64> {
+    hits.as_array().unwrap().iter().map(|hit| hit["id"].as_u64().unwrap()).collect()
+}
+
+/// Returns the ids of the hits, in ascending order.
+pub(self) fn sorted_hit_ids(hits: &Value) -> Vec<u64> {
+    let mut ids = hit_ids(hits);
+    ids.sort_unstable();
+    ids
+}
+
 pub(self) static NESTED_DOCUMENTS: Lazy<Value> = Lazy::new(|| {
     json!([
         {
@@ -0,0 +1,174 @@
+use once_cell::sync::Lazy;
+use serde_json::{json, Value};
+
+use super::sorted_hit_ids;
+use crate::common::Server;
+
+static DOCUMENTS: Lazy<Value> = Lazy::new(|| {
+    json!([
+        { "id": 1, "title": "The hobbit", "overview": "A dragon guards a treasure" },
+        { "id": 2, "title": "Dragon rider", "overview": "A boy and his friend" },
+        { "id": 3, "title": "Eragon", "overview": "A farm boy finds a dragon egg" },
+        { "id": 4, "title": "Dune", "overview": "A desert planet" },
+    ])
+});
+
+#[actix_rt::test]
+async fn query_operators() {
+    let server = Server::new().await;
+    let index = server.index("books");
+
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(0).await;
+
+    index
+        .search(json!({ "q": "dragon -boy" }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(sorted_hit_ids(&response["hits"]), [1]);
+        })
+        .await;
+
+    index
+        .search(json!({ "q": "title:dragon" }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(sorted_hit_ids(&response["hits"]), [2]);
+        })
+        .await;
+
+    index
+        .search(json!({ "q": "\"dragon egg\"" }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(sorted_hit_ids(&response["hits"]), [3]);
+        })
+        .await;
+
+    // the documents only matching the last alternative are found too
+    index
+        .search(json!({ "q": "dragon OR desert" }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(sorted_hit_ids(&response["hits"]), [1, 2, 3, 4]);
+            assert_eq!(response["estimatedTotalHits"], json!(4));
+        })
+        .await;
+
+    index
+        .search(
+            json!({ "q": "boy treasure OR egg", "matchingStrategy": "all" }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(sorted_hit_ids(&response["hits"]), [3]);
+            },
+        )
+        .await;
+
+    // a word looking like an attribute which isn't a field is kept as is
+    index
+        .search(json!({ "q": "planet -unknown:thing" }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(sorted_hit_ids(&response["hits"]), [4]);
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn or_alternatives_are_ranked_together() {
+    let server = Server::new().await;
+    let index = server.index("books");
+
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(0).await;
+
+    // the best hit of each alternative comes before the other hits
+    let (response, code) = index.search_post(json!({ "q": "dragon OR desert" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"][1]["id"], json!(4), "{}", response);
+
+    let (response, code) =
+        index.search_post(json!({ "q": "dragon OR desert", "offset": 1, "limit": 1 })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"][0]["id"], json!(4), "{}", response);
+}
+
+#[actix_rt::test]
+async fn excluded_documents_are_never_counted() {
+    let server = Server::new().await;
+    let index = server.index("books");
+
+    let documents: Vec<_> = (1..=20)
+        .map(|id| {
+            let overview = if id % 2 == 0 { "A dragon" } else { "A dragon and a boy" };
+            json!({ "id": id, "title": format!("Dragon {}", id), "overview": overview })
+        })
+        .collect();
+    index.update_settings(json!({ "pagination": { "maxTotalHits": 6 } })).await;
+    index.add_documents(json!(documents), None).await;
+    index.wait_task(1).await;
+
+    // the hits past the excluded ones are reachable up to the maximum total hits
+    let (response, code) =
+        index.search_post(json!({ "q": "dragon -boy", "hitsPerPage": 3, "page": 2 })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["totalHits"], json!(6));
+    assert_eq!(response["totalPages"], json!(2));
+    let hits = response["hits"].as_array().unwrap();
+    assert_eq!(hits.len(), 3, "{}", response);
+    assert!(hits.iter().all(|hit| hit["id"].as_u64().unwrap() % 2 == 0), "{}", response);
+}
+
+#[actix_rt::test]
+async fn only_positive_terms_are_highlighted() {
+    let server = Server::new().await;
+    let index = server.index("books");
+
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(0).await;
+
+    index
+        .search(
+            json!({ "q": "overview:treasure -boy", "attributesToHighlight": ["overview"] }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(
+                    response["hits"][0]["_formatted"]["overview"],
+                    json!("A dragon guards a <em>treasure</em>")
+                );
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn invalid_query_operators() {
+    let server = Server::new().await;
+    let index = server.index("books");
+
+    index.update_settings(json!({ "searchableAttributes": ["title"] })).await;
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = index.search_post(json!({ "q": "\"dragon egg" })).await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(
+        response["message"],
+        json!("The query is invalid: the quote of `\"dragon egg` is never closed.")
+    );
+
+    let (response, code) = index.search_post(json!({ "q": "dragon OR" })).await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["message"], json!("The query is invalid: `OR` must be between two terms."));
+
+    let (response, code) =
+        index.search_post(json!({ "q": "a OR b c OR d e OR f g OR h i OR j" })).await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(
+        response["message"],
+        json!("The query is invalid: it has more than 16 combinations of terms joined by `OR`.")
+    );
+
+    let (response, code) = index.search_post(json!({ "q": "overview:dragon" })).await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(
+        response["message"],
+        json!("The query is invalid: attribute `overview` is not searchable.")
+    );
+}
@@ -0,0 +1,112 @@
+use once_cell::sync::Lazy;
+use serde_json::{json, Value};
+
+use super::hit_ids;
+use crate::common::Server;
+
+static DOCUMENTS: Lazy<Value> = Lazy::new(|| {
+    json!([
+        { "id": 1, "name": "iphone case", "category": "accessory", "discontinued": false },
+        { "id": 2, "name": "iphone charger", "category": "accessory", "discontinued": true },
+        { "id": 3, "name": "iphone 14", "category": "phone", "discontinued": false },
+        { "id": 4, "name": "iphone 13", "category": "phone", "discontinued": false },
+        { "id": 5, "name": "pixel 7", "category": "phone", "discontinued": false },
+    ])
+});
+
+#[actix_rt::test]
+async fn pin_and_hide_documents() {
+    let server = Server::new().await;
+    let index = server.index("products");
+
+    index
+        .update_settings(json!({
+            "filterableAttributes": ["category", "discontinued"],
+            "rules": [
+                {
+                    "uid": "pin-pixel",
+                    "conditions": { "queryIs": "IPhone" },
+                    "consequences": { "pin": [{ "id": 5, "position": 1 }], "hide": ["2"] },
+                },
+                {
+                    "uid": "phones-only",
+                    "conditions": { "queryContains": "14" },
+                    "consequences": { "filter": "category = phone" },
+                },
+            ],
+        }))
+        .await;
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+
+    index
+        .search(json!({ "q": "iphone" }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            let ids = hit_ids(&response["hits"]);
+            assert_eq!(ids[0], 5);
+            assert_eq!(ids.len(), 4);
+            assert!(!ids.contains(&2));
+            assert_eq!(response["appliedRules"], json!(["pin-pixel"]));
+            assert_eq!(response["estimatedTotalHits"], json!(4));
+        })
+        .await;
+
+    // the pinned documents must match the filter of the search
+    index
+        .search(json!({ "q": "iphone", "filter": "category = accessory" }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(hit_ids(&response["hits"]), [1]);
+        })
+        .await;
+
+    index
+        .search(json!({ "q": "iphone 14" }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(hit_ids(&response["hits"]), [3, 4]);
+            assert_eq!(response["appliedRules"], json!(["phones-only"]));
+        })
+        .await;
+
+    index
+        .search(json!({ "q": "pixel" }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(hit_ids(&response["hits"]), [5]);
+            assert!(response.get("appliedRules").is_none(), "{}", response);
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn boost_documents_when_a_filter_is_active() {
+    let server = Server::new().await;
+    let index = server.index("products");
+
+    index
+        .update_settings(json!({
+            "filterableAttributes": ["category", "discontinued"],
+            "rules": [{
+                "uid": "boost-phones",
+                "conditions": { "filterActive": "discontinued = false" },
+                "consequences": { "boost": "category = phone" },
+            }],
+        }))
+        .await;
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = index.settings().await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["rules"][0]["uid"], json!("boost-phones"));
+
+    index
+        .search(json!({ "q": "iphone", "filter": "discontinued = false" }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            let ids = hit_ids(&response["hits"]);
+            assert_eq!(ids.len(), 3);
+            let mut phones = ids[..2].to_vec();
+            phones.sort_unstable();
+            assert_eq!(phones, [3, 4]);
+            assert_eq!(response["appliedRules"], json!(["boost-phones"]));
+        })
+        .await;
+}
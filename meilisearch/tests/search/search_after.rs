@@ -0,0 +1,129 @@
+use once_cell::sync::Lazy;
+use serde_json::{json, Value};
+
+use super::hit_ids;
+use crate::common::index::Index;
+use crate::common::Server;
+
+static DOCUMENTS: Lazy<Value> = Lazy::new(|| {
+    json!([
+        { "id": 1, "title": "Dune", "year": 1965 },
+        { "id": 2, "title": "Hyperion", "year": 1989 },
+        { "id": 3, "title": "Foundation", "year": 1951 },
+        { "id": 4, "title": "Neuromancer", "year": 1984 },
+        { "id": 5, "title": "Solaris", "year": 1961 },
+        { "id": 6, "title": "Ubik", "year": 1969 },
+        { "id": 7, "title": "Contact", "year": 1985 },
+    ])
+});
+
+/// Walks all the pages of the search and returns the ids of the hits.
+async fn walk_pages(index: &Index<'_>, mut query: Value) -> Vec<u64> {
+    let mut ids = Vec::new();
+    loop {
+        let (response, code) = index.search_post(query.clone()).await;
+        assert_eq!(code, 200, "{}", response);
+        ids.extend(hit_ids(&response["hits"]));
+        match response.get("nextSearchAfter") {
+            Some(cursor) => query["searchAfter"] = cursor.clone(),
+            None => break ids,
+        }
+    }
+}
+
+#[actix_rt::test]
+async fn walk_pages_with_search_after() {
+    let server = Server::new().await;
+    let index = server.index("books");
+
+    index.update_settings(json!({ "sortableAttributes": ["year"] })).await;
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+
+    let ids = walk_pages(&index, json!({ "sort": ["year:asc"], "limit": 2 })).await;
+    assert_eq!(ids, [3, 5, 1, 6, 4, 7, 2]);
+
+    let ids = walk_pages(&index, json!({ "limit": 3 })).await;
+    let mut sorted = ids.clone();
+    sorted.sort_unstable();
+    assert_eq!(sorted, [1, 2, 3, 4, 5, 6, 7]);
+}
+
+#[actix_rt::test]
+async fn search_after_is_bound_by_max_total_hits() {
+    let server = Server::new().await;
+    let index = server.index("books");
+
+    index
+        .update_settings(json!({
+            "sortableAttributes": ["year"],
+            "pagination": { "maxTotalHits": 3 },
+        }))
+        .await;
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+
+    let ids = walk_pages(&index, json!({ "sort": ["year:asc"], "limit": 2 })).await;
+    assert_eq!(ids, [3, 5, 1]);
+}
+
+#[actix_rt::test]
+async fn search_after_is_stable_across_updates() {
+    let server = Server::new().await;
+    let index = server.index("books");
+
+    index.update_settings(json!({ "sortableAttributes": ["year"] })).await;
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = index.search_post(json!({ "sort": ["year:asc"], "limit": 3 })).await;
+    assert_eq!(code, 200, "{}", response);
+    let cursor = response["nextSearchAfter"].clone();
+
+    // the last hit of the page is deleted and a document is added before it
+    index.delete_document(1).await;
+    index.add_documents(json!([{ "id": 8, "title": "Brave New World", "year": 1932 }]), None).await;
+    index.wait_task(3).await;
+
+    let (response, code) =
+        index.search_post(json!({ "sort": ["year:asc"], "limit": 3, "searchAfter": cursor })).await;
+    assert_eq!(code, 200, "{}", response);
+    let ids: Vec<_> =
+        response["hits"].as_array().unwrap().iter().map(|hit| hit["id"].clone()).collect();
+    assert_eq!(ids, [json!(6), json!(4), json!(7)]);
+}
+
+#[actix_rt::test]
+async fn invalid_search_after() {
+    let server = Server::new().await;
+    let index = server.index("books");
+
+    index.update_settings(json!({ "sortableAttributes": ["year"] })).await;
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = index.search_post(json!({ "searchAfter": "not a cursor" })).await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(
+        response["message"],
+        json!("The `searchAfter` cursor is invalid: it was not returned by a search.")
+    );
+
+    let (response, _) = index.search_post(json!({ "limit": 2 })).await;
+    let cursor = response["nextSearchAfter"].clone();
+
+    // the cursors are signed
+    let (payload, _) = cursor.as_str().unwrap().split_once('.').unwrap();
+    let (response, code) = index.search_post(json!({ "searchAfter": payload })).await;
+    assert_eq!(code, 400, "{}", response);
+
+    let (response, code) =
+        index.search_post(json!({ "searchAfter": cursor, "sort": ["year:asc"] })).await;
+    assert_eq!(code, 400, "{}", response);
+
+    let (response, code) = index.search_post(json!({ "searchAfter": cursor, "offset": 2 })).await;
+    assert_eq!(code, 400, "{}", response);
+
+    let (response, code) = index.search_post(json!({ "searchAfter": cursor, "page": 2 })).await;
+    assert_eq!(code, 400, "{}", response);
+}
@@ -0,0 +1,92 @@
+use once_cell::sync::Lazy;
+use serde_json::{json, Value};
+
+use super::{hit_ids, sorted_hit_ids};
+use crate::common::Server;
+
+static DOCUMENTS: Lazy<Value> = Lazy::new(|| {
+    json!([
+        { "id": 1, "title": "Rust programming", "overview": "learn the rust programming language", "lang": "rust" },
+        { "id": 2, "title": "Programming Rust", "overview": "fast and safe systems programming with rust", "lang": "rust" },
+        { "id": 3, "title": "Rust for rustaceans", "overview": "idiomatic rust programming", "lang": "rust" },
+        { "id": 4, "title": "Cooking", "overview": "learn to cook pasta", "lang": "none" },
+        { "id": 5, "title": "Gardening", "overview": "learn to grow tomatoes", "lang": "none" },
+    ])
+});
+
+#[actix_rt::test]
+async fn similar_documents() {
+    let server = Server::new().await;
+    let index = server.index("books");
+
+    index
+        .update_settings(json!({
+            "searchableAttributes": ["title", "overview"],
+            "filterableAttributes": ["id"],
+        }))
+        .await;
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = index.similar(json!({ "id": 1, "attributesToRetrieve": ["id"] })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["id"], json!("1"));
+    let hits = response["hits"].as_array().unwrap();
+    // the source document is never returned and the rust books come first.
+    assert!(hits.iter().all(|hit| hit["id"] != json!(1)), "{}", response);
+    assert_eq!(hits[0].as_object().unwrap().len(), 1);
+    assert_eq!(sorted_hit_ids(&json!(hits[..2])), [2, 3]);
+
+    let (response, code) =
+        index.similar(json!({ "id": "1", "filter": "id != 2", "limit": 1 })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["limit"], json!(1));
+    assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+    assert_eq!(response["hits"][0]["id"], json!(3));
+}
+
+#[actix_rt::test]
+async fn similar_documents_pagination() {
+    let server = Server::new().await;
+    let index = server.index("books");
+
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = index.similar(json!({ "id": 1 })).await;
+    assert_eq!(code, 200, "{}", response);
+    let all_ids = hit_ids(&response["hits"]);
+    assert_eq!(all_ids.len(), 4, "{}", response);
+    assert_eq!(response["estimatedTotalHits"], json!(4));
+
+    // the source document doesn't shift the pages.
+    let mut paginated_ids = Vec::new();
+    for offset in 0..4 {
+        let (response, code) =
+            index.similar(json!({ "id": 1, "offset": offset, "limit": 1 })).await;
+        assert_eq!(code, 200, "{}", response);
+        assert_eq!(response["estimatedTotalHits"], json!(4));
+        paginated_ids.extend(hit_ids(&response["hits"]));
+    }
+    assert_eq!(paginated_ids, all_ids);
+}
+
+#[actix_rt::test]
+async fn similar_errors() {
+    let server = Server::new().await;
+    let index = server.index("books");
+
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = index.similar(json!({ "id": 42 })).await;
+    assert_eq!(code, 404, "{}", response);
+    assert_eq!(response["code"], json!("document_not_found"));
+
+    let (response, code) = index.similar(json!({ "id": [1] })).await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], json!("invalid_document_id"));
+
+    let (response, code) = index.similar(json!({})).await;
+    assert_eq!(code, 400, "{}", response);
+}
@@ -0,0 +1,81 @@
+use meilisearch::Opt;
+use serde_json::{json, Value};
+
+use crate::common::server::default_settings;
+use crate::common::Server;
+
+fn suggestions(response: &Value) -> Vec<(String, String, u64)> {
+    response["suggestions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|suggestion| {
+            (
+                suggestion["suggestion"].as_str().unwrap().to_string(),
+                suggestion["source"].as_str().unwrap().to_string(),
+                suggestion["frequency"].as_u64().unwrap(),
+            )
+        })
+        .collect()
+}
+
+#[actix_rt::test]
+async fn suggest_words_and_popular_queries() {
+    let temp = tempfile::tempdir().unwrap();
+    let options = Opt { enable_search_analytics: true, ..default_settings(temp.path()) };
+    let server = Server::new_with_options(options).await.unwrap();
+    let index = server.index("books");
+
+    index
+        .add_documents(
+            json!([
+                { "id": 1, "title": "Harry Potter" },
+                { "id": 2, "title": "Hermione Granger" },
+                { "id": 3, "title": "Harry and the Hendersons" },
+            ]),
+            None,
+        )
+        .await;
+    index.wait_task(0).await;
+
+    index.search_post(json!({ "q": "Harry Potter" })).await;
+    index.search_post(json!({ "q": "harry potter" })).await;
+    // a query without any hit is never suggested.
+    index.search_post(json!({ "q": "voldemort" })).await;
+    let task_uid = server.register_suggestions_refresh();
+    server.wait_task(task_uid).await;
+
+    let (response, code) = index.suggestions(json!({ "q": "Har" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        suggestions(&response),
+        [
+            ("harry potter".to_string(), "query".to_string(), 2),
+            ("harry".to_string(), "word".to_string(), 2),
+        ]
+    );
+
+    // the last word is completed with the words of the documents.
+    let (response, code) = index.suggestions(json!({ "q": "harry pot", "limit": 1 })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(suggestions(&response), [("harry potter".to_string(), "query".to_string(), 2)]);
+
+    // the long enough prefixes are typo tolerant.
+    let (response, code) = index.suggestions(json!({ "q": "hermiome" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(suggestions(&response), [("hermione".to_string(), "word".to_string(), 1)]);
+
+    let (response, code) = index.suggestions(json!({ "q": "vol" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["suggestions"], json!([]));
+}
+
+#[actix_rt::test]
+async fn suggest_on_unknown_index() {
+    let server = Server::new().await;
+    let index = server.index("books");
+
+    let (response, code) = index.suggestions(json!({ "q": "har" })).await;
+    assert_eq!(code, 404, "{}", response);
+    assert_eq!(response["code"], json!("index_not_found"));
+}
@@ -0,0 +1,162 @@
+use once_cell::sync::Lazy;
+use serde_json::{json, Value};
+
+use super::hit_ids;
+use crate::common::Server;
+
+static DOCUMENTS: Lazy<Value> = Lazy::new(|| {
+    json!([
+        { "id": 1, "title": "red shirt", "_vectors": [1.0, 0.0, 0.0] },
+        { "id": 2, "title": "red dress", "_vectors": [0.0, 1.0, 0.0] },
+        { "id": 3, "title": "blue shirt", "_vectors": [[0.9, 0.1, 0.0], [0.0, 0.0, 1.0]] },
+        { "id": 4, "title": "green scarf", "_vectors": [0.0, 0.2, 0.9] },
+        { "id": 5, "title": "red scarf" },
+    ])
+});
+
+#[actix_rt::test]
+async fn vector_search() {
+    let server = Server::new().await;
+    let index = server.index("products");
+
+    index.update_settings(json!({ "filterableAttributes": ["id"] })).await;
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = index.search_post(json!({ "vector": [1.0, 0.05, 0.0] })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(hit_ids(&response["hits"]), [1, 3, 2, 4]);
+    assert_eq!(response["estimatedTotalHits"], json!(4));
+
+    let (response, code) = index
+        .search_post(json!({ "vector": [1.0, 0.05, 0.0], "filter": "id > 1", "limit": 2 }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(hit_ids(&response["hits"]), [3, 2]);
+
+    // a document is as close as its closest vector.
+    let (response, code) =
+        index.search_post(json!({ "vector": [0.0, 0.0, 1.0], "limit": 1 })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(hit_ids(&response["hits"]), [3]);
+}
+
+#[actix_rt::test]
+async fn vectors_follow_the_documents() {
+    let server = Server::new().await;
+    let index = server.index("products");
+
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(0).await;
+
+    // the vector of a document is replaced, its other fields are updated.
+    index
+        .add_documents(
+            json!([{ "id": 1, "title": "red shirt", "_vectors": [0.0, 0.0, 1.0] }]),
+            None,
+        )
+        .await;
+    index.update_documents(json!([{ "id": 2, "title": "dark red dress" }]), None).await;
+    index.wait_task(2).await;
+
+    let (response, code) = index.search_post(json!({ "vector": [1.0, 0.0, 0.0] })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(hit_ids(&response["hits"])[0], 3);
+    assert_eq!(response["estimatedTotalHits"], json!(4));
+
+    index.delete_document(3).await;
+    index
+        .add_documents(
+            json!([{ "id": 5, "title": "red scarf", "_vectors": [0.0, 0.1, 1.0] }]),
+            None,
+        )
+        .await;
+    index.wait_task(4).await;
+
+    let (response, code) = index.search_post(json!({ "vector": [0.0, 0.0, 1.0] })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(hit_ids(&response["hits"]), [1, 5, 4, 2]);
+
+    // the dimensions can change once all the vectors are removed.
+    index.clear_all_documents().await;
+    index.add_documents(json!([{ "id": 1, "_vectors": [1.0, 0.0] }]), None).await;
+    let response = index.wait_task(6).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    let (response, code) = index.search_post(json!({ "vector": [1.0, 0.0] })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(hit_ids(&response["hits"]), [1]);
+}
+
+#[actix_rt::test]
+async fn hybrid_search() {
+    let server = Server::new().await;
+    let index = server.index("products");
+
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(0).await;
+
+    // the vector brings the documents without the `red` word.
+    let (response, code) = index
+        .search_post(json!({ "q": "red", "vector": [0.0, 0.1, 1.0], "semanticRatio": 0.5 }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    let mut ids = hit_ids(&response["hits"]);
+    ids.sort_unstable();
+    assert_eq!(ids, [1, 2, 3, 4, 5]);
+
+    let (response, code) = index
+        .search_post(json!({ "q": "red", "vector": [0.0, 0.1, 1.0], "semanticRatio": 0.9 }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(hit_ids(&response["hits"])[..2], [3, 4]);
+
+    let (response, code) = index
+        .search_post(json!({ "q": "red", "vector": [0.0, 0.1, 1.0], "semanticRatio": 0.0 }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    let mut ids = hit_ids(&response["hits"]);
+    ids.sort_unstable();
+    assert_eq!(ids, [1, 2, 5]);
+
+    let (response, code) = index
+        .search_post(json!({ "q": "red", "vector": [0.0, 0.1, 1.0], "semanticRatio": 1.0 }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(hit_ids(&response["hits"])[..2], [3, 4]);
+}
+
+#[actix_rt::test]
+async fn vector_errors() {
+    let server = Server::new().await;
+    let index = server.index("products");
+
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = index.search_post(json!({ "vector": [1.0, 0.0] })).await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(
+        response["message"],
+        json!("The `vector` is invalid: the vectors of the index have 3 dimensions but it has 2.")
+    );
+
+    let (response, code) =
+        index.search_post(json!({ "vector": [1.0, 0.0, 0.0], "semanticRatio": 1.5 })).await;
+    assert_eq!(code, 400, "{}", response);
+
+    index.add_documents(json!([{ "id": 6, "_vectors": [1.0, 0.0] }]), None).await;
+    let response = index.wait_task(1).await;
+    assert_eq!(response["status"], "failed", "{}", response);
+    assert_eq!(response["error"]["code"], json!("invalid_vectors_field"));
+
+    index.add_documents(json!([{ "id": 7, "_vectors": "nope" }]), None).await;
+    let response = index.wait_task(2).await;
+    assert_eq!(response["status"], "failed", "{}", response);
+    assert_eq!(response["error"]["code"], json!("invalid_vectors_field"));
+
+    // the failed batches didn't change the vectors.
+    let (response, code) = index.search_post(json!({ "vector": [1.0, 0.0, 0.0] })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["estimatedTotalHits"], json!(4));
+}
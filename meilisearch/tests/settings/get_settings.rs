@@ -16,11 +16,13 @@ static DEFAULT_SETTINGS_VALUES: Lazy<HashMap<&'static str, Value>> = Lazy::new(|
         json!(["words", "typo", "proximity", "attribute", "sort", "exactness"]),
     );
     map.insert("stop_words", json!([]));
+    map.insert("stop_words_presets", json!([]));
     map.insert("synonyms", json!({}));
     map.insert(
         "faceting",
         json!({
             "maxValuesPerFacet": json!(100),
+            "sortFacetValuesBy": { "*": "alpha" },
         }),
     );
     map.insert(
@@ -29,6 +31,8 @@ static DEFAULT_SETTINGS_VALUES: Lazy<HashMap<&'static str, Value>> = Lazy::new(|
             "maxTotalHits": json!(1000),
         }),
     );
+    map.insert("rules", json!([]));
+    map.insert("search_cutoff_ms", json!(null));
     map
 });
 
@@ -48,7 +52,7 @@ async fn get_settings() {
     let (response, code) = index.settings().await;
     assert_eq!(code, 200);
     let settings = response.as_object().unwrap();
-    assert_eq!(settings.keys().len(), 11);
+    assert_eq!(settings.keys().len(), 14);
     assert_eq!(settings["displayedAttributes"], json!(["*"]));
     assert_eq!(settings["searchableAttributes"], json!(["*"]));
     assert_eq!(settings["filterableAttributes"], json!([]));
@@ -59,10 +63,12 @@ async fn get_settings() {
         json!(["words", "typo", "proximity", "attribute", "sort", "exactness"])
     );
     assert_eq!(settings["stopWords"], json!([]));
+    assert_eq!(settings["stopWordsPresets"], json!([]));
     assert_eq!(
         settings["faceting"],
         json!({
             "maxValuesPerFacet": 100,
+            "sortFacetValuesBy": { "*": "alpha" },
         })
     );
     assert_eq!(
@@ -71,6 +77,8 @@ async fn get_settings() {
             "maxTotalHits": 1000,
         })
     );
+    assert_eq!(settings["rules"], json!([]));
+    assert_eq!(settings["searchCutoffMs"], json!(null));
 }
 
 #[actix_rt::test]
@@ -266,10 +274,13 @@ test_setting_routes!(
     searchable_attributes put,
     distinct_attribute put,
     stop_words put,
+    stop_words_presets put,
     ranking_rules put,
     synonyms put,
     pagination patch,
-    faceting patch
+    faceting patch,
+    rules put,
+    search_cutoff_ms put
 );
 
 #[actix_rt::test]
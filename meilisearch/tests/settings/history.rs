@@ -0,0 +1,57 @@
+use serde_json::json;
+
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn settings_history_and_rollback() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index.update_settings(json!({ "rankingRules": ["words", "typo"] })).await;
+    index.wait_task(0).await;
+    index.update_settings(json!({ "rankingRules": ["typo", "words"], "stopWords": ["the"] })).await;
+    index.wait_task(1).await;
+
+    let (response, code) = server.service.get("/indexes/test/settings/history").await;
+    assert_eq!(code, 200, "{}", response);
+    let results = response["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+
+    // the most recent version first
+    assert_eq!(results[0]["version"], json!(2));
+    assert_eq!(results[0]["taskUids"], json!([1]));
+    assert_eq!(results[0]["settings"]["rankingRules"], json!(["typo", "words"]));
+    assert_eq!(
+        results[0]["diff"],
+        json!({
+            "rankingRules": { "before": ["words", "typo"], "after": ["typo", "words"] },
+            "stopWords": { "before": [], "after": ["the"] },
+        })
+    );
+    assert_eq!(results[1]["version"], json!(1));
+    assert_eq!(results[1]["diff"], json!(null));
+
+    let (response, code) =
+        server.service.post("/indexes/test/settings/rollback/1", json!(null)).await;
+    assert_eq!(code, 202, "{}", response);
+    let response = index.wait_task(2).await;
+    assert_eq!(response["status"], json!("succeeded"), "{}", response);
+
+    let (response, _) = index.settings().await;
+    assert_eq!(response["rankingRules"], json!(["words", "typo"]));
+    assert_eq!(response["stopWords"], json!([]));
+
+    // the rollback is a version of its own
+    let (response, _) = server.service.get("/indexes/test/settings/history").await;
+    assert_eq!(response["results"][0]["version"], json!(3));
+    assert_eq!(response["results"][0]["taskUids"], json!([2]));
+
+    let (response, code) =
+        server.service.post("/indexes/test/settings/rollback/12", json!(null)).await;
+    assert_eq!(code, 404, "{}", response);
+    assert_eq!(
+        response["message"],
+        json!("Version `12` of the settings not found. Only the last 100 versions are kept.")
+    );
+    assert_eq!(response["code"], json!("settings_version_not_found"));
+}
@@ -1,2 +1,4 @@
 mod distinct;
 mod get_settings;
+mod history;
+mod text_imports;
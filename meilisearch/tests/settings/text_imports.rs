@@ -0,0 +1,96 @@
+use serde_json::json;
+
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn import_synonyms_from_text() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let synonyms = "# the tv words\ntv, television\ni-pod, i pod => ipod\ntv, telly\n";
+    let (response, code) =
+        server.service.patch_text("/indexes/test/settings/synonyms", synonyms).await;
+    assert_eq!(code, 202, "{}", response);
+    index.wait_task(0).await;
+
+    let (response, code) = server.service.get("/indexes/test/settings/synonyms").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response,
+        json!({
+            "i pod": ["ipod"],
+            "i-pod": ["ipod"],
+            "telly": ["tv"],
+            "television": ["tv"],
+            "tv": ["telly", "television"],
+        })
+    );
+
+    let (response, code) = server
+        .service
+        .patch_text("/indexes/test/settings/synonyms", "tv, television\na => b => c")
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(
+        response["message"],
+        json!("The synonyms are invalid at line 2: it contains more than one `=>`.")
+    );
+}
+
+#[actix_rt::test]
+async fn import_stop_words_from_text() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let stop_words = "| a snowball list\nthe\nof   | a preposition\n\nand\n";
+    let (response, code) =
+        server.service.patch_text("/indexes/test/settings/stop-words", stop_words).await;
+    assert_eq!(code, 202, "{}", response);
+    index.wait_task(0).await;
+
+    let (response, code) = server.service.get("/indexes/test/settings/stop-words").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response, json!(["and", "of", "the"]));
+
+    let (response, code) =
+        server.service.patch("/indexes/test/settings/stop-words", json!(["the"])).await;
+    assert_eq!(code, 415, "{}", response);
+    assert_eq!(
+        response["message"],
+        json!("The Content-Type `application/json` is invalid. Accepted values for the Content-Type header are: `text/plain`")
+    );
+}
+
+#[actix_rt::test]
+async fn stop_words_presets() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let (response, code) = index
+        .update_settings(json!({ "stopWords": ["hobbit"], "stopWordsPresets": ["en", "fr"] }))
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    index.wait_task(0).await;
+
+    // the words of the presets are not returned along with the stop words of the user
+    let (response, code) = index.settings().await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["stopWords"], json!(["hobbit"]));
+    assert_eq!(response["stopWordsPresets"], json!(["en", "fr"]));
+
+    index.update_settings(json!({ "stopWords": ["dragon"] })).await;
+    index.wait_task(1).await;
+    let (response, _) = index.settings().await;
+    assert_eq!(response["stopWords"], json!(["dragon"]));
+    assert_eq!(response["stopWordsPresets"], json!(["en", "fr"]));
+
+    let (response, code) = server.service.delete("/indexes/test/settings/stop-words-presets").await;
+    assert_eq!(code, 202, "{}", response);
+    index.wait_task(2).await;
+    let (response, _) = index.settings().await;
+    assert_eq!(response["stopWords"], json!(["dragon"]));
+    assert_eq!(response["stopWordsPresets"], json!([]));
+
+    let (response, code) = index.update_settings(json!({ "stopWordsPresets": ["klingon"] })).await;
+    assert_eq!(code, 400, "{}", response);
+}